@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use crate::input::PlayerInputState;
+use crate::{Enemy, Hitbox, PlayState, shoot_bullet};
+
+/// How far past an enemy's hitbox the cursor still counts as on it.
+const STICKY_RADIUS_SCALE: f32 = 1.5;
+
+pub struct AimAssistPlugin;
+
+impl Plugin for AimAssistPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorTarget>()
+            .add_systems(
+                Update,
+                update_cursor_target
+                    .before(shoot_bullet)
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(Update, draw_hovered);
+    }
+}
+
+/// The enemy under the cursor this frame, worked out once for everyone that
+/// cares: firing aims at it, and it wears the `Hovered` ring.
+#[derive(Resource, Default)]
+pub struct CursorTarget {
+    pub entity: Option<Entity>,
+    pub position: Option<Vec2>,
+}
+
+/// Marks the enemy the cursor is locked onto.
+#[derive(Component)]
+pub struct Hovered;
+
+fn update_cursor_target(
+    mut commands: Commands,
+    input: Res<PlayerInputState>,
+    mut target: ResMut<CursorTarget>,
+    enemies: Query<(Entity, &Transform, &Hitbox), With<Enemy>>,
+    hovered: Query<Entity, With<Hovered>>,
+) {
+    let found = input.aim.and_then(|cursor| {
+        enemies
+            .iter()
+            .map(|(entity, tf, hitbox)| {
+                let position = tf.translation.truncate();
+                (entity, position, position.distance(cursor), hitbox.radius)
+            })
+            .filter(|&(.., distance, radius)| distance <= radius * STICKY_RADIUS_SCALE)
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+    });
+
+    let entity = found.map(|(entity, ..)| entity);
+    if target.entity != entity {
+        for old in &hovered {
+            commands.entity(old).remove::<Hovered>();
+        }
+        if let Some(entity) = entity {
+            commands.entity(entity).insert(Hovered);
+        }
+    }
+    target.entity = entity;
+    target.position = found.map(|(_, position, ..)| position);
+}
+
+fn draw_hovered(mut gizmos: Gizmos, hovered: Query<(&Transform, &Hitbox), With<Hovered>>) {
+    for (tf, hitbox) in &hovered {
+        gizmos.circle_2d(
+            tf.translation.truncate(),
+            hitbox.radius * STICKY_RADIUS_SCALE,
+            Color::srgba(1.0, 1.0, 1.0, 0.35),
+        );
+    }
+}
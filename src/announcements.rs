@@ -0,0 +1,142 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::{GameState, ResetSet};
+
+const MAX_QUEUED: usize = 3;
+const FADE_SECS: f32 = 0.3;
+
+pub struct AnnouncementPlugin;
+
+impl Plugin for AnnouncementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Announcements>()
+            .add_systems(Startup, spawn_banner)
+            .add_systems(Update, show_announcements)
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                clear_announcements.in_set(ResetSet),
+            )
+            .add_systems(OnEnter(GameState::GameOver), clear_announcements);
+    }
+}
+
+/// Ordered by priority: when the queue overflows, the lowest goes first.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum AnnouncementStyle {
+    Info,
+    Milestone,
+    Warning,
+}
+
+impl AnnouncementStyle {
+    fn color(self) -> Color {
+        match self {
+            AnnouncementStyle::Info => Color::WHITE,
+            AnnouncementStyle::Milestone => Color::srgb(1.0, 0.85, 0.3),
+            AnnouncementStyle::Warning => Color::srgb(1.0, 0.3, 0.3),
+        }
+    }
+}
+
+pub struct Announcement {
+    pub text: String,
+    /// Total time on screen, fades included.
+    pub duration: f32,
+    pub style: AnnouncementStyle,
+}
+
+/// Banners waiting to be shown, one at a time.
+#[derive(Resource, Default)]
+pub struct Announcements {
+    queue: VecDeque<Announcement>,
+    current: Option<(Announcement, f32)>,
+}
+
+impl Announcements {
+    pub fn push(&mut self, text: impl Into<String>, duration: f32, style: AnnouncementStyle) {
+        self.queue.push_back(Announcement {
+            text: text.into(),
+            duration,
+            style,
+        });
+
+        // Drop the oldest of the least important entries until we fit
+        while self.queue.len() > MAX_QUEUED {
+            let lowest = self.queue.iter().map(|a| a.style).min().unwrap();
+            let index = self.queue.iter().position(|a| a.style == lowest).unwrap();
+            self.queue.remove(index);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.queue.clear();
+        self.current = None;
+    }
+}
+
+#[derive(Component)]
+struct BannerText;
+
+fn spawn_banner(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(20.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Text::new(""),
+                fonts.display(44.0),
+                TextColor(Color::NONE),
+                TextLayout::new_with_justify(Justify::Center),
+                BannerText,
+            ));
+        });
+}
+
+fn show_announcements(
+    time: Res<Time>,
+    mut announcements: ResMut<Announcements>,
+    banner: Single<(&mut Text, &mut TextColor), With<BannerText>>,
+) {
+    let (mut text, mut color) = banner.into_inner();
+
+    if announcements.current.is_none() {
+        let Some(next) = announcements.queue.pop_front() else {
+            return;
+        };
+        text.0 = next.text.clone();
+        announcements.current = Some((next, 0.0));
+    }
+
+    let Some((current, elapsed)) = announcements.current.as_mut() else {
+        return;
+    };
+    *elapsed += time.delta_secs();
+
+    let fade_in = (*elapsed / FADE_SECS).min(1.0);
+    let fade_out = ((current.duration - *elapsed) / FADE_SECS).min(1.0);
+    let alpha = fade_in.min(fade_out).max(0.0);
+    color.0 = current.style.color().with_alpha(alpha);
+
+    if *elapsed >= current.duration {
+        announcements.current = None;
+        text.0.clear();
+    }
+}
+
+fn clear_announcements(
+    mut announcements: ResMut<Announcements>,
+    mut banner: Query<&mut Text, With<BannerText>>,
+) {
+    announcements.clear();
+    for mut text in &mut banner {
+        text.0.clear();
+    }
+}
@@ -0,0 +1,175 @@
+//! Markers that keep important drops findable in a crowd: a beam of light
+//! straight up from the drop, tinted by rarity, a name that fades in as the
+//! player nears and bobs above it, and an arrow at the edge of the view
+//! while the drop is off screen. Ordinary drops get none of this.
+//!
+//! The beam is a gizmo, so a beacon costs two entities, both children of
+//! the drop and gone with it.
+
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::{GameState, Player, Settings, layers};
+
+const BEAM_HEIGHT: f32 = 120.0;
+const BEAM_ALPHA: f32 = 0.35;
+const LABEL_HEIGHT: f32 = 30.0;
+/// The label is fully shown this close, and gone `LABEL_FADE` further out.
+const LABEL_RANGE: f32 = 800.0;
+const LABEL_FADE: f32 = 100.0;
+const BOB_HEIGHT: f32 = 3.0;
+const BOB_SPEED: f32 = 2.5;
+/// How far inside the edge of the view the arrow sits.
+const ARROW_INSET: f32 = 24.0;
+
+pub struct BeaconPlugin;
+
+impl Plugin for BeaconPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (draw_beams, update_labels, update_arrows).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rarity {
+    Uncommon,
+    Rare,
+}
+
+impl Rarity {
+    fn color(self) -> Color {
+        match self {
+            Rarity::Uncommon => Color::srgb(0.3, 0.9, 1.0),
+            Rarity::Rare => Color::srgb(1.0, 0.8, 0.2),
+        }
+    }
+}
+
+/// On a drop worth finding from across the field.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Beacon {
+    pub label: &'static str,
+    pub rarity: Rarity,
+}
+
+#[derive(Component)]
+struct BeaconLabel;
+
+#[derive(Component)]
+struct BeaconArrow;
+
+/// Marks `drop` as `beacon`. Called as the drop is spawned, so the children
+/// never outlive it.
+pub fn spawn_beacon(commands: &mut Commands, fonts: &FontAssets, drop: Entity, beacon: Beacon) {
+    let color = beacon.rarity.color();
+    // Children sit relative to the drop, on the floating text layer
+    let z = layers::FLOATING_TEXT - layers::PICKUPS;
+    commands.entity(drop).insert(beacon);
+    commands.spawn((
+        Text2d::new(beacon.label),
+        fonts.body(12.0),
+        TextColor(color.with_alpha(0.0)),
+        Transform::from_xyz(0.0, LABEL_HEIGHT, z),
+        BeaconLabel,
+        ChildOf(drop),
+    ));
+    commands.spawn((
+        Text2d::new(">"),
+        fonts.body(20.0),
+        TextColor(color),
+        Transform::from_xyz(0.0, 0.0, z),
+        Visibility::Hidden,
+        BeaconArrow,
+        ChildOf(drop),
+    ));
+}
+
+fn draw_beams(mut gizmos: Gizmos, beacons: Query<(&Transform, &Beacon)>) {
+    for (tf, beacon) in &beacons {
+        let base = tf.translation.truncate();
+        let color = beacon.rarity.color();
+        gizmos.line_2d(
+            base,
+            base + Vec2::Y * BEAM_HEIGHT,
+            color.with_alpha(BEAM_ALPHA),
+        );
+        // A fainter halo either side to give the beam some width
+        for side in [-1.5, 1.5] {
+            let offset = Vec2::new(side, 0.0);
+            gizmos.line_2d(
+                base + offset,
+                base + offset + Vec2::Y * BEAM_HEIGHT,
+                color.with_alpha(BEAM_ALPHA / 3.0),
+            );
+        }
+    }
+}
+
+/// Reduced motion keeps the label still.
+fn update_labels(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    player: Query<&Transform, (With<Player>, Without<BeaconLabel>)>,
+    beacons: Query<&Transform, (With<Beacon>, Without<BeaconLabel>)>,
+    mut labels: Query<(&ChildOf, &mut Transform, &mut TextColor), With<BeaconLabel>>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let player = player.translation.truncate();
+    for (child_of, mut tf, mut color) in &mut labels {
+        let Ok(drop) = beacons.get(child_of.parent()) else {
+            continue;
+        };
+        let distance = drop.translation.truncate().distance(player);
+        let alpha = ((LABEL_RANGE + LABEL_FADE - distance) / LABEL_FADE).clamp(0.0, 1.0);
+        if color.0.alpha() != alpha {
+            color.0.set_alpha(alpha);
+        }
+        if alpha > 0.0 && !settings.reduced_motion {
+            // Each drop bobs on its own phase, keyed off where it lies
+            let phase = drop.translation.x * 0.01;
+            tf.translation.y =
+                LABEL_HEIGHT + BOB_HEIGHT * (time.elapsed_secs() * BOB_SPEED + phase).sin();
+        } else if tf.translation.y != LABEL_HEIGHT {
+            tf.translation.y = LABEL_HEIGHT;
+        }
+    }
+}
+
+/// Pins each off-screen drop's arrow to the edge of the view, on the line
+/// from the view's center to the drop, pointing at it.
+fn update_arrows(
+    windows: Query<&Window>,
+    camera: Query<&Transform, (With<Camera2d>, Without<BeaconArrow>)>,
+    beacons: Query<&Transform, (With<Beacon>, Without<BeaconArrow>)>,
+    mut arrows: Query<(&ChildOf, &mut Transform, &mut Visibility), With<BeaconArrow>>,
+) {
+    let (Ok(window), Ok(camera)) = (windows.single(), camera.single()) else {
+        return;
+    };
+    let center = camera.translation.truncate();
+    let half = window.size() / 2.0;
+    let inner = (half - Vec2::splat(ARROW_INSET)).max(Vec2::ONE);
+    for (child_of, mut tf, mut visibility) in &mut arrows {
+        let Ok(drop) = beacons.get(child_of.parent()) else {
+            continue;
+        };
+        let position = drop.translation.truncate();
+        let offset = position - center;
+        if offset.x.abs() <= half.x && offset.y.abs() <= half.y {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        visibility.set_if_neq(Visibility::Inherited);
+        let scale = (inner / offset.abs()).min_element();
+        let edge = center + offset * scale;
+        let local = edge - position;
+        tf.translation.x = local.x;
+        tf.translation.y = local.y;
+        tf.rotation = Quat::from_rotation_z(offset.to_angle());
+    }
+}
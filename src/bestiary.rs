@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::director::enemy_type_label;
+use crate::fonts::FontAssets;
+use crate::run_stats::RunStats;
+use crate::storage::{self, Persistence};
+use crate::upgrades::find_upgrade;
+use crate::weapons::{ALL_WEAPONS, WeaponKind, WeaponSlots};
+use crate::{
+    ALL_ENEMY_TYPES, EnemyKilledEvent, EnemyType, GameState, PlayState, Player, enemy_base_stats,
+    enemy_points, enemy_speed, enemy_type_color,
+};
+
+const BESTIARY_KEY: &str = "bestiary";
+const ENTRIES_PER_PAGE: usize = 4;
+
+pub struct BestiaryPlugin;
+
+impl Plugin for BestiaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Bestiary::load())
+            .init_resource::<BestiaryView>()
+            .add_systems(
+                Update,
+                (count_kills, note_weapon_levels).run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), (fold_run_damage, close_view))
+            .add_systems(
+                Update,
+                (navigate_bestiary, rebuild_bestiary)
+                    .chain()
+                    .run_if(in_state(GameState::GameOver)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+struct WeaponRecord {
+    highest_level: u32,
+    damage: u64,
+}
+
+/// Lifetime tallies behind the bestiary. Kills count as they happen;
+/// weapon damage is folded in as each run ends.
+#[derive(Resource, Default)]
+struct Bestiary {
+    kills: HashMap<&'static str, u64>,
+    weapons: HashMap<&'static str, WeaponRecord>,
+}
+
+impl Bestiary {
+    /// `enemy <id> <kills>` and `weapon <id> <level> <damage>` lines; unknown
+    /// ids are dropped.
+    fn load() -> Self {
+        let mut bestiary = Self::default();
+        for line in storage::load(BESTIARY_KEY).unwrap_or_default().lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["enemy", id, kills] => {
+                    if let (Some(kind), Ok(kills)) = (EnemyType::from_id(id), kills.parse()) {
+                        bestiary.kills.insert(kind.id(), kills);
+                    }
+                }
+                ["weapon", id, level, damage] => {
+                    if let (Some(kind), Ok(highest_level), Ok(damage)) =
+                        (WeaponKind::from_id(id), level.parse(), damage.parse())
+                    {
+                        bestiary.weapons.insert(
+                            kind.id(),
+                            WeaponRecord {
+                                highest_level,
+                                damage,
+                            },
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+        bestiary
+    }
+
+    fn save(&self, persistence: &mut Persistence) {
+        let mut text = String::new();
+        for kind in ALL_ENEMY_TYPES {
+            if let Some(kills) = self.kills.get(kind.id()) {
+                text.push_str(&format!("enemy {} {kills}\n", kind.id()));
+            }
+        }
+        for kind in ALL_WEAPONS {
+            if let Some(record) = self.weapons.get(kind.id()) {
+                text.push_str(&format!(
+                    "weapon {} {} {}\n",
+                    kind.id(),
+                    record.highest_level,
+                    record.damage
+                ));
+            }
+        }
+        persistence.save(BESTIARY_KEY, text);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum BestiaryTab {
+    #[default]
+    Enemies,
+    Weapons,
+}
+
+#[derive(Resource, Default)]
+struct BestiaryView {
+    open: bool,
+    tab: BestiaryTab,
+    page: usize,
+}
+
+impl BestiaryView {
+    fn entry_count(&self) -> usize {
+        match self.tab {
+            BestiaryTab::Enemies => ALL_ENEMY_TYPES.len(),
+            BestiaryTab::Weapons => ALL_WEAPONS.len(),
+        }
+    }
+
+    fn page_count(&self) -> usize {
+        self.entry_count().div_ceil(ENTRIES_PER_PAGE).max(1)
+    }
+}
+
+#[derive(Component)]
+struct BestiaryScreen;
+
+fn count_kills(mut bestiary: ResMut<Bestiary>, mut kills: MessageReader<EnemyKilledEvent>) {
+    for kill in kills.read() {
+        *bestiary.kills.entry(kill.kind.id()).or_insert(0) += 1;
+    }
+}
+
+fn note_weapon_levels(
+    mut bestiary: ResMut<Bestiary>,
+    slots: Query<&WeaponSlots, (With<Player>, Changed<WeaponSlots>)>,
+) {
+    for weapon in slots.iter().flat_map(WeaponSlots::iter) {
+        let record = bestiary.weapons.entry(weapon.kind.id()).or_default();
+        record.highest_level = record.highest_level.max(weapon.level);
+    }
+}
+
+/// Saves once per run, with the run's weapon damage added, rather than on
+/// every kill.
+fn fold_run_damage(
+    mut bestiary: ResMut<Bestiary>,
+    stats: Res<RunStats>,
+    mut persistence: ResMut<Persistence>,
+) {
+    for (kind, weapon) in stats.weapons() {
+        bestiary.weapons.entry(kind.id()).or_default().damage += u64::from(weapon.damage);
+    }
+    bestiary.save(&mut persistence);
+}
+
+fn close_view(mut view: ResMut<BestiaryView>) {
+    *view = BestiaryView::default();
+}
+
+/// B opens and closes it, Tab switches between enemies and weapons, and
+/// the arrow keys turn the page.
+fn navigate_bestiary(input: Res<ButtonInput<KeyCode>>, mut view: ResMut<BestiaryView>) {
+    if input.just_pressed(KeyCode::KeyB) {
+        view.open = !view.open;
+    }
+    if !view.open {
+        return;
+    }
+    if input.just_pressed(KeyCode::Escape) {
+        view.open = false;
+    } else if input.just_pressed(KeyCode::Tab) {
+        view.tab = match view.tab {
+            BestiaryTab::Enemies => BestiaryTab::Weapons,
+            BestiaryTab::Weapons => BestiaryTab::Enemies,
+        };
+        view.page = 0;
+    } else if input.just_pressed(KeyCode::ArrowRight) {
+        view.page = (view.page + 1).min(view.page_count() - 1);
+    } else if input.just_pressed(KeyCode::ArrowLeft) {
+        view.page = view.page.saturating_sub(1);
+    }
+}
+
+fn enemy_lines(bestiary: &Bestiary, kind: EnemyType) -> (String, Color) {
+    let kills = bestiary.kills.get(kind.id()).copied().unwrap_or(0);
+    if kills == 0 {
+        return ("???".to_string(), Color::srgb(0.5, 0.5, 0.5));
+    }
+    let (glyph, health, _) = enemy_base_stats(kind);
+    let text = format!(
+        "{glyph}  {}\nHP {health}   Speed {:.0}   Score {}   Killed {kills}",
+        enemy_type_label(kind),
+        enemy_speed(kind),
+        enemy_points(kind),
+    );
+    (text, enemy_type_color(kind))
+}
+
+fn weapon_lines(bestiary: &Bestiary, kind: WeaponKind) -> (String, Color) {
+    let Some(record) = bestiary.weapons.get(kind.id()) else {
+        return ("???".to_string(), Color::srgb(0.5, 0.5, 0.5));
+    };
+    let name = find_upgrade(kind.id()).map_or(kind.id(), |def| def.name);
+    let text = format!(
+        "{} {name}\nHighest level {}   Damage dealt {}",
+        kind.icon(),
+        record.highest_level,
+        record.damage
+    );
+    (text, kind.color())
+}
+
+/// Redrawn whole on any change; it is a handful of text nodes.
+fn rebuild_bestiary(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    bestiary: Res<Bestiary>,
+    view: Res<BestiaryView>,
+    screens: Query<Entity, With<BestiaryScreen>>,
+) {
+    if !view.is_changed() {
+        return;
+    }
+    for entity in &screens {
+        commands.entity(entity).despawn();
+    }
+    if !view.open {
+        return;
+    }
+
+    let (title, entries): (&str, Vec<(String, Color)>) = match view.tab {
+        BestiaryTab::Enemies => (
+            "BESTIARY - Enemies",
+            ALL_ENEMY_TYPES
+                .iter()
+                .map(|&kind| enemy_lines(&bestiary, kind))
+                .collect(),
+        ),
+        BestiaryTab::Weapons => (
+            "BESTIARY - Weapons",
+            ALL_WEAPONS
+                .iter()
+                .map(|&kind| weapon_lines(&bestiary, kind))
+                .collect(),
+        ),
+    };
+
+    let mut lines = vec![(title.to_string(), fonts.display(40.0), Color::WHITE)];
+    for (text, color) in entries
+        .into_iter()
+        .skip(view.page * ENTRIES_PER_PAGE)
+        .take(ENTRIES_PER_PAGE)
+    {
+        lines.push((text, fonts.body(20.0), color));
+    }
+    lines.push((
+        format!(
+            "Page {}/{}   [Left/Right] Page   [Tab] Switch   [B] Close",
+            view.page + 1,
+            view.page_count()
+        ),
+        fonts.body(18.0),
+        Color::srgb(0.7, 0.7, 0.7),
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(14.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            GlobalZIndex(1),
+            BestiaryScreen,
+            DespawnOnExit(GameState::GameOver),
+        ))
+        .with_children(|list| {
+            for (text, font, color) in lines {
+                list.spawn((
+                    Text::new(text),
+                    font,
+                    TextColor(color),
+                    TextLayout::new_with_justify(Justify::Center),
+                ));
+            }
+        });
+}
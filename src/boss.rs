@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::announcements::{AnnouncementStyle, Announcements};
+use crate::challenge::RunRules;
+use crate::difficulty::DifficultyConfig;
+use crate::fonts::FontAssets;
+use crate::message_log::LogMessage;
+use crate::patterns;
+use crate::unlocks::RunRecord;
+use crate::{
+    Curse, Downed, ENEMY_PROJECTILE_SPEED, Enemy, EnemyTime, EnemyType, GameState, GameplaySet,
+    Health, Invulnerable, PlayState, Player, PlayerCharacter, ResetSet, Settings, SpawnContext,
+    enemy_heading, enemy_speed, enemy_type_color, nearest, roll_spawn_position, spawn_enemy,
+};
+
+/// The first boss arrives this far into the run, then one more each interval
+/// after the last, as long as none is still alive.
+const BOSS_FIRST_SECS: f32 = 240.0;
+const BOSS_INTERVAL_SECS: f32 = 300.0;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NextBoss(BOSS_FIRST_SECS))
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                reset_next_boss.in_set(ResetSet),
+            )
+            .add_systems(
+                Update,
+                (summon_boss, run_boss_patterns)
+                    .chain()
+                    .in_set(GameplaySet)
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(Update, draw_boss_telegraphs);
+    }
+}
+
+/// Every timing and threshold of one boss's attacks. A new boss is a new one
+/// of these.
+pub struct BossPattern {
+    /// Health fractions at which the ring and frenzy phases begin.
+    rings_below: f32,
+    frenzy_below: f32,
+    ring_projectiles: usize,
+    ring_warning_secs: f32,
+    /// In frenzy each ring is turned this far past the last, walking its
+    /// gaps around the boss.
+    frenzy_spin: f32,
+    charge_warning_secs: f32,
+    charge_secs: f32,
+    charge_speed: f32,
+    /// Walking between attacks.
+    cooldown_secs: f32,
+    /// Untouchable for this long after changing phase.
+    phase_shield_secs: f32,
+}
+
+pub const GOLIATH: BossPattern = BossPattern {
+    rings_below: 0.66,
+    frenzy_below: 0.33,
+    ring_projectiles: 12,
+    ring_warning_secs: 0.8,
+    frenzy_spin: 0.2,
+    charge_warning_secs: 1.0,
+    charge_secs: 0.6,
+    charge_speed: 600.0,
+    cooldown_secs: 2.5,
+    phase_shield_secs: 1.0,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum BossPhase {
+    /// Just walks at the player.
+    Chase,
+    /// Fires a ring of projectiles between walks.
+    Rings,
+    /// Turning rings and charges in turn.
+    Frenzy,
+}
+
+impl BossPattern {
+    fn phase_for(&self, health_fraction: f32) -> BossPhase {
+        if health_fraction < self.frenzy_below {
+            BossPhase::Frenzy
+        } else if health_fraction < self.rings_below {
+            BossPhase::Rings
+        } else {
+            BossPhase::Chase
+        }
+    }
+}
+
+enum BossAttack {
+    Cooldown(Timer),
+    RingWarning(Timer),
+    /// The direction is fixed when the warning starts, so the line drawn is
+    /// exactly the path it will take.
+    ChargeWarning {
+        timer: Timer,
+        direction: Vec2,
+    },
+    Charging {
+        timer: Timer,
+        direction: Vec2,
+    },
+}
+
+/// A boss moves itself, so generic enemy movement skips it. All of its
+/// attack state lives here and goes with the entity when the run ends.
+#[derive(Component)]
+pub struct Boss {
+    pattern: &'static BossPattern,
+    phase: BossPhase,
+    attack: BossAttack,
+    /// In frenzy, whether the next attack is a charge rather than a ring.
+    charge_next: bool,
+    /// How far the next ring is turned.
+    ring_phase: f32,
+}
+
+impl Boss {
+    pub fn new(pattern: &'static BossPattern) -> Self {
+        Self {
+            pattern,
+            phase: BossPhase::Chase,
+            attack: BossAttack::Cooldown(Timer::from_seconds(
+                pattern.cooldown_secs,
+                TimerMode::Once,
+            )),
+            charge_next: false,
+            ring_phase: 0.0,
+        }
+    }
+}
+
+/// Run time at which the next boss is due.
+#[derive(Resource)]
+struct NextBoss(f32);
+
+fn reset_next_boss(mut next: ResMut<NextBoss>) {
+    next.0 = BOSS_FIRST_SECS;
+}
+
+fn summon_boss(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    record: Res<RunRecord>,
+    curse: Res<Curse>,
+    rules: Res<RunRules>,
+    difficulty: Res<DifficultyConfig>,
+    mut next: ResMut<NextBoss>,
+    mut announcements: ResMut<Announcements>,
+    mut log: MessageWriter<LogMessage>,
+    player: Query<&Transform, With<Player>>,
+    players: Query<&Transform, With<PlayerCharacter>>,
+    bosses: Query<(), With<Boss>>,
+) {
+    if record.secs() < next.0 || !bosses.is_empty() {
+        return;
+    }
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let ctx = SpawnContext {
+        players: players.iter().map(|tf| tf.translation.truncate()).collect(),
+        bias: None,
+    };
+    let Some(position) =
+        roll_spawn_position(&mut thread_rng(), player.translation.truncate(), &ctx)
+    else {
+        return;
+    };
+
+    spawn_enemy(
+        &mut commands,
+        &fonts,
+        position,
+        EnemyType::Boss,
+        &curse,
+        &rules,
+        &difficulty,
+    );
+    next.0 = record.secs() + BOSS_INTERVAL_SECS;
+    announcements.push("A BOSS APPROACHES", 2.0, AnnouncementStyle::Warning);
+    log.write(LogMessage("A boss has appeared".to_string()));
+}
+
+/// One state machine per boss: the phase only ever moves forward with lost
+/// health, and each phase change cancels whatever attack was under way.
+fn run_boss_patterns(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    time: EnemyTime,
+    rules: Res<RunRules>,
+    mut announcements: ResMut<Announcements>,
+    players: Query<&Transform, (With<PlayerCharacter>, Without<Downed>, Without<Boss>)>,
+    mut bosses: Query<(Entity, &mut Transform, &Health, &Enemy, &mut Boss)>,
+) {
+    let targets: Vec<Vec2> = players.iter().map(|tf| tf.translation.truncate()).collect();
+    for (entity, mut tf, health, enemy, boss) in &mut bosses {
+        let boss = boss.into_inner();
+        let pattern = boss.pattern;
+        let phase = pattern
+            .phase_for(health.current as f32 / health.max as f32)
+            .max(boss.phase);
+        if phase != boss.phase {
+            boss.phase = phase;
+            boss.attack =
+                BossAttack::Cooldown(Timer::from_seconds(pattern.cooldown_secs, TimerMode::Once));
+            commands
+                .entity(entity)
+                .insert(Invulnerable(Timer::from_seconds(
+                    pattern.phase_shield_secs,
+                    TimerMode::Once,
+                )));
+            let banner = match phase {
+                BossPhase::Frenzy => "The boss is in a frenzy!",
+                _ => "The boss grows angry!",
+            };
+            announcements.push(banner, 1.5, AnnouncementStyle::Warning);
+        }
+
+        let position = tf.translation.truncate();
+        let toward_target = || {
+            nearest(position, targets.iter().copied())
+                .map_or(Vec2::X, |target| (target - position).normalize_or(Vec2::X))
+        };
+        let mut step = Vec2::ZERO;
+        let attack = match &mut boss.attack {
+            BossAttack::Cooldown(timer) => {
+                let heading = enemy_heading(entity, position, None, &targets, time.elapsed_secs());
+                step = heading * enemy_speed(enemy.kind) * rules.enemy_speed;
+                if !timer.tick(time.delta()).is_finished() || phase == BossPhase::Chase {
+                    None
+                } else if phase == BossPhase::Frenzy && boss.charge_next {
+                    boss.charge_next = false;
+                    Some(BossAttack::ChargeWarning {
+                        timer: Timer::from_seconds(pattern.charge_warning_secs, TimerMode::Once),
+                        direction: toward_target(),
+                    })
+                } else {
+                    boss.charge_next = phase == BossPhase::Frenzy;
+                    Some(BossAttack::RingWarning(Timer::from_seconds(
+                        pattern.ring_warning_secs,
+                        TimerMode::Once,
+                    )))
+                }
+            }
+            BossAttack::RingWarning(timer) => {
+                if timer.tick(time.delta()).is_finished() {
+                    let shots = if phase == BossPhase::Frenzy {
+                        boss.ring_phase += pattern.frenzy_spin;
+                        patterns::spiral(pattern.ring_projectiles, boss.ring_phase)
+                    } else {
+                        patterns::ring(pattern.ring_projectiles)
+                    };
+                    patterns::spawn_enemy_projectiles(
+                        &mut commands,
+                        &fonts,
+                        position,
+                        &shots,
+                        &patterns::Volley {
+                            speed: ENEMY_PROJECTILE_SPEED,
+                            damage: 1,
+                        },
+                    );
+                    Some(BossAttack::Cooldown(Timer::from_seconds(
+                        pattern.cooldown_secs,
+                        TimerMode::Once,
+                    )))
+                } else {
+                    None
+                }
+            }
+            BossAttack::ChargeWarning { timer, direction } => timer
+                .tick(time.delta())
+                .is_finished()
+                .then(|| BossAttack::Charging {
+                    timer: Timer::from_seconds(pattern.charge_secs, TimerMode::Once),
+                    direction: *direction,
+                }),
+            BossAttack::Charging { timer, direction } => {
+                step = *direction * pattern.charge_speed;
+                timer.tick(time.delta()).is_finished().then(|| {
+                    BossAttack::Cooldown(Timer::from_seconds(
+                        pattern.cooldown_secs,
+                        TimerMode::Once,
+                    ))
+                })
+            }
+        };
+        if let Some(attack) = attack {
+            boss.attack = attack;
+        }
+
+        let delta = step * time.delta_secs();
+        tf.translation.x += delta.x;
+        tf.translation.y += delta.y;
+    }
+}
+
+/// A ring closing in before a volley and a line along a coming charge. The
+/// phase-change shield blinks, or with reduced motion holds steady.
+fn draw_boss_telegraphs(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    bosses: Query<(&Transform, &Boss, Has<Invulnerable>)>,
+) {
+    let color = enemy_type_color(EnemyType::Boss);
+    for (tf, boss, invulnerable) in &bosses {
+        let position = tf.translation.truncate();
+        match &boss.attack {
+            BossAttack::RingWarning(timer) => {
+                gizmos.circle_2d(position, 30.0 + 60.0 * timer.fraction_remaining(), color);
+            }
+            BossAttack::ChargeWarning { direction, .. } => {
+                let length = boss.pattern.charge_speed * boss.pattern.charge_secs;
+                gizmos.line_2d(position, position + *direction * length, color);
+            }
+            BossAttack::Cooldown(_) | BossAttack::Charging { .. } => {}
+        }
+        if invulnerable && (settings.reduced_motion || time.elapsed_secs().fract() < 0.5) {
+            gizmos.circle_2d(position, 32.0, Color::WHITE);
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::upgrades::{UPGRADES, UpgradeDef, UpgradePool, find_upgrade};
+use crate::weapons::{MAX_WEAPON_LEVEL, MAX_WEAPON_SLOTS, Weapon, WeaponKind, WeaponSlots};
+use crate::{GameState, PlayState, Player, PlayerStats, spawn_state_ui_root};
+
+const ICON_SIZE: f32 = 28.0;
+const PIP_SIZE: f32 = 4.0;
+const FRAME_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+const INSPECT_SECS: f32 = 2.5;
+/// Slot `i`'s key, left to right along the weapon row.
+const SLOT_KEYS: [KeyCode; MAX_WEAPON_SLOTS] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+];
+
+pub struct BuildHudPlugin;
+
+impl Plugin for BuildHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<BuildChanged>()
+            .add_systems(OnEnter(GameState::Playing), spawn_build_strip)
+            .add_systems(Update, rebuild_build_strip)
+            .add_systems(
+                Update,
+                (inspect_weapons, show_inspect_card)
+                    .chain()
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(OnEnter(PlayState::Paused), spawn_build_details)
+            .add_systems(Update, cycle_targeting.run_if(in_state(PlayState::Paused)));
+    }
+}
+
+/// Sent whenever a weapon or upgrade is gained. The strip rebuilds only on
+/// this; cooldowns touch `WeaponSlots` every frame, so change detection on it
+/// would be noise.
+#[derive(Message)]
+pub struct BuildChanged;
+
+#[derive(Component)]
+struct WeaponRow;
+
+#[derive(Component)]
+struct PassiveRow;
+
+/// The card over the weapon row that a slot key opens, and which slot it is
+/// showing until its timer runs out.
+#[derive(Component, Default)]
+struct InspectCard {
+    shown: Option<(usize, Timer)>,
+}
+
+#[derive(Component)]
+struct InspectText;
+
+/// A weapon's line on the pause screen, rewritten when its targeting changes.
+#[derive(Component)]
+struct WeaponLine(WeaponKind);
+
+/// Passive upgrades taken so far, in definition order.
+fn passives(pool: &UpgradePool) -> impl Iterator<Item = (&'static UpgradeDef, u32)> + '_ {
+    UPGRADES
+        .iter()
+        .filter(|def| def.weapon().is_none())
+        .map(|def| (def, pool.stacks(def)))
+        .filter(|&(_, stacks)| stacks > 0)
+}
+
+fn spawn_build_strip(mut commands: Commands, fonts: Res<FontAssets>) {
+    let root = spawn_state_ui_root(&mut commands, GameState::Playing);
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ChildOf(root),
+        ))
+        .with_children(|strip| {
+            strip
+                .spawn((
+                    Node {
+                        display: Display::None,
+                        padding: UiRect::all(Val::Px(6.0)),
+                        ..default()
+                    },
+                    BackgroundColor(FRAME_COLOR),
+                    InspectCard::default(),
+                ))
+                .with_child((
+                    Text::default(),
+                    fonts.body(14.0),
+                    TextColor(Color::WHITE),
+                    InspectText,
+                ));
+            strip.spawn((
+                Node {
+                    column_gap: Val::Px(6.0),
+                    ..default()
+                },
+                WeaponRow,
+            ));
+            strip.spawn((
+                Node {
+                    column_gap: Val::Px(4.0),
+                    ..default()
+                },
+                PassiveRow,
+            ));
+        });
+}
+
+fn rebuild_build_strip(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut changes: MessageReader<BuildChanged>,
+    weapon_row: Query<(Entity, Ref<WeaponRow>)>,
+    passive_row: Query<Entity, With<PassiveRow>>,
+    slots: Query<&WeaponSlots, With<Player>>,
+    pool: Res<UpgradePool>,
+) {
+    let changed = !changes.is_empty();
+    changes.clear();
+    let (Ok((weapon_row, marker)), Ok(passive_row), Ok(slots)) =
+        (weapon_row.single(), passive_row.single(), slots.single())
+    else {
+        return;
+    };
+    if !changed && !marker.is_added() {
+        return;
+    }
+
+    commands
+        .entity(weapon_row)
+        .despawn_related::<Children>()
+        .with_children(|row| {
+            for weapon in slots.iter() {
+                spawn_weapon_icon(row, &fonts, weapon);
+            }
+        });
+    commands
+        .entity(passive_row)
+        .despawn_related::<Children>()
+        .with_children(|row| {
+            for (def, stacks) in passives(&pool) {
+                spawn_passive_icon(row, &fonts, def, stacks);
+            }
+        });
+}
+
+fn spawn_weapon_icon(parent: &mut ChildSpawnerCommands, fonts: &FontAssets, weapon: &Weapon) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(2.0),
+            ..default()
+        })
+        .with_children(|icon| {
+            icon.spawn((
+                Node {
+                    width: Val::Px(ICON_SIZE),
+                    height: Val::Px(ICON_SIZE),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(FRAME_COLOR),
+            ))
+            .with_children(|frame| {
+                frame.spawn((
+                    Text::new(weapon.kind.icon()),
+                    fonts.body(18.0),
+                    TextColor(weapon.kind.color()),
+                ));
+                if weapon.kind.picks_targets() {
+                    frame.spawn((
+                        Text::new(weapon.targeting.letter()),
+                        fonts.body(9.0),
+                        TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                        Node {
+                            position_type: PositionType::Absolute,
+                            right: Val::Px(2.0),
+                            bottom: Val::Px(0.0),
+                            ..default()
+                        },
+                    ));
+                }
+            });
+
+            // One pip per level, dimmed up to the cap
+            icon.spawn(Node {
+                column_gap: Val::Px(1.0),
+                ..default()
+            })
+            .with_children(|pips| {
+                for level in 1..=MAX_WEAPON_LEVEL {
+                    let color = if level <= weapon.level {
+                        weapon.kind.color()
+                    } else {
+                        Color::srgba(1.0, 1.0, 1.0, 0.2)
+                    };
+                    pips.spawn((
+                        Node {
+                            width: Val::Px(PIP_SIZE),
+                            height: Val::Px(PIP_SIZE),
+                            ..default()
+                        },
+                        BackgroundColor(color),
+                    ));
+                }
+            });
+        });
+}
+
+/// Smaller than a weapon icon, with the stack count underneath.
+fn spawn_passive_icon(
+    parent: &mut ChildSpawnerCommands,
+    fonts: &FontAssets,
+    def: &UpgradeDef,
+    stacks: u32,
+) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|icon| {
+            icon.spawn((
+                Node {
+                    width: Val::Px(ICON_SIZE * 0.75),
+                    height: Val::Px(ICON_SIZE * 0.75),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(def.rarity.color()),
+            ))
+            .with_children(|frame| {
+                frame.spawn((
+                    Text::new(def.icon),
+                    fonts.body(14.0),
+                    TextColor(Color::WHITE),
+                ));
+            });
+            icon.spawn((
+                Text::new(format!("x{stacks}")),
+                fonts.body(10.0),
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            ));
+        });
+}
+
+/// A slot key shows that slot's card. Pressed while another slot key is held
+/// down, it swaps the two slots instead and shows the weapon that moved.
+fn inspect_weapons(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut build_changed: MessageWriter<BuildChanged>,
+    mut slots: Query<&mut WeaponSlots, With<Player>>,
+    mut card: Query<&mut InspectCard>,
+) {
+    let Some(pressed) = SLOT_KEYS.iter().position(|&key| keys.just_pressed(key)) else {
+        return;
+    };
+    let (Ok(mut slots), Ok(mut card)) = (slots.single_mut(), card.single_mut()) else {
+        return;
+    };
+    let held = SLOT_KEYS
+        .iter()
+        .position(|&key| keys.pressed(key) && !keys.just_pressed(key));
+    if held.is_some_and(|held| slots.swap(held, pressed)) {
+        build_changed.write(BuildChanged);
+    }
+    if slots.get(pressed).is_some() {
+        card.shown = Some((pressed, Timer::from_seconds(INSPECT_SECS, TimerMode::Once)));
+    }
+}
+
+/// Rewritten every frame it is up, so a level gained or a mode switched
+/// while it shows is reflected straight away.
+fn show_inspect_card(
+    time: Res<Time>,
+    stats: Res<PlayerStats>,
+    slots: Query<&WeaponSlots, With<Player>>,
+    mut card: Query<(&mut InspectCard, &mut Node)>,
+    mut text: Query<&mut Text, With<InspectText>>,
+) {
+    let (Ok((mut card, mut node)), Ok(mut text)) = (card.single_mut(), text.single_mut()) else {
+        return;
+    };
+    let slot = card.shown.as_mut().and_then(|(slot, timer)| {
+        let expired = timer.tick(time.delta()).is_finished();
+        (!expired).then_some(*slot)
+    });
+    let weapon = slot.and_then(|slot| slots.single().ok()?.get(slot).map(|w| (slot, w)));
+    let Some((slot, weapon)) = weapon else {
+        card.shown = None;
+        node.display = Display::None;
+        return;
+    };
+    node.display = Display::Flex;
+    let name = find_upgrade(weapon.kind.id()).map_or(weapon.kind.id(), |def| def.name);
+    text.0 = format!(
+        "[{}] {} {name}  Lv {}/{MAX_WEAPON_LEVEL}\n~{:.1} dps",
+        slot + 1,
+        weapon.kind.icon(),
+        weapon.level,
+        weapon.estimated_dps(&stats)
+    );
+}
+
+fn weapon_line(weapon: &Weapon) -> String {
+    let (name, description) = find_upgrade(weapon.kind.id())
+        .map_or((weapon.kind.id(), ""), |def| (def.name, def.description));
+    let mut line = format!(
+        "{} {name}  Lv {}/{MAX_WEAPON_LEVEL}  {description}",
+        weapon.kind.icon(),
+        weapon.level
+    );
+    if weapon.kind.picks_targets() {
+        line.push_str(&format!("  [T] {}", weapon.targeting.name()));
+    }
+    line
+}
+
+/// T steps every weapon that picks its own targets to the next mode. The
+/// weapons read it as they fire, so nothing needs respawning.
+fn cycle_targeting(
+    input: Res<ButtonInput<KeyCode>>,
+    mut build_changed: MessageWriter<BuildChanged>,
+    mut slots: Query<&mut WeaponSlots, With<Player>>,
+    mut lines: Query<(&mut Text, &WeaponLine)>,
+) {
+    if !input.just_pressed(KeyCode::KeyT) {
+        return;
+    }
+    let Ok(mut slots) = slots.single_mut() else {
+        return;
+    };
+    for (mut text, line) in &mut lines {
+        let Some(weapon) = slots.get_mut(line.0) else {
+            continue;
+        };
+        if weapon.kind.picks_targets() {
+            weapon.targeting = weapon.targeting.next();
+            text.0 = weapon_line(weapon);
+        }
+    }
+    build_changed.write(BuildChanged);
+}
+
+/// The pause screen spells the build out: every weapon and passive with its
+/// level and what it does.
+fn spawn_build_details(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    slots: Query<&WeaponSlots, With<Player>>,
+    pool: Res<UpgradePool>,
+) {
+    let mut lines = vec![(
+        "PAUSED".to_string(),
+        fonts.display(40.0),
+        Color::WHITE,
+        None,
+    )];
+    for weapon in slots.iter().flat_map(WeaponSlots::iter) {
+        lines.push((
+            weapon_line(weapon),
+            fonts.body(20.0),
+            weapon.kind.color(),
+            Some(WeaponLine(weapon.kind)),
+        ));
+    }
+    for (def, stacks) in passives(&pool) {
+        lines.push((
+            format!(
+                "{} {}  x{stacks}/{}  {}",
+                def.icon, def.name, def.max_stacks, def.description
+            ),
+            fonts.body(18.0),
+            Color::srgb(0.85, 0.85, 0.85),
+            None,
+        ));
+    }
+    lines.push((
+        "[Esc] Resume   [F9] Save & Quit   [F12] Photo   [G] Rumble".to_string(),
+        fonts.body(18.0),
+        Color::srgb(0.7, 0.7, 0.7),
+        None,
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            DespawnOnExit(PlayState::Paused),
+        ))
+        .with_children(|list| {
+            for (text, font, color, weapon_line) in lines {
+                let mut line = list.spawn((Text::new(text), font, TextColor(color)));
+                if let Some(weapon_line) = weapon_line {
+                    line.insert(weapon_line);
+                }
+            }
+        });
+}
@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+
+use crate::fonts::FontAssets;
+use crate::savegame::RunSuspended;
+use crate::storage::{self, Persistence};
+use crate::weapons::{WeaponKind, WeaponSlots};
+use crate::weekly::{self, IsoWeek, WeeklyChallenge};
+use crate::{
+    Curse, GameState, GameplaySpeed, Player, PlayerStats, Score, setup_new_game,
+    spawn_state_ui_root,
+};
+
+/// How many modifiers a daily challenge stacks.
+const DAILY_MODIFIER_COUNT: usize = 2;
+
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunMode>()
+            .init_resource::<RunRules>()
+            .insert_resource(DailyBests::load())
+            .insert_resource(ThisWeek(weekly::scheduled(IsoWeek::from_day(today()))))
+            .insert_resource(WeeklyCompletions::load())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                apply_run_modifiers.after(setup_new_game),
+            )
+            .add_systems(OnEnter(GameState::GameOver), spawn_challenge_summary)
+            .add_systems(
+                Update,
+                start_daily_challenge.run_if(in_state(GameState::GameOver)),
+            );
+    }
+}
+
+/// A rule tweak applied once at the start of a run. The weekly schedule
+/// names them by variant.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum RunModifier {
+    SwiftHorde,
+    NoShields,
+    GlassCannon,
+    AuraStart,
+    Cursed,
+}
+
+pub const ALL_MODIFIERS: &[RunModifier] = &[
+    RunModifier::SwiftHorde,
+    RunModifier::NoShields,
+    RunModifier::GlassCannon,
+    RunModifier::AuraStart,
+    RunModifier::Cursed,
+];
+
+impl RunModifier {
+    fn description(self) -> &'static str {
+        match self {
+            RunModifier::SwiftHorde => "Enemies move 50% faster",
+            RunModifier::NoShields => "No shield drops",
+            RunModifier::GlassCannon => "+2 bullet damage, -20% move speed",
+            RunModifier::AuraStart => "Start with the aura",
+            RunModifier::Cursed => "Start with 2 curse",
+        }
+    }
+}
+
+/// Whether the current run is a normal one, today's or this week's
+/// challenge, or one set up from a share code.
+#[derive(Resource, Default, Clone, PartialEq, Debug)]
+pub enum RunMode {
+    #[default]
+    Normal,
+    Daily {
+        /// Days since the Unix epoch, UTC.
+        day: u64,
+        modifiers: Vec<RunModifier>,
+    },
+    Weekly(WeeklyChallenge),
+    /// Plays like a challenge, but sets no best: it could be any day's.
+    Shared {
+        modifiers: Vec<RunModifier>,
+    },
+}
+
+impl RunMode {
+    pub fn modifiers(&self) -> &[RunModifier] {
+        match self {
+            RunMode::Normal => &[],
+            RunMode::Daily { modifiers, .. } | RunMode::Shared { modifiers } => modifiers,
+            RunMode::Weekly(challenge) => &challenge.modifiers,
+        }
+    }
+}
+
+/// Run-wide rules that modifiers may bend; reset to defaults every run.
+#[derive(Resource)]
+pub struct RunRules {
+    pub enemy_speed: f32,
+    pub enemy_health: f32,
+    /// Scales the director's spawn budget, on top of the curse.
+    pub spawn_budget: f32,
+    /// Scales the experience needed for each level.
+    pub xp_needed: f32,
+    pub shield_drops: bool,
+}
+
+impl Default for RunRules {
+    fn default() -> Self {
+        Self {
+            enemy_speed: 1.0,
+            enemy_health: 1.0,
+            spawn_budget: 1.0,
+            xp_needed: 1.0,
+            shield_drops: true,
+        }
+    }
+}
+
+const DAILY_BESTS_KEY: &str = "daily_bests";
+
+/// Best challenge score per day, one `day score` pair per line in storage.
+#[derive(Resource, Default)]
+struct DailyBests(HashMap<u64, u32>);
+
+impl DailyBests {
+    fn load() -> Self {
+        let Some(text) = storage::load(DAILY_BESTS_KEY) else {
+            return Self::default();
+        };
+        let bests = text
+            .lines()
+            .filter_map(|line| {
+                let (day, score) = line.split_once(' ')?;
+                Some((day.parse().ok()?, score.parse().ok()?))
+            })
+            .collect();
+        Self(bests)
+    }
+
+    fn save(&self, persistence: &mut Persistence) {
+        let mut days: Vec<_> = self.0.iter().collect();
+        days.sort();
+        let text: String = days
+            .into_iter()
+            .map(|(day, score)| format!("{day} {score}\n"))
+            .collect();
+        persistence.save(DAILY_BESTS_KEY, text);
+    }
+}
+
+fn today() -> u64 {
+    storage::unix_time_secs() / 86_400
+}
+
+/// The challenge scheduled for the week the game was started in.
+#[derive(Resource)]
+struct ThisWeek(Option<WeeklyChallenge>);
+
+const WEEKLY_DONE_KEY: &str = "weekly_done";
+
+/// Weeks whose challenge has been completed, one week per line in storage.
+#[derive(Resource, Default)]
+struct WeeklyCompletions(Vec<IsoWeek>);
+
+impl WeeklyCompletions {
+    fn load() -> Self {
+        let text = storage::load(WEEKLY_DONE_KEY).unwrap_or_default();
+        Self(text.lines().filter_map(IsoWeek::parse).collect())
+    }
+
+    fn save(&self, persistence: &mut Persistence) {
+        let text: String = self.0.iter().map(|week| week.id() + "\n").collect();
+        persistence.save(WEEKLY_DONE_KEY, text);
+    }
+}
+
+/// splitmix64, so neighbouring days land on unrelated seeds.
+fn daily_seed(day: u64) -> u64 {
+    let mut z = day.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Same day, same modifiers, on every machine. ChaCha8 is pinned by its
+/// algorithm, where `StdRng` may change with the platform or rand release.
+pub fn daily_modifiers(day: u64) -> Vec<RunModifier> {
+    let mut rng = ChaCha8Rng::seed_from_u64(daily_seed(day));
+    ALL_MODIFIERS
+        .choose_multiple(&mut rng, DAILY_MODIFIER_COUNT)
+        .copied()
+        .collect()
+}
+
+/// Days since the epoch to a calendar year, month and day (Howard
+/// Hinnant's `civil_from_days`).
+pub fn civil_from_days(day: i64) -> (i64, i64, i64) {
+    let z = day + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
+
+/// The other way round (`days_from_civil`).
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn format_day(day: u64) -> String {
+    let (y, m, d) = civil_from_days(day as i64);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// "Tank Tide: Enemies move 50% faster, Start with 2 curse, reach 500"
+fn weekly_line(challenge: &WeeklyChallenge) -> String {
+    let rules: Vec<&str> = challenge
+        .modifiers
+        .iter()
+        .map(|m| m.description())
+        .collect();
+    format!(
+        "{}: {}, reach {}",
+        challenge.name,
+        rules.join(", "),
+        challenge.target
+    )
+}
+
+/// A week with a scheduled challenge plays that in place of the daily.
+fn start_daily_challenge(
+    input: Res<ButtonInput<KeyCode>>,
+    this_week: Res<ThisWeek>,
+    mut mode: ResMut<RunMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if input.just_pressed(KeyCode::KeyC) {
+        *mode = match &this_week.0 {
+            Some(challenge) => RunMode::Weekly(challenge.clone()),
+            None => {
+                let day = today();
+                RunMode::Daily {
+                    day,
+                    modifiers: daily_modifiers(day),
+                }
+            }
+        };
+        next_state.set(GameState::Resetting);
+    }
+}
+
+/// Runs right after `setup_new_game` has reset stats and spawned the player.
+pub fn apply_run_modifiers(
+    mode: Res<RunMode>,
+    mut rules: ResMut<RunRules>,
+    mut stats: ResMut<PlayerStats>,
+    mut curse: ResMut<Curse>,
+    mut weapons: Query<&mut WeaponSlots, With<Player>>,
+) {
+    *rules = RunRules::default();
+    for modifier in mode.modifiers() {
+        match modifier {
+            RunModifier::SwiftHorde => rules.enemy_speed = 1.5,
+            RunModifier::NoShields => rules.shield_drops = false,
+            RunModifier::GlassCannon => {
+                stats.bullet_damage += 2;
+                stats.move_speed *= 0.8;
+            }
+            RunModifier::AuraStart => {
+                for mut slots in &mut weapons {
+                    slots.add_or_level(WeaponKind::Aura);
+                }
+            }
+            RunModifier::Cursed => curse.0 += 2,
+        }
+    }
+}
+
+fn spawn_challenge_summary(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mode: Res<RunMode>,
+    score: Res<Score>,
+    suspended: Res<RunSuspended>,
+    speed: Res<GameplaySpeed>,
+    this_week: Res<ThisWeek>,
+    mut bests: ResMut<DailyBests>,
+    mut completions: ResMut<WeeklyCompletions>,
+    mut persistence: ResMut<Persistence>,
+) {
+    let root = spawn_state_ui_root(&mut commands, GameState::GameOver);
+    // A saved run has not finished yet, and a slowed one is not comparable,
+    // so neither can set a best or complete a week
+    let counts = !suspended.0 && speed.0 >= 1.0;
+    let prompt = match &this_week.0 {
+        Some(challenge) => format!("Press C for This week: {}", weekly_line(challenge)),
+        None => "Press C for the Daily Challenge".to_string(),
+    };
+
+    let text = match &*mode {
+        RunMode::Normal => prompt,
+        RunMode::Shared { modifiers } => {
+            let rules: Vec<&str> = modifiers.iter().map(|m| m.description()).collect();
+            format!("Shared run\n{}\n{prompt}", rules.join("\n"))
+        }
+        RunMode::Weekly(challenge) => {
+            if counts && score.0 >= challenge.target && !completions.0.contains(&challenge.week) {
+                completions.0.push(challenge.week);
+                completions.save(&mut persistence);
+            }
+            let status = if completions.0.contains(&challenge.week) {
+                "Completed!".to_string()
+            } else if speed.0 < 1.0 {
+                "Not counted at reduced speed".to_string()
+            } else {
+                format!("Score {} of {}", score.0, challenge.target)
+            };
+            format!(
+                "This week ({}): {}\n{status}\nPress C to try again",
+                challenge.week.id(),
+                weekly_line(challenge),
+            )
+        }
+        RunMode::Daily { day, modifiers } => {
+            if counts {
+                let best = bests.0.entry(*day).or_insert(0);
+                *best = (*best).max(score.0);
+                bests.save(&mut persistence);
+            }
+            let best = bests.0.get(day).copied().unwrap_or(0);
+
+            let rules: Vec<&str> = modifiers.iter().map(|m| m.description()).collect();
+            let note = if speed.0 < 1.0 {
+                " (* reduced speed, not counted)"
+            } else {
+                ""
+            };
+            format!(
+                "Daily Challenge {}\n{}\nBest today: {best}{note}\nPress C to try again",
+                format_day(*day),
+                rules.join("\n"),
+            )
+        }
+    };
+
+    commands.spawn((
+        Text::new(text),
+        fonts.body(22.0),
+        TextColor(Color::srgb(1.0, 0.85, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(70.0),
+            left: Val::Percent(25.0),
+            ..default()
+        },
+        ChildOf(root),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day_of(year: i64, month: i64, day: i64) -> u64 {
+        days_from_civil(year, month, day) as u64
+    }
+
+    #[test]
+    fn a_date_always_gets_the_same_seed_and_modifiers() {
+        let day = day_of(2026, 10, 14);
+        assert_eq!(daily_seed(day), daily_seed(day));
+        assert_eq!(daily_modifiers(day), daily_modifiers(day));
+        assert_eq!(daily_modifiers(day).len(), DAILY_MODIFIER_COUNT);
+    }
+
+    #[test]
+    fn the_daily_modifiers_never_repeat_one_within_a_day() {
+        for day in day_of(2026, 1, 1)..day_of(2027, 1, 1) {
+            let [first, second] = daily_modifiers(day)[..] else {
+                panic!("day {day} drew the wrong number of modifiers");
+            };
+            assert_ne!(first, second);
+        }
+    }
+
+    #[test]
+    fn neighbouring_days_get_unrelated_seeds() {
+        let start = day_of(2026, 1, 1);
+        for day in start..start + 365 {
+            let flipped = (daily_seed(day) ^ daily_seed(day + 1)).count_ones();
+            assert!((12..=52).contains(&flipped), "day {day}: {flipped} bits");
+        }
+    }
+
+    #[test]
+    fn neighbouring_days_get_different_modifiers() {
+        let today = day_of(2026, 10, 14);
+        assert_ne!(daily_modifiers(today), daily_modifiers(today + 1));
+
+        let week: Vec<_> = (today..today + 7).map(daily_modifiers).collect();
+        let repeats = week.windows(2).filter(|pair| pair[0] == pair[1]).count();
+        assert!(repeats <= 1, "{week:?}");
+    }
+
+    #[test]
+    fn known_dates_convert_both_ways() {
+        for (day, date) in [
+            (0, (1970, 1, 1)),
+            (-1, (1969, 12, 31)),
+            (59, (1970, 3, 1)),
+            (11_016, (2000, 2, 29)),
+            (11_017, (2000, 3, 1)),
+            (20_740, (2026, 10, 14)),
+            (-719_468, (0, 3, 1)),
+        ] {
+            assert_eq!(civil_from_days(day), date);
+            assert_eq!(days_from_civil(date.0, date.1, date.2), day);
+        }
+    }
+
+    #[test]
+    fn every_day_round_trips_and_follows_the_last() {
+        let mut previous = civil_from_days(-1);
+        for day in 0..150_000 {
+            let (y, m, d) = civil_from_days(day);
+            assert_eq!(days_from_civil(y, m, d), day);
+            let next_day = (y, m, d) == (previous.0, previous.1, previous.2 + 1);
+            let next_month = d == 1 && (y, m) == (previous.0, previous.1 + 1);
+            let next_year = (m, d) == (1, 1) && y == previous.0 + 1 && previous.1 == 12;
+            assert!(
+                next_day || next_month || next_year,
+                "{previous:?} then {:?}",
+                (y, m, d)
+            );
+            previous = (y, m, d);
+        }
+    }
+
+    #[test]
+    fn the_day_formats_as_an_iso_date() {
+        assert_eq!(format_day(day_of(2026, 2, 3)), "2026-02-03");
+    }
+}
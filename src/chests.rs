@@ -0,0 +1,374 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::beacons::{Beacon, Rarity, spawn_beacon};
+use crate::build_hud::BuildChanged;
+use crate::difficulty::DifficultyConfig;
+use crate::fonts::FontAssets;
+use crate::loot::{Drop, DropPity, DropTable, LootEntry, LootSpawner, PityCategory};
+use crate::message_log::LogMessage;
+use crate::upgrades::{UpgradeDef, UpgradePool, UpgradeTargets};
+use crate::weapons::WeaponSlots;
+use crate::{
+    Curse, EnemyKilledEvent, EnemyType, GameState, InGameEntity, PlayState, Player, PlayerStats,
+    ResetSet, is_elite, layers,
+};
+
+const CHEST_RADIUS: f32 = 20.0;
+/// Rolled for every elite killed.
+const ELITE_DROPS: DropTable = DropTable::new(&[
+    (0.2, LootEntry::Drop(Drop::Chest)),
+    (0.8, LootEntry::Nothing),
+]);
+/// Reward counts a chest can roll, with their weights.
+const CHEST_SIZES: &[(usize, u32)] = &[(1, 6), (3, 3), (5, 1)];
+const CHEST_GOLD: u32 = 25;
+/// Handed out in place of an upgrade once the pool has run dry.
+const FALLBACK_GOLD: u32 = 10;
+/// Delay between revealed lines.
+const REVEAL_STEP_SECS: f32 = 0.35;
+
+pub struct ChestPlugin;
+
+impl Plugin for ChestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChestReveal>()
+            .add_systems(
+                Update,
+                (drop_chests, open_chests)
+                    .chain()
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(OnEnter(PlayState::ChestReveal), spawn_reveal_ui)
+            .add_systems(
+                Update,
+                (advance_reveal, close_reveal)
+                    .chain()
+                    .run_if(in_state(PlayState::ChestReveal)),
+            )
+            .add_systems(OnEnter(GameState::Resetting), reset_reveal.in_set(ResetSet));
+    }
+}
+
+#[derive(Component)]
+pub struct Chest;
+
+/// What the last chest gave, already applied; the reveal only shows it.
+#[derive(Resource, Default)]
+struct ChestReveal {
+    rewards: Vec<String>,
+    shown: usize,
+    timer: Timer,
+    /// The player asked to see everything now.
+    skip: bool,
+}
+
+#[derive(Component)]
+struct RevealList;
+
+/// One line of what a chest gave.
+#[derive(Clone, Copy)]
+enum ChestReward {
+    Upgrade(&'static UpgradeDef),
+    Gold(u32),
+}
+
+impl ChestReward {
+    fn describe(self) -> String {
+        match self {
+            ChestReward::Upgrade(upgrade) => upgrade.description.to_string(),
+            ChestReward::Gold(amount) => format!("+{amount} gold"),
+        }
+    }
+}
+
+fn roll_chest_size(rng: &mut impl Rng) -> usize {
+    CHEST_SIZES
+        .choose_weighted(rng, |&(_, weight)| weight)
+        .map_or(1, |&(size, _)| size)
+}
+
+/// `size` rewards from `draw`, which takes the next upgrade from the pool,
+/// with gold standing in for any it can't give, then the chest's own gold.
+fn fill_chest(
+    size: usize,
+    mut draw: impl FnMut() -> Option<&'static UpgradeDef>,
+) -> Vec<ChestReward> {
+    let mut rewards: Vec<ChestReward> = (0..size)
+        .map(|_| draw().map_or(ChestReward::Gold(FALLBACK_GOLD), ChestReward::Upgrade))
+        .collect();
+    rewards.push(ChestReward::Gold(CHEST_GOLD));
+    rewards
+}
+
+/// Elites, bosses among them, are the only enemies worth a chest for now.
+fn drops_chest(kind: EnemyType) -> bool {
+    is_elite(kind)
+}
+
+fn reset_reveal(mut reveal: ResMut<ChestReveal>) {
+    *reveal = ChestReveal::default();
+}
+
+pub fn spawn_chest(commands: &mut Commands, fonts: &FontAssets, position: Vec2) {
+    let chest = commands
+        .spawn((
+            Text2d::new("$"),
+            fonts.body(22.0),
+            TextColor(Color::srgb(1.0, 0.8, 0.2)),
+            Transform::from_translation(position.extend(layers::PICKUPS)),
+            Chest,
+            InGameEntity,
+        ))
+        .id();
+    let beacon = Beacon {
+        label: "CHEST",
+        rarity: Rarity::Rare,
+    };
+    spawn_beacon(commands, fonts, chest, beacon);
+}
+
+fn drop_chests(
+    mut loot: LootSpawner,
+    mut pity: ResMut<DropPity>,
+    difficulty: Res<DifficultyConfig>,
+    stats: Res<PlayerStats>,
+    mut kills: MessageReader<EnemyKilledEvent>,
+) {
+    let mut rng = thread_rng();
+    for kill in kills.read() {
+        if drops_chest(kill.kind) {
+            let mut drops = ELITE_DROPS.roll(stats.luck, &mut rng);
+            pity.settle(PityCategory::Chest, difficulty.pity.chest, true, &mut drops);
+            // A boss never leaves empty-handed
+            if kill.kind == EnemyType::Boss && !drops.contains(&Drop::Chest) {
+                drops.push(Drop::Chest);
+            }
+            loot.spawn(drops, kill.position);
+        }
+    }
+}
+
+/// Walking onto a chest rolls and applies every reward at once, then pauses
+/// on the reveal screen to list them.
+fn open_chests(
+    mut commands: Commands,
+    mut player: Query<(&Transform, &mut WeaponSlots), With<Player>>,
+    chests: Query<(Entity, &Transform), With<Chest>>,
+    mut pool: ResMut<UpgradePool>,
+    mut stats: ResMut<PlayerStats>,
+    // The upgrades can deepen the curse, and the gold is paid out after
+    mut payout: ParamSet<(ResMut<Curse>, LootSpawner)>,
+    mut reveal: ResMut<ChestReveal>,
+    mut log: MessageWriter<LogMessage>,
+    mut build_changed: MessageWriter<BuildChanged>,
+    mut next_state: ResMut<NextState<PlayState>>,
+) {
+    let Ok((player_tf, mut weapons)) = player.single_mut() else {
+        return;
+    };
+    let player_pos = player_tf.translation.truncate();
+    let Some((chest, _)) = chests
+        .iter()
+        .find(|(_, tf)| tf.translation.truncate().distance(player_pos) <= CHEST_RADIUS)
+    else {
+        return;
+    };
+    commands.entity(chest).despawn();
+
+    let mut rng = thread_rng();
+    let size = roll_chest_size(&mut rng);
+    let mut curse = payout.p0();
+    let contents = fill_chest(size, || {
+        // One at a time, so each pick sees the stacks the last one added
+        let upgrade = pool.draw(1, stats.luck, &mut rng).first().copied()?;
+        pool.take(
+            upgrade,
+            &mut UpgradeTargets {
+                stats: &mut stats,
+                curse: &mut curse,
+                weapons: &mut weapons,
+            },
+        );
+        Some(upgrade)
+    });
+    let curse = Curse(curse.0);
+    let mut loot = payout.p1();
+    let rewards = contents
+        .into_iter()
+        .map(|reward| match reward {
+            ChestReward::Gold(amount) => {
+                loot.spawn([Drop::Gold(amount)], player_pos);
+                ChestReward::Gold(curse.reward_gold(amount)).describe()
+            }
+            ChestReward::Upgrade(_) => reward.describe(),
+        })
+        .collect();
+
+    build_changed.write(BuildChanged);
+    log.write(LogMessage(format!("Opened a chest: {size} rewards")));
+    *reveal = ChestReveal {
+        rewards,
+        shown: 0,
+        timer: Timer::from_seconds(REVEAL_STEP_SECS, TimerMode::Repeating),
+        skip: false,
+    };
+    next_state.set(PlayState::ChestReveal);
+}
+
+fn spawn_reveal_ui(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            DespawnOnExit(PlayState::ChestReveal),
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Text::new("TREASURE!"),
+                fonts.display(40.0),
+                TextColor(Color::srgb(1.0, 0.8, 0.2)),
+            ));
+            root.spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                RevealList,
+            ));
+        });
+}
+
+/// Adds one reward line per step, or all of them once skipped. The gameplay
+/// clock is stopped here, so this runs on real time.
+fn advance_reveal(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    time: Res<Time>,
+    mut reveal: ResMut<ChestReveal>,
+    list: Query<Entity, With<RevealList>>,
+) {
+    let Ok(list) = list.single() else {
+        return;
+    };
+    let remaining = reveal.rewards.len() - reveal.shown;
+    if remaining == 0 {
+        return;
+    }
+    let count = if reveal.skip {
+        remaining
+    } else if reveal.timer.tick(time.delta()).just_finished() {
+        1
+    } else {
+        return;
+    };
+
+    let start = reveal.shown;
+    reveal.shown += count;
+    let lines = reveal.rewards[start..reveal.shown].to_vec();
+    let done = reveal.shown == reveal.rewards.len();
+
+    commands.entity(list).with_children(|list| {
+        for line in lines {
+            list.spawn((Text::new(line), fonts.body(22.0), TextColor(Color::WHITE)));
+        }
+        if done {
+            list.spawn((
+                Text::new("[Space] Continue"),
+                fonts.body(18.0),
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            ));
+        }
+    });
+}
+
+/// Space shows the rest of the reveal at once, then closes it.
+fn close_reveal(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut reveal: ResMut<ChestReveal>,
+    mut next_state: ResMut<NextState<PlayState>>,
+) {
+    if !keys.any_just_pressed([KeyCode::Space, KeyCode::Enter, KeyCode::Escape]) {
+        return;
+    }
+    if reveal.shown < reveal.rewards.len() {
+        reveal.skip = true;
+        return;
+    }
+    next_state.set(PlayState::Running);
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::upgrades::find_upgrade;
+
+    fn lines(rewards: Vec<ChestReward>) -> Vec<String> {
+        rewards.into_iter().map(ChestReward::describe).collect()
+    }
+
+    #[test]
+    fn chest_sizes_roll_six_three_one() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut counts = [0u32; 6];
+        for _ in 0..10_000 {
+            counts[roll_chest_size(&mut rng)] += 1;
+        }
+        assert_eq!(counts[0] + counts[2] + counts[4], 0);
+        for (size, share) in [(1, 0.6), (3, 0.3), (5, 0.1)] {
+            let rolled = counts[size] as f32 / 10_000.0;
+            assert!((rolled - share).abs() < 0.02, "size {size} rolled {rolled}");
+        }
+    }
+
+    #[test]
+    fn fill_chest_ends_with_the_chest_gold() {
+        let vitality = find_upgrade("vitality").unwrap();
+        let rewards = fill_chest(3, || Some(vitality));
+        let upgrade = vitality.description.to_string();
+        assert_eq!(
+            lines(rewards),
+            [
+                upgrade.clone(),
+                upgrade.clone(),
+                upgrade,
+                format!("+{CHEST_GOLD} gold")
+            ]
+        );
+    }
+
+    #[test]
+    fn fill_chest_pays_gold_once_the_pool_runs_dry() {
+        let vitality = find_upgrade("vitality").unwrap();
+        let mut left = 1;
+        let rewards = fill_chest(3, || {
+            (left > 0).then(|| {
+                left -= 1;
+                vitality
+            })
+        });
+        let fallback = format!("+{FALLBACK_GOLD} gold");
+        assert_eq!(
+            lines(rewards),
+            [
+                vitality.description.to_string(),
+                fallback.clone(),
+                fallback,
+                format!("+{CHEST_GOLD} gold"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Close calls: a threat that slips past the player without touching them
+//! briefly slows the enemy side and is worth a few points. A threat counts
+//! once it has come inside the near-miss band around the player and left it
+//! again without ever reaching them.
+
+use bevy::prelude::*;
+
+use crate::despawn::Dead;
+use crate::performance::CosmeticBudget;
+use crate::popups::StatusPopup;
+use crate::ultimate::TimeDilation;
+use crate::{
+    Downed, Enemy, EnemyProjectile, GameState, GameTime, GameplaySet, Hitbox, PlayState,
+    PlayerCharacter, ResetSet, Score, Settings,
+};
+
+/// The band reaches this multiple of the distance at which the threat hits.
+const BAND_SCALE: f32 = 1.25;
+const SLOW_SECS: f32 = 0.4;
+const ENEMY_SLOW: f32 = 0.5;
+const COOLDOWN_SECS: f32 = 5.0;
+const CLOSE_CALL_POINTS: u32 = 25;
+const PULSE_RADIUS: f32 = 60.0;
+const PULSE_COLOR: Color = Color::srgb(0.7, 0.9, 1.0);
+
+pub struct CloseCallPlugin;
+
+impl Plugin for CloseCallPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CloseCall>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                reset_close_call.in_set(ResetSet),
+            )
+            .add_systems(
+                Update,
+                (track_near_misses, tick_close_call)
+                    .chain()
+                    .in_set(GameplaySet)
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(Update, draw_close_call_pulse);
+    }
+}
+
+#[derive(Resource, Default)]
+struct CloseCall {
+    /// The slow still running, if any.
+    slow: Option<Timer>,
+    /// Time until another close call can count.
+    cooldown: f32,
+    /// Where the last one happened, for the pulse.
+    at: Vec2,
+}
+
+/// On a threat while it is inside the band.
+#[derive(Component)]
+struct NearMissTracker {
+    /// Reached the player at some point, so leaving is no escape.
+    touched: bool,
+}
+
+fn reset_close_call(mut close_call: ResMut<CloseCall>) {
+    *close_call = CloseCall::default();
+}
+
+/// The tracker lives on the threat, so one that despawns mid-pass takes it
+/// along and is simply never counted.
+fn track_near_misses(
+    mut commands: Commands,
+    mut close_call: ResMut<CloseCall>,
+    mut dilation: ResMut<TimeDilation>,
+    mut score: ResMut<Score>,
+    mut popups: MessageWriter<StatusPopup>,
+    players: Query<(&Transform, &Hitbox), (With<PlayerCharacter>, Without<Downed>)>,
+    mut threats: Query<
+        (Entity, &Transform, &Hitbox, Option<&mut NearMissTracker>),
+        (Or<(With<Enemy>, With<EnemyProjectile>)>, Without<Dead>),
+    >,
+) {
+    let players: Vec<(Vec2, f32)> = players
+        .iter()
+        .map(|(tf, hitbox)| (tf.translation.truncate(), hitbox.radius))
+        .collect();
+    for (entity, tf, hitbox, tracker) in &mut threats {
+        // Nothing to dodge while it can't touch anyone
+        if hitbox.intangible {
+            continue;
+        }
+        let position = tf.translation.truncate();
+        // How far out of reach of the nearest player, in units of the hit
+        // distance: below 1 it touches, up to `BAND_SCALE` it's in the band
+        let Some((closest, player)) = players
+            .iter()
+            .map(|&(player, radius)| (position.distance(player) / (radius + hitbox.radius), player))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+        else {
+            continue;
+        };
+
+        match tracker {
+            Some(mut tracker) if closest <= BAND_SCALE => {
+                tracker.touched |= closest < 1.0;
+            }
+            Some(tracker) => {
+                commands.entity(entity).remove::<NearMissTracker>();
+                if tracker.touched || close_call.cooldown > 0.0 {
+                    continue;
+                }
+                close_call.slow = Some(Timer::from_seconds(SLOW_SECS, TimerMode::Once));
+                close_call.cooldown = COOLDOWN_SECS;
+                close_call.at = player;
+                dilation.close_call = ENEMY_SLOW;
+                score.0 += CLOSE_CALL_POINTS;
+                popups.write(StatusPopup {
+                    position: player + Vec2::new(0.0, 24.0),
+                    text: "Close call!",
+                });
+            }
+            None if closest <= BAND_SCALE => {
+                commands.entity(entity).insert(NearMissTracker {
+                    touched: closest < 1.0,
+                });
+            }
+            None => {}
+        }
+    }
+}
+
+fn tick_close_call(
+    time: GameTime,
+    mut close_call: ResMut<CloseCall>,
+    mut dilation: ResMut<TimeDilation>,
+) {
+    close_call.cooldown = (close_call.cooldown - time.delta_secs()).max(0.0);
+    let Some(slow) = close_call.slow.as_mut() else {
+        return;
+    };
+    if slow.tick(time.delta()).is_finished() {
+        close_call.slow = None;
+        dilation.close_call = 1.0;
+    }
+}
+
+/// A ring spreading out from where it happened while the slow lasts. With
+/// reduced motion or flourishes off the popup alone marks it.
+fn draw_close_call_pulse(
+    mut gizmos: Gizmos,
+    settings: Res<Settings>,
+    budget: Res<CosmeticBudget>,
+    close_call: Res<CloseCall>,
+) {
+    if settings.reduced_motion || !budget.flourishes {
+        return;
+    }
+    let Some(slow) = &close_call.slow else {
+        return;
+    };
+    let t = slow.fraction();
+    gizmos.circle_2d(
+        close_call.at,
+        PULSE_RADIUS * t,
+        PULSE_COLOR.with_alpha(0.6 * (1.0 - t)),
+    );
+}
@@ -0,0 +1,320 @@
+use std::collections::VecDeque;
+
+use bevy::input::InputSystems;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::challenge::RunRules;
+use crate::difficulty::DifficultyConfig;
+use crate::fonts::FontAssets;
+use crate::input::PlayerInputSystems;
+use crate::levelup::Experience;
+use crate::{
+    BulletPool, Curse, DamageEvent, DamageKind, DamageType, Enemy, EnemyType, GameTimeScale, Gold,
+    Invulnerable, Player, PlayerStats, SpawnContext, Team, roll_spawn_position, spawn_enemy,
+};
+
+const SCROLLBACK_LINES: usize = 10;
+/// Effectively forever; `god` again takes it off.
+const GOD_MODE_SECS: f32 = 1.0e9;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_systems(Startup, spawn_console)
+            .add_systems(
+                PreUpdate,
+                capture_console_input
+                    .after(InputSystems)
+                    .before(PlayerInputSystems),
+            )
+            .add_systems(Update, (run_console_commands, update_console_ui).chain());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ConsoleCommand {
+    Spawn(EnemyType, u32),
+    GiveXp(f32),
+    GiveGold(u32),
+    God,
+    SetSpeed(f32),
+    KillAll,
+    Seed,
+    TimeScale(f32),
+    TogglePool,
+}
+
+/// Splits on whitespace and matches the words against the known commands.
+fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let tokens: Vec<String> = line.split_whitespace().map(str::to_lowercase).collect();
+    let words: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    let number = |word: &str| {
+        word.parse::<f32>()
+            .map_err(|_| format!("not a number: {word}"))
+    };
+    let count = |word: &str| {
+        word.parse::<u32>()
+            .map_err(|_| format!("not a count: {word}"))
+    };
+
+    match words.as_slice() {
+        ["spawn", kind] | ["spawn", kind, _] => {
+            let kind = EnemyType::from_id(kind).ok_or_else(|| format!("no such enemy: {kind}"))?;
+            let n = words.get(2).map_or(Ok(1), |w| count(w))?;
+            Ok(ConsoleCommand::Spawn(kind, n))
+        }
+        ["give", "xp", amount] => Ok(ConsoleCommand::GiveXp(number(amount)?)),
+        ["give", "gold", amount] => Ok(ConsoleCommand::GiveGold(count(amount)?)),
+        ["god"] => Ok(ConsoleCommand::God),
+        ["setspeed", speed] => Ok(ConsoleCommand::SetSpeed(number(speed)?)),
+        ["kill_all"] => Ok(ConsoleCommand::KillAll),
+        ["seed"] => Ok(ConsoleCommand::Seed),
+        ["timescale", scale] => Ok(ConsoleCommand::TimeScale(number(scale)?)),
+        ["pool"] => Ok(ConsoleCommand::TogglePool),
+        [] => Err(String::new()),
+        _ => Err(format!("unknown command: {line}")),
+    }
+}
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    line: String,
+    scrollback: VecDeque<String>,
+    /// Parsed commands waiting for `run_console_commands`.
+    pending: Vec<ConsoleCommand>,
+}
+
+impl Console {
+    fn print(&mut self, text: impl Into<String>) {
+        if self.scrollback.len() == SCROLLBACK_LINES {
+            self.scrollback.pop_front();
+        }
+        self.scrollback.push_back(text.into());
+    }
+}
+
+/// While open, the console takes every key and click for itself and clears
+/// them so nothing downstream sees them.
+fn capture_console_input(
+    mut events: MessageReader<KeyboardInput>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+    mut console: ResMut<Console>,
+) {
+    if !console.open {
+        if keys.just_pressed(KeyCode::Backquote) {
+            console.open = true;
+            // The backtick that opened it is already in the queue
+            events.clear();
+            keys.reset_all();
+        }
+        return;
+    }
+
+    for event in events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Escape => {
+                console.open = false;
+                console.line.clear();
+            }
+            Key::Enter => {
+                let line = std::mem::take(&mut console.line);
+                console.print(format!("> {line}"));
+                match parse_command(&line) {
+                    Ok(command) => console.pending.push(command),
+                    Err(err) if err.is_empty() => {}
+                    Err(err) => console.print(err),
+                }
+            }
+            Key::Backspace => {
+                console.line.pop();
+            }
+            _ if event.key_code == KeyCode::Backquote => console.open = false,
+            _ => {
+                if let Some(text) = &event.text {
+                    console
+                        .line
+                        .extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+    }
+
+    keys.reset_all();
+    mouse.reset_all();
+}
+
+fn run_console_commands(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut console: ResMut<Console>,
+    mut stats: ResMut<PlayerStats>,
+    mut experience: ResMut<Experience>,
+    mut gold: ResMut<Gold>,
+    mut time_scale: ResMut<GameTimeScale>,
+    mut pool: ResMut<BulletPool>,
+    mut damage_events: MessageWriter<DamageEvent>,
+    curse: Res<Curse>,
+    rules: Res<RunRules>,
+    difficulty: Res<DifficultyConfig>,
+    player: Query<(Entity, &Transform, Has<Invulnerable>), With<Player>>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+) {
+    if console.pending.is_empty() {
+        return;
+    }
+    let pending = std::mem::take(&mut console.pending);
+    for command in pending {
+        let player = player.single().ok();
+        match command {
+            ConsoleCommand::Spawn(kind, n) => {
+                let Some((_, tf, _)) = player else {
+                    console.print("no player to spawn around");
+                    continue;
+                };
+                let center = tf.translation.truncate();
+                let ctx = SpawnContext {
+                    players: vec![center],
+                    bias: None,
+                };
+                let mut rng = thread_rng();
+                let mut spawned = 0;
+                for _ in 0..n {
+                    if let Some(position) = roll_spawn_position(&mut rng, center, &ctx) {
+                        spawn_enemy(
+                            &mut commands,
+                            &fonts,
+                            position,
+                            kind,
+                            &curse,
+                            &rules,
+                            &difficulty,
+                        );
+                        spawned += 1;
+                    }
+                }
+                console.print(format!("spawned {spawned} {kind:?}"));
+            }
+            ConsoleCommand::GiveXp(amount) => {
+                experience.xp += amount;
+                console.print(format!("+{amount} xp"));
+            }
+            ConsoleCommand::GiveGold(amount) => {
+                gold.0 += amount;
+                console.print(format!("+{amount} gold"));
+            }
+            ConsoleCommand::God => {
+                let Some((entity, _, invulnerable)) = player else {
+                    console.print("no player");
+                    continue;
+                };
+                if invulnerable {
+                    commands.entity(entity).remove::<Invulnerable>();
+                    console.print("god mode off");
+                } else {
+                    commands
+                        .entity(entity)
+                        .insert(Invulnerable(Timer::from_seconds(
+                            GOD_MODE_SECS,
+                            TimerMode::Once,
+                        )));
+                    console.print("god mode on");
+                }
+            }
+            ConsoleCommand::SetSpeed(speed) => {
+                stats.move_speed = speed;
+                console.print(format!("move speed {speed}"));
+            }
+            ConsoleCommand::KillAll => {
+                for (entity, tf) in &enemies {
+                    damage_events.write(DamageEvent {
+                        target: entity,
+                        amount: i32::MAX,
+                        source_team: Team::Player,
+                        kind: DamageKind::Projectile,
+                        damage_type: DamageType::Physical,
+                        origin: tf.translation.truncate(),
+                        weapon: None,
+                    });
+                }
+                console.print(format!("killed {}", enemies.iter().len()));
+            }
+            ConsoleCommand::Seed => {
+                console.print("runs are not seeded; spawning uses the thread RNG");
+            }
+            ConsoleCommand::TimeScale(scale) => {
+                time_scale.scale = scale.max(0.0);
+                console.print(format!("time scale {scale}"));
+            }
+            ConsoleCommand::TogglePool => {
+                pool.enabled = !pool.enabled;
+                let state = if pool.enabled { "on" } else { "off" };
+                console.print(format!("bullet pool {state}"));
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct ConsolePanel;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn spawn_console(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            // Above every other piece of UI
+            GlobalZIndex(10),
+            Visibility::Hidden,
+            ConsolePanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(""),
+                fonts.body(16.0),
+                TextColor(Color::srgb(0.6, 1.0, 0.6)),
+                ConsoleText,
+            ));
+        });
+}
+
+fn update_console_ui(
+    console: Res<Console>,
+    mut panel: Query<&mut Visibility, With<ConsolePanel>>,
+    mut text: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    for mut visibility in &mut panel {
+        *visibility = if console.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    for mut text in &mut text {
+        let mut shown: Vec<&str> = console.scrollback.iter().map(String::as_str).collect();
+        let prompt = format!("> {}_", console.line);
+        shown.push(&prompt);
+        text.0 = shown.join("\n");
+    }
+}
@@ -0,0 +1,269 @@
+//! One readiness widget for every ability on a cooldown or a charge. Each
+//! indicator is an icon whose backing fills from the bottom as the ability
+//! recovers, greyed out until it is ready and pulsing once when it gets
+//! there. An ability joins the row through `Ability` and a readiness lookup
+//! in `AbilityCooldowns`; the row itself follows whatever the lookup says
+//! the player has, so gaining or losing one mid-run reshapes it.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::melee::MeleeCooldown;
+use crate::ultimate::Ultimate;
+use crate::{GameState, Settings, spawn_state_ui_root};
+
+const INDICATOR_SIZE: f32 = 24.0;
+const FRAME_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+const UNAVAILABLE_COLOR: Color = Color::srgb(0.45, 0.45, 0.45);
+const PULSE_SECS: f32 = 0.3;
+
+pub struct CooldownPlugin;
+
+impl Plugin for CooldownPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_cooldown_row)
+            .add_systems(
+                Update,
+                (sync_cooldown_row, update_cooldown_indicators)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Ability {
+    /// The right-click swipe.
+    Melee,
+    /// The kill-charged time slow.
+    Ultimate,
+}
+
+/// Every ability, in the order the row shows them.
+const ALL_ABILITIES: &[Ability] = &[Ability::Melee, Ability::Ultimate];
+
+impl Ability {
+    fn icon(self) -> &'static str {
+        match self {
+            Ability::Melee => "/",
+            Ability::Ultimate => "~",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Ability::Melee => Color::srgb(1.0, 0.95, 0.8),
+            Ability::Ultimate => Color::srgb(0.7, 0.9, 1.0),
+        }
+    }
+}
+
+/// Where each ability's readiness is read from.
+#[derive(SystemParam)]
+pub struct AbilityCooldowns<'w> {
+    melee: Res<'w, MeleeCooldown>,
+    ultimate: Res<'w, Ultimate>,
+}
+
+impl AbilityCooldowns<'_> {
+    /// How far `ability` is back toward ready, from 0 to 1, or `None` if the
+    /// player doesn't have it.
+    pub fn readiness(&self, ability: Ability) -> Option<f32> {
+        let readiness = match ability {
+            Ability::Melee => self.melee.readiness(),
+            Ability::Ultimate => self.ultimate.readiness(),
+        };
+        Some(readiness.clamp(0.0, 1.0))
+    }
+
+    fn loadout(&self) -> Vec<Ability> {
+        ALL_ABILITIES
+            .iter()
+            .copied()
+            .filter(|&ability| self.readiness(ability).is_some())
+            .collect()
+    }
+}
+
+#[derive(Component)]
+struct CooldownRow;
+
+#[derive(Component)]
+struct CooldownIndicator {
+    ability: Ability,
+    fill: Entity,
+    glyph: Entity,
+    /// Ready as of last frame, to catch the moment it becomes so.
+    ready: bool,
+    pulse: Timer,
+}
+
+/// Just above the ultimate bar. Spawned empty for every run, so a restart
+/// always starts from the fresh loadout.
+fn spawn_cooldown_row(mut commands: Commands) {
+    let root = spawn_state_ui_root(&mut commands, GameState::Playing);
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(82.0),
+            left: Val::Px(10.0),
+            column_gap: Val::Px(4.0),
+            ..default()
+        },
+        CooldownRow,
+        ChildOf(root),
+    ));
+}
+
+fn spawn_indicator(commands: &mut Commands, fonts: &FontAssets, row: Entity, ability: Ability) {
+    let fill = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(0.0),
+                ..default()
+            },
+            BackgroundColor(ability.color().with_alpha(0.35)),
+        ))
+        .id();
+    let glyph = commands
+        .spawn((
+            Text::new(ability.icon()),
+            fonts.body(16.0),
+            TextColor(UNAVAILABLE_COLOR),
+        ))
+        .id();
+    let mut pulse = Timer::from_seconds(PULSE_SECS, TimerMode::Once);
+    pulse.finish();
+    commands
+        .spawn((
+            Node {
+                width: Val::Px(INDICATOR_SIZE),
+                height: Val::Px(INDICATOR_SIZE),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(FRAME_COLOR),
+            CooldownIndicator {
+                ability,
+                fill,
+                glyph,
+                ready: false,
+                pulse,
+            },
+            ChildOf(row),
+        ))
+        .add_children(&[fill, glyph]);
+}
+
+/// Rebuilds the row only when the loadout differs from what it shows.
+fn sync_cooldown_row(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    cooldowns: AbilityCooldowns,
+    row: Query<(Entity, Option<&Children>), With<CooldownRow>>,
+    indicators: Query<&CooldownIndicator>,
+) {
+    let Ok((row, children)) = row.single() else {
+        return;
+    };
+    let shown: Vec<Ability> = children
+        .into_iter()
+        .flatten()
+        .filter_map(|&child| indicators.get(child).ok())
+        .map(|indicator| indicator.ability)
+        .collect();
+    let loadout = cooldowns.loadout();
+    if shown == loadout {
+        return;
+    }
+    commands.entity(row).despawn_related::<Children>();
+    for ability in loadout {
+        spawn_indicator(&mut commands, &fonts, row, ability);
+    }
+}
+
+/// Node and color writes are skipped when nothing moved, so a full, idle
+/// row costs no layout.
+fn update_cooldown_indicators(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    cooldowns: AbilityCooldowns,
+    mut indicators: Query<(&mut CooldownIndicator, &mut BackgroundColor)>,
+    mut fills: Query<&mut Node, Without<CooldownIndicator>>,
+    mut glyphs: Query<&mut TextColor>,
+) {
+    for (mut indicator, mut background) in &mut indicators {
+        let Some(readiness) = cooldowns.readiness(indicator.ability) else {
+            continue;
+        };
+        let ready = readiness >= 1.0;
+        if ready && !indicator.ready && !settings.reduced_motion {
+            indicator.pulse.reset();
+        }
+        indicator.ready = ready;
+        indicator.pulse.tick(time.delta());
+
+        if let Ok(mut node) = fills.get_mut(indicator.fill) {
+            let height = Val::Percent(100.0 * readiness);
+            if node.height != height {
+                node.height = height;
+            }
+        }
+        if let Ok(mut color) = glyphs.get_mut(indicator.glyph) {
+            let glyph_color = if ready {
+                indicator.ability.color()
+            } else {
+                UNAVAILABLE_COLOR
+            };
+            if color.0 != glyph_color {
+                color.0 = glyph_color;
+            }
+        }
+        let flash = if indicator.pulse.is_finished() {
+            FRAME_COLOR
+        } else {
+            let t = indicator.pulse.fraction_remaining();
+            FRAME_COLOR.mix(&indicator.ability.color(), 0.6 * t)
+        };
+        if background.0 != flash {
+            background.0 = flash;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+
+    fn with_cooldowns<T>(world: &mut World, read: impl FnOnce(&AbilityCooldowns) -> T) -> T {
+        let mut state = SystemState::<AbilityCooldowns>::new(world);
+        read(&state.get(world))
+    }
+
+    #[test]
+    fn a_fresh_run_has_melee_ready_and_the_ultimate_empty() {
+        let mut world = World::new();
+        world.init_resource::<MeleeCooldown>();
+        world.init_resource::<Ultimate>();
+        with_cooldowns(&mut world, |cooldowns| {
+            assert_eq!(cooldowns.readiness(Ability::Melee), Some(1.0));
+            assert_eq!(cooldowns.readiness(Ability::Ultimate), Some(0.0));
+        });
+    }
+
+    #[test]
+    fn the_loadout_follows_the_row_order() {
+        let mut world = World::new();
+        world.init_resource::<MeleeCooldown>();
+        world.init_resource::<Ultimate>();
+        let loadout = with_cooldowns(&mut world, |cooldowns| cooldowns.loadout());
+        assert_eq!(loadout, ALL_ABILITIES);
+    }
+}
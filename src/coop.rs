@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::message_log::LogMessage;
+use crate::run_stats::RunStats;
+use crate::weapons::{TargetingMode, select_target};
+use crate::{
+    BulletPool, DOWNED_COLOR, Downed, Enemy, GameState, GameTime, Health, Hitbox, InGameEntity,
+    PLAYER_BASE_SPEED, ParkedBullet, ParkedBulletFilter, PlayState, PlayerCharacter, PlayerStats,
+    Settings, Team, layers, setup_new_game, spawn_projectile_fan, target_candidate,
+};
+
+/// Player two fires on its own, at whatever is closest within this range.
+const AUTO_FIRE_RANGE: f32 = 500.0;
+const AUTO_FIRE_SECS: f32 = 0.4;
+/// A teammate must stand this close to a downed player to revive them.
+const REVIVE_RADIUS: f32 = 40.0;
+const PLAYER_TWO_COLOR: Color = Color::srgb(0.4, 0.8, 1.0);
+
+pub struct CoopPlugin;
+
+impl Plugin for CoopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            spawn_player_two.after(setup_new_game),
+        )
+        .add_systems(
+            Update,
+            (
+                move_player_two,
+                player_two_auto_fire,
+                revive_downed,
+                tint_player_two,
+            )
+                .run_if(in_state(PlayState::Running)),
+        )
+        .add_systems(Update, draw_revive_progress)
+        .add_systems(Update, toggle_coop.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+/// The second player, on the arrow keys. With no mouse to aim it fires by itself.
+#[derive(Component)]
+struct PlayerTwo {
+    fire: Timer,
+}
+
+fn toggle_coop(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(KeyCode::Digit2) {
+        settings.coop = !settings.coop;
+        info!("co-op {}", if settings.coop { "on" } else { "off" });
+    }
+}
+
+fn spawn_player_two(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    settings: Res<Settings>,
+    stats: Res<PlayerStats>,
+) {
+    if !settings.coop {
+        return;
+    }
+
+    commands.spawn((
+        PlayerTwo {
+            fire: Timer::from_seconds(AUTO_FIRE_SECS, TimerMode::Repeating),
+        },
+        PlayerCharacter,
+        Health::new(stats.max_health),
+        Team::Player,
+        Hitbox::new(10.0),
+        InGameEntity,
+        Text2d::new("@"),
+        fonts.body(20.0),
+        TextColor(PLAYER_TWO_COLOR),
+        Transform::from_translation(Vec3::new(40.0, 0.0, layers::PLAYER)),
+    ));
+}
+
+fn move_player_two(
+    time: GameTime,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut player: Query<&mut Transform, (With<PlayerTwo>, Without<Downed>)>,
+) {
+    let Ok(mut transform) = player.single_mut() else {
+        return;
+    };
+
+    let mut direction = Vec2::ZERO;
+    if keys.pressed(KeyCode::ArrowLeft) {
+        direction.x -= 1.0;
+    }
+    if keys.pressed(KeyCode::ArrowRight) {
+        direction.x += 1.0;
+    }
+    if keys.pressed(KeyCode::ArrowUp) {
+        direction.y += 1.0;
+    }
+    if keys.pressed(KeyCode::ArrowDown) {
+        direction.y -= 1.0;
+    }
+
+    let delta = direction.normalize_or_zero() * PLAYER_BASE_SPEED * time.delta_secs();
+    transform.translation.x += delta.x;
+    transform.translation.y += delta.y;
+}
+
+fn player_two_auto_fire(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    time: GameTime,
+    stats: Res<PlayerStats>,
+    mut run_stats: ResMut<RunStats>,
+    mut pool: ResMut<BulletPool>,
+    mut player: Query<(&Transform, &mut PlayerTwo), (With<PlayerCharacter>, Without<Downed>)>,
+    enemies: Query<(Entity, &Transform, &Health, &Enemy)>,
+    mut parked: Query<ParkedBullet, ParkedBulletFilter>,
+) {
+    let Ok((tf, mut player_two)) = player.single_mut() else {
+        return;
+    };
+    if !player_two.fire.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let position = tf.translation.truncate();
+    let Some(target) = select_target(
+        TargetingMode::Nearest,
+        position,
+        AUTO_FIRE_RANGE,
+        enemies
+            .iter()
+            .map(|(e, tf, health, enemy)| target_candidate(e, tf, health, enemy)),
+        &mut thread_rng(),
+    )
+    .and_then(|target| enemies.get(target).ok())
+    .map(|(_, tf, ..)| tf.translation.truncate()) else {
+        return;
+    };
+
+    spawn_projectile_fan(
+        &mut commands,
+        &fonts,
+        &mut run_stats,
+        &mut pool,
+        &mut parked,
+        position,
+        (target - position).normalize_or_zero(),
+        stats.projectile_count,
+        &stats,
+        TargetingMode::Nearest,
+    );
+}
+
+/// A standing teammate next to a downed one fills their revive timer; walking
+/// off resets it.
+fn revive_downed(
+    mut commands: Commands,
+    time: GameTime,
+    mut log: MessageWriter<LogMessage>,
+    standing: Query<&Transform, (With<PlayerCharacter>, Without<Downed>)>,
+    mut downed: Query<(Entity, &Transform, &mut Downed, &mut Health)>,
+) {
+    for (entity, tf, mut down, mut health) in &mut downed {
+        let position = tf.translation.truncate();
+        let helped = standing
+            .iter()
+            .any(|other| other.translation.truncate().distance(position) <= REVIVE_RADIUS);
+        if !helped {
+            down.revive.reset();
+            continue;
+        }
+
+        if down.revive.tick(time.delta()).just_finished() {
+            health.current = (health.max / 2).max(1);
+            commands.entity(entity).remove::<Downed>();
+            log.write(LogMessage("A player was revived".to_string()));
+        }
+    }
+}
+
+fn tint_player_two(mut player: Query<(&mut TextColor, Has<Downed>), With<PlayerTwo>>) {
+    for (mut color, downed) in &mut player {
+        color.0 = if downed {
+            DOWNED_COLOR
+        } else {
+            PLAYER_TWO_COLOR
+        };
+    }
+}
+
+fn draw_revive_progress(mut gizmos: Gizmos, downed: Query<(&Transform, &Downed)>) {
+    for (tf, down) in &downed {
+        let center = tf.translation.truncate();
+        gizmos.circle_2d(center, REVIVE_RADIUS, Color::srgba(1.0, 1.0, 1.0, 0.2));
+        if down.revive.fraction() > 0.0 {
+            gizmos.arc_2d(
+                Isometry2d::from_translation(center),
+                std::f32::consts::TAU * down.revive.fraction(),
+                REVIVE_RADIUS,
+                Color::srgb(0.4, 1.0, 0.4),
+            );
+        }
+    }
+}
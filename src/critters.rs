@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::loot::{Drop, DropTable, LootEntry, LootSpawner};
+use crate::{
+    DeathEvent, GameTime, Health, Hitbox, InGameEntity, PlayState, Player, PlayerStats, Team,
+    layers,
+};
+
+pub const MAX_CRITTERS: usize = 3;
+
+const WANDER_SPEED: f32 = 60.0;
+const FLEE_SPEED: f32 = 200.0;
+const FLEE_RADIUS: f32 = 150.0;
+const LIFETIME_SECS: f32 = 30.0;
+const CRITTER_DROPS: DropTable = DropTable::new(&[
+    (1.0, LootEntry::Drop(Drop::Gold(3))),
+    (1.0, LootEntry::Drop(Drop::Xp(3.0))),
+]);
+
+pub struct CritterPlugin;
+
+impl Plugin for CritterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (move_critters, expire_critters, critter_death_system)
+                .run_if(in_state(PlayState::Running)),
+        );
+    }
+}
+
+/// Harmless wildlife on the neutral team: it can be shot but never hurts
+/// anything, and pays out a little when it dies.
+#[derive(Component)]
+pub struct Critter {
+    heading: Vec2,
+    turn: Timer,
+    lifetime: Timer,
+}
+
+fn turn_timer(rng: &mut impl Rng) -> Timer {
+    Timer::from_seconds(rng.gen_range(1.0..2.0), TimerMode::Once)
+}
+
+pub fn spawn_critter(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    rng: &mut impl Rng,
+    position: Vec2,
+) {
+    commands.spawn((
+        Critter {
+            heading: Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU)),
+            turn: turn_timer(rng),
+            lifetime: Timer::from_seconds(LIFETIME_SECS, TimerMode::Once),
+        },
+        Health::new(1),
+        Team::Neutral,
+        Hitbox::new(8.0),
+        Text2d::new("r"),
+        fonts.body(18.0),
+        TextColor(Color::srgb(0.85, 0.75, 0.6)),
+        Transform::from_translation(position.extend(layers::ENEMIES)),
+        InGameEntity,
+    ));
+}
+
+/// Wanders in a new random direction every second or two, and bolts straight
+/// away from the player when they get close.
+fn move_critters(
+    time: GameTime,
+    player: Query<&Transform, (With<Player>, Without<Critter>)>,
+    mut critters: Query<(&mut Transform, &mut Critter)>,
+) {
+    let player_pos = player.single().map(|tf| tf.translation.truncate()).ok();
+    let mut rng = thread_rng();
+
+    for (mut tf, mut critter) in &mut critters {
+        if critter.turn.tick(time.delta()).is_finished() {
+            critter.heading = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+            critter.turn = turn_timer(&mut rng);
+        }
+
+        let position = tf.translation.truncate();
+        let velocity = match player_pos {
+            Some(player_pos) if position.distance(player_pos) < FLEE_RADIUS => {
+                (position - player_pos).normalize_or(critter.heading) * FLEE_SPEED
+            }
+            _ => critter.heading * WANDER_SPEED,
+        };
+
+        let delta = velocity * time.delta_secs();
+        tf.translation.x += delta.x;
+        tf.translation.y += delta.y;
+    }
+}
+
+fn expire_critters(
+    mut commands: Commands,
+    time: GameTime,
+    mut critters: Query<(Entity, &mut Critter)>,
+) {
+    for (entity, mut critter) in &mut critters {
+        if critter.lifetime.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn critter_death_system(
+    mut commands: Commands,
+    mut death_events: MessageReader<DeathEvent>,
+    mut loot: LootSpawner,
+    stats: Res<PlayerStats>,
+    critters: Query<&Transform, With<Critter>>,
+) {
+    let mut rng = thread_rng();
+    for ev in death_events.read() {
+        let Ok(tf) = critters.get(ev.entity) else {
+            continue;
+        };
+
+        loot.spawn(
+            CRITTER_DROPS.roll(stats.luck, &mut rng),
+            tf.translation.truncate(),
+        );
+        commands.entity(ev.entity).despawn();
+    }
+}
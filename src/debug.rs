@@ -0,0 +1,321 @@
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::performance::CosmeticBudget;
+use crate::popups::ScorePopup;
+use crate::{
+    Afterimage, DeathParticle, Downed, Enemy, Hitbox, Invulnerable, Knockback, Player,
+    PlayerCharacter, SPAWN_RING_MAX, SPAWN_RING_MIN, Settings, Team, enemy_heading, enemy_speed,
+    enemy_standoff,
+};
+
+/// Text writes per second above which the HUD is doing more work than it
+/// should; the overlay turns red and a warning is logged.
+const TEXT_WRITE_BUDGET: u32 = 60;
+
+#[cfg(feature = "dev")]
+const MIN_TIME_SCALE: f32 = 0.1;
+#[cfg(feature = "dev")]
+const MAX_TIME_SCALE: f32 = 4.0;
+
+pub struct DebugDrawPlugin;
+
+impl Plugin for DebugDrawPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugDraw>()
+            .init_resource::<TextWrites>()
+            .add_systems(Startup, (spawn_text_writes_overlay, spawn_budget_overlay))
+            .add_systems(
+                Update,
+                (
+                    toggle_debug_draw,
+                    draw_collision_shapes.run_if(|d: Res<DebugDraw>| d.collision_shapes),
+                    draw_enemy_velocities.run_if(|d: Res<DebugDraw>| d.velocities),
+                    draw_enemy_states.run_if(|d: Res<DebugDraw>| d.ai_states),
+                    draw_spawn_ring.run_if(|d: Res<DebugDraw>| d.spawn_ring),
+                )
+                    .chain(),
+            )
+            // Last, so every Update write this frame has landed
+            .add_systems(
+                Last,
+                (
+                    count_text_writes,
+                    update_text_writes_overlay,
+                    update_budget_overlay,
+                )
+                    .chain(),
+            );
+
+        #[cfg(feature = "dev")]
+        app.add_plugins(crate::console::ConsolePlugin)
+            .add_plugins(crate::tuning::TuningPlugin)
+            .add_plugins(crate::leaks::LeakWatchPlugin)
+            .add_systems(Update, debug_time_controls);
+    }
+}
+
+/// Gizmo overlays, one flag per category. Every drawing system is gated on its
+/// flag by a run condition, so nothing is queried while a category is off.
+#[derive(Resource, Default)]
+pub struct DebugDraw {
+    pub collision_shapes: bool,
+    pub velocities: bool,
+    pub ai_states: bool,
+    pub spawn_ring: bool,
+    pub text_writes: bool,
+    /// Live cosmetics against the performance preset's caps.
+    pub cosmetic_budget: bool,
+    /// Spawn, kill and live-enemy graph over the last minute.
+    pub telemetry: bool,
+}
+
+/// How many `Text` components were changed over the last whole second.
+#[derive(Resource)]
+struct TextWrites {
+    window: Timer,
+    counting: u32,
+    last_second: u32,
+}
+
+impl Default for TextWrites {
+    fn default() -> Self {
+        Self {
+            window: Timer::from_seconds(1.0, TimerMode::Repeating),
+            counting: 0,
+            last_second: 0,
+        }
+    }
+}
+
+#[derive(Component)]
+struct TextWritesOverlay;
+
+#[derive(Component)]
+struct BudgetOverlay;
+
+/// P freezes gameplay, `.` lets one frame through while frozen, and `[`/`]`
+/// halve or double gameplay speed.
+#[cfg(feature = "dev")]
+fn debug_time_controls(input: Res<ButtonInput<KeyCode>>, mut scale: ResMut<crate::GameTimeScale>) {
+    if input.just_pressed(KeyCode::KeyP) {
+        scale.frozen = !scale.frozen;
+        info!(
+            "gameplay {}",
+            if scale.frozen { "frozen" } else { "resumed" }
+        );
+    }
+    if input.just_pressed(KeyCode::Period) && scale.frozen {
+        scale.step = true;
+    }
+
+    let factor = if input.just_pressed(KeyCode::BracketLeft) {
+        0.5
+    } else if input.just_pressed(KeyCode::BracketRight) {
+        2.0
+    } else {
+        return;
+    };
+    scale.scale = (scale.scale * factor).clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+    info!("gameplay speed x{}", scale.scale);
+}
+
+fn toggle_debug_draw(input: Res<ButtonInput<KeyCode>>, mut debug: ResMut<DebugDraw>) {
+    if input.just_pressed(KeyCode::F2) {
+        debug.cosmetic_budget = !debug.cosmetic_budget;
+    }
+    if input.just_pressed(KeyCode::F3) {
+        debug.telemetry = !debug.telemetry;
+    }
+    if input.just_pressed(KeyCode::F4) {
+        debug.collision_shapes = !debug.collision_shapes;
+    }
+    if input.just_pressed(KeyCode::F5) {
+        debug.velocities = !debug.velocities;
+    }
+    if input.just_pressed(KeyCode::F6) {
+        debug.ai_states = !debug.ai_states;
+    }
+    if input.just_pressed(KeyCode::F7) {
+        debug.spawn_ring = !debug.spawn_ring;
+    }
+    if input.just_pressed(KeyCode::F8) {
+        debug.text_writes = !debug.text_writes;
+    }
+}
+
+fn draw_collision_shapes(mut gizmos: Gizmos, hitboxes: Query<(&Transform, &Hitbox, &Team)>) {
+    for (tf, hitbox, team) in &hitboxes {
+        let color = match team {
+            Team::Player => Color::srgb(0.3, 1.0, 0.3),
+            Team::Enemy => Color::srgb(1.0, 0.3, 0.3),
+            Team::Neutral => Color::srgb(0.7, 0.7, 0.7),
+        };
+        gizmos.circle_2d(tf.translation.truncate(), hitbox.radius, color);
+    }
+}
+
+/// Enemy steering is stateless, so the velocity can be worked out again here.
+fn draw_enemy_velocities(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    players: Query<&Transform, (With<PlayerCharacter>, Without<Downed>)>,
+    enemies: Query<(Entity, &Transform, &Enemy)>,
+) {
+    let targets: Vec<Vec2> = players.iter().map(|tf| tf.translation.truncate()).collect();
+    for (entity, tf, enemy) in &enemies {
+        let position = tf.translation.truncate();
+        let heading = enemy_heading(
+            entity,
+            position,
+            enemy_standoff(enemy.kind),
+            &targets,
+            time.elapsed_secs(),
+        );
+        let velocity = heading * enemy_speed(enemy.kind);
+        // A quarter second ahead keeps the arrows readable
+        gizmos.arrow_2d(
+            position,
+            position + velocity * 0.25,
+            Color::srgb(1.0, 0.8, 0.2),
+        );
+    }
+}
+
+/// Pursuing enemies ring green, knocked-back ones orange, invulnerable ones grey.
+fn draw_enemy_states(
+    mut gizmos: Gizmos,
+    enemies: Query<(&Transform, &Hitbox, Has<Knockback>, Has<Invulnerable>), With<Enemy>>,
+) {
+    for (tf, hitbox, knocked_back, invulnerable) in &enemies {
+        let color = if invulnerable {
+            Color::srgb(0.6, 0.6, 0.6)
+        } else if knocked_back {
+            Color::srgb(1.0, 0.5, 0.1)
+        } else {
+            Color::srgb(0.3, 1.0, 0.3)
+        };
+        gizmos.circle_2d(tf.translation.truncate(), hitbox.radius + 4.0, color);
+    }
+}
+
+fn draw_spawn_ring(mut gizmos: Gizmos, player: Single<&Transform, With<Player>>) {
+    let center = player.translation.truncate();
+    let color = Color::srgba(1.0, 0.3, 1.0, 0.4);
+    gizmos.circle_2d(center, SPAWN_RING_MIN, color);
+    gizmos.circle_2d(center, SPAWN_RING_MAX, color);
+}
+
+/// Counts whether or not the overlay is up, so a regression shows in the log
+/// even when nobody is looking for it.
+fn count_text_writes(
+    time: Res<Time>,
+    mut writes: ResMut<TextWrites>,
+    changed: Query<(), (Changed<Text>, Without<TextWritesOverlay>)>,
+) {
+    writes.counting += changed.iter().count() as u32;
+    if !writes.window.tick(time.delta()).just_finished() {
+        return;
+    }
+    writes.last_second = std::mem::take(&mut writes.counting);
+    if writes.last_second > TEXT_WRITE_BUDGET {
+        warn!(
+            "{} text writes in the last second (budget {TEXT_WRITE_BUDGET})",
+            writes.last_second
+        );
+    }
+}
+
+fn spawn_text_writes_overlay(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands.spawn((
+        Text::new(""),
+        fonts.body(16.0),
+        TextColor(Color::srgb(0.6, 1.0, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        TextWritesOverlay,
+    ));
+}
+
+fn update_text_writes_overlay(
+    debug: Res<DebugDraw>,
+    writes: Res<TextWrites>,
+    mut overlay: Query<(&mut Text, &mut TextColor, &mut Visibility), With<TextWritesOverlay>>,
+) {
+    if !debug.is_changed() && !writes.window.just_finished() {
+        return;
+    }
+    let Ok((mut text, mut color, mut visibility)) = overlay.single_mut() else {
+        return;
+    };
+    *visibility = if debug.text_writes {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    text.0 = format!("Text writes/s: {}", writes.last_second);
+    color.0 = if writes.last_second > TEXT_WRITE_BUDGET {
+        Color::srgb(1.0, 0.3, 0.3)
+    } else {
+        Color::srgb(0.6, 1.0, 0.6)
+    };
+}
+
+fn spawn_budget_overlay(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands.spawn((
+        Text::new(""),
+        fonts.body(16.0),
+        TextColor(Color::srgb(0.6, 1.0, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(30.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        BudgetOverlay,
+    ));
+}
+
+/// Refreshed on the text-writes clock, once a second, so watching it costs
+/// next to nothing.
+fn update_budget_overlay(
+    debug: Res<DebugDraw>,
+    writes: Res<TextWrites>,
+    settings: Res<Settings>,
+    budget: Res<CosmeticBudget>,
+    afterimages: Query<(), With<Afterimage>>,
+    particles: Query<(), With<DeathParticle>>,
+    popups: Query<(), With<ScorePopup>>,
+    mut overlay: Query<(&mut Text, &mut Visibility), With<BudgetOverlay>>,
+) {
+    if !debug.is_changed() && !budget.is_changed() && !writes.window.just_finished() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = overlay.single_mut() else {
+        return;
+    };
+    *visibility = if debug.cosmetic_budget {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    if !debug.cosmetic_budget {
+        return;
+    }
+    text.0 = format!(
+        "Performance {}: trails {}/{}  particles {}/{}  popups {}/{}",
+        settings.performance.id(),
+        afterimages.iter().count(),
+        budget.trails,
+        particles.iter().count(),
+        budget.particles,
+        popups.iter().count(),
+        budget.popups
+    );
+}
@@ -0,0 +1,105 @@
+//! Batched despawning and pooled cosmetics, for the frames where a big kill
+//! turns hundreds of enemies into corpses, particles and stray shots at once.
+//!
+//! Bulk despawns are queued in `PendingDespawns` and cleared together by one
+//! exclusive system at the end of the frame, at most `DESPAWN_BUDGET` a
+//! frame with the rest left for the next. A queued entity is marked `Dead`
+//! and hidden straight away, so gameplay already treats it as gone.
+//!
+//! Short-lived cosmetics like death particles and afterimages instead come
+//! from `CosmeticPool`, which hands back a hidden one from its free list
+//! before ever spawning a new entity.
+
+use bevy::prelude::*;
+
+use crate::{GameState, InGameEntity, ResetSet};
+
+const DESPAWN_BUDGET: usize = 200;
+/// Spare cosmetics kept beyond this are despawned instead.
+const MAX_FREE_COSMETICS: usize = 256;
+
+pub struct DespawnPlugin;
+
+impl Plugin for DespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingDespawns>()
+            .init_resource::<CosmeticPool>()
+            // Restarting despawns every in-game entity, pooled ones included
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                clear_despawn_queues.in_set(ResetSet),
+            )
+            .add_systems(Last, drain_pending_despawns);
+    }
+}
+
+/// On an entity waiting in `PendingDespawns`. Collision and movement skip it.
+#[derive(Component)]
+pub struct Dead;
+
+#[derive(Resource, Default)]
+pub struct PendingDespawns(Vec<Entity>);
+
+impl PendingDespawns {
+    pub fn push(&mut self, commands: &mut Commands, entity: Entity) {
+        commands
+            .entity(entity)
+            .try_insert((Dead, Visibility::Hidden));
+        self.0.push(entity);
+    }
+}
+
+/// Hidden cosmetic entities ready for reuse.
+#[derive(Resource, Default)]
+pub struct CosmeticPool {
+    free: Vec<Entity>,
+}
+
+impl CosmeticPool {
+    /// A cosmetic with `bundle` on it, reusing a spare when there is one.
+    pub fn acquire(&mut self, commands: &mut Commands, bundle: impl Bundle) -> Entity {
+        match self.free.pop() {
+            Some(entity) => {
+                commands
+                    .entity(entity)
+                    .insert((bundle, Visibility::Inherited));
+                entity
+            }
+            None => commands.spawn((bundle, InGameEntity)).id(),
+        }
+    }
+
+    /// Takes `B`, the part that made it do anything, back off a cosmetic
+    /// and keeps the rest for next time.
+    pub fn release<B: Bundle>(
+        &mut self,
+        commands: &mut Commands,
+        pending: &mut PendingDespawns,
+        entity: Entity,
+    ) {
+        if self.free.len() >= MAX_FREE_COSMETICS {
+            pending.push(commands, entity);
+            return;
+        }
+        commands
+            .entity(entity)
+            .remove::<B>()
+            .insert(Visibility::Hidden);
+        self.free.push(entity);
+    }
+}
+
+fn clear_despawn_queues(mut pending: ResMut<PendingDespawns>, mut pool: ResMut<CosmeticPool>) {
+    pending.0.clear();
+    pool.free.clear();
+}
+
+/// Anything already gone some other way is skipped.
+fn drain_pending_despawns(world: &mut World) {
+    world.resource_scope(|world, mut pending: Mut<PendingDespawns>| {
+        let count = pending.0.len().min(DESPAWN_BUDGET);
+        for entity in pending.0.drain(..count) {
+            let _ = world.try_despawn(entity);
+        }
+    });
+}
@@ -0,0 +1,372 @@
+//! Spawn pacing and enemy toughness as data. The built-in values are the
+//! game's balance; `assets/difficulty.ron`, when present and valid, replaces
+//! them at startup. Everything that spawns reads `DifficultyConfig` on the
+//! spot rather than keeping a copy, so the dev tuning panel's edits apply
+//! from the next spawn on.
+
+use std::path::Path;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+use crate::{ALL_ENEMY_TYPES, DamageType, EnemyType};
+
+pub const CONFIG_PATH: &str = "assets/difficulty.ron";
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DifficultyConfig::load());
+    }
+}
+
+/// The spawn interval at `secs` into the run.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub secs: f32,
+    pub interval: f32,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct EnemyTuning {
+    pub kind: EnemyType,
+    /// Relative odds of this kind on a spawn roll; zero never rolls it.
+    pub weight: f32,
+    /// Applied to base health, on top of the curse and the run's rules.
+    pub health: f32,
+    /// Share of every knockback shrugged off, from 0 to 1. At 1 the kind
+    /// can't be pushed at all.
+    #[serde(default)]
+    pub knockback_resistance: f32,
+    #[serde(default)]
+    pub damage_taken: DamageTaken,
+}
+
+/// How much of each damage type a kind takes, as a multiple of the hit.
+/// Types left out of the file take it as dealt; 0 is immune.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DamageTaken {
+    pub physical: f32,
+    pub fire: f32,
+    pub frost: f32,
+    pub lightning: f32,
+}
+
+impl Default for DamageTaken {
+    fn default() -> Self {
+        Self {
+            physical: 1.0,
+            fire: 1.0,
+            frost: 1.0,
+            lightning: 1.0,
+        }
+    }
+}
+
+impl DamageTaken {
+    pub fn get(self, damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Physical => self.physical,
+            DamageType::Fire => self.fire,
+            DamageType::Frost => self.frost,
+            DamageType::Lightning => self.lightning,
+        }
+    }
+}
+
+/// Rolls in a row without a rare drop before the next roll is made to give
+/// it. A threshold of 0 leaves that drop to chance alone.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PityThresholds {
+    /// Enemy kills without a shield, counting only those made while the
+    /// player is below `shield_health`.
+    pub shield: u32,
+    /// Share of max health, from 0 to 1.
+    pub shield_health: f32,
+    /// Elite kills without a chest.
+    pub chest: u32,
+}
+
+impl Default for PityThresholds {
+    fn default() -> Self {
+        Self {
+            shield: 40,
+            shield_health: 0.3,
+            chest: 12,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct DifficultyConfig {
+    /// Seconds between spawn ticks over the run, by time: linear between
+    /// points and flat past either end.
+    pub spawn_interval: Vec<CurvePoint>,
+    /// Kinds left out never roll and keep their base health.
+    pub enemies: Vec<EnemyTuning>,
+    /// Share of the director's spawns placed in the arc ahead of a moving
+    /// player, from 0 to 1. Uniform spawning puts a third there, and a
+    /// player running in a straight line never sees the ones behind.
+    #[serde(default = "default_heading_bias")]
+    pub heading_bias: f32,
+    #[serde(default)]
+    pub pity: PityThresholds,
+}
+
+fn default_heading_bias() -> f32 {
+    0.6
+}
+
+impl Default for DifficultyConfig {
+    fn default() -> Self {
+        let weight = |kind| match kind {
+            EnemyType::Basic | EnemyType::Fast | EnemyType::Tank => 3.0,
+            // Bosses arrive on their own schedule
+            EnemyType::Boss => 0.0,
+            // Only ever an occasional nuisance
+            EnemyType::Thief => 0.4,
+            _ => 1.0,
+        };
+        let knockback_resistance = |kind| match kind {
+            EnemyType::Boss => 1.0,
+            _ => 0.0,
+        };
+        let damage_taken = |kind| match kind {
+            EnemyType::Frost => DamageTaken {
+                fire: 2.0,
+                frost: 0.5,
+                ..default()
+            },
+            _ => DamageTaken::default(),
+        };
+        Self {
+            spawn_interval: vec![CurvePoint {
+                secs: 0.0,
+                interval: 1.0,
+            }],
+            enemies: ALL_ENEMY_TYPES
+                .iter()
+                .map(|&kind| EnemyTuning {
+                    kind,
+                    weight: weight(kind),
+                    health: 1.0,
+                    knockback_resistance: knockback_resistance(kind),
+                    damage_taken: damage_taken(kind),
+                })
+                .collect(),
+            heading_bias: default_heading_bias(),
+            pity: PityThresholds::default(),
+        }
+    }
+}
+
+impl DifficultyConfig {
+    /// The file if there is one and it passes `validate`, the built-ins otherwise.
+    fn load() -> Self {
+        let Some(text) = storage::load_file(Path::new(CONFIG_PATH)) else {
+            return Self::default();
+        };
+        let parsed = ron::from_str::<Self>(&text)
+            .map_err(|err| err.to_string())
+            .and_then(|config| config.validate().map(|()| config));
+        match parsed {
+            Ok(config) => {
+                info!("difficulty loaded from {CONFIG_PATH}");
+                config
+            }
+            Err(err) => {
+                error!("{CONFIG_PATH}: {err}; using the built-in difficulty");
+                Self::default()
+            }
+        }
+    }
+
+    /// Pretty-printed, so a saved file diffs and hand-edits cleanly.
+    #[cfg(feature = "dev")]
+    pub fn save(&self) -> Result<(), String> {
+        self.validate()?;
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+        storage::write_file(Path::new(CONFIG_PATH), text.as_bytes()).map_err(|err| err.to_string())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.spawn_interval.is_empty() {
+            return Err("spawn_interval needs at least one point".to_string());
+        }
+        for (i, point) in self.spawn_interval.iter().enumerate() {
+            if !point.secs.is_finite() || point.secs < 0.0 {
+                return Err(format!("spawn_interval[{i}]: secs must be 0 or more"));
+            }
+            if !point.interval.is_finite() || point.interval <= 0.0 {
+                return Err(format!("spawn_interval[{i}]: interval must be above 0"));
+            }
+            if i > 0 && point.secs <= self.spawn_interval[i - 1].secs {
+                return Err(format!(
+                    "spawn_interval[{i}]: points must be in order of increasing secs"
+                ));
+            }
+        }
+
+        for tuning in &self.enemies {
+            let kind = tuning.kind.id();
+            if !tuning.weight.is_finite() || tuning.weight < 0.0 {
+                return Err(format!("{kind}: weight must be 0 or more"));
+            }
+            if !tuning.health.is_finite() || tuning.health <= 0.0 {
+                return Err(format!("{kind}: health must be above 0"));
+            }
+            if !(0.0..=1.0).contains(&tuning.knockback_resistance) {
+                return Err(format!("{kind}: knockback_resistance must be from 0 to 1"));
+            }
+            let taken = tuning.damage_taken;
+            if [taken.physical, taken.fire, taken.frost, taken.lightning]
+                .iter()
+                .any(|multiple| !multiple.is_finite() || *multiple < 0.0)
+            {
+                return Err(format!("{kind}: damage_taken multiples must be 0 or more"));
+            }
+            if self
+                .enemies
+                .iter()
+                .filter(|t| t.kind == tuning.kind)
+                .count()
+                > 1
+            {
+                return Err(format!("{kind}: listed more than once"));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.heading_bias) {
+            return Err("heading_bias must be from 0 to 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.pity.shield_health) {
+            return Err("pity.shield_health must be from 0 to 1".to_string());
+        }
+        if self.enemies.iter().map(|t| t.weight).sum::<f32>() <= 0.0 {
+            return Err("enemy weights must add up to more than 0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn spawn_interval_at(&self, secs: f32) -> f32 {
+        let points = &self.spawn_interval;
+        let Some(next) = points.iter().position(|p| p.secs > secs) else {
+            return points.last().map_or(1.0, |p| p.interval);
+        };
+        if next == 0 {
+            return points[0].interval;
+        }
+        let (a, b) = (points[next - 1], points[next]);
+        let t = (secs - a.secs) / (b.secs - a.secs);
+        a.interval + (b.interval - a.interval) * t
+    }
+
+    pub fn health_multiplier(&self, kind: EnemyType) -> f32 {
+        self.enemies
+            .iter()
+            .find(|t| t.kind == kind)
+            .map_or(1.0, |t| t.health)
+    }
+
+    /// Zero for kinds left out of the list.
+    pub fn knockback_resistance(&self, kind: EnemyType) -> f32 {
+        self.enemies
+            .iter()
+            .find(|t| t.kind == kind)
+            .map_or(0.0, |t| t.knockback_resistance)
+    }
+
+    /// Kinds left out of the list take every type as dealt.
+    pub fn damage_taken(&self, kind: EnemyType) -> DamageTaken {
+        self.enemies
+            .iter()
+            .find(|t| t.kind == kind)
+            .map_or(DamageTaken::default(), |t| t.damage_taken)
+    }
+
+    pub fn pick_enemy_type(&self, rng: &mut impl Rng) -> EnemyType {
+        self.enemies
+            .choose_weighted(rng, |t| t.weight)
+            .map_or(EnemyType::Basic, |t| t.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damage_taken_fills_missing_types_with_one() {
+        let taken: DamageTaken = ron::from_str("(fire: 2.0)").unwrap();
+        assert_eq!(taken.get(DamageType::Fire), 2.0);
+        for damage_type in [
+            DamageType::Physical,
+            DamageType::Frost,
+            DamageType::Lightning,
+        ] {
+            assert_eq!(taken.get(damage_type), 1.0);
+        }
+    }
+
+    #[test]
+    fn the_built_in_frost_enemy_burns_and_resists_frost() {
+        let config = DifficultyConfig::default();
+        let frost = config.damage_taken(EnemyType::Frost);
+        assert_eq!(frost.get(DamageType::Fire), 2.0);
+        assert_eq!(frost.get(DamageType::Frost), 0.5);
+        assert_eq!(
+            config.damage_taken(EnemyType::Basic).get(DamageType::Fire),
+            1.0
+        );
+    }
+
+    #[test]
+    fn negative_or_infinite_multiples_fail_validation() {
+        for bad in [-1.0, f32::INFINITY, f32::NAN] {
+            let mut config = DifficultyConfig::default();
+            config.enemies[0].damage_taken.fire = bad;
+            assert!(config.validate().is_err(), "{bad}");
+        }
+        let mut immune = DifficultyConfig::default();
+        immune.enemies[0].damage_taken.fire = 0.0;
+        assert!(immune.validate().is_ok());
+    }
+
+    #[test]
+    fn only_bosses_resist_knockback_by_default() {
+        let config = DifficultyConfig::default();
+        for &kind in ALL_ENEMY_TYPES {
+            let expected = if kind == EnemyType::Boss { 1.0 } else { 0.0 };
+            assert_eq!(config.knockback_resistance(kind), expected, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn kinds_left_out_take_full_knockback() {
+        let mut config = DifficultyConfig::default();
+        config
+            .enemies
+            .retain(|tuning| tuning.kind != EnemyType::Boss);
+        assert_eq!(config.knockback_resistance(EnemyType::Boss), 0.0);
+    }
+
+    #[test]
+    fn knockback_resistance_must_be_from_0_to_1() {
+        for good in [0.0, 0.5, 1.0] {
+            let mut config = DifficultyConfig::default();
+            config.enemies[0].knockback_resistance = good;
+            assert!(config.validate().is_ok(), "{good}");
+        }
+        for bad in [-0.1, 1.1, f32::NAN, f32::INFINITY] {
+            let mut config = DifficultyConfig::default();
+            config.enemies[0].knockback_resistance = bad;
+            let err = config.validate().unwrap_err();
+            assert!(err.contains("knockback_resistance"), "{bad}: {err}");
+        }
+    }
+}
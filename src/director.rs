@@ -0,0 +1,420 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::challenge::RunRules;
+use crate::critters::{Critter, MAX_CRITTERS, spawn_critter};
+use crate::difficulty::DifficultyConfig;
+use crate::fonts::FontAssets;
+use crate::packs::{PACK_CHANCE, PACK_FIRST_SECS, spawn_pack};
+use crate::unlocks::RunRecord;
+use crate::{
+    Curse, EnemyTime, EnemyType, GameState, GameTime, GameplaySet, PlayState, Player,
+    PlayerCharacter, ResetSet, Settings, SpawnBias, SpawnContext, reset_run_state,
+    roll_spawn_position, spawn_enemy, spawn_state_ui_root,
+};
+
+/// How many spawn ticks are planned ahead, and so how far the forecast sees.
+const FORECAST_TICKS: usize = 10;
+/// Chance per spawn tick of a critter turning up alongside the enemies.
+const CRITTER_CHANCE: f64 = 0.04;
+/// Time constant of the smoothing on the player's velocity.
+const HEADING_SMOOTHING_SECS: f32 = 0.75;
+/// Slower than this counts as standing still, and spawns go back to uniform.
+const MIN_HEADING_SPEED: f32 = 40.0;
+
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnDirector>()
+            .init_resource::<PlayerHeading>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                // The fresh plan is drawn at the fresh curse
+                (reset_director.after(reset_run_state), reset_player_heading).in_set(ResetSet),
+            )
+            .add_systems(OnEnter(GameState::Playing), spawn_forecast_ui)
+            .add_systems(
+                Update,
+                (track_player_heading, replan_on_threat_change, run_director)
+                    .chain()
+                    .in_set(GameplaySet)
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(Update, (toggle_forecast, update_forecast_ui).chain());
+    }
+}
+
+/// Decides what spawns a few ticks before it happens. Only the enemy kinds
+/// are planned; positions are rolled around the player at spawn time.
+#[derive(Resource)]
+pub struct SpawnDirector {
+    timer: Timer,
+    /// One entry per upcoming tick, soonest first.
+    plan: VecDeque<Vec<EnemyType>>,
+    /// Fractional spawn budget as of the last executed tick.
+    committed_budget: f32,
+    /// Budget after the last planned tick, where planning resumes.
+    planned_budget: f32,
+    /// Threat the current plan was drawn at.
+    planned_threat: f32,
+}
+
+impl Default for SpawnDirector {
+    fn default() -> Self {
+        Self {
+            // `run_director` sets the length from the difficulty curve before every tick
+            timer: Timer::new(Duration::ZERO, TimerMode::Repeating),
+            plan: VecDeque::new(),
+            committed_budget: 0.0,
+            planned_budget: 0.0,
+            planned_threat: 1.0,
+        }
+    }
+}
+
+impl SpawnDirector {
+    /// Tops the plan back up to `FORECAST_TICKS` entries.
+    fn fill(&mut self, threat: f32, difficulty: &DifficultyConfig, rng: &mut impl Rng) {
+        self.planned_threat = threat;
+        while self.plan.len() < FORECAST_TICKS {
+            // Fractional budget carries over, so +10% threat means one extra spawn every ten ticks
+            self.planned_budget += threat;
+            let mut tick = Vec::new();
+            while self.planned_budget >= 1.0 {
+                self.planned_budget -= 1.0;
+                tick.push(difficulty.pick_enemy_type(rng));
+            }
+            self.plan.push_back(tick);
+        }
+    }
+
+    /// Throws the plan away and redraws it from the last executed tick.
+    fn replan(&mut self, threat: f32, difficulty: &DifficultyConfig, rng: &mut impl Rng) {
+        self.plan.clear();
+        self.planned_budget = self.committed_budget;
+        self.fill(threat, difficulty, rng);
+    }
+
+    /// Pops the next tick's spawns.
+    fn advance(
+        &mut self,
+        threat: f32,
+        difficulty: &DifficultyConfig,
+        rng: &mut impl Rng,
+    ) -> Vec<EnemyType> {
+        self.fill(threat, difficulty, rng);
+        let tick = self.plan.pop_front().unwrap_or_default();
+        self.committed_budget = (self.committed_budget + threat).fract();
+        self.fill(threat, difficulty, rng);
+        tick
+    }
+
+    /// Planned spawn counts per kind over the forecast window, most numerous first.
+    pub fn forecast(&self) -> Vec<(EnemyType, usize)> {
+        let mut counts: Vec<(EnemyType, usize)> = Vec::new();
+        for &kind in self.plan.iter().flatten() {
+            match counts.iter_mut().find(|(k, _)| *k == kind) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((kind, 1)),
+            }
+        }
+        counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        counts
+    }
+}
+
+pub fn enemy_type_label(kind: EnemyType) -> &'static str {
+    match kind {
+        EnemyType::Basic => "Grunts",
+        EnemyType::Fast => "Swarm",
+        EnemyType::Tank => "Tanks",
+        EnemyType::Frost => "Frost",
+        EnemyType::Spitter => "Spitters",
+        EnemyType::Shielder => "Shielders",
+        EnemyType::Healer => "Healers",
+        EnemyType::Phaser => "Phasers",
+        EnemyType::Thief => "Thieves",
+        EnemyType::Boss => "Boss",
+    }
+}
+
+/// Spawns per tick: the curse and the run's rules both scale the budget.
+fn spawn_threat(curse: &Curse, rules: &RunRules) -> f32 {
+    curse.threat_multiplier() * rules.spawn_budget
+}
+
+/// The player's velocity, exponentially smoothed so a quick sidestep
+/// doesn't swing where enemies come from.
+#[derive(Resource, Default)]
+struct PlayerHeading {
+    velocity: Vec2,
+    last_position: Option<Vec2>,
+}
+
+fn reset_player_heading(mut heading: ResMut<PlayerHeading>) {
+    *heading = PlayerHeading::default();
+}
+
+fn track_player_heading(
+    time: GameTime,
+    mut heading: ResMut<PlayerHeading>,
+    player: Query<&Transform, With<Player>>,
+) {
+    let dt = time.delta_secs();
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let position = player.translation.truncate();
+    let last = heading.last_position.replace(position);
+    if dt <= 0.0 {
+        return;
+    }
+    let Some(last) = last else {
+        return;
+    };
+    let blend = 1.0 - (-dt / HEADING_SMOOTHING_SECS).exp();
+    heading.velocity = heading.velocity.lerp((position - last) / dt, blend);
+}
+
+fn reset_director(
+    mut director: ResMut<SpawnDirector>,
+    curse: Res<Curse>,
+    rules: Res<RunRules>,
+    difficulty: Res<DifficultyConfig>,
+) {
+    *director = SpawnDirector::default();
+    director.fill(spawn_threat(&curse, &rules), &difficulty, &mut thread_rng());
+}
+
+/// New weights redraw the plan too, so a tuning change shows up in the
+/// forecast straight away rather than ten ticks later.
+fn replan_on_threat_change(
+    mut director: ResMut<SpawnDirector>,
+    curse: Res<Curse>,
+    rules: Res<RunRules>,
+    difficulty: Res<DifficultyConfig>,
+) {
+    let threat = spawn_threat(&curse, &rules);
+    let threat_changed =
+        (curse.is_changed() || rules.is_changed()) && threat != director.planned_threat;
+    if threat_changed || difficulty.is_changed() {
+        director.replan(threat, &difficulty, &mut thread_rng());
+    }
+}
+
+fn run_director(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    time: EnemyTime,
+    curse: Res<Curse>,
+    rules: Res<RunRules>,
+    difficulty: Res<DifficultyConfig>,
+    record: Res<RunRecord>,
+    heading: Res<PlayerHeading>,
+    mut director: ResMut<SpawnDirector>,
+    player_q: Query<&Transform, With<Player>>,
+    players: Query<&Transform, With<PlayerCharacter>>,
+    critters: Query<(), With<Critter>>,
+) {
+    let interval = difficulty.spawn_interval_at(record.secs());
+    director
+        .timer
+        .set_duration(Duration::from_secs_f32(interval));
+    if !director.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let Ok(player) = player_q.single() else {
+        return;
+    };
+
+    let center = player.translation.truncate();
+    let ctx = SpawnContext {
+        players: players.iter().map(|tf| tf.translation.truncate()).collect(),
+        bias: (heading.velocity.length() >= MIN_HEADING_SPEED).then(|| SpawnBias {
+            heading: heading.velocity.normalize(),
+            share: difficulty.heading_bias,
+        }),
+    };
+
+    let mut rng = thread_rng();
+    for kind in director.advance(spawn_threat(&curse, &rules), &difficulty, &mut rng) {
+        if let Some(position) = roll_spawn_position(&mut rng, center, &ctx) {
+            spawn_enemy(
+                &mut commands,
+                &fonts,
+                position,
+                kind,
+                &curse,
+                &rules,
+                &difficulty,
+            );
+        }
+    }
+
+    if record.secs() >= PACK_FIRST_SECS
+        && rng.gen_bool(PACK_CHANCE)
+        && let Some(position) = roll_spawn_position(&mut rng, center, &ctx)
+    {
+        spawn_pack(
+            &mut commands,
+            &fonts,
+            &mut rng,
+            position,
+            center - position,
+            &curse,
+            &rules,
+            &difficulty,
+        );
+    }
+
+    if critters.iter().len() < MAX_CRITTERS
+        && rng.gen_bool(CRITTER_CHANCE)
+        && let Some(position) = roll_spawn_position(&mut rng, center, &ctx)
+    {
+        spawn_critter(&mut commands, &fonts, &mut rng, position);
+    }
+}
+
+#[derive(Component)]
+struct ForecastText;
+
+fn spawn_forecast_ui(mut commands: Commands, fonts: Res<FontAssets>) {
+    let root = spawn_state_ui_root(&mut commands, GameState::Playing);
+    commands.spawn((
+        Text::new(""),
+        fonts.body(18.0),
+        TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        },
+        ForecastText,
+        ChildOf(root),
+    ));
+}
+
+fn toggle_forecast(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(KeyCode::KeyI) {
+        settings.show_forecast = !settings.show_forecast;
+    }
+}
+
+/// The director's timer ticks every frame, so compare against what is shown
+/// instead of trusting change detection.
+fn update_forecast_ui(
+    director: Res<SpawnDirector>,
+    settings: Res<Settings>,
+    mut shown: Local<Vec<(EnemyType, usize)>>,
+    mut query: Query<(&mut Text, Ref<ForecastText>)>,
+) {
+    let Ok((mut text, marker)) = query.single_mut() else {
+        return;
+    };
+
+    let forecast = if settings.show_forecast {
+        director.forecast()
+    } else {
+        Vec::new()
+    };
+    if !marker.is_added() && *shown == forecast {
+        return;
+    }
+
+    text.0 = if forecast.is_empty() {
+        String::new()
+    } else {
+        let parts: Vec<String> = forecast
+            .iter()
+            .map(|&(kind, count)| format!("{} x{}", enemy_type_label(kind), count))
+            .collect();
+        format!("Incoming: {}", parts.join(", "))
+    };
+    *shown = forecast;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::CurvePoint;
+    use crate::{ALL_ENEMY_TYPES, enemy_base_stats, enemy_points, scaled_health};
+
+    const MINUTE_TEN: f32 = 600.0;
+
+    /// Spawns every second at the start, four a second by minute ten.
+    fn ramping_difficulty() -> DifficultyConfig {
+        DifficultyConfig {
+            spawn_interval: vec![
+                CurvePoint {
+                    secs: 0.0,
+                    interval: 1.0,
+                },
+                CurvePoint {
+                    secs: MINUTE_TEN,
+                    interval: 0.25,
+                },
+            ],
+            ..default()
+        }
+    }
+
+    #[test]
+    fn minute_ten_with_three_curse_spawns_a_third_more() {
+        let difficulty = ramping_difficulty();
+        let curse = Curse(3);
+        let threat = spawn_threat(&curse, &RunRules::default());
+        assert!((threat - 1.3).abs() < 1e-6);
+
+        let interval = difficulty.spawn_interval_at(MINUTE_TEN);
+        assert!(interval > 0.0 && interval.is_finite());
+        let ticks = (60.0 / interval) as usize;
+        let mut director = SpawnDirector::default();
+        let mut rng = StdRng::seed_from_u64(5);
+        let spawned: usize = (0..ticks)
+            .map(|_| director.advance(threat, &difficulty, &mut rng).len())
+            .sum();
+        // A minute at four ticks a second, 1.3 spawns a tick
+        assert!(spawned.abs_diff(312) <= 1, "{spawned}");
+    }
+
+    #[test]
+    fn minute_ten_with_three_curse_keeps_health_and_rewards_in_range() {
+        let difficulty = ramping_difficulty();
+        let curse = Curse(3);
+        let rules = RunRules::default();
+        for &kind in ALL_ENEMY_TYPES {
+            let (_, base, _) = enemy_base_stats(kind);
+            let multiplier =
+                curse.threat_multiplier() * rules.enemy_health * difficulty.health_multiplier(kind);
+            let health = scaled_health(base, multiplier);
+            assert!(health >= base, "{kind:?}: {health}");
+            assert_eq!(health, (base as f32 * 1.3).round().max(1.0) as i32);
+
+            let points = enemy_points(kind) as f32;
+            let xp = points * curse.reward_multiplier();
+            assert!(xp >= points && xp.is_finite(), "{kind:?}: {xp}");
+        }
+        assert_eq!(curse.reward_gold(100), 145);
+    }
+
+    #[test]
+    fn extreme_curse_and_loops_saturate_rather_than_wrap() {
+        let curse = Curse(u32::MAX);
+        let rules = RunRules {
+            enemy_health: 1.5_f32.powi(40),
+            spawn_budget: 2.0_f32.powi(40),
+            ..default()
+        };
+        let threat = spawn_threat(&curse, &rules);
+        assert!(threat.is_finite() && threat > 0.0);
+        let health = scaled_health(500, curse.threat_multiplier() * rules.enemy_health);
+        assert_eq!(health, i32::MAX);
+        assert_eq!(curse.reward_gold(u32::MAX), u32::MAX);
+    }
+}
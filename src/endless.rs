@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+
+use crate::announcements::{AnnouncementStyle, Announcements};
+use crate::challenge::{RunRules, apply_run_modifiers};
+use crate::fonts::FontAssets;
+use crate::savegame::RunSuspended;
+use crate::storage::{self, Persistence};
+use crate::unlocks::RunRecord;
+use crate::{GameState, GameplaySpeed, PlayState, Score, Settings, spawn_state_ui_root};
+
+/// Length of one loop of gameplay time.
+const LOOP_SECS: f32 = 15.0 * 60.0;
+/// Applied once more at the start of every loop after the first.
+const LOOP_ENEMY_HEALTH: f32 = 1.5;
+const LOOP_ENEMY_SPEED: f32 = 1.5;
+const LOOP_SPAWN_BUDGET: f32 = 2.0;
+const LOOP_XP_NEEDED: f32 = 1.5;
+
+const ENDLESS_BESTS_KEY: &str = "endless_bests";
+const ENDLESS_BESTS_SHOWN: usize = 5;
+
+pub struct EndlessPlugin;
+
+impl Plugin for EndlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EndlessLoop>()
+            .insert_resource(EndlessBests::load())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                start_endless.after(apply_run_modifiers),
+            )
+            .add_systems(Update, advance_loop.run_if(in_state(PlayState::Running)))
+            .add_systems(OnEnter(GameState::GameOver), spawn_endless_summary)
+            .add_systems(Update, toggle_endless.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+/// Which loop the run is on. Fixed at the start of the run, so toggling the
+/// setting on the game over screen only affects the next one.
+#[derive(Resource)]
+pub struct EndlessLoop {
+    enabled: bool,
+    current: u32,
+}
+
+impl Default for EndlessLoop {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            current: 1,
+        }
+    }
+}
+
+impl EndlessLoop {
+    /// The loop under way, or `None` outside endless mode.
+    pub fn current(&self) -> Option<u32> {
+        self.enabled.then_some(self.current)
+    }
+}
+
+/// Best endless scores with the loop each one reached, highest first.
+#[derive(Resource, Default)]
+struct EndlessBests(Vec<(u32, u32)>);
+
+impl EndlessBests {
+    /// One `score loop` pair per line in storage.
+    fn load() -> Self {
+        let Some(text) = storage::load(ENDLESS_BESTS_KEY) else {
+            return Self::default();
+        };
+        let mut bests: Vec<(u32, u32)> = text
+            .lines()
+            .filter_map(|line| {
+                let (score, reached) = line.split_once(' ')?;
+                Some((score.parse().ok()?, reached.parse().ok()?))
+            })
+            .collect();
+        bests.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        Self(bests)
+    }
+
+    fn save(&self, persistence: &mut Persistence) {
+        let text: String = self
+            .0
+            .iter()
+            .map(|(score, reached)| format!("{score} {reached}\n"))
+            .collect();
+        persistence.save(ENDLESS_BESTS_KEY, text);
+    }
+
+    fn record(&mut self, score: u32, reached: u32) {
+        let at = self.0.partition_point(|&(best, _)| best >= score);
+        self.0.insert(at, (score, reached));
+        self.0.truncate(ENDLESS_BESTS_SHOWN);
+    }
+}
+
+fn toggle_endless(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(KeyCode::Digit4) {
+        settings.endless = !settings.endless;
+        info!("endless {}", if settings.endless { "on" } else { "off" });
+    }
+}
+
+fn start_endless(settings: Res<Settings>, mut endless: ResMut<EndlessLoop>) {
+    *endless = EndlessLoop {
+        enabled: settings.endless,
+        ..default()
+    };
+}
+
+/// Each loop only ever stacks more onto the run's rules; the field, weapons
+/// and experience carry straight over. A resumed run late into a loop
+/// catches up on every loop it has passed.
+fn advance_loop(
+    record: Res<RunRecord>,
+    mut endless: ResMut<EndlessLoop>,
+    mut rules: ResMut<RunRules>,
+    mut announcements: ResMut<Announcements>,
+) {
+    if !endless.enabled {
+        return;
+    }
+    while record.secs() >= endless.current as f32 * LOOP_SECS {
+        endless.current += 1;
+        rules.enemy_health *= LOOP_ENEMY_HEALTH;
+        rules.enemy_speed *= LOOP_ENEMY_SPEED;
+        rules.spawn_budget *= LOOP_SPAWN_BUDGET;
+        rules.xp_needed *= LOOP_XP_NEEDED;
+        announcements.push(
+            format!("LOOP {}", endless.current),
+            2.0,
+            AnnouncementStyle::Warning,
+        );
+    }
+}
+
+fn spawn_endless_summary(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    endless: Res<EndlessLoop>,
+    score: Res<Score>,
+    suspended: Res<RunSuspended>,
+    speed: Res<GameplaySpeed>,
+    mut bests: ResMut<EndlessBests>,
+    mut persistence: ResMut<Persistence>,
+) {
+    let Some(reached) = endless.current() else {
+        return;
+    };
+    // Same rule as the daily bests: unfinished or slowed runs don't count
+    if !suspended.0 && speed.0 >= 1.0 {
+        bests.record(score.0, reached);
+        bests.save(&mut persistence);
+    }
+
+    let mut text = "Endless bests".to_string();
+    for (score, reached) in &bests.0 {
+        text.push_str(&format!("\n{score}  (loop {reached})"));
+    }
+
+    let root = spawn_state_ui_root(&mut commands, GameState::GameOver);
+    commands.spawn((
+        Text::new(text),
+        fonts.body(18.0),
+        TextColor(Color::srgb(1.0, 0.5, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(5.0),
+            right: Val::Percent(3.0),
+            ..default()
+        },
+        ChildOf(root),
+    ));
+}
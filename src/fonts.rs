@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::Settings;
+
+const BODY_FONT_PATH: &str = "fonts/DejaVuSansMono.ttf";
+const DISPLAY_FONT_PATH: &str = "fonts/DejaVuSans-Bold.ttf";
+
+pub struct FontAssetsPlugin;
+
+impl Plugin for FontAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        // Loaded while building, since the first `OnEnter` runs ahead of
+        // every startup schedule and already spawns text
+        let fonts = load_fonts(
+            app.world().resource::<AssetServer>(),
+            app.world().resource::<Settings>().font,
+        );
+        app.insert_resource(fonts)
+            .add_systems(Update, fall_back_missing_fonts);
+    }
+}
+
+/// Which fonts the game draws its text with.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FontChoice {
+    /// The fonts shipped under `assets/fonts/`.
+    Bundled,
+    /// Bevy's built-in font, for when the bundled ones are unwanted.
+    BuiltIn,
+}
+
+impl FontChoice {
+    pub fn id(self) -> &'static str {
+        match self {
+            FontChoice::Bundled => "bundled",
+            FontChoice::BuiltIn => "builtin",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "bundled" => Some(FontChoice::Bundled),
+            "builtin" => Some(FontChoice::BuiltIn),
+            _ => None,
+        }
+    }
+}
+
+/// Body text and big headers, kept apart so either can be swapped alone.
+#[derive(Resource)]
+pub struct FontAssets {
+    pub body: Handle<Font>,
+    pub display: Handle<Font>,
+}
+
+impl FontAssets {
+    pub fn body(&self, font_size: f32) -> TextFont {
+        TextFont {
+            font: self.body.clone(),
+            font_size,
+            ..default()
+        }
+    }
+
+    /// For game over, banners and other headers.
+    pub fn display(&self, font_size: f32) -> TextFont {
+        TextFont {
+            font: self.display.clone(),
+            font_size,
+            ..default()
+        }
+    }
+}
+
+fn load_fonts(asset_server: &AssetServer, choice: FontChoice) -> FontAssets {
+    match choice {
+        FontChoice::Bundled => FontAssets {
+            body: asset_server.load(BODY_FONT_PATH),
+            display: asset_server.load(DISPLAY_FONT_PATH),
+        },
+        FontChoice::BuiltIn => FontAssets {
+            body: default(),
+            display: default(),
+        },
+    }
+}
+
+/// A font file that is missing or broken swaps to the built-in font, both for
+/// text spawned from now on and for text already using it.
+fn fall_back_missing_fonts(
+    asset_server: Res<AssetServer>,
+    mut fonts: ResMut<FontAssets>,
+    mut texts: Query<&mut TextFont>,
+) {
+    let fonts = fonts.bypass_change_detection();
+    for handle in [&mut fonts.body, &mut fonts.display] {
+        if !asset_server.load_state(&*handle).is_failed() {
+            continue;
+        }
+        warn!(
+            "could not load font {:?}; using the built-in font",
+            handle.path()
+        );
+        for mut text in &mut texts {
+            if text.font == *handle {
+                text.font = default();
+            }
+        }
+        *handle = default();
+    }
+}
@@ -0,0 +1,196 @@
+//! The ghost of your best run. The player's position is recorded once a
+//! second of play, and a run that beats the best score keeps its path. Later
+//! runs show a faint marker retracing it on the same clock, to race against.
+//!
+//! The ghost has no hitbox or team, so nothing collides with it, aims at it
+//! or picks anything up for it.
+
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::savegame::RunSuspended;
+use crate::storage::{self, Persistence};
+use crate::unlocks::RunRecord;
+use crate::{
+    GameState, GameTime, InGameEntity, PlayState, Player, ResetSet, Score, Settings, layers,
+};
+
+const GHOST_KEY: &str = "ghost";
+const SAMPLE_SECS: f32 = 1.0;
+const GHOST_COLOR: Color = Color::srgba(0.6, 0.8, 1.0, 0.3);
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BestGhost::load())
+            .init_resource::<GhostRecording>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                start_recording.in_set(ResetSet),
+            )
+            .add_systems(OnEnter(GameState::Playing), spawn_ghost)
+            .add_systems(
+                Update,
+                (record_path, move_ghost).run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), keep_best_path)
+            .add_systems(Update, toggle_ghost.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+/// The best score's run and the path it took, one position per
+/// `SAMPLE_SECS` from the start.
+#[derive(Resource, Default)]
+struct BestGhost {
+    score: u32,
+    path: Vec<Vec2>,
+}
+
+impl BestGhost {
+    /// A `score=` line, then one `x y` line per sample.
+    fn load() -> Self {
+        let Some(text) = storage::load(GHOST_KEY) else {
+            return Self::default();
+        };
+        let mut lines = text.lines();
+        let Some(score) = lines
+            .next()
+            .and_then(|line| line.strip_prefix("score="))
+            .and_then(|score| score.parse().ok())
+        else {
+            return Self::default();
+        };
+        let path = lines
+            .map_while(|line| {
+                let (x, y) = line.split_once(' ')?;
+                Some(Vec2::new(x.parse().ok()?, y.parse().ok()?))
+            })
+            .collect();
+        Self { score, path }
+    }
+
+    fn save(&self, persistence: &mut Persistence) {
+        let mut text = format!("score={}\n", self.score);
+        for point in &self.path {
+            text.push_str(&format!("{} {}\n", point.x, point.y));
+        }
+        persistence.save(GHOST_KEY, text);
+    }
+
+    /// Where the run was `secs` in, between the samples either side; `None`
+    /// once it had ended.
+    fn position_at(&self, secs: f32) -> Option<Vec2> {
+        let at = secs / SAMPLE_SECS;
+        let index = at as usize;
+        let from = *self.path.get(index)?;
+        let to = self.path.get(index + 1).copied().unwrap_or(from);
+        Some(from.lerp(to, at.fract()))
+    }
+}
+
+/// This run's path so far.
+#[derive(Resource, Default)]
+struct GhostRecording {
+    path: Vec<Vec2>,
+    /// Gameplay seconds since the run started, which the ghost replays on.
+    secs: f32,
+    /// Cleared for a resumed run, whose path is missing its start.
+    from_start: bool,
+}
+
+/// The marker retracing `BestGhost`.
+#[derive(Component)]
+struct Ghost;
+
+fn start_recording(mut recording: ResMut<GhostRecording>) {
+    *recording = GhostRecording {
+        from_start: true,
+        ..default()
+    };
+}
+
+fn spawn_ghost(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    settings: Res<Settings>,
+    best: Res<BestGhost>,
+) {
+    let Some(start) = best.path.first() else {
+        return;
+    };
+    if !settings.ghost {
+        return;
+    }
+    commands.spawn((
+        Ghost,
+        InGameEntity,
+        Text2d::new("@"),
+        fonts.body(20.0),
+        TextColor(GHOST_COLOR),
+        Transform::from_translation(start.extend(layers::GHOST)),
+    ));
+}
+
+/// A sample on the first frame and then every `SAMPLE_SECS`, so sample `n`
+/// is where the player was `n` samples in.
+fn record_path(
+    time: GameTime,
+    record: Res<RunRecord>,
+    mut recording: ResMut<GhostRecording>,
+    player: Query<&Transform, With<Player>>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    // A resumed run; the ghost picks up from where it is
+    if recording.path.is_empty() && record.secs() > SAMPLE_SECS {
+        recording.from_start = false;
+        recording.secs = record.secs();
+    }
+    recording.secs += time.delta_secs();
+    let due = (recording.secs / SAMPLE_SECS) as usize + 1;
+    if recording.path.len() < due {
+        recording.path.push(player.translation.truncate());
+    }
+}
+
+fn move_ghost(
+    recording: Res<GhostRecording>,
+    best: Res<BestGhost>,
+    mut ghost: Query<(&mut Transform, &mut Visibility), With<Ghost>>,
+) {
+    let Ok((mut tf, mut visibility)) = ghost.single_mut() else {
+        return;
+    };
+    match best.position_at(recording.secs) {
+        Some(position) => {
+            tf.translation.x = position.x;
+            tf.translation.y = position.y;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
+/// Only a run played through from the start can become the ghost.
+fn keep_best_path(
+    score: Res<Score>,
+    suspended: Res<RunSuspended>,
+    mut recording: ResMut<GhostRecording>,
+    mut best: ResMut<BestGhost>,
+    mut persistence: ResMut<Persistence>,
+) {
+    if suspended.0 || !recording.from_start || recording.path.is_empty() || score.0 <= best.score {
+        return;
+    }
+    best.score = score.0;
+    best.path = std::mem::take(&mut recording.path);
+    best.save(&mut persistence);
+}
+
+fn toggle_ghost(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(KeyCode::Digit6) {
+        settings.ghost = !settings.ghost;
+        info!("ghost {}", if settings.ghost { "on" } else { "off" });
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::unlocks::RunRecord;
+use crate::{
+    AOE_EDGE_SHARE, Blast, DamageEvent, DamageKind, DamageType, Enemy, EnemyTime, EnemyType,
+    GameState, InGameEntity, PLAYER_BASE_SPEED, PlayState, PlayerCharacter, ResetSet, Settings,
+    Team, aoe_damage,
+};
+
+/// No meteors at all before this point in the run.
+const METEOR_START_SECS: f32 = 300.0;
+/// Seconds between strikes once they start, shrinking each minute after.
+const METEOR_INTERVAL_SECS: f32 = 8.0;
+const METEOR_INTERVAL_STEP_SECS: f32 = 0.5;
+const METEOR_MIN_INTERVAL_SECS: f32 = 3.0;
+const METEOR_MAX_PER_STRIKE: usize = 3;
+const METEOR_WARNING_SECS: f32 = 1.5;
+const METEOR_RADIUS: f32 = 80.0;
+const METEOR_PLAYER_DAMAGE: i32 = 2;
+const IMPACT_SECS: f32 = 0.3;
+const METEOR_COLOR: Color = Color::srgb(1.0, 0.25, 0.15);
+
+// Standing at the centre, an unslowed player must always be able to clear the zone in time
+const _: () = assert!(METEOR_RADIUS < PLAYER_BASE_SPEED * METEOR_WARNING_SECS);
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MeteorTimer(Timer::from_seconds(
+            METEOR_INTERVAL_SECS,
+            TimerMode::Once,
+        )))
+        .add_systems(
+            OnEnter(GameState::Resetting),
+            reset_meteor_timer.in_set(ResetSet),
+        )
+        .add_systems(
+            Update,
+            (schedule_meteors, land_meteors, fade_impacts)
+                .chain()
+                .run_if(in_state(PlayState::Running)),
+        )
+        .add_systems(Update, (draw_telegraphs, draw_impacts));
+    }
+}
+
+#[derive(Resource)]
+struct MeteorTimer(Timer);
+
+/// A strike on its way down; lands when the timer runs out.
+#[derive(Component)]
+struct MeteorTelegraph(Timer);
+
+/// What is left for a moment after a strike lands.
+#[derive(Component)]
+struct MeteorImpact(Timer);
+
+/// Gap between strikes `secs` into the run, or `None` while they are still off.
+fn meteor_interval(secs: f32) -> Option<f32> {
+    if secs < METEOR_START_SECS {
+        return None;
+    }
+    let minutes = ((secs - METEOR_START_SECS) / 60.0).floor();
+    let interval = METEOR_INTERVAL_SECS - minutes * METEOR_INTERVAL_STEP_SECS;
+    Some(interval.max(METEOR_MIN_INTERVAL_SECS))
+}
+
+fn reset_meteor_timer(mut timer: ResMut<MeteorTimer>) {
+    timer.0 = Timer::from_seconds(METEOR_INTERVAL_SECS, TimerMode::Once);
+}
+
+/// Marks one to three impact zones somewhere on screen, fully inside it.
+fn schedule_meteors(
+    mut commands: Commands,
+    time: EnemyTime,
+    record: Res<RunRecord>,
+    mut timer: ResMut<MeteorTimer>,
+    windows: Query<&Window>,
+    camera: Query<&Transform, With<Camera2d>>,
+) {
+    let Some(interval) = meteor_interval(record.secs()) else {
+        return;
+    };
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    timer.0 = Timer::from_seconds(interval, TimerMode::Once);
+
+    let (Ok(window), Ok(camera)) = (windows.single(), camera.single()) else {
+        return;
+    };
+    let center = camera.translation.truncate();
+    let half = (window.size() / 2.0 - Vec2::splat(METEOR_RADIUS)).max(Vec2::ZERO);
+
+    let mut rng = thread_rng();
+    for _ in 0..rng.gen_range(1..=METEOR_MAX_PER_STRIKE) {
+        let offset = Vec2::new(
+            rng.gen_range(-half.x..=half.x),
+            rng.gen_range(-half.y..=half.y),
+        );
+        commands.spawn((
+            MeteorTelegraph(Timer::from_seconds(METEOR_WARNING_SECS, TimerMode::Once)),
+            Transform::from_translation((center + offset).extend(0.0)),
+            InGameEntity,
+        ));
+    }
+}
+
+/// Everything inside the zone when it lands is hit, hardest at the centre:
+/// players hard, enemies outright, for half their usual score. Bosses are
+/// spared, or a stray strike would end the fight.
+fn land_meteors(
+    mut commands: Commands,
+    time: EnemyTime,
+    mut damage_events: MessageWriter<DamageEvent>,
+    mut telegraphs: Query<(Entity, &Transform, &mut MeteorTelegraph)>,
+    players: Query<(Entity, &Transform), With<PlayerCharacter>>,
+    enemies: Query<(Entity, &Transform, &Enemy)>,
+) {
+    for (entity, tf, mut telegraph) in &mut telegraphs {
+        if !telegraph.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let center = tf.translation.truncate();
+        let positions = |(entity, tf): (Entity, &Transform)| (entity, tf.translation.truncate());
+
+        aoe_damage(
+            &mut damage_events,
+            &Blast {
+                center,
+                radius: METEOR_RADIUS,
+                amount: METEOR_PLAYER_DAMAGE,
+                source_team: Team::Enemy,
+                kind: DamageKind::Hazard,
+                damage_type: DamageType::Fire,
+            },
+            AOE_EDGE_SHARE,
+            players.iter().map(positions),
+        );
+        aoe_damage(
+            &mut damage_events,
+            &Blast {
+                center,
+                radius: METEOR_RADIUS,
+                amount: i32::MAX,
+                source_team: Team::Player,
+                kind: DamageKind::Hazard,
+                damage_type: DamageType::Fire,
+            },
+            AOE_EDGE_SHARE,
+            enemies
+                .iter()
+                .filter(|(_, _, enemy)| enemy.kind != EnemyType::Boss)
+                .map(|(entity, tf, _)| positions((entity, tf))),
+        );
+
+        commands.entity(entity).despawn();
+        commands.spawn((
+            MeteorImpact(Timer::from_seconds(IMPACT_SECS, TimerMode::Once)),
+            Transform::from_translation(center.extend(0.0)),
+            InGameEntity,
+        ));
+    }
+}
+
+fn fade_impacts(
+    mut commands: Commands,
+    time: EnemyTime,
+    mut impacts: Query<(Entity, &mut MeteorImpact)>,
+) {
+    for (entity, mut impact) in &mut impacts {
+        if impact.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// The zone's edge stays put while a second ring closes in on the centre,
+/// reaching it as the meteor lands.
+fn draw_telegraphs(mut gizmos: Gizmos, telegraphs: Query<(&Transform, &MeteorTelegraph)>) {
+    for (tf, telegraph) in &telegraphs {
+        let center = tf.translation.truncate();
+        gizmos.circle_2d(center, METEOR_RADIUS, METEOR_COLOR.with_alpha(0.5));
+        gizmos.circle_2d(
+            center,
+            METEOR_RADIUS * telegraph.0.fraction_remaining(),
+            METEOR_COLOR,
+        );
+    }
+}
+
+/// A bright expanding flash, or with reduced motion a plain cross where it hit.
+fn draw_impacts(
+    mut gizmos: Gizmos,
+    settings: Res<Settings>,
+    impacts: Query<(&Transform, &MeteorImpact)>,
+) {
+    for (tf, impact) in &impacts {
+        let center = tf.translation.truncate();
+        if settings.reduced_motion {
+            let arm = METEOR_RADIUS * 0.25;
+            gizmos.line_2d(
+                center - Vec2::splat(arm),
+                center + Vec2::splat(arm),
+                METEOR_COLOR,
+            );
+            gizmos.line_2d(
+                center + Vec2::new(-arm, arm),
+                center + Vec2::new(arm, -arm),
+                METEOR_COLOR,
+            );
+            continue;
+        }
+        let t = impact.0.fraction();
+        let color = Color::WHITE.mix(&METEOR_COLOR, t).with_alpha(1.0 - t);
+        for ring in 0..3 {
+            gizmos.circle_2d(
+                center,
+                METEOR_RADIUS * (0.6 + 0.4 * t) - ring as f32 * 6.0,
+                color,
+            );
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+
+use crate::announcements::{AnnouncementStyle, Announcements};
+use crate::{GameState, GameTime, PlayState, Player, PlayerStats, spawn_state_ui_root};
+
+const LOCKOUT_SECS: f32 = 1.5;
+const BAR_WIDTH: f32 = 120.0;
+
+pub struct HeatPlugin;
+
+impl Plugin for HeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_heat_bar)
+            .add_systems(Update, cool_weapons.run_if(in_state(PlayState::Running)))
+            .add_systems(Update, update_heat_bar);
+    }
+}
+
+/// Weapon heat on the player. Stays at zero unless `PlayerStats::heat_per_shot`
+/// is set, so firing is untouched until something turns overheating on.
+#[derive(Component, Default)]
+pub struct Heat {
+    pub value: f32,
+    lockout: Option<Timer>,
+}
+
+impl Heat {
+    pub fn is_locked(&self) -> bool {
+        self.lockout.is_some()
+    }
+
+    /// Adds one shot's worth of heat; reaching the max locks firing for a while.
+    pub fn add_shot(&mut self, stats: &PlayerStats) {
+        if stats.heat_per_shot <= 0.0 {
+            return;
+        }
+        self.value = (self.value + stats.heat_per_shot).min(stats.max_heat);
+        if self.value >= stats.max_heat {
+            self.lockout = Some(Timer::from_seconds(LOCKOUT_SECS, TimerMode::Once));
+        }
+    }
+}
+
+fn cool_weapons(time: GameTime, stats: Res<PlayerStats>, mut heat: Query<&mut Heat, With<Player>>) {
+    let Ok(mut heat) = heat.single_mut() else {
+        return;
+    };
+    // Leave the component untouched when idle so the bar's change detection stays quiet
+    if heat.value <= 0.0 && !heat.is_locked() {
+        return;
+    }
+
+    let heat = &mut *heat;
+    match &mut heat.lockout {
+        Some(lockout) => {
+            if lockout.tick(time.delta()).is_finished() {
+                heat.lockout = None;
+                heat.value = 0.0;
+            }
+        }
+        None => heat.value = (heat.value - stats.heat_decay * time.delta_secs()).max(0.0),
+    }
+}
+
+#[derive(Component)]
+struct HeatBar;
+
+#[derive(Component)]
+struct HeatBarFill;
+
+fn spawn_heat_bar(mut commands: Commands) {
+    let root = spawn_state_ui_root(&mut commands, GameState::Playing);
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(60.0),
+                left: Val::Px(10.0),
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            Visibility::Hidden,
+            HeatBar,
+            ChildOf(root),
+        ))
+        .with_children(|bar| {
+            bar.spawn((
+                Node {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(1.0, 0.5, 0.2)),
+                HeatBarFill,
+            ));
+        });
+}
+
+fn update_heat_bar(
+    stats: Res<PlayerStats>,
+    mut announcements: ResMut<Announcements>,
+    heat: Query<Ref<Heat>, With<Player>>,
+    mut bar: Query<&mut Visibility, With<HeatBar>>,
+    mut fill: Query<(&mut Node, &mut BackgroundColor), With<HeatBarFill>>,
+    mut was_locked: Local<bool>,
+) {
+    let (Ok(heat), Ok(mut visibility), Ok((mut node, mut color))) =
+        (heat.single(), bar.single_mut(), fill.single_mut())
+    else {
+        return;
+    };
+
+    if stats.is_changed() {
+        *visibility = if stats.heat_per_shot > 0.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    if !heat.is_changed() {
+        return;
+    }
+
+    if heat.is_locked() && !*was_locked {
+        announcements.push("Overheated!", 1.0, AnnouncementStyle::Warning);
+    }
+    *was_locked = heat.is_locked();
+
+    node.width = Val::Percent(100.0 * heat.value / stats.max_heat.max(f32::EPSILON));
+    color.0 = if heat.is_locked() {
+        Color::srgb(1.0, 0.15, 0.15)
+    } else {
+        Color::srgb(1.0, 0.5, 0.2)
+    };
+}
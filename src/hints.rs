@@ -0,0 +1,206 @@
+//! First-run prompts floated beside the player. Each one shows until the
+//! player does what it asks, then is gone for good on this profile. 5 on the
+//! results screen turns them off, or back on from scratch.
+
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::input::PlayerInputState;
+use crate::pickups::{Pickup, PickupCollected};
+use crate::storage::{self, Persistence};
+use crate::unlocks::RunRecord;
+use crate::{GameState, InGameEntity, PlayState, Player, Settings, layers};
+
+const HINTS_KEY: &str = "hints";
+/// Hints only start in a run's opening minute; later, the player knows.
+const HINT_WINDOW_SECS: f32 = 60.0;
+const HINT_FADE_IN_SECS: f32 = 0.5;
+const HINT_COLOR: Color = Color::srgb(0.9, 0.9, 0.7);
+
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SeenHints::load())
+            .add_systems(OnEnter(GameState::Playing), spawn_control_hints)
+            .add_systems(
+                Update,
+                (spawn_pickup_hint, dismiss_hints, place_hints)
+                    .chain()
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(Update, toggle_hints.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Hint {
+    Move,
+    Shoot,
+    Pickup,
+}
+
+const ALL_HINTS: &[Hint] = &[Hint::Move, Hint::Shoot, Hint::Pickup];
+
+impl Hint {
+    fn id(self) -> &'static str {
+        match self {
+            Hint::Move => "move",
+            Hint::Shoot => "shoot",
+            Hint::Pickup => "pickup",
+        }
+    }
+
+    fn text(self) -> &'static str {
+        match self {
+            Hint::Move => "WASD to move",
+            Hint::Shoot => "Click to shoot",
+            Hint::Pickup => "Walk over pickups to grab them",
+        }
+    }
+
+    /// Where it sits relative to the player, so hints up together never
+    /// cover each other or the player.
+    fn offset(self) -> Vec2 {
+        match self {
+            Hint::Move => Vec2::new(0.0, 36.0),
+            Hint::Shoot => Vec2::new(0.0, -36.0),
+            Hint::Pickup => Vec2::new(0.0, 56.0),
+        }
+    }
+}
+
+/// Hints already acted on, stored as one comma-separated line.
+#[derive(Resource, Default)]
+struct SeenHints(Vec<Hint>);
+
+impl SeenHints {
+    fn load() -> Self {
+        let text = storage::load(HINTS_KEY).unwrap_or_default();
+        let seen = text
+            .trim()
+            .split(',')
+            .filter_map(|id| ALL_HINTS.iter().copied().find(|h| h.id() == id))
+            .collect();
+        Self(seen)
+    }
+
+    fn save(&self, persistence: &mut Persistence) {
+        let ids: Vec<&str> = self.0.iter().map(|h| h.id()).collect();
+        persistence.save(HINTS_KEY, ids.join(","));
+    }
+
+    fn wants(&self, settings: &Settings, hint: Hint) -> bool {
+        settings.hints && !self.0.contains(&hint)
+    }
+}
+
+#[derive(Component)]
+struct HintText {
+    hint: Hint,
+    age: f32,
+}
+
+fn spawn_hint(commands: &mut Commands, fonts: &FontAssets, hint: Hint) {
+    commands.spawn((
+        Text2d::new(hint.text()),
+        fonts.body(14.0),
+        TextColor(HINT_COLOR.with_alpha(0.0)),
+        Transform::from_translation(hint.offset().extend(layers::FLOATING_TEXT)),
+        HintText { hint, age: 0.0 },
+        InGameEntity,
+    ));
+}
+
+fn spawn_control_hints(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    settings: Res<Settings>,
+    seen: Res<SeenHints>,
+) {
+    for hint in [Hint::Move, Hint::Shoot] {
+        if seen.wants(&settings, hint) {
+            spawn_hint(&mut commands, &fonts, hint);
+        }
+    }
+}
+
+/// Waits for the first pickup of the run to land.
+fn spawn_pickup_hint(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    settings: Res<Settings>,
+    seen: Res<SeenHints>,
+    record: Res<RunRecord>,
+    new_pickups: Query<(), Added<Pickup>>,
+    hints: Query<&HintText>,
+) {
+    if new_pickups.is_empty()
+        || record.secs() > HINT_WINDOW_SECS
+        || !seen.wants(&settings, Hint::Pickup)
+        || hints.iter().any(|h| h.hint == Hint::Pickup)
+    {
+        return;
+    }
+    spawn_hint(&mut commands, &fonts, Hint::Pickup);
+}
+
+/// The frame a hint's action happens, the hint goes and is remembered.
+fn dismiss_hints(
+    mut commands: Commands,
+    input: Res<PlayerInputState>,
+    mut collected: MessageReader<PickupCollected>,
+    mut seen: ResMut<SeenHints>,
+    mut persistence: ResMut<Persistence>,
+    hints: Query<(Entity, &HintText)>,
+) {
+    let picked_up = collected.read().count() > 0;
+    for (entity, text) in &hints {
+        let done = match text.hint {
+            Hint::Move => input.movement != Vec2::ZERO,
+            Hint::Shoot => input.fire,
+            Hint::Pickup => picked_up,
+        };
+        if done {
+            commands.entity(entity).despawn();
+            seen.0.push(text.hint);
+            seen.save(&mut persistence);
+        }
+    }
+}
+
+fn place_hints(
+    time: Res<Time>,
+    player: Query<&Transform, (With<Player>, Without<HintText>)>,
+    mut hints: Query<(&mut Transform, &mut TextColor, &mut HintText)>,
+) {
+    let Ok(player_tf) = player.single() else {
+        return;
+    };
+    let anchor = player_tf.translation.truncate();
+    for (mut tf, mut color, mut hint) in &mut hints {
+        hint.age += time.delta_secs();
+        let position = anchor + hint.hint.offset();
+        tf.translation.x = position.x;
+        tf.translation.y = position.y;
+        color.0 = HINT_COLOR.with_alpha((hint.age / HINT_FADE_IN_SECS).min(1.0));
+    }
+}
+
+/// Turning hints back on forgets which were seen, so all of them show again.
+fn toggle_hints(
+    input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut seen: ResMut<SeenHints>,
+    mut persistence: ResMut<Persistence>,
+) {
+    if !input.just_pressed(KeyCode::Digit5) {
+        return;
+    }
+    settings.hints = !settings.hints;
+    if settings.hints {
+        seen.0.clear();
+        seen.save(&mut persistence);
+    }
+    info!("hints {}", if settings.hints { "on" } else { "off" });
+}
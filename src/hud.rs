@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+
+use crate::endless::EndlessLoop;
+use crate::fonts::FontAssets;
+use crate::streaks::KillStreak;
+use crate::unlocks::RunRecord;
+use crate::{Curse, CurseText, GameState, Score, ScoreText, spawn_state_ui_root};
+
+/// The whole HUD is refreshed at most this often; nothing on it needs to be
+/// closer to live than this.
+const HUD_REFRESH_SECS: f32 = 0.1;
+/// Shown once per curse point; the bundled body font has the glyph.
+const CURSE_SKULL: &str = "☠";
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_run_readouts)
+            .add_systems(
+                Update,
+                update_hud.run_if(
+                    in_state(GameState::Playing)
+                        .and(on_timer(Duration::from_secs_f32(HUD_REFRESH_SECS))),
+                ),
+            );
+    }
+}
+
+#[derive(Component)]
+struct RunTimerText;
+
+#[derive(Component)]
+struct StreakText;
+
+fn spawn_run_readouts(mut commands: Commands, fonts: Res<FontAssets>) {
+    let root = spawn_state_ui_root(&mut commands, GameState::Playing);
+    commands.spawn((
+        Text::new("00:00"),
+        fonts.body(24.0),
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Percent(48.0),
+            ..default()
+        },
+        RunTimerText,
+        ChildOf(root),
+    ));
+    commands.spawn((
+        Text::new(""),
+        fonts.body(18.0),
+        TextColor(Color::srgb(1.0, 0.85, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),
+            left: Val::Percent(48.0),
+            ..default()
+        },
+        StreakText,
+        ChildOf(root),
+    ));
+}
+
+/// Assigns only when the shown string actually differs, so an unchanged
+/// value never marks the text changed or sends it back through layout.
+fn set_text(text: &mut Mut<Text>, value: String) {
+    if text.0 != value {
+        text.0 = value;
+    }
+}
+
+/// Every numeric HUD readout in one place, on a capped cadence. Each is
+/// formatted first and compared, so the timer only relays out when the
+/// second ticks over.
+fn update_hud(
+    score: Res<Score>,
+    curse: Res<Curse>,
+    record: Res<RunRecord>,
+    endless: Res<EndlessLoop>,
+    streak: Res<KillStreak>,
+    mut texts: ParamSet<(
+        Query<&mut Text, With<ScoreText>>,
+        Query<&mut Text, With<CurseText>>,
+        Query<&mut Text, With<RunTimerText>>,
+        Query<&mut Text, With<StreakText>>,
+    )>,
+) {
+    if let Ok(mut text) = texts.p0().single_mut() {
+        set_text(&mut text, format!("Score: {}", score.0));
+    }
+    if let Ok(mut text) = texts.p1().single_mut() {
+        let value = CURSE_SKULL.repeat(curse.0 as usize);
+        set_text(&mut text, value);
+    }
+    if let Ok(mut text) = texts.p2().single_mut() {
+        let secs = record.secs() as u32;
+        let clock = format!("{:02}:{:02}", secs / 60, secs % 60);
+        let value = match endless.current() {
+            Some(current) => format!("Loop {current}  {clock}"),
+            None => clock,
+        };
+        set_text(&mut text, value);
+    }
+    if let Ok(mut text) = texts.p3().single_mut() {
+        let value = if streak.count() == 0 {
+            String::new()
+        } else {
+            format!("Streak: {}", streak.count())
+        };
+        set_text(&mut text, value);
+    }
+}
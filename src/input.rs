@@ -0,0 +1,481 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::input::InputSystems;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::{PrimaryWindow, WindowScaleFactorChanged};
+
+use crate::GameState;
+use crate::storage::{self, Persistence};
+use crate::upgrades::find_upgrade;
+
+const REPLAY_MAGIC: &[u8; 4] = b"VREP";
+const REPLAY_VERSION: u8 = 3;
+/// Pixels a left-half drag must travel before it counts as movement.
+const TOUCH_DEADZONE: f32 = 12.0;
+const FOCUS_KEY: KeyCode = KeyCode::ShiftLeft;
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        let replay = Replay::from_args(std::env::args().skip(1));
+        if let Replay::Playback { frames, .. } = &replay
+            && let Some(first) = frames.first()
+        {
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(first.duration()));
+        }
+
+        app.init_resource::<PlayerInputState>()
+            .insert_resource(replay)
+            .add_message::<LevelUpChoice>()
+            .add_systems(
+                PreUpdate,
+                (read_live_input, step_replay)
+                    .chain()
+                    .in_set(PlayerInputSystems)
+                    .after(InputSystems),
+            )
+            .add_systems(PostUpdate, record_level_up_choices)
+            .add_systems(OnEnter(GameState::GameOver), finish_recording);
+    }
+}
+
+/// Fills `PlayerInputState` from devices or a replay. Anything that wants to
+/// hide devices from gameplay runs before this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerInputSystems;
+
+/// This frame's player intent. Gameplay reads this instead of devices, so a
+/// replay can stand in for the keyboard and mouse.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
+pub struct PlayerInputState {
+    /// Unnormalized WASD direction.
+    pub movement: Vec2,
+    /// Fire was pressed this frame.
+    pub fire: bool,
+    /// Fire is down, whenever it was pressed.
+    pub fire_held: bool,
+    /// Cursor in world space, if it is over the window.
+    pub aim: Option<Vec2>,
+    /// The ultimate was pressed this frame.
+    pub ultimate: bool,
+    /// Melee was pressed this frame.
+    pub melee: bool,
+    /// Focus is held: move at a crawl with the hitbox shown.
+    pub focus: bool,
+    /// A level-up choice to make this frame. Only a replay sets this; live
+    /// choices come from the level-up panel.
+    pub level_up: Option<LevelUpChoice>,
+}
+
+/// What was done with a level-up, written when the choice is made so a
+/// recording can make it again.
+#[derive(Message, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LevelUpChoice {
+    /// Took the upgrade with this id.
+    Take(&'static str),
+    Skip,
+}
+
+/// One frame of recorded input, with the frame time it ran at.
+#[derive(Clone, Copy, Debug)]
+struct InputFrame {
+    dt: f32,
+    input: PlayerInputState,
+}
+
+impl InputFrame {
+    fn duration(&self) -> Duration {
+        Duration::from_secs_f32(self.dt.max(0.0))
+    }
+}
+
+/// A recording is the player's side of a run: input, frame times and
+/// level-up choices. Runs aren't seeded, so spawns, drops and offers still
+/// come from the thread RNG, and a playback repeats what the player did
+/// rather than the run it was done in.
+#[derive(Resource)]
+enum Replay {
+    Off,
+    Recording {
+        path: PathBuf,
+        frames: Vec<InputFrame>,
+        choices: Vec<(u32, LevelUpChoice)>,
+    },
+    Playback {
+        frames: Vec<InputFrame>,
+        choices: Vec<(u32, LevelUpChoice)>,
+        cursor: usize,
+    },
+}
+
+impl Replay {
+    /// `--record <path>` captures the first run; `--replay <path>` plays one back.
+    /// Other arguments are skipped.
+    fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        while let Some(arg) = args.next() {
+            if arg != "--record" && arg != "--replay" {
+                continue;
+            }
+            let Some(path) = args.next().map(PathBuf::from) else {
+                warn!("{arg} needs a path");
+                break;
+            };
+            if arg == "--record" {
+                return Replay::Recording {
+                    path,
+                    frames: Vec::new(),
+                    choices: Vec::new(),
+                };
+            }
+            match storage::read_file(&path).map(|bytes| decode(&bytes)) {
+                Ok(Some(Decoded { frames, choices })) => {
+                    return Replay::Playback {
+                        frames,
+                        choices,
+                        cursor: 0,
+                    };
+                }
+                Ok(None) => warn!("{} is not a replay file", path.display()),
+                Err(err) => warn!("could not read replay {}: {err}", path.display()),
+            }
+        }
+        Replay::Off
+    }
+}
+
+fn encode(frames: &[InputFrame], choices: &[(u32, LevelUpChoice)]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(13 + frames.len() * 21);
+    out.extend_from_slice(REPLAY_MAGIC);
+    out.push(REPLAY_VERSION);
+
+    out.extend_from_slice(&(frames.len() as u32).to_le_bytes());
+    for frame in frames {
+        let input = &frame.input;
+        let flags = u8::from(input.fire)
+            | u8::from(input.ultimate) << 1
+            | u8::from(input.aim.is_some()) << 2
+            | u8::from(input.melee) << 3
+            | u8::from(input.fire_held) << 4
+            | u8::from(input.focus) << 5;
+        let aim = input.aim.unwrap_or_default();
+        for value in [frame.dt, input.movement.x, input.movement.y, aim.x, aim.y] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out.push(flags);
+    }
+
+    // An upgrade id, or no id for a skip
+    out.extend_from_slice(&(choices.len() as u32).to_le_bytes());
+    for (frame, choice) in choices {
+        let id = match choice {
+            LevelUpChoice::Take(id) => id.as_bytes(),
+            LevelUpChoice::Skip => &[],
+        };
+        out.extend_from_slice(&frame.to_le_bytes());
+        out.push(id.len() as u8);
+        out.extend_from_slice(id);
+    }
+    out
+}
+
+struct Decoded {
+    frames: Vec<InputFrame>,
+    choices: Vec<(u32, LevelUpChoice)>,
+}
+
+fn decode(bytes: &[u8]) -> Option<Decoded> {
+    let mut rest = bytes;
+    let mut take = |n: usize| -> Option<&[u8]> {
+        let (head, tail) = rest.split_at_checked(n)?;
+        rest = tail;
+        Some(head)
+    };
+    let u32_at = |b: &[u8]| u32::from_le_bytes(b.try_into().unwrap());
+    let f32_at = |b: &[u8]| f32::from_le_bytes(b.try_into().unwrap());
+
+    if take(4)? != REPLAY_MAGIC || take(1)?[0] != REPLAY_VERSION {
+        return None;
+    }
+
+    let frame_count = u32_at(take(4)?) as usize;
+    let mut frames = Vec::with_capacity(frame_count);
+    for _ in 0..frame_count {
+        let values = take(20)?;
+        let value = |i: usize| f32_at(&values[i * 4..i * 4 + 4]);
+        let flags = take(1)?[0];
+        frames.push(InputFrame {
+            dt: value(0),
+            input: PlayerInputState {
+                movement: Vec2::new(value(1), value(2)),
+                fire: flags & 1 != 0,
+                ultimate: flags & 2 != 0,
+                aim: (flags & 4 != 0).then(|| Vec2::new(value(3), value(4))),
+                melee: flags & 8 != 0,
+                fire_held: flags & 16 != 0,
+                focus: flags & 32 != 0,
+                level_up: None,
+            },
+        });
+    }
+
+    let choice_count = u32_at(take(4)?) as usize;
+    let mut choices = Vec::with_capacity(choice_count);
+    for _ in 0..choice_count {
+        let frame = u32_at(take(4)?);
+        let len = take(1)?[0] as usize;
+        let choice = match take(len)? {
+            [] => LevelUpChoice::Skip,
+            id => LevelUpChoice::Take(find_upgrade(std::str::from_utf8(id).ok()?)?.id),
+        };
+        choices.push((frame, choice));
+    }
+    Some(Decoded { frames, choices })
+}
+
+/// Keyboard and mouse, with touch on top: a drag that starts on the left half
+/// of the screen is a movement stick, a tap on the right half fires at it.
+fn read_live_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    mut scale_changes: MessageReader<WindowScaleFactorChanged>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    mut last_tap: Local<Option<Vec2>>,
+    mut input: ResMut<PlayerInputState>,
+) {
+    let mut movement = Vec2::ZERO;
+    if keys.pressed(KeyCode::KeyA) {
+        movement.x -= 1.0;
+    }
+    if keys.pressed(KeyCode::KeyD) {
+        movement.x += 1.0;
+    }
+    if keys.pressed(KeyCode::KeyW) {
+        movement.y += 1.0;
+    }
+    if keys.pressed(KeyCode::KeyS) {
+        movement.y -= 1.0;
+    }
+
+    let mut fire = mouse.just_pressed(MouseButton::Left);
+    let mut fire_held = mouse.pressed(MouseButton::Left);
+    let mut aim = None;
+
+    // A tap is kept in logical pixels, which move under it when the scale
+    // factor changes; better no touch aim than aim at the wrong spot
+    if scale_changes.read().count() > 0 {
+        *last_tap = None;
+    }
+
+    if let (Ok(window), Ok((camera, cam_tf))) = (windows.single(), camera_q.single()) {
+        let half_width = window.width() / 2.0;
+
+        for touch in touches.iter() {
+            let drag = touch.position() - touch.start_position();
+            if touch.start_position().x < half_width {
+                if drag.length() > TOUCH_DEADZONE {
+                    // Screen y points down
+                    movement += Vec2::new(drag.x, -drag.y).normalize();
+                }
+            } else {
+                fire_held = true;
+            }
+        }
+        for touch in touches.iter_just_pressed() {
+            if touch.position().x >= half_width {
+                fire = true;
+                *last_tap = Some(touch.position());
+            }
+        }
+
+        // Touch screens have no cursor; the last tap stands in for it
+        aim = window
+            .cursor_position()
+            .or(*last_tap)
+            .and_then(|cursor| cursor_to_world(camera, cam_tf, cursor));
+    }
+
+    *input = PlayerInputState {
+        movement,
+        fire,
+        fire_held,
+        aim,
+        ultimate: keys.just_pressed(KeyCode::KeyF),
+        melee: mouse.just_pressed(MouseButton::Right),
+        focus: keys.pressed(FOCUS_KEY),
+        level_up: None,
+    };
+}
+
+/// Cursor and touch positions are logical pixels, which is also what
+/// `viewport_to_world_2d` takes, camera viewport offset included. Aim must
+/// never pass through physical pixels on the way, or it drifts on any
+/// display with OS scaling.
+fn cursor_to_world(camera: &Camera, cam_tf: &GlobalTransform, cursor: Vec2) -> Option<Vec2> {
+    camera.viewport_to_world_2d(cam_tf, cursor).ok()
+}
+
+/// Records the live input, or overwrites it with the recorded frame.
+fn step_replay(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    mut replay: ResMut<Replay>,
+    mut input: ResMut<PlayerInputState>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+) {
+    match &mut *replay {
+        Replay::Off => {}
+        Replay::Recording { frames, .. } => {
+            if *state.get() != GameState::Playing {
+                return;
+            }
+            frames.push(InputFrame {
+                dt: time.delta_secs(),
+                input: *input,
+            });
+        }
+        Replay::Playback {
+            frames,
+            choices,
+            cursor,
+        } => {
+            if *state.get() != GameState::Playing {
+                return;
+            }
+            let Some(frame) = frames.get(*cursor) else {
+                return;
+            };
+            let index = *cursor as u32;
+
+            *input = frame.input;
+            input.level_up = choices
+                .iter()
+                .find(|(f, _)| *f == index)
+                .map(|&(_, choice)| choice);
+            *cursor += 1;
+            // Feed the next frame the same delta it was recorded with
+            match frames.get(*cursor) {
+                Some(next) => *time_strategy = TimeUpdateStrategy::ManualDuration(next.duration()),
+                None => {
+                    *time_strategy = TimeUpdateStrategy::Automatic;
+                    info!("replay finished after {} frames", frames.len());
+                }
+            }
+        }
+    }
+}
+
+/// Pins each choice to the frame it was made on, which `step_replay` has
+/// already recorded by the time the level-up panel runs.
+fn record_level_up_choices(mut made: MessageReader<LevelUpChoice>, mut replay: ResMut<Replay>) {
+    let Replay::Recording {
+        frames, choices, ..
+    } = &mut *replay
+    else {
+        made.clear();
+        return;
+    };
+    let index = frames.len().saturating_sub(1) as u32;
+    choices.extend(made.read().map(|&choice| (index, choice)));
+}
+
+fn finish_recording(mut replay: ResMut<Replay>, mut persistence: ResMut<Persistence>) {
+    let Replay::Recording {
+        path,
+        frames,
+        choices,
+    } = &*replay
+    else {
+        return;
+    };
+
+    persistence.save_file(path.clone(), encode(frames, choices));
+    info!("saving {} frames to {}", frames.len(), path.display());
+    // Only the first run is captured
+    *replay = Replay::Off;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::camera::{CameraProjection, RenderTargetInfo};
+
+    use super::*;
+
+    /// A camera on an 800x600 logical window at `scale_factor`, as
+    /// `camera_system` would leave it.
+    fn camera(scale_factor: f32) -> Camera {
+        let mut projection = OrthographicProjection::default_2d();
+        projection.update(800.0, 600.0);
+        let mut camera = Camera::default();
+        camera.computed.clip_from_view = projection.get_clip_from_view();
+        camera.computed.target_info = Some(RenderTargetInfo {
+            physical_size: (Vec2::new(800.0, 600.0) * scale_factor).as_uvec2(),
+            scale_factor,
+        });
+        camera
+    }
+
+    #[test]
+    fn cursor_to_world_uses_logical_pixels_at_scale_factor_two() {
+        let cam_tf = GlobalTransform::from_translation(Vec3::new(100.0, -50.0, 0.0));
+        let camera = camera(2.0);
+        let center = cursor_to_world(&camera, &cam_tf, Vec2::new(400.0, 300.0)).unwrap();
+        assert!(center.distance(Vec2::new(100.0, -50.0)) < 1e-3);
+        let corner = cursor_to_world(&camera, &cam_tf, Vec2::new(600.0, 150.0)).unwrap();
+        assert!(corner.distance(Vec2::new(300.0, 100.0)) < 1e-3);
+    }
+
+    #[test]
+    fn cursor_to_world_ignores_the_scale_factor() {
+        let cam_tf = GlobalTransform::IDENTITY;
+        let cursor = Vec2::new(123.0, 456.0);
+        let one = cursor_to_world(&camera(1.0), &cam_tf, cursor).unwrap();
+        let two = cursor_to_world(&camera(2.0), &cam_tf, cursor).unwrap();
+        assert!(one.distance(two) < 1e-3);
+    }
+
+    #[test]
+    fn a_replay_reads_back_what_was_recorded() {
+        let frames = [
+            InputFrame {
+                dt: 1.0 / 60.0,
+                input: PlayerInputState {
+                    movement: Vec2::new(1.0, -1.0),
+                    fire: true,
+                    fire_held: true,
+                    aim: Some(Vec2::new(12.5, -3.0)),
+                    focus: true,
+                    ..default()
+                },
+            },
+            InputFrame {
+                dt: 0.02,
+                input: PlayerInputState {
+                    ultimate: true,
+                    melee: true,
+                    ..default()
+                },
+            },
+        ];
+        let choices = [(0, LevelUpChoice::Take("clover")), (1, LevelUpChoice::Skip)];
+        let decoded = decode(&encode(&frames, &choices)).unwrap();
+        assert_eq!(decoded.choices, choices);
+        assert_eq!(decoded.frames.len(), 2);
+        for (read, written) in decoded.frames.iter().zip(&frames) {
+            assert_eq!(read.dt, written.dt);
+            assert_eq!(read.input, written.input);
+        }
+    }
+
+    #[test]
+    fn a_replay_from_another_version_or_cut_short_is_refused() {
+        let mut bytes = encode(&[], &[(0, LevelUpChoice::Take("clover"))]);
+        assert!(decode(&bytes[..bytes.len() - 1]).is_none());
+        bytes[4] = REPLAY_VERSION - 1;
+        assert!(decode(&bytes).is_none());
+    }
+}
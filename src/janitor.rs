@@ -0,0 +1,121 @@
+//! Keeps long runs from piling up inert entities. Each category has a cap,
+//! and once a second the oldest entries over it are cleared away. Pooled
+//! cosmetics are capped by the `CosmeticBudget` instead, and swept the
+//! moment it shrinks as well, nearest the end of their fade first.
+//!
+//! Experience is credited the moment it drops, so there are no gems lying
+//! around to merge, and nothing the janitor removes is ever earned XP.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+
+use crate::despawn::{Dead, PendingDespawns};
+use crate::performance::CosmeticBudget;
+use crate::pickups::Pickup;
+use crate::{Afterimage, DeathParticle, Dying, GameState};
+
+const SWEEP_SECS: f32 = 1.0;
+const MAX_CORPSES: usize = 100;
+const MAX_PICKUPS: usize = 50;
+
+pub struct FieldJanitorPlugin;
+
+impl Plugin for FieldJanitorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                stamp_spawned,
+                field_janitor.run_if(
+                    on_timer(Duration::from_secs_f32(SWEEP_SECS))
+                        .or(resource_changed::<CosmeticBudget>),
+                ),
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// When an entity joined a janitor category, in seconds since startup.
+#[derive(Component)]
+pub struct SpawnedAt(pub f32);
+
+/// Corpses only turn into `Dying` on death, so they are stamped as they
+/// appear rather than at spawn.
+fn stamp_spawned(
+    mut commands: Commands,
+    time: Res<Time>,
+    new: Query<Entity, (Or<(Added<Dying>, Added<Pickup>)>, Without<SpawnedAt>)>,
+) {
+    for entity in &new {
+        commands
+            .entity(entity)
+            .insert(SpawnedAt(time.elapsed_secs()));
+    }
+}
+
+fn field_janitor(
+    mut commands: Commands,
+    mut pending: ResMut<PendingDespawns>,
+    corpses: Query<(Entity, &SpawnedAt), (With<Dying>, Without<Dead>)>,
+    pickups: Query<(Entity, &SpawnedAt), (With<Pickup>, Without<Dead>)>,
+    budget: Res<CosmeticBudget>,
+    mut afterimages: Query<(Entity, &mut Afterimage)>,
+    mut particles: Query<(Entity, &mut DeathParticle)>,
+) {
+    for entity in oldest_over_cap(corpses.iter(), MAX_CORPSES) {
+        pending.push(&mut commands, entity);
+    }
+    for entity in oldest_over_cap(pickups.iter(), MAX_PICKUPS) {
+        pending.push(&mut commands, entity);
+    }
+
+    // Run out rather than released here, so the pool only ever takes them
+    // back from the systems that fade them
+    let faded = afterimages
+        .iter()
+        .map(|(entity, a)| (entity, a.0.fraction()));
+    for entity in most_faded_over_cap(faded, budget.trails) {
+        if let Ok((_, mut afterimage)) = afterimages.get_mut(entity) {
+            afterimage.0.finish();
+        }
+    }
+    let faded = particles
+        .iter()
+        .map(|(entity, p)| (entity, p.timer.fraction()));
+    for entity in most_faded_over_cap(faded, budget.particles) {
+        if let Ok((_, mut particle)) = particles.get_mut(entity) {
+            particle.timer.finish();
+        }
+    }
+}
+
+/// The entries past `cap`, by how far through their fade they are, furthest
+/// first.
+fn most_faded_over_cap(entries: impl Iterator<Item = (Entity, f32)>, cap: usize) -> Vec<Entity> {
+    let mut entries: Vec<(Entity, f32)> = entries.collect();
+    if entries.len() <= cap {
+        return Vec::new();
+    }
+    entries.sort_by(|a, b| b.1.total_cmp(&a.1));
+    entries.truncate(entries.len() - cap);
+    entries.into_iter().map(|(entity, _)| entity).collect()
+}
+
+/// The entries past `cap`, oldest first.
+fn oldest_over_cap<'a>(
+    entries: impl Iterator<Item = (Entity, &'a SpawnedAt)>,
+    cap: usize,
+) -> Vec<Entity> {
+    let mut entries: Vec<(Entity, f32)> = entries.map(|(entity, at)| (entity, at.0)).collect();
+    if entries.len() <= cap {
+        return Vec::new();
+    }
+    entries.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let excess = entries.len() - cap;
+    entries.truncate(excess);
+    entries.into_iter().map(|(entity, _)| entity).collect()
+}
@@ -0,0 +1,11 @@
+//! Z ordering for world-space sprites and text, lowest first. Movement only
+//! ever touches x/y, so an entity keeps the layer it was spawned on.
+//! z = 0 is left free for a background.
+
+/// The best run's ghost, under everything that plays.
+pub const GHOST: f32 = 0.5;
+pub const PICKUPS: f32 = 1.0;
+pub const ENEMIES: f32 = 2.0;
+pub const BULLETS: f32 = 3.0;
+pub const PLAYER: f32 = 4.0;
+pub const FLOATING_TEXT: f32 = 5.0;
@@ -0,0 +1,227 @@
+//! A cheap leak detector for dev builds. Every few seconds of uninterrupted
+//! play it counts entities by category, and a category that has grown at
+//! every one of the last few samples gets a warning in the log: the usual
+//! sign of something spawned per event and never despawned.
+//!
+//! Enemy numbers climb on their own early in a run, so a warning there is a
+//! prompt to look, not proof.
+
+use bevy::prelude::*;
+
+use crate::pickups::Pickup;
+use crate::{Bullet, Dying, Enemy, GameState, PlayState, ResetSet};
+
+const SAMPLE_SECS: f32 = 5.0;
+/// Samples in a row that must each be higher than the last.
+const SAMPLES: usize = 6;
+
+#[derive(Clone, Copy)]
+enum Category {
+    Total,
+    Enemies,
+    Bullets,
+    Pickups,
+    Corpses,
+    UiNodes,
+}
+
+const CATEGORIES: [Category; 6] = [
+    Category::Total,
+    Category::Enemies,
+    Category::Bullets,
+    Category::Pickups,
+    Category::Corpses,
+    Category::UiNodes,
+];
+
+impl Category {
+    fn label(self) -> &'static str {
+        match self {
+            Category::Total => "entities",
+            Category::Enemies => "enemies",
+            Category::Bullets => "bullets",
+            Category::Pickups => "pickups",
+            Category::Corpses => "corpses",
+            Category::UiNodes => "UI nodes",
+        }
+    }
+}
+
+pub struct LeakWatchPlugin;
+
+impl Plugin for LeakWatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LeakWatch>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                reset_leak_watch.in_set(ResetSet),
+            )
+            // Only counted while the run is actually moving, so a menu or a
+            // level-up choice never reads as growth
+            .add_systems(Update, sample_entities.run_if(in_state(PlayState::Running)));
+    }
+}
+
+/// Per category, the last count and how many samples in a row have risen,
+/// in fixed arrays so sampling never allocates.
+#[derive(Resource)]
+struct LeakWatch {
+    timer: Timer,
+    last: [Option<u32>; CATEGORIES.len()],
+    rises: [usize; CATEGORIES.len()],
+    /// The count the current streak of rises started from.
+    streak_start: [u32; CATEGORIES.len()],
+    /// Warnings raised this run.
+    warnings: u32,
+}
+
+impl Default for LeakWatch {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(SAMPLE_SECS, TimerMode::Repeating),
+            last: [None; CATEGORIES.len()],
+            rises: [0; CATEGORIES.len()],
+            streak_start: [0; CATEGORIES.len()],
+            warnings: 0,
+        }
+    }
+}
+
+impl LeakWatch {
+    /// Takes the `i`th category's new count. Returns the count its streak
+    /// started from once it has risen at each of the last `SAMPLES`.
+    fn record(&mut self, i: usize, count: u32) -> Option<u32> {
+        match self.last[i] {
+            Some(last) if count > last => {
+                if self.rises[i] == 0 {
+                    self.streak_start[i] = last;
+                }
+                self.rises[i] += 1;
+            }
+            _ => self.rises[i] = 0,
+        }
+        self.last[i] = Some(count);
+
+        // `SAMPLES` rising counts have one fewer rise between them
+        if self.rises[i] < SAMPLES - 1 {
+            return None;
+        }
+        // Start the streak over so a real leak warns once per window rather
+        // than every sample
+        self.rises[i] = 0;
+        self.warnings += 1;
+        Some(self.streak_start[i])
+    }
+}
+
+fn reset_leak_watch(mut watch: ResMut<LeakWatch>) {
+    *watch = LeakWatch::default();
+}
+
+fn sample_entities(
+    time: Res<Time>,
+    mut watch: ResMut<LeakWatch>,
+    all: Query<()>,
+    enemies: Query<(), With<Enemy>>,
+    bullets: Query<(), With<Bullet>>,
+    pickups: Query<(), With<Pickup>>,
+    corpses: Query<(), With<Dying>>,
+    nodes: Query<(), With<Node>>,
+) {
+    if !watch.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    for (i, category) in CATEGORIES.into_iter().enumerate() {
+        let count = match category {
+            Category::Total => all.iter().count(),
+            Category::Enemies => enemies.iter().count(),
+            Category::Bullets => bullets.iter().count(),
+            Category::Pickups => pickups.iter().count(),
+            Category::Corpses => corpses.iter().count(),
+            Category::UiNodes => nodes.iter().count(),
+        } as u32;
+
+        if let Some(start) = watch.record(i, count) {
+            warn!(
+                "possible leak: {} grew at each of the last {SAMPLES} samples ({start} -> {count})",
+                category.label(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    /// Every update is one sample.
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .insert_state(GameState::Playing)
+            .add_sub_state::<PlayState>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                SAMPLE_SECS,
+            )))
+            .add_plugins(LeakWatchPlugin);
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .set_max_delta(Duration::from_secs_f32(SAMPLE_SECS));
+        app
+    }
+
+    fn entity_count(app: &mut App) -> usize {
+        let world = app.world_mut();
+        world.query::<()>().iter(world).count()
+    }
+
+    fn spawn_nodes(app: &mut App, n: usize) -> Vec<Entity> {
+        (0..n)
+            .map(|_| app.world_mut().spawn(Node::default()).id())
+            .collect()
+    }
+
+    #[test]
+    fn entity_count_is_stable_across_spawn_despawn_cycles() {
+        let mut app = headless_app();
+        app.update();
+        let baseline = entity_count(&mut app);
+        for _ in 0..SAMPLES * 3 {
+            let nodes = spawn_nodes(&mut app, 20);
+            app.update();
+            for node in nodes {
+                app.world_mut().despawn(node);
+            }
+            app.update();
+            assert_eq!(entity_count(&mut app), baseline);
+        }
+        assert_eq!(app.world().resource::<LeakWatch>().warnings, 0);
+    }
+
+    #[test]
+    fn a_leak_is_caught() {
+        let mut app = headless_app();
+        app.update();
+        for _ in 0..SAMPLES {
+            spawn_nodes(&mut app, 3);
+            app.update();
+        }
+        // Both the total and the UI nodes kept growing
+        assert_eq!(app.world().resource::<LeakWatch>().warnings, 2);
+    }
+
+    #[test]
+    fn a_dip_restarts_the_streak() {
+        let mut watch = LeakWatch::default();
+        for count in [1, 2, 3, 2, 3, 4, 5, 6] {
+            assert_eq!(watch.record(0, count), None);
+        }
+        assert_eq!(watch.record(0, 7), Some(2));
+        assert_eq!(watch.record(0, 8), None);
+    }
+}
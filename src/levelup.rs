@@ -0,0 +1,434 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::announcements::{AnnouncementStyle, Announcements};
+use crate::build_hud::BuildChanged;
+use crate::challenge::RunRules;
+use crate::fonts::FontAssets;
+use crate::input::{LevelUpChoice, PlayerInputState};
+use crate::message_log::LogMessage;
+use crate::upgrades::{UpgradeDef, UpgradePool, UpgradeTargets, find_upgrade, format_delta};
+use crate::weapons::WeaponSlots;
+use crate::{Curse, EnemyKilledEvent, GameState, Gold, PlayState, Player, PlayerStats, ResetSet};
+
+const OFFER_SIZE: usize = 3;
+const SKIP_GOLD: u32 = 10;
+const BUTTON_COLOR: Color = Color::srgb(0.15, 0.15, 0.2);
+const BANISH_COLOR: Color = Color::srgb(0.5, 0.15, 0.15);
+
+pub struct LevelUpPlugin;
+
+impl Plugin for LevelUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Experience>()
+            .init_resource::<LevelUpOffer>()
+            .init_resource::<UpgradePool>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                reset_experience.in_set(ResetSet),
+            )
+            .add_systems(Update, gain_experience.run_if(in_state(PlayState::Running)))
+            .add_systems(OnEnter(PlayState::LevelUp), open_offer)
+            .add_systems(
+                Update,
+                (level_up_input, refresh_level_up_ui)
+                    .chain()
+                    .run_if(in_state(PlayState::LevelUp)),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct Experience {
+    pub xp: f32,
+    pub level: u32,
+    /// Level-ups earned but not yet chosen.
+    pub pending: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self {
+            xp: 0.0,
+            level: 1,
+            pending: 0,
+        }
+    }
+}
+
+fn xp_to_next(level: u32, rules: &RunRules) -> f32 {
+    5.0 * level as f32 * rules.xp_needed
+}
+
+/// Redraws until the set of options differs from `previous`, if the pool allows it.
+fn reroll_offer(
+    pool: &UpgradePool,
+    luck: f32,
+    rng: &mut impl Rng,
+    previous: &[&'static UpgradeDef],
+) -> Vec<&'static UpgradeDef> {
+    let same_set = |offer: &[&'static UpgradeDef]| {
+        offer.len() == previous.len() && offer.iter().all(|u| previous.iter().any(|p| p.id == u.id))
+    };
+
+    let mut offer = pool.draw(OFFER_SIZE, luck, rng);
+    for _ in 0..16 {
+        if !same_set(&offer) {
+            break;
+        }
+        offer = pool.draw(OFFER_SIZE, luck, rng);
+    }
+    offer
+}
+
+#[derive(Resource, Default)]
+struct LevelUpOffer {
+    options: Vec<&'static UpgradeDef>,
+    /// The next card picked is banished instead of taken.
+    banishing: bool,
+}
+
+#[derive(Component)]
+struct LevelUpUi;
+
+#[derive(Component, Clone, Copy)]
+enum LevelUpButton {
+    Pick(usize),
+    Reroll,
+    /// Arms or disarms banishing.
+    Banish,
+    /// Holds or releases the current offer for the next level-up.
+    Lock,
+    Skip,
+    /// A replayed pick, taken whether or not this run offered it.
+    Take(&'static UpgradeDef),
+}
+
+fn reset_experience(mut experience: ResMut<Experience>, mut pool: ResMut<UpgradePool>) {
+    *experience = Experience::default();
+    pool.reset();
+}
+
+fn gain_experience(
+    mut kills: MessageReader<EnemyKilledEvent>,
+    curse: Res<Curse>,
+    rules: Res<RunRules>,
+    mut experience: ResMut<Experience>,
+    mut announcements: ResMut<Announcements>,
+    mut log: MessageWriter<LogMessage>,
+    mut next_state: ResMut<NextState<PlayState>>,
+) {
+    for kill in kills.read() {
+        experience.xp += kill.points as f32 * curse.reward_multiplier();
+    }
+
+    while experience.xp >= xp_to_next(experience.level, &rules) {
+        experience.xp -= xp_to_next(experience.level, &rules);
+        experience.level += 1;
+        experience.pending += 1;
+        log.write(LogMessage(format!("Reached level {}", experience.level)));
+
+        if experience.level.is_multiple_of(5) {
+            announcements.push(
+                format!("Level {}!", experience.level),
+                2.0,
+                AnnouncementStyle::Milestone,
+            );
+        }
+    }
+
+    if experience.pending > 0 {
+        next_state.set(PlayState::LevelUp);
+    }
+}
+
+fn open_offer(
+    mut offer: ResMut<LevelUpOffer>,
+    mut pool: ResMut<UpgradePool>,
+    stats: Res<PlayerStats>,
+) {
+    offer.options = pool.next_offer(OFFER_SIZE, stats.luck, &mut thread_rng());
+    offer.banishing = false;
+}
+
+fn level_up_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    input: Res<PlayerInputState>,
+    buttons: Query<(&Interaction, &LevelUpButton), Changed<Interaction>>,
+    mut offer: ResMut<LevelUpOffer>,
+    mut pool: ResMut<UpgradePool>,
+    mut stats: ResMut<PlayerStats>,
+    mut curse: ResMut<Curse>,
+    mut gold: ResMut<Gold>,
+    mut weapons: Single<&mut WeaponSlots, With<Player>>,
+    mut experience: ResMut<Experience>,
+    mut announcements: ResMut<Announcements>,
+    mut build_changed: MessageWriter<BuildChanged>,
+    mut choices: MessageWriter<LevelUpChoice>,
+    mut next_state: ResMut<NextState<PlayState>>,
+) {
+    let replayed = input.level_up.and_then(|choice| match choice {
+        LevelUpChoice::Take(id) => find_upgrade(id).map(LevelUpButton::Take),
+        LevelUpChoice::Skip => Some(LevelUpButton::Skip),
+    });
+
+    let clicked = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| *button);
+
+    let pressed = if keys.just_pressed(KeyCode::Digit1) {
+        Some(LevelUpButton::Pick(0))
+    } else if keys.just_pressed(KeyCode::Digit2) {
+        Some(LevelUpButton::Pick(1))
+    } else if keys.just_pressed(KeyCode::Digit3) {
+        Some(LevelUpButton::Pick(2))
+    } else if keys.just_pressed(KeyCode::KeyR) {
+        Some(LevelUpButton::Reroll)
+    } else if keys.just_pressed(KeyCode::KeyX) {
+        Some(LevelUpButton::Banish)
+    } else if keys.just_pressed(KeyCode::KeyK) {
+        Some(LevelUpButton::Lock)
+    } else if keys.any_just_pressed([KeyCode::KeyS, KeyCode::Escape]) {
+        Some(LevelUpButton::Skip)
+    } else {
+        None
+    };
+
+    let Some(action) = replayed.or(clicked).or(pressed) else {
+        return;
+    };
+
+    let mut rng = thread_rng();
+    let taken = match action {
+        LevelUpButton::Pick(index) => {
+            let Some(upgrade) = offer.options.get(index).copied() else {
+                return;
+            };
+            if offer.banishing {
+                offer.banishing = false;
+                if pool.banish(upgrade) {
+                    offer.options.remove(index);
+                    let mut options = std::mem::take(&mut offer.options);
+                    pool.refill(&mut options, OFFER_SIZE, stats.luck, &mut rng);
+                    if pool.is_holding() {
+                        pool.hold(&options);
+                    }
+                    offer.options = options;
+                    announcements.push(
+                        format!("Banished {}", upgrade.name),
+                        1.5,
+                        AnnouncementStyle::Info,
+                    );
+                }
+                return;
+            }
+            Some(upgrade)
+        }
+        LevelUpButton::Take(upgrade) => Some(upgrade),
+        LevelUpButton::Reroll => {
+            if stats.rerolls > 0 {
+                stats.rerolls -= 1;
+                // A new offer is no longer the one that was kept
+                pool.release_hold();
+                offer.options = reroll_offer(&pool, stats.luck, &mut rng, &offer.options);
+            }
+            return;
+        }
+        LevelUpButton::Banish => {
+            offer.banishing = !offer.banishing && pool.banishes_left() > 0;
+            return;
+        }
+        LevelUpButton::Lock => {
+            if pool.is_holding() {
+                pool.release_hold();
+            } else {
+                pool.hold(&offer.options);
+            }
+            return;
+        }
+        LevelUpButton::Skip => None,
+    };
+
+    match taken {
+        Some(upgrade) => {
+            let curse_before = curse.0;
+            pool.take(
+                upgrade,
+                &mut UpgradeTargets {
+                    stats: &mut stats,
+                    curse: &mut curse,
+                    weapons: &mut weapons,
+                },
+            );
+            build_changed.write(BuildChanged);
+            choices.write(LevelUpChoice::Take(upgrade.id));
+
+            announcements.push(upgrade.description, 1.5, AnnouncementStyle::Info);
+            if curse.0 > curse_before {
+                announcements.push(
+                    format!("The curse deepens ({})", curse.0),
+                    2.0,
+                    AnnouncementStyle::Warning,
+                );
+            }
+        }
+        None => {
+            gold.0 += curse.reward_gold(SKIP_GOLD);
+            choices.write(LevelUpChoice::Skip);
+        }
+    }
+
+    // Chain straight into the next choice if several levels were gained at once
+    experience.pending = experience.pending.saturating_sub(1);
+    if experience.pending > 0 {
+        offer.options = pool.next_offer(OFFER_SIZE, stats.luck, &mut rng);
+        offer.banishing = false;
+    } else {
+        next_state.set(PlayState::Running);
+    }
+}
+
+/// Rebuilds the panel whenever the offer, the pool's banishes and lock, or
+/// the reroll count change. Each card lists what it would change, previewed
+/// against the base stats.
+fn refresh_level_up_ui(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    offer: Res<LevelUpOffer>,
+    pool: Res<UpgradePool>,
+    stats: Res<PlayerStats>,
+    curse: Res<Curse>,
+    weapons: Single<&WeaponSlots, With<Player>>,
+    experience: Res<Experience>,
+    existing: Query<Entity, With<LevelUpUi>>,
+) {
+    if !offer.is_changed() && !pool.is_changed() && !stats.is_changed() {
+        return;
+    }
+
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            LevelUpUi,
+            DespawnOnExit(PlayState::LevelUp),
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Text::new(format!("LEVEL UP! (Lv {})", experience.level)),
+                fonts.display(40.0),
+                TextColor(Color::WHITE),
+            ));
+
+            for (i, upgrade) in offer.options.iter().enumerate() {
+                let deltas: Vec<String> = upgrade
+                    .preview(&stats, &curse, &weapons)
+                    .into_iter()
+                    .map(format_delta)
+                    .collect();
+                spawn_button(
+                    root,
+                    &fonts,
+                    format!("{}. {}", i + 1, upgrade.description),
+                    &deltas,
+                    upgrade.rarity.color(),
+                    LevelUpButton::Pick(i),
+                );
+            }
+
+            spawn_button(
+                root,
+                &fonts,
+                format!("Reroll ({} left) [R]", stats.rerolls),
+                &[],
+                BUTTON_COLOR,
+                LevelUpButton::Reroll,
+            );
+            let banish = if offer.banishing {
+                "Banishing: pick a card [X to cancel]".to_string()
+            } else {
+                format!("Banish ({} left) [X]", pool.banishes_left())
+            };
+            spawn_button(
+                root,
+                &fonts,
+                banish,
+                &[],
+                if offer.banishing {
+                    BANISH_COLOR
+                } else {
+                    BUTTON_COLOR
+                },
+                LevelUpButton::Banish,
+            );
+            let lock = if pool.is_holding() {
+                "Locked for next level [K]"
+            } else {
+                "Lock these for next level [K]"
+            };
+            spawn_button(
+                root,
+                &fonts,
+                lock.to_string(),
+                &[],
+                BUTTON_COLOR,
+                LevelUpButton::Lock,
+            );
+            spawn_button(
+                root,
+                &fonts,
+                format!("Skip: +{SKIP_GOLD} gold [S]"),
+                &[],
+                BUTTON_COLOR,
+                LevelUpButton::Skip,
+            );
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildSpawnerCommands,
+    fonts: &FontAssets,
+    label: String,
+    details: &[String],
+    color: Color,
+    button: LevelUpButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(320.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            BackgroundColor(color),
+            button,
+        ))
+        .with_children(|b| {
+            b.spawn((Text::new(label), fonts.body(22.0), TextColor(Color::WHITE)));
+            for line in details {
+                b.spawn((
+                    Text::new(line.clone()),
+                    fonts.body(16.0),
+                    TextColor(Color::srgb(0.8, 0.8, 0.85)),
+                ));
+            }
+        });
+}
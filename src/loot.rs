@@ -0,0 +1,364 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::chests::spawn_chest;
+use crate::fonts::FontAssets;
+use crate::levelup::Experience;
+use crate::pickups::{PickupKind, spawn_pickup};
+use crate::{Curse, GameState, Gold, ResetSet};
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DropPity>()
+            .add_systems(OnEnter(GameState::Resetting), reset_pity.in_set(ResetSet));
+    }
+}
+
+/// One thing a loot source can hand out.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Drop {
+    Shield,
+    Chest,
+    Gold(u32),
+    Xp(f32),
+}
+
+impl Drop {
+    /// Gold and experience scaled by the curse's reward multiplier; the
+    /// rest comes as it is.
+    fn cursed(self, curse: &Curse) -> Self {
+        match self {
+            Drop::Gold(amount) => Drop::Gold(curse.reward_gold(amount)),
+            Drop::Xp(amount) => Drop::Xp(amount * curse.reward_multiplier()),
+            Drop::Shield | Drop::Chest => self,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum LootEntry {
+    /// Weight for rolling no loot at all; luck never scales it.
+    Nothing,
+    Drop(Drop),
+    /// Rolled again, as one more pick.
+    #[allow(dead_code)] // no source nests tables yet
+    Table(&'static DropTable),
+}
+
+/// Weighted loot, rolled `rolls` times. Weights are relative, so a table
+/// doesn't have to sum to anything in particular.
+#[derive(Debug)]
+pub struct DropTable {
+    rolls: u32,
+    entries: &'static [(f32, LootEntry)],
+}
+
+impl DropTable {
+    pub const fn new(entries: &'static [(f32, LootEntry)]) -> Self {
+        Self::with_rolls(1, entries)
+    }
+
+    pub const fn with_rolls(rolls: u32, entries: &'static [(f32, LootEntry)]) -> Self {
+        Self { rolls, entries }
+    }
+
+    /// Luck scales up every entry that gives something, so it only ever
+    /// crowds out the "nothing" entries.
+    fn weight(entry: &(f32, LootEntry), luck: f32) -> f32 {
+        let (weight, entry) = entry;
+        let weight = weight.max(0.0);
+        match entry {
+            LootEntry::Nothing => weight,
+            LootEntry::Drop(_) | LootEntry::Table(_) => weight * (1.0 + luck.max(0.0)),
+        }
+    }
+
+    /// Everything one pass over the table gives, nested tables included. A
+    /// table with no weight at all simply gives nothing.
+    pub fn roll(&self, luck: f32, rng: &mut impl Rng) -> Vec<Drop> {
+        let mut drops = Vec::new();
+        self.roll_into(luck, rng, &mut drops);
+        drops
+    }
+
+    fn roll_into(&self, luck: f32, rng: &mut impl Rng, drops: &mut Vec<Drop>) {
+        let weights: Vec<f32> = self.entries.iter().map(|e| Self::weight(e, luck)).collect();
+        for _ in 0..self.rolls {
+            let picked = weighted_index(&weights, rng).map(|i| self.entries[i].1);
+            match picked {
+                Some(LootEntry::Drop(drop)) => drops.push(drop),
+                Some(LootEntry::Table(table)) => table.roll_into(luck, rng, drops),
+                Some(LootEntry::Nothing) | None => {}
+            }
+        }
+    }
+}
+
+/// A pick from `weights`, each index as likely as its share of the total,
+/// or `None` when there is no weight at all. Negative weights count as none.
+pub fn weighted_index(weights: &[f32], rng: &mut impl Rng) -> Option<usize> {
+    let total: f32 = weights.iter().map(|weight| weight.max(0.0)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut roll = rng.gen_range(0.0..total);
+    for (i, weight) in weights.iter().enumerate() {
+        let weight = weight.max(0.0);
+        if roll < weight {
+            return Some(i);
+        }
+        roll -= weight;
+    }
+    // Float rounding can leave `roll` just past the end; take the last one then
+    Some(weights.len() - 1)
+}
+
+/// The rare drops that come with a pity timer, so a bad streak can only
+/// last so long.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PityCategory {
+    Shield,
+    Chest,
+}
+
+impl PityCategory {
+    /// What a forced drop gives.
+    fn drop(self) -> Drop {
+        match self {
+            PityCategory::Shield => Drop::Shield,
+            PityCategory::Chest => Drop::Chest,
+        }
+    }
+}
+
+/// Rolls since each rare drop last came up, this run.
+#[derive(Resource, Default)]
+pub struct DropPity {
+    shield: u32,
+    chest: u32,
+}
+
+impl DropPity {
+    fn misses_mut(&mut self, category: PityCategory) -> &mut u32 {
+        match category {
+            PityCategory::Shield => &mut self.shield,
+            PityCategory::Chest => &mut self.chest,
+        }
+    }
+
+    /// Settles one roll of a table that can give `category`, with `drops`
+    /// what it gave. Once `threshold` counted rolls in a row have missed,
+    /// the drop is added to `drops`, so the guarantee never depends on the
+    /// RNG. A roll that isn't `counted` leaves the count where it is, but a
+    /// natural drop resets it all the same.
+    pub fn settle(
+        &mut self,
+        category: PityCategory,
+        threshold: u32,
+        counted: bool,
+        drops: &mut Vec<Drop>,
+    ) {
+        let drop = category.drop();
+        let misses = self.misses_mut(category);
+        if drops.contains(&drop) {
+            *misses = 0;
+            return;
+        }
+        if !counted || threshold == 0 {
+            return;
+        }
+        *misses += 1;
+        if *misses >= threshold {
+            drops.push(drop);
+            *misses = 0;
+        }
+    }
+}
+
+fn reset_pity(mut pity: ResMut<DropPity>) {
+    *pity = DropPity::default();
+}
+
+/// Turns rolled drops into the world: pickups and chests appear where the
+/// loot fell, gold and experience are credited on the spot. Every gold and
+/// experience reward goes through here, so the curse pays out on all of
+/// them alike.
+#[derive(SystemParam)]
+pub struct LootSpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    fonts: Res<'w, FontAssets>,
+    curse: Res<'w, Curse>,
+    gold: ResMut<'w, Gold>,
+    experience: ResMut<'w, Experience>,
+}
+
+impl LootSpawner<'_, '_> {
+    pub fn spawn(&mut self, drops: impl IntoIterator<Item = Drop>, position: Vec2) {
+        for drop in drops {
+            match drop.cursed(&self.curse) {
+                Drop::Shield => {
+                    spawn_pickup(
+                        &mut self.commands,
+                        &self.fonts,
+                        PickupKind::Shield,
+                        position,
+                    );
+                }
+                Drop::Chest => spawn_chest(&mut self.commands, &self.fonts, position),
+                Drop::Gold(amount) => self.gold.0 += amount,
+                Drop::Xp(amount) => self.experience.xp += amount,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static ONLY_GOLD: DropTable = DropTable::new(&[(1.0, LootEntry::Drop(Drop::Gold(5)))]);
+    static EVEN: DropTable = DropTable::new(&[
+        (1.0, LootEntry::Nothing),
+        (1.0, LootEntry::Drop(Drop::Shield)),
+    ]);
+
+    fn shields(table: &DropTable, luck: f32, rolls: u32) -> u32 {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..rolls)
+            .map(|_| table.roll(luck, &mut rng).len() as u32)
+            .sum()
+    }
+
+    #[test]
+    fn an_empty_or_weightless_table_gives_nothing() {
+        static EMPTY: DropTable = DropTable::new(&[]);
+        static WEIGHTLESS: DropTable = DropTable::new(&[
+            (0.0, LootEntry::Drop(Drop::Chest)),
+            (-1.0, LootEntry::Nothing),
+        ]);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(EMPTY.roll(0.0, &mut rng).is_empty());
+        assert!(WEIGHTLESS.roll(5.0, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn every_roll_picks_once() {
+        static THREE: DropTable =
+            DropTable::with_rolls(3, &[(1.0, LootEntry::Drop(Drop::Gold(5)))]);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(THREE.roll(0.0, &mut rng), vec![Drop::Gold(5); 3]);
+    }
+
+    #[test]
+    fn nested_tables_roll_into_the_same_drops() {
+        static OUTER: DropTable = DropTable::with_rolls(2, &[(1.0, LootEntry::Table(&ONLY_GOLD))]);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(OUTER.roll(0.0, &mut rng), vec![Drop::Gold(5); 2]);
+    }
+
+    #[test]
+    fn weights_set_the_odds() {
+        let hits = shields(&EVEN, 0.0, 10_000);
+        assert!((4_700..5_300).contains(&hits), "{hits}");
+    }
+
+    #[test]
+    fn luck_only_crowds_out_nothing() {
+        // Luck 1 doubles the shield's weight: two in three
+        let hits = shields(&EVEN, 1.0, 10_000);
+        assert!((6_400..6_950).contains(&hits), "{hits}");
+        // Negative luck is treated as none
+        assert_eq!(shields(&EVEN, -3.0, 2_000), shields(&EVEN, 0.0, 2_000));
+    }
+
+    /// Settles one shield roll that gave `drops`, returning what it ends up
+    /// giving.
+    fn settle(
+        pity: &mut DropPity,
+        threshold: u32,
+        counted: bool,
+        mut drops: Vec<Drop>,
+    ) -> Vec<Drop> {
+        pity.settle(PityCategory::Shield, threshold, counted, &mut drops);
+        drops
+    }
+
+    #[test]
+    fn pity_forces_the_drop_after_threshold_misses_then_resets() {
+        let mut pity = DropPity::default();
+        for _ in 0..2 {
+            assert!(settle(&mut pity, 3, true, vec![]).is_empty());
+            assert!(settle(&mut pity, 3, true, vec![]).is_empty());
+            assert_eq!(settle(&mut pity, 3, true, vec![]), [Drop::Shield]);
+        }
+    }
+
+    #[test]
+    fn a_natural_drop_resets_the_count() {
+        let mut pity = DropPity::default();
+        settle(&mut pity, 3, true, vec![]);
+        settle(&mut pity, 3, true, vec![]);
+        assert_eq!(
+            settle(&mut pity, 3, false, vec![Drop::Shield]),
+            [Drop::Shield]
+        );
+        assert!(settle(&mut pity, 3, true, vec![]).is_empty());
+        assert!(settle(&mut pity, 3, true, vec![]).is_empty());
+    }
+
+    #[test]
+    fn uncounted_misses_leave_the_count() {
+        let mut pity = DropPity::default();
+        settle(&mut pity, 2, true, vec![]);
+        for _ in 0..5 {
+            assert!(settle(&mut pity, 2, false, vec![]).is_empty());
+        }
+        assert_eq!(settle(&mut pity, 2, true, vec![]), [Drop::Shield]);
+    }
+
+    #[test]
+    fn a_zero_threshold_never_forces_a_drop() {
+        let mut pity = DropPity::default();
+        for _ in 0..10 {
+            assert!(settle(&mut pity, 0, true, vec![]).is_empty());
+        }
+    }
+
+    #[test]
+    fn each_category_keeps_its_own_count() {
+        let mut pity = DropPity::default();
+        settle(&mut pity, 2, true, vec![]);
+        let mut drops = Vec::new();
+        pity.settle(PityCategory::Chest, 2, true, &mut drops);
+        assert!(drops.is_empty());
+        pity.settle(PityCategory::Chest, 2, true, &mut drops);
+        assert_eq!(drops, [Drop::Chest]);
+        assert_eq!(settle(&mut pity, 2, true, vec![]), [Drop::Shield]);
+    }
+
+    #[test]
+    fn weighted_index_skips_zero_and_negative_weights() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..100 {
+            assert_eq!(weighted_index(&[0.0, -2.0, 1.0], &mut rng), Some(2));
+        }
+        assert_eq!(weighted_index(&[], &mut rng), None);
+        assert_eq!(weighted_index(&[0.0, -1.0], &mut rng), None);
+    }
+
+    #[test]
+    fn curse_raises_gold_and_experience_only() {
+        let curse = Curse(3);
+        let multiplier = curse.reward_multiplier();
+        assert_eq!(
+            Drop::Gold(20).cursed(&curse),
+            Drop::Gold((20.0 * multiplier).round() as u32)
+        );
+        assert_eq!(Drop::Xp(10.0).cursed(&curse), Drop::Xp(10.0 * multiplier));
+        assert_eq!(Drop::Shield.cursed(&curse), Drop::Shield);
+        assert_eq!(Drop::Gold(20).cursed(&Curse(0)), Drop::Gold(20));
+    }
+}
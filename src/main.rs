@@ -1,17 +1,192 @@
-use bevy::input::ButtonState;
-use bevy::input::mouse::MouseButtonInput;
+// Bevy system signatures routinely trip these lints; they read fine as-is.
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+mod aim;
+mod announcements;
+mod beacons;
+mod bestiary;
+mod boss;
+mod build_hud;
+mod challenge;
+mod chests;
+mod closecall;
+#[cfg(feature = "dev")]
+mod console;
+mod cooldowns;
+mod coop;
+mod critters;
+mod debug;
+mod despawn;
+mod difficulty;
+mod director;
+mod endless;
+mod fonts;
+mod ghost;
+mod hazards;
+mod heat;
+mod hints;
+mod hud;
+mod input;
+mod janitor;
+mod layers;
+#[cfg(feature = "dev")]
+mod leaks;
+mod levelup;
+mod loot;
+mod melee;
+mod message_log;
+mod minimap;
+mod music;
+mod packs;
+mod patterns;
+mod performance;
+mod phaser;
+mod photo;
+mod pickups;
+mod popups;
+mod rumble;
+mod run_export;
+mod run_stats;
+mod savegame;
+mod sfx;
+mod sharecode;
+mod shrines;
+mod spectator;
+mod storage;
+mod streaks;
+mod telemetry;
+mod thief;
+#[cfg(feature = "dev")]
+mod tuning;
+mod ultimate;
+mod unlocks;
+mod upgrades;
+mod vignette;
+mod weapons;
+mod weekly;
+
+use aim::CursorTarget;
+use boss::Boss;
+use challenge::{RunMode, RunRules};
+use despawn::{CosmeticPool, Dead, PendingDespawns};
+use difficulty::DifficultyConfig;
+use fonts::{FontAssets, FontChoice};
+use heat::Heat;
+use input::PlayerInputState;
+use message_log::LogMessage;
+use packs::{LeaderPose, PackLeader, PackMember};
+use performance::{CosmeticBudget, PerformancePreset};
+use phaser::PhaseState;
+use popups::StatusPopup;
+use run_stats::RunStats;
+use savegame::RunSuspended;
+use storage::Persistence;
+use thief::Thief;
+use ultimate::TimeDilation;
+use weapons::{
+    BLASTER_PROJECTILE, ENEMY_PROJECTILE_COLOR, FacesVelocity, ProjectileVisual, Shot,
+    TargetCandidate, TargetingMode, WeaponKind, WeaponSlots, select_target,
+};
 
-const PLAYER_RADIUS: f32 = 10.0;
-const ENEMY_RADIUS: f32 = 10.0;
+const BULLET_POOL_CAPACITY: usize = 256;
+const PLAYER_BASE_SPEED: f32 = 300.0;
+const MAX_PLAYER_SLOW: f32 = 0.5;
+/// Share of full speed the player keeps while focusing.
+const FOCUS_SPEED: f32 = 0.5;
+const MAX_BARRIER_CHARGES: u32 = 3;
+const BARRIER_GRACE_SECS: f32 = 0.5;
+const HIT_GRACE_SECS: f32 = 0.5;
+const KNOCKBACK_DISTANCE: f32 = 40.0;
+const KNOCKBACK_SECS: f32 = 0.1;
+/// A melee swipe shoves much harder than a touch, to clear space around the player.
+const MELEE_KNOCKBACK_DISTANCE: f32 = 140.0;
+const CURSE_THREAT_PER_POINT: f32 = 0.10;
+const CURSE_REWARD_PER_POINT: f32 = 0.15;
+/// Enemies appear between these distances from the player.
+const SPAWN_RING_MIN: f32 = 300.0;
+const SPAWN_RING_MAX: f32 = 500.0;
+/// Closer than this to any player and a spawn position is rolled again.
+const MIN_SPAWN_DISTANCE: f32 = 150.0;
+const SPAWN_ATTEMPTS: usize = 8;
+const SPAWN_PROTECTION_SECS: f32 = 0.5;
+const SPAWN_PROTECTION_ALPHA: f32 = 0.5;
+/// Half the width of the arc ahead of a moving player that biased spawns
+/// favor.
+const HEADING_CONE: f32 = std::f32::consts::FRAC_PI_3;
+const RICOCHET_RANGE: f32 = 250.0;
 const BULLET_RADIUS: f32 = 5.0;
+/// A bullet that has flown this far without hitting anything was a miss.
+const BULLET_RANGE: f32 = 5000.0;
+/// A full charge shot hits this many times harder, and is this many times bigger.
+const CHARGE_DAMAGE_SCALE: f32 = 4.0;
+const CHARGE_SIZE_SCALE: f32 = 2.5;
+/// Angle between neighbouring bullets in a fan.
+const FAN_SPACING_DEGREES: f32 = 8.0;
+const RICOCHET_FALLOFF: f32 = 0.25;
+const CORPSE_FADE_SECS: f32 = 0.3;
+const SPITTER_FIRE_SECS: f32 = 2.5;
+const SPITTER_RANGE: f32 = 400.0;
+const SHIELD_FIELD_RADIUS: f32 = 150.0;
+/// Share of bullet damage that gets through a shield field.
+const SHIELD_DAMAGE_TAKEN: f32 = 0.5;
+const SHIELD_TINT: Color = Color::srgb(0.5, 0.5, 1.0);
+const HEAL_INTERVAL_SECS: f32 = 2.0;
+const HEAL_RANGE: f32 = 200.0;
+/// Ranged support enemies hold about this far from the player.
+const STANDOFF_DISTANCE: f32 = 250.0;
+/// Slack around the standoff distance where they stop and hover.
+const STANDOFF_BAND: f32 = 50.0;
+const ENEMY_PROJECTILE_SPEED: f32 = 180.0;
+const CORPSE_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+const AFTERIMAGE_SECS: f32 = 0.05;
+const AFTERIMAGE_LIFETIME: f32 = 0.15;
+const AFTERIMAGE_ALPHA: f32 = 0.35;
+const DEATH_BURST_PARTICLES: usize = 4;
+const DOWNED_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+const REVIVE_SECS: f32 = 3.0;
+/// Fraction of full speed enemies amble at with no player to chase.
+const WANDER_SPEED: f32 = 0.4;
+/// Enemies this far from every player stop chasing and stand idle until one
+/// comes back within the shorter radius; the gap keeps them from flickering.
+const LEASH_RADIUS: f32 = 900.0;
+const LEASH_REAGGRO_RADIUS: f32 = 800.0;
+const LEASH_CHECK_SECS: f32 = 0.25;
 
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
 enum GameState {
-    #[default]
     Playing,
     GameOver,
+    /// Before each run, for one frame: the field is cleared and the run
+    /// state reset, then `Playing` is queued. Every way into a run passes
+    /// through here, the first one included.
+    #[default]
+    Resetting,
+}
+
+/// Whether the run is simulating or paused on an in-run screen.
+#[derive(SubStates, Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[source(GameState = GameState::Playing)]
+enum PlayState {
+    #[default]
+    Running,
+    LevelUp,
+    Shrine,
+    /// Showing what a treasure chest just gave.
+    ChestReveal,
+    /// Stopped by the player, with the build laid out.
+    Paused,
+    /// Everyone is down; the camera is free and the horde carries on until
+    /// the run is wrapped up.
+    Spectating,
 }
 
 #[derive(Component)]
@@ -20,370 +195,4000 @@ struct GameOverText;
 #[derive(Resource)]
 struct Score(u32);
 
+/// Voluntary difficulty taken on through upgrades: each point makes the horde
+/// bigger and tougher but pays out more.
+#[derive(Resource, Default)]
+struct Curse(u32);
+
+impl Curse {
+    /// Applied to spawn budget and enemy health.
+    fn threat_multiplier(&self) -> f32 {
+        1.0 + CURSE_THREAT_PER_POINT * self.0 as f32
+    }
+
+    /// Applied to experience and gold gains.
+    fn reward_multiplier(&self) -> f32 {
+        1.0 + CURSE_REWARD_PER_POINT * self.0 as f32
+    }
+
+    /// `amount` gold as it pays out, to the nearest coin.
+    fn reward_gold(&self, amount: u32) -> u32 {
+        (amount as f32 * self.reward_multiplier()).round() as u32
+    }
+}
+
+#[derive(Component)]
+struct CurseText;
+
+/// Gold earned during the current run.
+#[derive(Resource, Default)]
+struct Gold(u32);
+
+#[derive(Resource)]
+struct Settings {
+    show_minimap: bool,
+    /// Some players would rather not know what is coming.
+    show_forecast: bool,
+    /// Spawn a second player on the arrow keys.
+    coop: bool,
+    /// Turns off flashing and pulsing screen effects.
+    reduced_motion: bool,
+    /// One of `GAME_SPEEDS`; slows the enemy side of the game only.
+    game_speed: f32,
+    /// Only read at startup, when the fonts are loaded.
+    font: FontChoice,
+    /// Runs go on past the loop timer, each loop harder than the last.
+    endless: bool,
+    /// Gamepad rumble on hits and kills.
+    rumble: bool,
+    /// First-run control prompts.
+    hints: bool,
+    /// Replay the best run's path as a ghost.
+    ghost: bool,
+    /// How much cosmetic clutter to allow; see `performance`.
+    performance: PerformancePreset,
+    sound_caps: sfx::SoundCaps,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            show_minimap: true,
+            show_forecast: true,
+            coop: false,
+            reduced_motion: false,
+            game_speed: 1.0,
+            font: FontChoice::Bundled,
+            endless: false,
+            rumble: true,
+            hints: true,
+            ghost: true,
+            performance: PerformancePreset::High,
+            sound_caps: sfx::SoundCaps::default(),
+        }
+    }
+}
+
+const SETTINGS_KEY: &str = "settings";
+/// Game speeds on offer, in the order they cycle through.
+const GAME_SPEEDS: &[f32] = &[1.0, 0.9, 0.8];
+
+impl Settings {
+    /// Stored as `name=value` lines; unknown or missing names keep their defaults.
+    fn load() -> Self {
+        let mut settings = Self::default();
+        for line in storage::load(SETTINGS_KEY).unwrap_or_default().lines() {
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (name, value) = (name.trim(), value.trim());
+            if name == "game_speed" {
+                // Only the speeds on offer; anything else keeps full speed
+                if let Ok(speed) = value.parse::<f32>()
+                    && GAME_SPEEDS.contains(&speed)
+                {
+                    settings.game_speed = speed;
+                }
+                continue;
+            }
+            if settings.sound_caps.read_setting(name, value) {
+                continue;
+            }
+            if name == "font" {
+                if let Some(font) = FontChoice::from_id(value) {
+                    settings.font = font;
+                }
+                continue;
+            }
+            if name == "performance" {
+                if let Some(preset) = PerformancePreset::from_id(value) {
+                    settings.performance = preset;
+                }
+                continue;
+            }
+            let Ok(value) = value.parse::<bool>() else {
+                continue;
+            };
+            match name {
+                "show_minimap" => settings.show_minimap = value,
+                "show_forecast" => settings.show_forecast = value,
+                "coop" => settings.coop = value,
+                "reduced_motion" => settings.reduced_motion = value,
+                "endless" => settings.endless = value,
+                "rumble" => settings.rumble = value,
+                "hints" => settings.hints = value,
+                "ghost" => settings.ghost = value,
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    fn save(&self, persistence: &mut Persistence) {
+        let mut text = format!(
+            "show_minimap={}\nshow_forecast={}\ncoop={}\nreduced_motion={}\ngame_speed={}\nfont={}\nendless={}\nrumble={}\nhints={}\nghost={}\nperformance={}\n",
+            self.show_minimap,
+            self.show_forecast,
+            self.coop,
+            self.reduced_motion,
+            self.game_speed,
+            self.font.id(),
+            self.endless,
+            self.rumble,
+            self.hints,
+            self.ghost,
+            self.performance.id()
+        );
+        text.push_str(&self.sound_caps.settings_lines());
+        persistence.save(SETTINGS_KEY, text);
+    }
+}
+
+fn save_settings(settings: Res<Settings>, mut persistence: ResMut<Persistence>) {
+    // The initial insert counts as a change; nothing new to write then
+    if settings.is_changed() && !settings.is_added() {
+        settings.save(&mut persistence);
+    }
+}
+
 #[derive(Component)]
 struct InGameEntity;
 
 #[derive(Component)]
 struct ScoreText;
 
-#[derive(Resource)]
-struct EnemySpawnTimer(Timer);
-
+/// Player one: mouse aim, WASD, and the one the HUD follows.
 #[derive(Component)]
 struct Player;
 
-#[derive(Component, Clone, Copy)]
-enum EnemyType {
-    Basic,
-    Fast,
-    Tank,
-}
+/// Any player-controlled body, player one or the co-op partner. Enemies hunt
+/// these, and the run ends only once every one of them is down.
+#[derive(Component)]
+struct PlayerCharacter;
 
+/// Knocked out but revivable while a teammate is still standing.
 #[derive(Component)]
-struct Enemy {
-    kind: EnemyType,
-    health: i32,
+struct Downed {
+    revive: Timer,
+}
+
+/// Closest of `targets` to `from`.
+fn nearest(from: Vec2, targets: impl Iterator<Item = Vec2>) -> Option<Vec2> {
+    targets.min_by(|a, b| {
+        a.distance_squared(from)
+            .total_cmp(&b.distance_squared(from))
+    })
 }
 
+/// Player move speed after buffs and slows, refreshed every frame before `move_player`.
 #[derive(Component)]
-struct Bullet {
-    direction: Vec2,
-    speed: f32,
+struct EffectiveSpeed {
+    value: f32,
+    slow: f32,
 }
 
-fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .insert_resource(EnemySpawnTimer(Timer::from_seconds(
-            1.0,
-            TimerMode::Repeating,
-        )))
-        .insert_state(GameState::Playing)
-        .insert_resource(Score(0))
-        .add_systems(Update, update_score_ui)
-        .add_systems(OnEnter(GameState::GameOver), cleanup_ingame_entities)
-        .add_systems(Startup, setup)
-        .add_systems(Update, move_player.run_if(in_state(GameState::Playing)))
-        .add_systems(Update, shoot_bullet.run_if(in_state(GameState::Playing)))
-        .add_systems(Update, bullet_movement_system)
-        .add_systems(Update, spawn_enemies.run_if(in_state(GameState::Playing)))
-        .add_systems(
-            Update,
-            move_enemies_toward_player.run_if(in_state(GameState::Playing)),
-        )
-        .add_systems(OnEnter(GameState::GameOver), spawn_game_over_text)
-        .add_systems(Update, restart_on_r.run_if(in_state(GameState::GameOver)))
-        .add_systems(Update, bullet_enemy_collision_system)
-        .add_systems(Update, enemy_player_collision_system)
-        .add_systems(OnEnter(GameState::Playing), setup_new_game)
-        .run();
+impl EffectiveSpeed {
+    fn new(value: f32) -> Self {
+        Self { value, slow: 0.0 }
+    }
 }
 
-fn setup(mut commands: Commands) {
-    commands.spawn(Camera2d);
-    // Score UI (screen space)
-    commands.spawn((
-        Text::new("Score: 0"),
-        TextFont {
-            font_size: 28.0,
-            font: default(),
-            ..default()
-        },
-        TextColor(Color::WHITE),
-        Node {
-            position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
-            left: Val::Px(10.0),
-            ..default()
-        },
-        ScoreText,
-    ));
+/// Brief green flicker when lifesteal heals the player.
+#[derive(Component)]
+struct HealFlash(Timer);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum BuffStat {
+    MoveSpeed,
+    /// Weapon cooldowns run down faster.
+    FireRate,
+    /// Everything the players deal, hazards aside.
+    Damage,
 }
 
-fn move_player(
-    input: Res<ButtonInput<KeyCode>>,
-    time: Res<Time>,
-    mut player_transform: Single<&mut Transform, With<Player>>,
-) {
-    let mut direction = Vec2::ZERO;
-    if input.pressed(KeyCode::KeyA) {
-        direction.x -= 1.0;
-    }
-    if input.pressed(KeyCode::KeyD) {
-        direction.x += 1.0;
+/// A timed change to one stat, as a fraction of it; negative is a debuff.
+struct Buff {
+    stat: BuffStat,
+    amount: f32,
+    timer: Timer,
+}
+
+/// Every timed modifier currently on an entity.
+#[derive(Component, Default)]
+struct Buffs(Vec<Buff>);
+
+impl Buffs {
+    fn add(&mut self, stat: BuffStat, amount: f32, secs: f32) {
+        self.0.push(Buff {
+            stat,
+            amount,
+            timer: Timer::from_seconds(secs, TimerMode::Once),
+        });
     }
-    if input.pressed(KeyCode::KeyW) {
-        direction.y += 1.0;
+
+    /// Stacks additively, and never drops a stat below a tenth of itself.
+    fn multiplier(&self, stat: BuffStat) -> f32 {
+        let total: f32 = self
+            .0
+            .iter()
+            .filter(|b| b.stat == stat)
+            .map(|b| b.amount)
+            .sum();
+        (1.0 + total).max(0.1)
     }
-    if input.pressed(KeyCode::KeyS) {
-        direction.y -= 1.0;
+}
+
+/// Run-scoped player stats that upgrades modify.
+#[derive(Resource, Clone)]
+struct PlayerStats {
+    move_speed: f32,
+    bullet_speed: f32,
+    bullet_damage: i32,
+    max_health: i32,
+    /// Shifts upgrade and drop rolls toward rarer outcomes.
+    luck: f32,
+    /// Fraction of player damage dealt that returns as healing.
+    lifesteal: f32,
+    /// Level-up rerolls left this run.
+    rerolls: u32,
+    /// Extra enemies each bullet can bounce to after a kill.
+    ricochet: u32,
+    /// Player bullets destroy enemy projectiles they touch.
+    bullet_parry: bool,
+    /// Heat added by each shot; zero turns overheating off entirely.
+    heat_per_shot: f32,
+    /// Heat shed per second while not locked out.
+    heat_decay: f32,
+    max_heat: f32,
+    /// Bullets per shot of the basic weapon, fanned around the aim.
+    projectile_count: u32,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        Self {
+            move_speed: PLAYER_BASE_SPEED,
+            bullet_speed: 600.0,
+            bullet_damage: 1,
+            max_health: 1,
+            luck: 0.0,
+            lifesteal: 0.0,
+            rerolls: 2,
+            ricochet: 0,
+            bullet_parry: false,
+            heat_per_shot: 0.0,
+            heat_decay: 0.5,
+            max_heat: 1.0,
+            projectile_count: 1,
+        }
     }
+}
 
-    if direction != Vec2::ZERO {
-        let speed = 300.0;
-        let delta = direction.normalize() * speed * time.delta_secs();
-        player_transform.translation.x += delta.x;
-        player_transform.translation.y += delta.y;
+/// Fractional lifesteal healing waiting to add up to a whole hit point.
+#[derive(Resource, Default)]
+struct LifestealAccumulator(f32);
+
+/// A stepped frame advances gameplay by exactly this much, whatever the real
+/// frame took.
+const FRAME_STEP_SECS: f32 = 1.0 / 60.0;
+/// The longest frame gameplay will simulate in one go. A hitch past this
+/// slows the game for a frame instead of moving everything in one jump: at
+/// this cap a bullet steps 20 units, short of the 30 an enemy's hitbox and
+/// its own span, so it can't hop over one.
+const MAX_FRAME_SECS: f32 = 1.0 / 30.0;
+
+/// Debug control over gameplay time only; UI and overlays keep real time.
+#[derive(Resource)]
+struct GameTimeScale {
+    scale: f32,
+    frozen: bool,
+    /// Let one frame through while frozen.
+    step: bool,
+}
+
+impl Default for GameTimeScale {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            frozen: false,
+            step: false,
+        }
     }
 }
 
-fn shoot_bullet(
-    mut mousebtn_evr: MessageReader<MouseButtonInput>,
-    mut commands: Commands,
-    windows: Query<&Window>,
-    camera_q: Query<(&Camera, &GlobalTransform)>,
-    player_q: Query<&Transform, With<Player>>,
-) {
-    let Ok(player_tf) = player_q.single() else {
-        return; // Player is dead, do nothing
-    };
+/// Gameplay's own clock. `advance_game_clock` moves it on once a frame, at
+/// the start of the frame, and only while the run is actually simulating:
+/// never on the pause menu, the level-up screen or any other stop, nor while
+/// debug-frozen. Each frame counts at most `MAX_FRAME_SECS`, so a window drag
+/// or an OS stall is one slow frame rather than a jump.
+///
+/// The run timer, difficulty scaling, buffs, the spawn director and the boss
+/// all run on it through `GameTime`, so they always agree on how long the
+/// run has lasted. Dilation is left to `EnemyTime` on top.
+#[derive(Resource, Default)]
+struct GameClock {
+    /// This frame's step; zero on every frame that doesn't simulate.
+    delta: Duration,
+    elapsed: Duration,
+    /// This frame is a debug step let through while frozen.
+    stepped: bool,
+}
 
-    let window = windows.single().unwrap();
-    let (camera, cam_tf) = camera_q.single().unwrap();
-
-    for ev in mousebtn_evr.read() {
-        if ev.state == ButtonState::Pressed && ev.button == MouseButton::Left {
-            if let Some(cursor_pos) = window.cursor_position() {
-                if let Ok(world_pos) = camera.viewport_to_world_2d(cam_tf, cursor_pos) {
-                    let dir = (world_pos - player_tf.translation.truncate()).normalize();
-
-                    commands.spawn((
-                        Text2d::new("*"),
-                        TextFont {
-                            font_size: 20.0,
-                            font: default(),
-                            ..default()
-                        },
-                        TextColor(Color::WHITE),
-                        Transform::from_translation(player_tf.translation),
-                        Bullet {
-                            direction: dir,
-                            speed: 600.0,
-                        },
-                        InGameEntity,
-                    ));
-                }
+impl GameClock {
+    /// One frame that took `real`. A pending step is used up here, so it
+    /// covers exactly one frame.
+    fn advance(&mut self, real: Duration, simulating: bool, scale: &mut GameTimeScale) {
+        self.stepped = scale.frozen && std::mem::take(&mut scale.step);
+        self.delta = if !simulating {
+            Duration::ZERO
+        } else if scale.frozen {
+            if self.stepped {
+                Duration::from_secs_f32(FRAME_STEP_SECS)
+            } else {
+                Duration::ZERO
             }
-        }
+        } else {
+            real.min(Duration::from_secs_f32(MAX_FRAME_SECS))
+                .mul_f32(scale.scale)
+        };
+        self.elapsed += self.delta;
     }
 }
 
-fn bullet_movement_system(
+/// Spectating keeps the world going around the downed player.
+fn advance_game_clock(
     time: Res<Time>,
-    mut commands: Commands,
-    mut q: Query<(Entity, &mut Transform, &Bullet)>,
+    mut scale: ResMut<GameTimeScale>,
+    play: Option<Res<State<PlayState>>>,
+    mut clock: ResMut<GameClock>,
 ) {
-    for (entity, mut tf, bullet) in q.iter_mut() {
-        let delta = bullet.direction * bullet.speed * time.delta_secs();
-        tf.translation.x += delta.x;
-        tf.translation.y += delta.y;
+    let simulating =
+        play.is_some_and(|play| matches!(play.get(), PlayState::Running | PlayState::Spectating));
+    clock.advance(time.delta(), simulating, &mut scale);
+}
 
-        // Simple lifetime check
-        if tf.translation.length() > 5000.0 {
-            commands.entity(entity).despawn();
-        }
+/// `GameClock` as gameplay systems take it, in place of `Res<Time>`.
+#[derive(SystemParam)]
+struct GameTime<'w> {
+    clock: Res<'w, GameClock>,
+}
+
+impl GameTime<'_> {
+    fn delta(&self) -> Duration {
+        self.clock.delta
+    }
+
+    fn delta_secs(&self) -> f32 {
+        self.clock.delta.as_secs_f32()
+    }
+
+    fn elapsed_secs(&self) -> f32 {
+        self.clock.elapsed.as_secs_f32()
     }
 }
 
-fn bullet_enemy_collision_system(
-    mut commands: Commands,
-    mut score: ResMut<Score>,
-    bullets: Query<(Entity, &Transform), With<Bullet>>,
-    mut enemies: Query<(Entity, &Transform, &mut Enemy)>,
-) {
-    for (bullet_entity, bullet_tf) in &bullets {
-        for (enemy_entity, enemy_tf, mut enemy) in &mut enemies {
-            let distance = bullet_tf
-                .translation
-                .truncate()
-                .distance(enemy_tf.translation.truncate());
+/// The settings' game speed, fixed for the run when it starts.
+#[derive(Resource)]
+struct GameplaySpeed(f32);
+
+impl Default for GameplaySpeed {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
 
-            if distance < BULLET_RADIUS + ENEMY_RADIUS {
-                commands.entity(bullet_entity).despawn();
+fn apply_game_speed(settings: Res<Settings>, mut speed: ResMut<GameplaySpeed>) {
+    speed.0 = settings.game_speed;
+}
 
-                enemy.health -= 1;
+/// The enemy side's clock: gameplay time slowed by the ultimate's and close
+/// calls' dilation and the game-speed setting. Enemy movement, enemy fire, the spawn director
+/// and arena hazards all run on it; nothing the player does.
+#[derive(SystemParam)]
+struct EnemyTime<'w> {
+    time: GameTime<'w>,
+    dilation: Res<'w, TimeDilation>,
+    speed: Res<'w, GameplaySpeed>,
+}
 
-                if enemy.health <= 0 {
-                    score.0 += match enemy.kind {
-                        EnemyType::Basic => 1,
-                        EnemyType::Fast => 2,
-                        EnemyType::Tank => 5,
-                    };
+impl EnemyTime<'_> {
+    fn scale(&self) -> f32 {
+        self.dilation.scale() * self.speed.0
+    }
 
-                    commands.entity(enemy_entity).despawn();
-                }
+    fn delta(&self) -> Duration {
+        self.time.delta().mul_f32(self.scale())
+    }
 
-                break;
-            }
-        }
+    fn delta_secs(&self) -> f32 {
+        self.time.delta_secs() * self.scale()
     }
-}
 
-fn update_score_ui(score: Res<Score>, mut query: Query<&mut Text, With<ScoreText>>) {
-    if score.is_changed() {
-        if let Ok(mut text) = query.single_mut() {
-            text.0 = format!("Score: {}", score.0);
-        }
+    fn elapsed_secs(&self) -> f32 {
+        self.time.elapsed_secs()
     }
 }
 
-fn enemy_player_collision_system(
-    mut next_state: ResMut<NextState<GameState>>,
-    player_q: Query<(Entity, &Transform), With<Player>>,
-    enemies: Query<&Transform, With<Enemy>>,
-) {
-    let Ok((_player_entity, player_tf)) = player_q.single() else {
-        return;
-    };
+/// Scoring and spawning, which must stop on the very frame the run ends
+/// rather than the next, once the state change has actually applied.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct GameplaySet;
+
+/// Every per-run reset, run on entering `Resetting` once the field is clear.
+/// A plugin with run-scoped state resets it here, never on entering
+/// `Playing`, so nothing from the last run is left for the next to see.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct ResetSet;
+
+/// False from the moment anything queues the end of the run: the last player
+/// falling or the run being suspended.
+fn run_not_ending(game: Res<NextState<GameState>>, play: Res<NextState<PlayState>>) -> bool {
+    let game_over = matches!(
+        *game,
+        NextState::Pending(GameState::GameOver) | NextState::PendingIfNeq(GameState::GameOver)
+    );
+    let last_player_down = matches!(
+        *play,
+        NextState::Pending(PlayState::Spectating) | NextState::PendingIfNeq(PlayState::Spectating)
+    );
+    !game_over && !last_player_down
+}
+
+/// Run condition for gameplay that isn't driven by the clock, such as
+/// collisions and firing, so a freeze really stops everything.
+fn gameplay_unfrozen(scale: Res<GameTimeScale>, clock: Res<GameClock>) -> bool {
+    !scale.frozen || clock.stepped
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+enum EnemyType {
+    Basic,
+    Fast,
+    Tank,
+    Frost,
+    /// Keeps its distance and lobs slow projectiles.
+    Spitter,
+    /// Harmless itself; halves bullet damage to the enemies around it.
+    Shielder,
+    /// Hangs back and patches up the most hurt enemy nearby.
+    Healer,
+    /// Spends one second in three out of reach; see `phaser`.
+    Phaser,
+    /// Steals pickups off the ground and runs; see `thief`.
+    Thief,
+    /// Arrives alone every few minutes; its attacks live in `boss`.
+    Boss,
+}
 
-    for enemy_tf in &enemies {
-        let distance = player_tf
-            .translation
-            .truncate()
-            .distance(enemy_tf.translation.truncate());
+/// Every enemy type, in the order listings show them.
+const ALL_ENEMY_TYPES: &[EnemyType] = &[
+    EnemyType::Basic,
+    EnemyType::Fast,
+    EnemyType::Tank,
+    EnemyType::Frost,
+    EnemyType::Spitter,
+    EnemyType::Shielder,
+    EnemyType::Healer,
+    EnemyType::Phaser,
+    EnemyType::Thief,
+    EnemyType::Boss,
+];
 
-        if distance < PLAYER_RADIUS + ENEMY_RADIUS {
-            println!("Game Over!");
-            next_state.set(GameState::GameOver);
+impl EnemyType {
+    /// Stable name for saves and the console.
+    fn id(self) -> &'static str {
+        match self {
+            EnemyType::Basic => "basic",
+            EnemyType::Fast => "fast",
+            EnemyType::Tank => "tank",
+            EnemyType::Frost => "frost",
+            EnemyType::Spitter => "spitter",
+            EnemyType::Shielder => "shielder",
+            EnemyType::Healer => "healer",
+            EnemyType::Phaser => "phaser",
+            EnemyType::Thief => "thief",
+            EnemyType::Boss => "boss",
         }
     }
+
+    fn from_id(id: &str) -> Option<Self> {
+        Some(match id {
+            "basic" => EnemyType::Basic,
+            "fast" => EnemyType::Fast,
+            "tank" => EnemyType::Tank,
+            "frost" => EnemyType::Frost,
+            "spitter" => EnemyType::Spitter,
+            "shielder" => EnemyType::Shielder,
+            "healer" => EnemyType::Healer,
+            "phaser" => EnemyType::Phaser,
+            "thief" => EnemyType::Thief,
+            "boss" => EnemyType::Boss,
+            _ => return None,
+        })
+    }
 }
 
-fn spawn_enemies(
-    mut commands: Commands,
-    time: Res<Time>,
-    mut timer: ResMut<EnemySpawnTimer>,
-    player_q: Query<&Transform, With<Player>>,
-) {
-    if !timer.0.tick(time.delta()).just_finished() {
-        return;
+/// Tanks and bosses are the elites: tougher, and worth a chest.
+fn is_elite(kind: EnemyType) -> bool {
+    matches!(kind, EnemyType::Tank | EnemyType::Boss)
+}
+
+/// How targeting sees an enemy.
+fn target_candidate(
+    entity: Entity,
+    tf: &Transform,
+    health: &Health,
+    enemy: &Enemy,
+) -> TargetCandidate {
+    TargetCandidate {
+        entity,
+        position: tf.translation.truncate(),
+        health: health.current,
+        elite: is_elite(enemy.kind),
     }
+}
 
-    let Ok(player) = player_q.single() else {
-        return;
-    };
+/// Signature color for each enemy type, used by UI that refers back to enemies.
+fn enemy_type_color(kind: EnemyType) -> Color {
+    match kind {
+        EnemyType::Basic => Color::WHITE,
+        EnemyType::Fast => Color::srgb(1.0, 0.9, 0.2),
+        EnemyType::Tank => Color::srgb(1.0, 0.35, 0.2),
+        EnemyType::Frost => Color::srgb(0.4, 0.7, 1.0),
+        EnemyType::Spitter => Color::srgb(0.5, 1.0, 0.3),
+        EnemyType::Shielder => Color::srgb(0.6, 0.6, 1.0),
+        EnemyType::Healer => Color::srgb(0.4, 1.0, 0.6),
+        EnemyType::Phaser => Color::srgb(0.7, 0.5, 1.0),
+        EnemyType::Thief => Color::srgb(1.0, 0.75, 0.1),
+        EnemyType::Boss => Color::srgb(0.9, 0.3, 1.0),
+    }
+}
 
-    let mut rng = thread_rng();
-    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
-    let distance = rng.gen_range(300.0..500.0);
+/// Slows the player while they stand within `radius`.
+#[derive(Component)]
+struct FrostAura {
+    radius: f32,
+    slow: f32,
+}
 
-    let spawn_pos = Vec3::new(
-        player.translation.x + angle.cos() * distance,
-        player.translation.y + angle.sin() * distance,
-        0.0,
-    );
+#[derive(Component)]
+struct Enemy {
+    kind: EnemyType,
+}
 
-    // Random enemy type
-    let enemy_type = match rng.gen_range(0..3) {
-        0 => EnemyType::Basic,
-        1 => EnemyType::Fast,
-        _ => EnemyType::Tank,
-    };
+/// Projects a block field: other enemies within `radius` take reduced bullet damage.
+#[derive(Component)]
+struct Shielder {
+    radius: f32,
+}
 
-    let (symbol, health, color) = match enemy_type {
-        EnemyType::Basic => ("E", 1, Color::WHITE),
-        EnemyType::Fast => ("e", 1, Color::WHITE),
-        EnemyType::Tank => ("EE", 3, Color::WHITE),
-    };
+/// Inside at least one shielder's field this frame. Fields don't stack, so
+/// this is all the damage hook needs to know.
+#[derive(Component)]
+struct Shielded {
+    /// Color to go back to once the enemy leaves every field.
+    base_color: Color,
+    /// Fraction of a hit point absorbed so far, so 1-damage bullets still
+    /// land every other hit.
+    carry: f32,
+}
 
-    commands.spawn((
-        Enemy {
-            kind: enemy_type,
-            health,
-        },
-        Transform::from_translation(spawn_pos),
-        GlobalTransform::default(),
-        Text2d::new(symbol),
-        TextFont {
-            font_size: 20.0,
-            font: default(),
-            ..default()
-        },
-        TextColor(color),
-        InGameEntity,
-    ));
+impl Shielded {
+    /// Damage that gets through the field, carrying the fractional part over.
+    fn mitigate(&mut self, amount: i32) -> i32 {
+        let total = amount as f32 * SHIELD_DAMAGE_TAKEN + self.carry;
+        let dealt = total.floor();
+        self.carry = total - dealt;
+        dealt as i32
+    }
 }
 
-fn move_enemies_toward_player(
-    time: Res<Time>,
-    player: Single<&Transform, With<Player>>,
-    mut enemies: Query<(&mut Transform, &Enemy), Without<Player>>,
-) {
-    let player_pos = player.translation;
+/// Heals the most damaged enemy within `HEAL_RANGE` on every tick.
+#[derive(Component)]
+struct Healer(Timer);
 
-    for (mut transform, enemy) in &mut enemies {
-        let direction = (player_pos - transform.translation).truncate();
+/// The short-lived line from a healer to whoever it just healed.
+#[derive(Component)]
+struct HealBeam {
+    from: Vec2,
+    to: Vec2,
+    timer: Timer,
+}
 
-        if direction != Vec2::ZERO {
-            let speed = match enemy.kind {
-                EnemyType::Basic => 150.0,
-                EnemyType::Fast => 300.0,
-                EnemyType::Tank => 75.0,
-            };
+/// Fires an `EnemyProjectile` at the player on every tick while in range.
+#[derive(Component)]
+struct Spitter(Timer);
 
-            let delta = direction.normalize() * speed * time.delta_secs();
+/// A shot fired by an enemy; spent on its first hit like a player bullet.
+#[derive(Component)]
+struct EnemyProjectile {
+    velocity: Vec2,
+}
 
-            transform.translation.x += delta.x;
-            transform.translation.y += delta.y;
-        }
-    }
+/// A killed enemy fading out. It has already lost `Enemy` and everything that
+/// lets it move, collide or deal damage.
+#[derive(Component)]
+struct Dying(Timer);
+
+/// Leaves a fading copy of the glyph behind every `AFTERIMAGE_SECS`, so a
+/// Fast enemy's motion reads at a glance.
+#[derive(Component)]
+struct Afterimages(Timer);
+
+/// One left-behind copy; fades out over `AFTERIMAGE_LIFETIME`.
+#[derive(Component)]
+struct Afterimage(Timer);
+
+/// Short-lived debris thrown out by some deaths.
+#[derive(Component)]
+struct DeathParticle {
+    velocity: Vec2,
+    timer: Timer,
 }
 
-fn cleanup_ingame_entities(mut commands: Commands, query: Query<Entity, With<InGameEntity>>) {
-    for entity in &query {
-        commands.entity(entity).despawn();
-    }
+#[derive(Component)]
+struct Health {
+    current: i32,
+    max: i32,
 }
 
-fn restart_on_r(input: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
-    if input.just_pressed(KeyCode::KeyR) {
-        next_state.set(GameState::Playing);
+impl Health {
+    fn new(max: i32) -> Self {
+        Self { current: max, max }
     }
 }
 
-fn spawn_game_over_text(mut commands: Commands) {
-    commands.spawn((
-        Text::new("GAME OVER\nPress R to Restart"),
-        TextFont {
-            font_size: 50.0,
-            font: default(),
-            ..default()
-        },
-        TextColor(Color::WHITE),
-        Node {
-            position_type: PositionType::Absolute,
-            top: Val::Percent(40.0),
-            left: Val::Percent(25.0),
-            ..default()
-        },
-        GameOverText,
-    ));
+/// Which side an entity fights for; see `can_hit` for who can hurt whom.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum Team {
+    Player,
+    Enemy,
+    Neutral,
 }
 
-fn setup_new_game(
-    mut commands: Commands,
-    mut score: ResMut<Score>,
-    game_over_text: Query<Entity, With<GameOverText>>,
-) {
-    // Reset score
-    score.0 = 0;
+/// Collision rules between teams: player attacks hit enemies and neutrals,
+/// enemy attacks only hit the player, neutrals never deal damage.
+fn can_hit(attacker: Team, target: Team) -> bool {
+    matches!(
+        (attacker, target),
+        (Team::Player, Team::Enemy) | (Team::Player, Team::Neutral) | (Team::Enemy, Team::Player)
+    )
+}
 
-    // Remove game over text
-    for entity in &game_over_text {
-        commands.entity(entity).despawn();
+#[derive(Component)]
+struct Hitbox {
+    radius: f32,
+    /// Passed through by every hit, its own included, while set.
+    intangible: bool,
+}
+
+impl Hitbox {
+    fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            intangible: false,
+        }
     }
+}
 
-    // Respawn player
+/// Absorbs one hit per charge before health is touched.
+#[derive(Component)]
+struct Barrier {
+    charges: u32,
+}
+
+/// Ignores all incoming damage until the timer runs out.
+#[derive(Component)]
+struct Invulnerable(Timer);
+
+/// On a freshly spawned enemy, so nothing can be farmed the instant it
+/// appears: until the timer runs out it is faded, intangible and takes no
+/// damage from anything.
+#[derive(Component)]
+struct SpawnProtection(Timer);
+
+/// Pushes an entity away from whatever touched it, on top of its own movement.
+#[derive(Component)]
+struct Knockback {
+    direction: Vec2,
+    distance: f32,
+    timer: Timer,
+}
+
+impl Knockback {
+    fn new(direction: Vec2) -> Self {
+        Self::with_distance(direction, KNOCKBACK_DISTANCE)
+    }
+
+    fn with_distance(direction: Vec2, distance: f32) -> Self {
+        Self {
+            direction,
+            distance,
+            timer: Timer::from_seconds(KNOCKBACK_SECS, TimerMode::Once),
+        }
+    }
+}
+
+/// The one way knockback reaches a target, so every source treats enemy
+/// resistance alike: resistance shortens the push, and a kind that resists
+/// it fully isn't moved and says so.
+fn apply_knockback_to(
+    commands: &mut Commands,
+    status_popups: &mut MessageWriter<StatusPopup>,
+    difficulty: &DifficultyConfig,
+    target: Entity,
+    position: Vec2,
+    enemy: Option<&Enemy>,
+    mut knockback: Knockback,
+) {
+    let resistance = enemy.map_or(0.0, |enemy| difficulty.knockback_resistance(enemy.kind));
+    if resistance >= 1.0 {
+        status_popups.write(StatusPopup {
+            position,
+            text: "immune",
+        });
+        return;
+    }
+    knockback.distance *= 1.0 - resistance;
+    commands.entity(target).insert(knockback);
+}
+
+/// Share of the knockback distance covered after `fraction` of the push; ease-out,
+/// so the impulse is strongest on impact and decays to nothing.
+fn knockback_travelled(fraction: f32) -> f32 {
+    let remaining = 1.0 - fraction.clamp(0.0, 1.0);
+    1.0 - remaining * remaining
+}
+
+/// Expanding ring drawn when a barrier charge breaks.
+#[derive(Component)]
+struct BarrierFlash(Timer);
+
+/// Damage dealt to whatever an entity's hitbox touches.
+#[derive(Component)]
+struct ContactDamage(i32);
+
+#[derive(Message)]
+struct DamageEvent {
+    target: Entity,
+    amount: i32,
+    source_team: Team,
+    kind: DamageKind,
+    damage_type: DamageType,
+    /// Where the hit came from, for knockback.
+    origin: Vec2,
+    /// The player weapon behind the hit, if any, for the run's stats.
+    weapon: Option<WeaponKind>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum DamageKind {
+    Projectile,
+    Contact,
+    Aura,
+    #[allow(dead_code)] // no damage-over-time weapons yet
+    Dot,
+    /// The arena itself, such as meteor strikes.
+    Hazard,
+    Melee,
+}
+
+impl DamageKind {
+    /// Lingering damage is cheap to stack, so it heals at half rate; the
+    /// arena's damage is none of the player's doing.
+    fn lifesteal_rate(self) -> f32 {
+        match self {
+            DamageKind::Projectile | DamageKind::Contact | DamageKind::Melee => 1.0,
+            DamageKind::Aura | DamageKind::Dot => 0.5,
+            DamageKind::Hazard => 0.0,
+        }
+    }
+}
+
+/// What a hit is made of, which some enemies take more or less of; see
+/// `DamageTaken`. `DamageKind` is how it was delivered.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+enum DamageType {
+    Physical,
+    Fire,
+    Frost,
+    #[allow(dead_code)] // no lightning weapons yet
+    Lightning,
+}
+
+/// Share of full damage an area hit still deals at its edge.
+const AOE_EDGE_SHARE: f32 = 1.0 / 3.0;
+
+/// Share of an area hit's damage at `distance` from its center: all of it
+/// at the center, falling linearly to `edge_share` at `radius`, and `None`
+/// past it.
+fn aoe_falloff(distance: f32, radius: f32, edge_share: f32) -> Option<f32> {
+    if distance > radius {
+        return None;
+    }
+    let t = if radius > 0.0 { distance / radius } else { 0.0 };
+    Some(1.0 + (edge_share - 1.0) * t)
+}
+
+/// One burst of area damage, before falloff.
+struct Blast {
+    center: Vec2,
+    radius: f32,
+    amount: i32,
+    source_team: Team,
+    kind: DamageKind,
+    damage_type: DamageType,
+}
+
+/// Hits every target inside the blast, scaled by `aoe_falloff`. Every area
+/// source goes through here so they all fall off the same way; a graze
+/// never rounds down to nothing.
+fn aoe_damage(
+    damage_events: &mut MessageWriter<DamageEvent>,
+    blast: &Blast,
+    edge_share: f32,
+    targets: impl IntoIterator<Item = (Entity, Vec2)>,
+) {
+    for (target, position) in targets {
+        let distance = position.distance(blast.center);
+        let Some(share) = aoe_falloff(distance, blast.radius, edge_share) else {
+            continue;
+        };
+        // `as` saturates, so an outright kill stays one
+        let amount = ((blast.amount as f32 * share).round() as i32).max(1);
+        damage_events.write(DamageEvent {
+            target,
+            amount,
+            source_team: blast.source_team,
+            kind: blast.kind,
+            damage_type: blast.damage_type,
+            origin: blast.center,
+            weapon: None,
+        });
+    }
+}
+
+/// Sent when an enemy dies, after its score has been awarded.
+#[derive(Message)]
+struct EnemyKilledEvent {
+    kind: EnemyType,
+    position: Vec2,
+    points: u32,
+}
+
+/// Sent once when an entity's health reaches zero.
+#[derive(Message)]
+struct DeathEvent {
+    entity: Entity,
+    /// What landed the killing blow.
+    kind: DamageKind,
+}
+
+#[derive(Component)]
+struct Bullet {
+    direction: Vec2,
+    speed: f32,
+    active: bool,
+    /// Struck something since it was fired; one that never does is a miss.
+    hit: bool,
+    /// The weapon it was fired from, credited with its hits and misses.
+    weapon: WeaponKind,
+    /// How far it has flown since it was fired, bounces included.
+    travelled: f32,
+}
+
+/// Lets a bullet that kills its target carry on to the nearest other enemy.
+#[derive(Component)]
+struct Ricochet {
+    bounces: u32,
+    /// Fraction of damage lost on each bounce.
+    falloff: f32,
+    /// Enemies this bullet has already hit; never retargeted.
+    hit: Vec<Entity>,
+    /// Target struck this frame, waiting to see whether it died.
+    pending: Option<Entity>,
+    /// The firing weapon's mode when the bullet left it.
+    targeting: TargetingMode,
+}
+
+impl Ricochet {
+    fn new(bounces: u32, targeting: TargetingMode) -> Self {
+        Self {
+            bounces,
+            targeting,
+            falloff: RICOCHET_FALLOFF,
+            hit: Vec::new(),
+            pending: None,
+        }
+    }
+}
+
+/// Marks a bullet as owned by the `BulletPool`; it is parked instead of despawned.
+#[derive(Component)]
+struct PooledBullet;
+
+type BulletParts = (
+    &'static mut Bullet,
+    &'static mut Visibility,
+    Has<PooledBullet>,
+);
+type ParkedBullet = (
+    Entity,
+    &'static mut Transform,
+    &'static mut Bullet,
+    &'static mut ContactDamage,
+    &'static mut Visibility,
+);
+type ParkedBulletFilter = (
+    With<PooledBullet>,
+    Without<Player>,
+    Without<PlayerCharacter>,
+    Without<Enemy>,
+);
+
+/// Inactive bullet entities kept around so firing doesn't spawn/despawn every shot.
+#[derive(Resource)]
+struct BulletPool {
+    free: Vec<Entity>,
+    capacity: usize,
+    /// Off sends every shot through a fresh spawn, to compare the two paths;
+    /// `pool` in the dev console flips it.
+    enabled: bool,
+}
+
+impl BulletPool {
+    fn new(capacity: usize) -> Self {
+        Self {
+            free: Vec::with_capacity(capacity),
+            capacity,
+            enabled: true,
+        }
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .insert_state(GameState::Resetting)
+        .add_sub_state::<PlayState>()
+        .insert_resource(Score(0))
+        .init_resource::<Gold>()
+        .init_resource::<Curse>()
+        .insert_resource(Settings::load())
+        .init_resource::<GameplaySpeed>()
+        .init_resource::<PlayerStats>()
+        .init_resource::<LifestealAccumulator>()
+        .init_resource::<GameTimeScale>()
+        .init_resource::<GameClock>()
+        .insert_resource(BulletPool::new(BULLET_POOL_CAPACITY))
+        .add_message::<DamageEvent>()
+        .add_message::<DeathEvent>()
+        .add_message::<EnemyKilledEvent>()
+        .add_plugins(popups::ScorePopupPlugin)
+        .add_plugins(minimap::MinimapPlugin)
+        .add_plugins(pickups::PickupPlugin)
+        .add_plugins(levelup::LevelUpPlugin)
+        .add_plugins(announcements::AnnouncementPlugin)
+        .add_plugins(weapons::WeaponPlugin)
+        .add_plugins(difficulty::DifficultyPlugin)
+        .add_plugins(director::DirectorPlugin)
+        .add_plugins(debug::DebugDrawPlugin)
+        .add_plugins(heat::HeatPlugin)
+        .add_plugins(ultimate::UltimatePlugin)
+        .add_plugins(shrines::ShrinePlugin)
+        .add_plugins(critters::CritterPlugin)
+        .add_plugins(challenge::ChallengePlugin)
+        .add_plugins(sharecode::ShareCodePlugin)
+        .add_plugins(input::InputPlugin)
+        .add_plugins(coop::CoopPlugin)
+        .add_plugins(spectator::SpectatorPlugin)
+        .add_plugins(vignette::VignettePlugin)
+        .add_plugins(message_log::MessageLogPlugin)
+        .add_plugins(chests::ChestPlugin)
+        .add_plugins(closecall::CloseCallPlugin)
+        .add_plugins(unlocks::UnlockPlugin)
+        .add_plugins(aim::AimAssistPlugin)
+        .add_plugins(hud::HudPlugin)
+        .add_plugins(savegame::RunSavePlugin)
+        .add_plugins(hazards::HazardPlugin)
+        .add_plugins(build_hud::BuildHudPlugin)
+        .add_plugins(fonts::FontAssetsPlugin)
+        .add_plugins(music::MusicPlugin)
+        .add_plugins(sfx::SfxPlugin)
+        .add_plugins(streaks::StreakPlugin)
+        .add_plugins(boss::BossPlugin)
+        .add_plugins(packs::PackPlugin)
+        .add_plugins(phaser::PhaserPlugin)
+        .add_plugins(thief::ThiefPlugin)
+        .add_plugins(cooldowns::CooldownPlugin)
+        .add_plugins(loot::LootPlugin)
+        .add_plugins(beacons::BeaconPlugin)
+        .add_plugins(performance::PerformancePlugin)
+        .add_plugins(run_stats::RunStatsPlugin)
+        .add_plugins(run_export::RunExportPlugin)
+        .add_plugins(photo::PhotoModePlugin)
+        .add_plugins(telemetry::TelemetryPlugin)
+        .add_plugins(melee::MeleePlugin)
+        .add_plugins(endless::EndlessPlugin)
+        .add_plugins(ghost::GhostPlugin)
+        .add_plugins(storage::PersistencePlugin)
+        .add_plugins(rumble::RumblePlugin)
+        .add_plugins(bestiary::BestiaryPlugin)
+        .add_plugins(janitor::FieldJanitorPlugin)
+        .add_plugins(despawn::DespawnPlugin)
+        .add_plugins(hints::HintPlugin)
+        .add_plugins(RunResetPlugin)
+        .add_plugins(CombatPlugin)
+        .add_systems(
+            Update,
+            (tick_invulnerability, tick_spawn_protection, tick_buffs)
+                .run_if(in_state(PlayState::Running)),
+        )
+        .add_systems(
+            Update,
+            (draw_barriers, draw_focus_hitbox, sync_player_max_health),
+        )
+        .add_systems(Update, update_tank_glyphs)
+        .add_systems(First, advance_game_clock.after(bevy::time::TimeSystems))
+        .add_systems(Last, save_settings)
+        .add_systems(OnEnter(GameState::GameOver), cleanup_ingame_entities)
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            (
+                update_effective_speed,
+                update_player_tint,
+                apply_knockback,
+                move_player,
+            )
+                .chain()
+                .run_if(in_state(PlayState::Running)),
+        )
+        .add_systems(
+            Update,
+            (draw_frost_auras, draw_shield_fields, draw_heal_beams),
+        )
+        .add_systems(
+            Update,
+            (
+                fade_corpses,
+                move_death_particles,
+                leave_afterimages,
+                fade_afterimages,
+            )
+                .run_if(in_state(PlayState::Running).or(in_state(PlayState::Spectating))),
+        )
+        .add_systems(PostUpdate, check_z_layers.run_if(|| cfg!(debug_assertions)))
+        .add_systems(
+            Update,
+            shoot_bullet.run_if(in_state(PlayState::Running).and(gameplay_unfrozen)),
+        )
+        .add_systems(
+            Update,
+            bullet_movement_system.run_if(in_state(PlayState::Running)),
+        )
+        .add_systems(
+            Update,
+            face_velocity
+                .after(bullet_movement_system)
+                .after(move_enemy_projectiles),
+        )
+        .add_systems(
+            Update,
+            (
+                update_leashes.run_if(on_timer(Duration::from_secs_f32(LEASH_CHECK_SECS))),
+                move_enemies_toward_player,
+                spitters_fire,
+                healers_heal,
+                move_enemy_projectiles,
+            )
+                .run_if(in_state(PlayState::Running).or(in_state(PlayState::Spectating))),
+        )
+        .add_systems(OnEnter(GameState::GameOver), spawn_game_over_text)
+        .add_systems(
+            Update,
+            (restart_on_r, cycle_game_speed).run_if(in_state(GameState::GameOver)),
+        )
+        .add_systems(Update, toggle_pause.run_if(in_state(GameState::Playing)))
+        .add_systems(
+            OnEnter(GameState::Playing),
+            (setup_new_game, apply_game_speed),
+        )
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    stats: Res<PlayerStats>,
+    mut pool: ResMut<BulletPool>,
+) {
+    commands.spawn(Camera2d);
+
+    // Pre-spawn hidden bullets for the pool
+    for _ in 0..pool.capacity {
+        let entity = spawn_bullet(
+            &mut commands,
+            &fonts,
+            &stats,
+            &BLASTER_PROJECTILE,
+            Vec2::ZERO,
+            Vec2::ZERO,
+            false,
+        );
+        commands
+            .entity(entity)
+            .insert((PooledBullet, Visibility::Hidden));
+        pool.free.push(entity);
+    }
+}
+
+fn tick_buffs(time: GameTime, mut q: Query<&mut Buffs>) {
+    for mut buffs in &mut q {
+        if buffs.0.is_empty() {
+            continue;
+        }
+        buffs
+            .0
+            .retain_mut(|buff| !buff.timer.tick(time.delta()).is_finished());
+    }
+}
+
+/// Every speed source in one place, in a fixed order: base stat, then buffs,
+/// then the slow, then focus. All of them multiply, so focus halves whatever
+/// the others leave.
+fn combined_speed(base: f32, buff_multiplier: f32, slow: f32, focus: bool) -> f32 {
+    let focus = if focus { FOCUS_SPEED } else { 1.0 };
+    base * buff_multiplier * (1.0 - slow) * focus
+}
+
+/// Slows stack across auras, but never past `MAX_PLAYER_SLOW`.
+fn stacked_slow(slows: impl IntoIterator<Item = f32>) -> f32 {
+    slows.into_iter().sum::<f32>().min(MAX_PLAYER_SLOW)
+}
+
+fn update_effective_speed(
+    stats: Res<PlayerStats>,
+    input: Res<PlayerInputState>,
+    player: Single<(&Transform, &Buffs, &mut EffectiveSpeed), With<Player>>,
+    auras: Query<(&Transform, &FrostAura)>,
+) {
+    let (player_tf, buffs, mut speed) = player.into_inner();
+    let player_pos = player_tf.translation.truncate();
+
+    let buff_multiplier = buffs.multiplier(BuffStat::MoveSpeed);
+
+    let slow = stacked_slow(
+        auras
+            .iter()
+            .filter(|(tf, aura)| tf.translation.truncate().distance(player_pos) < aura.radius)
+            .map(|(_, aura)| aura.slow),
+    );
+
+    speed.value = combined_speed(stats.move_speed, buff_multiplier, slow, input.focus);
+    speed.slow = slow;
+}
+
+fn update_player_tint(
+    mut commands: Commands,
+    time: GameTime,
+    player: Single<
+        (
+            Entity,
+            &EffectiveSpeed,
+            Option<&mut HealFlash>,
+            Has<Downed>,
+            &mut TextColor,
+        ),
+        With<Player>,
+    >,
+) {
+    let (entity, speed, heal_flash, downed, mut color) = player.into_inner();
+
+    if downed {
+        color.0 = DOWNED_COLOR;
+        return;
+    }
+
+    // Tint toward icy blue the harder the player is slowed
+    let chill = speed.slow / MAX_PLAYER_SLOW;
+    color.0 = Color::WHITE.mix(&Color::srgb(0.4, 0.7, 1.0), chill);
+
+    if let Some(mut flash) = heal_flash {
+        if flash.0.tick(time.delta()).is_finished() {
+            commands.entity(entity).remove::<HealFlash>();
+        } else {
+            color.0 = Color::srgb(0.3, 1.0, 0.3);
+        }
+    }
+}
+
+/// Catches any system that starts writing `translation.z` and breaks draw order.
+fn check_z_layers(
+    players: Query<&Transform, With<Player>>,
+    enemies: Query<&Transform, With<Enemy>>,
+    bullets: Query<&Transform, With<Bullet>>,
+) {
+    for tf in &players {
+        debug_assert_eq!(tf.translation.z, layers::PLAYER, "player left its z layer");
+    }
+    for tf in &enemies {
+        debug_assert_eq!(tf.translation.z, layers::ENEMIES, "enemy left its z layer");
+    }
+    for tf in &bullets {
+        debug_assert_eq!(tf.translation.z, layers::BULLETS, "bullet left its z layer");
+    }
+}
+
+/// Marks every enemy inside a living shielder's field. Shielders are few, so
+/// one pass per frame here keeps the per-hit check to a component lookup.
+fn update_shield_fields(
+    mut commands: Commands,
+    shielders: Query<(Entity, &Transform, &Shielder)>,
+    mut enemies: Query<(Entity, &Transform, &mut TextColor, Option<&Shielded>), With<Enemy>>,
+) {
+    let fields: Vec<(Entity, Vec2, f32)> = shielders
+        .iter()
+        .map(|(entity, tf, shielder)| (entity, tf.translation.truncate(), shielder.radius))
+        .collect();
+
+    for (entity, tf, mut color, shielded) in &mut enemies {
+        let position = tf.translation.truncate();
+        // A shielder never covers itself
+        let covered = fields.iter().any(|&(source, center, radius)| {
+            source != entity && center.distance(position) <= radius
+        });
+
+        match (covered, shielded) {
+            (true, None) => {
+                commands.entity(entity).insert(Shielded {
+                    base_color: color.0,
+                    carry: 0.0,
+                });
+                color.0 = color.0.mix(&SHIELD_TINT, 0.4);
+            }
+            (false, Some(shielded)) => {
+                // The fade of spawn protection or a phase may have moved on since
+                color.0 = shielded.base_color.with_alpha(color.0.alpha());
+                commands.entity(entity).remove::<Shielded>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// While focusing, the exact circle enemies have to touch. It pulses
+/// gently, or holds still with reduced motion.
+fn draw_focus_hitbox(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    input: Res<PlayerInputState>,
+    settings: Res<Settings>,
+    player: Query<(&Transform, &Hitbox), (With<Player>, Without<Downed>)>,
+) {
+    if !input.focus {
+        return;
+    }
+    let alpha = if settings.reduced_motion {
+        0.9
+    } else {
+        0.7 + 0.2 * (time.elapsed_secs() * 8.0).sin()
+    };
+    for (tf, hitbox) in &player {
+        gizmos.circle_2d(
+            tf.translation.truncate(),
+            hitbox.radius,
+            Color::WHITE.with_alpha(alpha),
+        );
+    }
+}
+
+fn draw_shield_fields(mut gizmos: Gizmos, shielders: Query<(&Transform, &Shielder)>) {
+    for (tf, shielder) in &shielders {
+        gizmos.circle_2d(
+            tf.translation.truncate(),
+            shielder.radius,
+            SHIELD_TINT.with_alpha(0.15),
+        );
+    }
+}
+
+fn draw_frost_auras(mut gizmos: Gizmos, auras: Query<(&Transform, &FrostAura)>) {
+    for (tf, aura) in &auras {
+        gizmos.circle_2d(
+            tf.translation.truncate(),
+            aura.radius,
+            Color::srgba(0.4, 0.7, 1.0, 0.15),
+        );
+    }
+}
+
+/// Runs before input movement so both add up in the same frame.
+fn apply_knockback(
+    mut commands: Commands,
+    time: GameTime,
+    mut q: Query<(Entity, &mut Transform, &mut Knockback)>,
+) {
+    for (entity, mut tf, mut knockback) in &mut q {
+        let before = knockback_travelled(knockback.timer.fraction());
+        knockback.timer.tick(time.delta());
+        let after = knockback_travelled(knockback.timer.fraction());
+
+        let delta = knockback.direction * knockback.distance * (after - before);
+        tf.translation.x += delta.x;
+        tf.translation.y += delta.y;
+
+        if knockback.timer.is_finished() {
+            commands.entity(entity).remove::<Knockback>();
+        }
+    }
+}
+
+fn move_player(
+    input: Res<PlayerInputState>,
+    time: GameTime,
+    player: Single<(&mut Transform, &EffectiveSpeed), (With<Player>, Without<Downed>)>,
+) {
+    let (mut player_transform, speed) = player.into_inner();
+
+    if input.movement != Vec2::ZERO {
+        let delta = input.movement.normalize() * speed.value * time.delta_secs();
+        player_transform.translation.x += delta.x;
+        player_transform.translation.y += delta.y;
+    }
+}
+
+/// Every player projectile is spawned here, looking the way its weapon's
+/// `ProjectileVisual` says.
+fn spawn_bullet(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    stats: &PlayerStats,
+    visual: &ProjectileVisual,
+    position: Vec2,
+    direction: Vec2,
+    active: bool,
+) -> Entity {
+    debug_assert!(
+        visual.color != ENEMY_PROJECTILE_COLOR,
+        "player projectile in the enemy color"
+    );
+    let mut bullet = commands.spawn((
+        Transform::from_translation(position.extend(layers::BULLETS)),
+        Bullet {
+            direction,
+            speed: stats.bullet_speed,
+            active,
+            hit: false,
+            weapon: WeaponKind::Blaster,
+            travelled: 0.0,
+        },
+        Hitbox::new(BULLET_RADIUS),
+        Team::Player,
+        ContactDamage(stats.bullet_damage),
+        InGameEntity,
+    ));
+    visual.apply(&mut bullet, fonts);
+    bullet.id()
+}
+
+/// Takes a bullet out of play: pooled bullets are hidden and returned, overflow is despawned.
+fn release_bullet(
+    commands: &mut Commands,
+    pool: &mut BulletPool,
+    entity: Entity,
+    bullet: &mut Bullet,
+    visibility: &mut Visibility,
+    pooled: bool,
+) {
+    if !bullet.active {
+        return;
+    }
+    bullet.active = false;
+
+    if pooled {
+        *visibility = Visibility::Hidden;
+        pool.free.push(entity);
+    } else {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn shoot_bullet(
+    input: Res<PlayerInputState>,
+    cursor_target: Res<CursorTarget>,
+    time: GameTime,
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    stats: Res<PlayerStats>,
+    mut run_stats: ResMut<RunStats>,
+    mut pool: ResMut<BulletPool>,
+    mut player_q: Query<(&Transform, &mut WeaponSlots, &mut Heat), (With<Player>, Without<Downed>)>,
+    mut parked: Query<ParkedBullet, ParkedBulletFilter>,
+) {
+    let Ok((player_tf, mut slots, mut heat)) = player_q.single_mut() else {
+        return; // Player is dead, do nothing
+    };
+    let Some(blaster) = slots.get_mut(WeaponKind::Blaster) else {
+        return;
+    };
+
+    // A cursor resting on an enemy sticks to its center
+    let aim = cursor_target.position.or(input.aim);
+    let can_start = aim.is_some() && !heat.is_locked();
+    let Some(shot) = blaster.pull(input.fire, input.fire_held, can_start, time.delta_secs()) else {
+        return;
+    };
+    // A burst shot falling due with the cursor off the window is lost
+    let Some(world_pos) = aim else {
+        return;
+    };
+
+    let targeting = blaster.targeting;
+    let origin = player_tf.translation.truncate();
+    let dir = (world_pos - origin).normalize();
+    heat.add_shot(&stats);
+
+    match shot {
+        Shot::Volley => spawn_projectile_fan(
+            &mut commands,
+            &fonts,
+            &mut run_stats,
+            &mut pool,
+            &mut parked,
+            origin,
+            dir,
+            stats.projectile_count,
+            &stats,
+            targeting,
+        ),
+        Shot::Charged(charge) => spawn_charged_bullet(
+            &mut commands,
+            &fonts,
+            &mut run_stats,
+            &stats,
+            origin,
+            dir,
+            charge,
+            targeting,
+        ),
+    }
+}
+
+/// One bullet scaled up by `charge`, spawned outside the pool so no parked
+/// bullet is ever left oversized.
+fn spawn_charged_bullet(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    run_stats: &mut RunStats,
+    stats: &PlayerStats,
+    origin: Vec2,
+    dir: Vec2,
+    charge: f32,
+    targeting: TargetingMode,
+) {
+    run_stats.weapon_mut(WeaponKind::Blaster).shots += 1;
+    let size = 1.0 + (CHARGE_SIZE_SCALE - 1.0) * charge;
+    let damage = stats.bullet_damage as f32 * (1.0 + (CHARGE_DAMAGE_SCALE - 1.0) * charge);
+
+    let entity = spawn_bullet(
+        commands,
+        fonts,
+        stats,
+        &BLASTER_PROJECTILE,
+        origin,
+        dir,
+        true,
+    );
+    let mut bullet = commands.entity(entity);
+    bullet.insert((
+        Transform::from_translation(origin.extend(layers::BULLETS)).with_scale(Vec3::splat(size)),
+        Hitbox::new(BULLET_RADIUS * size),
+        ContactDamage(damage.round() as i32),
+    ));
+    if stats.ricochet > 0 {
+        bullet.insert(Ricochet::new(stats.ricochet, targeting));
+    }
+}
+
+/// Aim directions for a fan of `count` shots `FAN_SPACING_DEGREES` apart,
+/// centred on `dir`: one goes straight, two at ±4°, three at 0 and ±8°.
+fn fan_directions(dir: Vec2, count: u32) -> impl Iterator<Item = Vec2> {
+    let count = count.max(1);
+    let middle = (count - 1) as f32 / 2.0;
+    (0..count).map(move |i| {
+        let offset = ((i as f32 - middle) * FAN_SPACING_DEGREES).to_radians();
+        Vec2::from_angle(offset).rotate(dir)
+    })
+}
+
+/// One volley of the basic weapon, shared by everything that fires it.
+/// Bullets take their speed and damage from `stats`, and reuse parked pool
+/// bullets before spawning overflow ones.
+fn spawn_projectile_fan(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    run_stats: &mut RunStats,
+    pool: &mut BulletPool,
+    parked: &mut Query<ParkedBullet, ParkedBulletFilter>,
+    origin: Vec2,
+    dir: Vec2,
+    count: u32,
+    stats: &PlayerStats,
+    targeting: TargetingMode,
+) {
+    for direction in fan_directions(dir, count) {
+        run_stats.weapon_mut(WeaponKind::Blaster).shots += 1;
+        let pooled = if pool.enabled { pool.free.pop() } else { None };
+        let entity = match pooled.and_then(|entity| parked.get_mut(entity).ok()) {
+            Some((entity, mut tf, mut bullet, mut damage, mut visibility)) => {
+                tf.translation = origin.extend(layers::BULLETS);
+                bullet.direction = direction;
+                bullet.speed = stats.bullet_speed;
+                bullet.active = true;
+                bullet.hit = false;
+                bullet.travelled = 0.0;
+                damage.0 = stats.bullet_damage;
+                *visibility = Visibility::Inherited;
+                entity
+            }
+            None => spawn_bullet(
+                commands,
+                fonts,
+                stats,
+                &BLASTER_PROJECTILE,
+                origin,
+                direction,
+                true,
+            ),
+        };
+
+        // Pooled bullets may still carry state from their last flight
+        if stats.ricochet > 0 {
+            commands
+                .entity(entity)
+                .insert(Ricochet::new(stats.ricochet, targeting));
+        } else {
+            commands.entity(entity).remove::<Ricochet>();
+        }
+    }
+}
+
+fn bullet_movement_system(
+    time: GameTime,
+    mut commands: Commands,
+    mut pool: ResMut<BulletPool>,
+    mut run_stats: ResMut<RunStats>,
+    mut q: Query<(
+        Entity,
+        &mut Transform,
+        &mut Bullet,
+        &mut Visibility,
+        Has<PooledBullet>,
+    )>,
+) {
+    for (entity, mut tf, mut bullet, mut visibility, pooled) in q.iter_mut() {
+        if !bullet.active {
+            continue;
+        }
+
+        let delta = bullet.direction * bullet.speed * time.delta_secs();
+        tf.translation.x += delta.x;
+        tf.translation.y += delta.y;
+        bullet.travelled += delta.length();
+
+        if bullet.travelled > BULLET_RANGE {
+            if !bullet.hit {
+                run_stats.weapon_mut(bullet.weapon).misses += 1;
+            }
+            release_bullet(
+                &mut commands,
+                &mut pool,
+                entity,
+                &mut bullet,
+                &mut visibility,
+                pooled,
+            );
+        }
+    }
+}
+
+fn hitbox_collision_system(
+    mut commands: Commands,
+    mut damage_events: MessageWriter<DamageEvent>,
+    mut pool: ResMut<BulletPool>,
+    mut run_stats: ResMut<RunStats>,
+    mut pending: ResMut<PendingDespawns>,
+    mut attackers: Query<
+        (
+            Entity,
+            &Transform,
+            &Hitbox,
+            &Team,
+            &ContactDamage,
+            Option<BulletParts>,
+            Option<&mut Ricochet>,
+            Has<EnemyProjectile>,
+        ),
+        Without<Dead>,
+    >,
+    targets: Query<(Entity, &Transform, &Hitbox, &Team), With<Health>>,
+) {
+    for (
+        attacker_entity,
+        attacker_tf,
+        attacker_hitbox,
+        attacker_team,
+        damage,
+        mut bullet,
+        mut ricochet,
+        enemy_projectile,
+    ) in &mut attackers
+    {
+        if let Some((bullet, _, _)) = &bullet
+            && !bullet.active
+        {
+            continue;
+        }
+        let weapon = bullet.as_ref().map(|(bullet, _, _)| bullet.weapon);
+
+        if attacker_hitbox.intangible {
+            continue;
+        }
+
+        // Waiting on `resolve_ricochets` to decide where it goes next
+        if let Some(ricochet) = &ricochet
+            && ricochet.pending.is_some()
+        {
+            continue;
+        }
+
+        for (target_entity, target_tf, target_hitbox, target_team) in &targets {
+            // Passed straight through, a bullet included
+            if target_hitbox.intangible || !can_hit(*attacker_team, *target_team) {
+                continue;
+            }
+
+            if let Some(ricochet) = &ricochet
+                && ricochet.hit.contains(&target_entity)
+            {
+                continue;
+            }
+
+            let distance = attacker_tf
+                .translation
+                .truncate()
+                .distance(target_tf.translation.truncate());
+
+            if distance < attacker_hitbox.radius + target_hitbox.radius {
+                damage_events.write(DamageEvent {
+                    target: target_entity,
+                    amount: damage.0,
+                    source_team: *attacker_team,
+                    kind: if bullet.is_some() || enemy_projectile {
+                        DamageKind::Projectile
+                    } else {
+                        DamageKind::Contact
+                    },
+                    damage_type: weapon.map_or(DamageType::Physical, WeaponKind::damage_type),
+                    origin: attacker_tf.translation.truncate(),
+                    weapon,
+                });
+                if let Some((bullet, _, _)) = &mut bullet
+                    && !bullet.hit
+                {
+                    bullet.hit = true;
+                    run_stats.weapon_mut(bullet.weapon).hits += 1;
+                }
+
+                // The parry pass may have claimed it this frame too
+                if enemy_projectile {
+                    pending.push(&mut commands, attacker_entity);
+                    break;
+                }
+
+                // A bouncing bullet holds on until we know whether the hit killed
+                if let Some(ricochet) = &mut ricochet
+                    && ricochet.bounces > 0
+                {
+                    ricochet.hit.push(target_entity);
+                    ricochet.pending = Some(target_entity);
+                    break;
+                }
+
+                // Bullets are spent on their first hit
+                if let Some((bullet, visibility, pooled)) = &mut bullet {
+                    release_bullet(
+                        &mut commands,
+                        &mut pool,
+                        attacker_entity,
+                        bullet,
+                        visibility,
+                        *pooled,
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Sends bullets whose last hit was a kill on to the enemy their targeting
+/// mode prefers among those they have not hit yet; everything else is
+/// released as a normal spent bullet.
+fn resolve_ricochets(
+    mut commands: Commands,
+    mut pool: ResMut<BulletPool>,
+    mut bullets: Query<(
+        Entity,
+        &Transform,
+        &mut Ricochet,
+        &mut ContactDamage,
+        BulletParts,
+    )>,
+    enemies: Query<(Entity, &Transform, &Health, &Enemy)>,
+) {
+    let mut rng = thread_rng();
+    for (entity, tf, mut ricochet, mut damage, (mut bullet, mut visibility, pooled)) in &mut bullets
+    {
+        let Some(struck) = ricochet.pending.take() else {
+            continue;
+        };
+
+        // Despawned already, or about to be
+        let killed = enemies
+            .get(struck)
+            .ok()
+            .is_none_or(|(_, _, health, _)| health.current <= 0);
+
+        let position = tf.translation.truncate();
+        let candidates = enemies
+            .iter()
+            .filter(|(e, _, health, _)| health.current > 0 && !ricochet.hit.contains(e))
+            .map(|(e, tf, health, enemy)| target_candidate(e, tf, health, enemy));
+        let next = select_target(
+            ricochet.targeting,
+            position,
+            RICOCHET_RANGE,
+            candidates,
+            &mut rng,
+        )
+        .and_then(|target| enemies.get(target).ok())
+        .map(|(_, tf, ..)| tf.translation.truncate());
+
+        match next {
+            Some(target) if killed => {
+                ricochet.bounces -= 1;
+                bullet.direction = (target - position).normalize_or(bullet.direction);
+                damage.0 = ((damage.0 as f32 * (1.0 - ricochet.falloff)).round() as i32).max(1);
+            }
+            _ => release_bullet(
+                &mut commands,
+                &mut pool,
+                entity,
+                &mut bullet,
+                &mut visibility,
+                pooled,
+            ),
+        }
+    }
+}
+
+/// `amount` after the target's `taken` multiple for its damage type and the
+/// player's `buffed` one. A resisted hit still lands for one; only a
+/// multiple of 0 is immune.
+fn scaled_damage(amount: i32, taken: f32, buffed: f32) -> i32 {
+    let scaled = (amount as f32 * taken * buffed).round() as i32;
+    if taken <= 0.0 {
+        0
+    } else if taken < 1.0 {
+        scaled.max(1)
+    } else {
+        scaled
+    }
+}
+
+/// The single place damage lands, so barriers and invulnerability apply to every source.
+fn apply_damage_system(
+    mut commands: Commands,
+    stats: Res<PlayerStats>,
+    mut lifesteal: ResMut<LifestealAccumulator>,
+    mut damage_events: MessageReader<DamageEvent>,
+    mut death_events: MessageWriter<DeathEvent>,
+    mut status_popups: MessageWriter<StatusPopup>,
+    mut run_stats: ResMut<RunStats>,
+    difficulty: Res<DifficultyConfig>,
+    player_buffs: Query<&Buffs, With<Player>>,
+    mut targets: Query<(
+        &Transform,
+        &mut Health,
+        &Team,
+        Option<&Enemy>,
+        Option<&mut Barrier>,
+        Option<&mut Shielded>,
+        Has<Invulnerable>,
+        Has<SpawnProtection>,
+    )>,
+) {
+    // Targets made invulnerable this frame; the Invulnerable insert only lands next frame
+    let mut shielded = Vec::new();
+    let damage_multiplier = player_buffs
+        .single()
+        .map_or(1.0, |buffs| buffs.multiplier(BuffStat::Damage));
+
+    for ev in damage_events.read() {
+        let Ok((tf, mut health, team, enemy, barrier, shielded_by, invulnerable, protected)) =
+            targets.get_mut(ev.target)
+        else {
+            continue;
+        };
+
+        // Already dead this frame, or friendly fire
+        if health.current <= 0 || !can_hit(ev.source_team, *team) {
+            continue;
+        }
+
+        if invulnerable || protected || shielded.contains(&ev.target) {
+            continue;
+        }
+
+        if let Some(mut barrier) = barrier {
+            barrier.charges -= 1;
+            if barrier.charges == 0 {
+                commands.entity(ev.target).remove::<Barrier>();
+            }
+            commands.entity(ev.target).insert((
+                Invulnerable(Timer::from_seconds(BARRIER_GRACE_SECS, TimerMode::Once)),
+                BarrierFlash(Timer::from_seconds(0.25, TimerMode::Once)),
+            ));
+            shielded.push(ev.target);
+            continue;
+        }
+
+        // The target's take on the damage type comes first, then the
+        // player's buffs, then the shield field below
+        let taken = enemy.map_or(1.0, |enemy| {
+            difficulty.damage_taken(enemy.kind).get(ev.damage_type)
+        });
+        let buffed = if ev.source_team == Team::Player && ev.kind != DamageKind::Hazard {
+            damage_multiplier
+        } else {
+            1.0
+        };
+        let amount = scaled_damage(ev.amount, taken, buffed);
+        // Pre-mitigation: shield fields only stop player bullets
+        let amount = match shielded_by {
+            Some(mut field)
+                if ev.kind == DamageKind::Projectile && ev.source_team == Team::Player =>
+            {
+                field.mitigate(amount)
+            }
+            _ => amount,
+        };
+        if amount == 0 {
+            continue;
+        }
+
+        let before = health.current;
+        health.current = (health.current - amount).min(health.max);
+
+        // Overkill doesn't heal, or count as damage dealt
+        let dealt = (before - health.current.max(0)).max(0);
+        if ev.source_team == Team::Player {
+            lifesteal.0 += dealt as f32 * stats.lifesteal * ev.kind.lifesteal_rate();
+        }
+        if let Some(weapon) = ev.weapon {
+            let weapon = run_stats.weapon_mut(weapon);
+            weapon.damage += dealt as u32;
+            if health.current <= 0 {
+                weapon.kills += 1;
+            }
+        }
+
+        if health.current <= 0 {
+            death_events.write(DeathEvent {
+                entity: ev.target,
+                kind: ev.kind,
+            });
+        } else if ev.kind == DamageKind::Contact {
+            // Shove the target clear and give it a moment before the next touch counts
+            let position = tf.translation.truncate();
+            let away = (position - ev.origin).normalize_or(Vec2::X);
+            apply_knockback_to(
+                &mut commands,
+                &mut status_popups,
+                &difficulty,
+                ev.target,
+                position,
+                enemy,
+                Knockback::new(away),
+            );
+            commands
+                .entity(ev.target)
+                .insert(Invulnerable(Timer::from_seconds(
+                    HIT_GRACE_SECS,
+                    TimerMode::Once,
+                )));
+            shielded.push(ev.target);
+        } else if ev.kind == DamageKind::Melee {
+            let position = tf.translation.truncate();
+            let away = (position - ev.origin).normalize_or(Vec2::X);
+            apply_knockback_to(
+                &mut commands,
+                &mut status_popups,
+                &difficulty,
+                ev.target,
+                position,
+                enemy,
+                Knockback::with_distance(away, MELEE_KNOCKBACK_DISTANCE),
+            );
+        }
+    }
+}
+
+/// Grows the player's health pool when max health upgrades land, healing the difference.
+fn sync_player_max_health(stats: Res<PlayerStats>, mut player: Query<&mut Health, With<Player>>) {
+    if !stats.is_changed() {
+        return;
+    }
+
+    for mut health in &mut player {
+        if stats.max_health > health.max {
+            health.current += stats.max_health - health.max;
+        }
+        health.max = stats.max_health;
+        health.current = health.current.min(health.max);
+    }
+}
+
+fn apply_lifesteal(
+    mut commands: Commands,
+    mut lifesteal: ResMut<LifestealAccumulator>,
+    player: Single<(Entity, &mut Health), (With<Player>, Without<Downed>)>,
+) {
+    let (entity, mut health) = player.into_inner();
+
+    // Nothing to heal: don't bank healing for later
+    if health.current >= health.max {
+        lifesteal.0 = 0.0;
+        return;
+    }
+
+    let whole = lifesteal.0.floor();
+    if whole < 1.0 {
+        return;
+    }
+    lifesteal.0 -= whole;
+
+    health.current = (health.current + whole as i32).min(health.max);
+    commands
+        .entity(entity)
+        .insert(HealFlash(Timer::from_seconds(0.1, TimerMode::Once)));
+}
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: GameTime,
+    mut q: Query<(Entity, &mut Invulnerable)>,
+) {
+    for (entity, mut invulnerable) in &mut q {
+        if invulnerable.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+fn tick_spawn_protection(
+    mut commands: Commands,
+    time: GameTime,
+    mut q: Query<(
+        Entity,
+        &mut SpawnProtection,
+        &mut Hitbox,
+        &mut TextColor,
+        Option<&PhaseState>,
+    )>,
+) {
+    for (entity, mut protection, mut hitbox, mut color, phase) in &mut q {
+        if !protection.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+        commands.entity(entity).remove::<SpawnProtection>();
+        match phase {
+            Some(phase) => phase.show(&mut hitbox, &mut color),
+            None => {
+                hitbox.intangible = false;
+                color.0 = color.0.with_alpha(1.0);
+            }
+        }
+    }
+}
+
+fn draw_barriers(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    time: GameTime,
+    barriers: Query<(&Transform, &Barrier)>,
+    mut flashes: Query<(Entity, &Transform, &mut BarrierFlash)>,
+) {
+    for (tf, barrier) in &barriers {
+        for i in 0..barrier.charges {
+            gizmos.circle_2d(
+                tf.translation.truncate(),
+                14.0 + i as f32 * 3.0,
+                Color::srgb(0.3, 0.9, 1.0),
+            );
+        }
+    }
+
+    for (entity, tf, mut flash) in &mut flashes {
+        if flash.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).remove::<BarrierFlash>();
+            continue;
+        }
+
+        let t = flash.0.fraction();
+        gizmos.circle_2d(
+            tf.translation.truncate(),
+            14.0 + t * 30.0,
+            Color::srgba(1.0, 1.0, 1.0, 1.0 - t),
+        );
+    }
+}
+
+fn enemy_death_system(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut cosmetics: ResMut<CosmeticPool>,
+    budget: Res<CosmeticBudget>,
+    mut death_events: MessageReader<DeathEvent>,
+    mut kill_events: MessageWriter<EnemyKilledEvent>,
+    mut score: ResMut<Score>,
+    enemies: Query<(&Enemy, &Transform)>,
+    particles: Query<(), With<DeathParticle>>,
+) {
+    let mut particles = particles.iter().count();
+    for ev in death_events.read() {
+        let Ok((enemy, tf)) = enemies.get(ev.entity) else {
+            continue;
+        };
+
+        let points = enemy_points(enemy.kind);
+        // The arena did the work, so a hazard kill is only worth half
+        let points = if ev.kind == DamageKind::Hazard {
+            points.div_ceil(2)
+        } else {
+            points
+        };
+        score.0 += points;
+
+        kill_events.write(EnemyKilledEvent {
+            kind: enemy.kind,
+            position: tf.translation.truncate(),
+            points,
+        });
+
+        // Rewards are already out; what's left is just the corpse
+        commands
+            .entity(ev.entity)
+            .remove::<(
+                Enemy,
+                Health,
+                Team,
+                Hitbox,
+                ContactDamage,
+                FrostAura,
+                Spitter,
+                Shielder,
+                Shielded,
+                Healer,
+                Boss,
+                Knockback,
+                PackMember,
+            )>()
+            .insert((
+                Dying(Timer::from_seconds(CORPSE_FADE_SECS, TimerMode::Once)),
+                TextColor(CORPSE_COLOR),
+            ));
+
+        // A burst that would go over budget is left out whole
+        if enemy.kind == EnemyType::Tank && particles + DEATH_BURST_PARTICLES <= budget.particles {
+            particles += DEATH_BURST_PARTICLES;
+            spawn_death_burst(
+                &mut commands,
+                &mut cosmetics,
+                &fonts,
+                tf.translation.truncate(),
+            );
+        }
+    }
+}
+
+fn spawn_death_burst(
+    commands: &mut Commands,
+    cosmetics: &mut CosmeticPool,
+    fonts: &FontAssets,
+    position: Vec2,
+) {
+    let mut rng = thread_rng();
+    let offset = rng.gen_range(0.0..std::f32::consts::TAU);
+    for i in 0..DEATH_BURST_PARTICLES {
+        let angle = offset + i as f32 * std::f32::consts::TAU / DEATH_BURST_PARTICLES as f32;
+        cosmetics.acquire(
+            commands,
+            (
+                DeathParticle {
+                    velocity: Vec2::from_angle(angle) * 120.0,
+                    timer: Timer::from_seconds(0.25, TimerMode::Once),
+                },
+                Text2d::new("."),
+                fonts.body(20.0),
+                TextColor(enemy_type_color(EnemyType::Tank)),
+                Transform::from_translation(position.extend(layers::ENEMIES)),
+            ),
+        );
+    }
+}
+
+/// Greys out, shrinks and fades a corpse, then queues it for despawning.
+fn fade_corpses(
+    mut commands: Commands,
+    time: GameTime,
+    mut pending: ResMut<PendingDespawns>,
+    mut corpses: Query<(Entity, &mut Dying, &mut Transform, &mut TextColor), Without<Dead>>,
+) {
+    for (entity, mut dying, mut tf, mut color) in &mut corpses {
+        dying.0.tick(time.delta());
+        let remaining = 1.0 - dying.0.fraction();
+        tf.scale = Vec3::splat(0.5 + 0.5 * remaining);
+        color.0.set_alpha(remaining);
+
+        if dying.0.is_finished() {
+            pending.push(&mut commands, entity);
+        }
+    }
+}
+
+fn move_death_particles(
+    mut commands: Commands,
+    time: GameTime,
+    mut cosmetics: ResMut<CosmeticPool>,
+    mut pending: ResMut<PendingDespawns>,
+    mut particles: Query<(Entity, &mut DeathParticle, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut particle, mut tf, mut color) in &mut particles {
+        particle.timer.tick(time.delta());
+        let delta = particle.velocity * time.delta_secs();
+        tf.translation.x += delta.x;
+        tf.translation.y += delta.y;
+        color.0.set_alpha(1.0 - particle.timer.fraction());
+
+        if particle.timer.is_finished() {
+            cosmetics.release::<DeathParticle>(&mut commands, &mut pending, entity);
+        }
+    }
+}
+
+/// Off with reduced motion, like the other trailing effects, and capped by
+/// the cosmetic budget.
+fn leave_afterimages(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut cosmetics: ResMut<CosmeticPool>,
+    time: EnemyTime,
+    settings: Res<Settings>,
+    budget: Res<CosmeticBudget>,
+    mut enemies: Query<(&Transform, &Text2d, &Enemy, &mut Afterimages)>,
+    afterimages: Query<(), With<Afterimage>>,
+) {
+    let mut live = afterimages.iter().count();
+    for (tf, glyph, enemy, mut afterimages) in &mut enemies {
+        if !afterimages.0.tick(time.delta()).just_finished()
+            || settings.reduced_motion
+            || live >= budget.trails
+        {
+            continue;
+        }
+        live += 1;
+        let (font, color) = enemy_style(&fonts, enemy.kind);
+        cosmetics.acquire(
+            &mut commands,
+            (
+                Text2d::new(glyph.0.clone()),
+                font,
+                TextColor(color.0.with_alpha(AFTERIMAGE_ALPHA)),
+                Transform::from_translation(tf.translation),
+                Afterimage(Timer::from_seconds(AFTERIMAGE_LIFETIME, TimerMode::Once)),
+            ),
+        );
+    }
+}
+
+fn fade_afterimages(
+    mut commands: Commands,
+    time: EnemyTime,
+    mut cosmetics: ResMut<CosmeticPool>,
+    mut pending: ResMut<PendingDespawns>,
+    mut afterimages: Query<(Entity, &mut Afterimage, &mut TextColor)>,
+) {
+    for (entity, mut afterimage, mut color) in &mut afterimages {
+        afterimage.0.tick(time.delta());
+        if afterimage.0.is_finished() {
+            cosmetics.release::<Afterimage>(&mut commands, &mut pending, entity);
+            continue;
+        }
+        color
+            .0
+            .set_alpha(AFTERIMAGE_ALPHA * (1.0 - afterimage.0.fraction()));
+    }
+}
+
+/// A fallen player goes down if a teammate is still standing to revive them;
+/// once nobody is, the bodies are cleared and the run moves to spectating.
+fn player_death_system(
+    mut commands: Commands,
+    mut death_events: MessageReader<DeathEvent>,
+    mut log: MessageWriter<LogMessage>,
+    mut next_state: ResMut<NextState<PlayState>>,
+    players: Query<(Entity, &Health, Has<Downed>), With<PlayerCharacter>>,
+) {
+    let mut fallen = Vec::new();
+    for ev in death_events.read() {
+        if players.contains(ev.entity) {
+            fallen.push(ev.entity);
+        }
+    }
+    if fallen.is_empty() {
+        return;
+    }
+
+    let standing = players
+        .iter()
+        .any(|(entity, health, downed)| !downed && health.current > 0 && !fallen.contains(&entity));
+    if !standing {
+        log.write(LogMessage("Game over".to_string()));
+        for (entity, ..) in &players {
+            commands.entity(entity).despawn();
+        }
+        next_state.set(PlayState::Spectating);
+        return;
+    }
+
+    for entity in fallen {
+        log.write(LogMessage("A player is down".to_string()));
+        commands.entity(entity).insert(Downed {
+            revive: Timer::from_seconds(REVIVE_SECS, TimerMode::Once),
+        });
+    }
+}
+
+fn scaled_health(base: i32, multiplier: f32) -> i32 {
+    // `as` saturates, so extreme multipliers can't wrap negative
+    ((base as f32 * multiplier).round() as i32).max(1)
+}
+
+/// What a spawn position is checked against. The arena has no walls or
+/// edges yet, so for now that is only where the players stand.
+struct SpawnContext {
+    players: Vec<Vec2>,
+    /// Leans the roll toward the way the player is heading; `None` rolls
+    /// uniformly around the ring.
+    bias: Option<SpawnBias>,
+}
+
+/// `share` of rolls land within `HEADING_CONE` of `heading`, the rest
+/// anywhere else on the ring.
+struct SpawnBias {
+    heading: Vec2,
+    share: f32,
+}
+
+fn roll_spawn_angle(rng: &mut impl Rng, bias: Option<&SpawnBias>) -> f32 {
+    use std::f32::consts::TAU;
+    let Some(bias) = bias else {
+        return rng.gen_range(0.0..TAU);
+    };
+    let ahead = bias.heading.to_angle();
+    if rng.gen_bool(bias.share.clamp(0.0, 1.0) as f64) {
+        ahead + rng.gen_range(-HEADING_CONE..HEADING_CONE)
+    } else {
+        ahead + HEADING_CONE + rng.gen_range(0.0..TAU - 2.0 * HEADING_CONE)
+    }
+}
+
+fn is_valid_spawn(position: Vec2, ctx: &SpawnContext) -> bool {
+    ctx.players
+        .iter()
+        .all(|player| player.distance(position) >= MIN_SPAWN_DISTANCE)
+}
+
+/// A point on the spawn ring around `center`, re-rolled until it is valid.
+/// `None` once every attempt has failed; skip the spawn rather than force it.
+fn roll_spawn_position(rng: &mut impl Rng, center: Vec2, ctx: &SpawnContext) -> Option<Vec2> {
+    (0..SPAWN_ATTEMPTS)
+        .map(|_| {
+            let angle = roll_spawn_angle(rng, ctx.bias.as_ref());
+            let distance = rng.gen_range(SPAWN_RING_MIN..SPAWN_RING_MAX);
+            center + Vec2::from_angle(angle) * distance
+        })
+        .find(|&position| is_valid_spawn(position, ctx))
+}
+
+/// Score for a kill, before any hazard discount.
+fn enemy_points(kind: EnemyType) -> u32 {
+    match kind {
+        EnemyType::Basic => 1,
+        EnemyType::Fast => 2,
+        EnemyType::Tank => 5,
+        EnemyType::Frost => 3,
+        EnemyType::Spitter => 3,
+        EnemyType::Shielder => 4,
+        EnemyType::Healer => 4,
+        EnemyType::Phaser => 3,
+        EnemyType::Thief => 4,
+        EnemyType::Boss => 50,
+    }
+}
+
+/// Glyph, base health before the curse, and hitbox radius.
+fn enemy_base_stats(kind: EnemyType) -> (&'static str, i32, f32) {
+    match kind {
+        EnemyType::Basic => ("E", 1, 10.0),
+        EnemyType::Fast => ("e", 1, 10.0),
+        EnemyType::Tank => ("EEE", 3, 10.0),
+        EnemyType::Frost => ("F", 2, 10.0),
+        EnemyType::Spitter => ("S", 2, 10.0),
+        EnemyType::Shielder => ("D", 3, 12.0),
+        EnemyType::Healer => ("H", 2, 10.0),
+        EnemyType::Phaser => ("P", 2, 10.0),
+        EnemyType::Thief => ("$", 1, 10.0),
+        EnemyType::Boss => ("BOSS", 80, 24.0),
+    }
+}
+
+/// How a kind looks on the field: its signature color, and a bigger glyph
+/// for the Tank so its size reads as its threat. Every enemy is spawned
+/// through `spawn_enemy`, so this is the only place the look is decided.
+fn enemy_style(fonts: &FontAssets, kind: EnemyType) -> (TextFont, TextColor) {
+    let size = match kind {
+        EnemyType::Tank => 26.0,
+        _ => 20.0,
+    };
+    (fonts.body(size), TextColor(enemy_type_color(kind)))
+}
+
+fn spawn_enemy(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    position: Vec2,
+    enemy_type: EnemyType,
+    curse: &Curse,
+    rules: &RunRules,
+    difficulty: &DifficultyConfig,
+) -> Entity {
+    let (symbol, health, radius) = enemy_base_stats(enemy_type);
+    let (font, color) = enemy_style(fonts, enemy_type);
+
+    let mut enemy = commands.spawn((
+        Enemy { kind: enemy_type },
+        Health::new(scaled_health(
+            health,
+            curse.threat_multiplier()
+                * rules.enemy_health
+                * difficulty.health_multiplier(enemy_type),
+        )),
+        Team::Enemy,
+        Hitbox {
+            radius,
+            intangible: true,
+        },
+        SpawnProtection(Timer::from_seconds(SPAWN_PROTECTION_SECS, TimerMode::Once)),
+        Transform::from_translation(position.extend(layers::ENEMIES)),
+        GlobalTransform::default(),
+        Text2d::new(symbol),
+        font,
+        TextColor(color.0.with_alpha(SPAWN_PROTECTION_ALPHA)),
+        InGameEntity,
+    ));
+
+    match enemy_type {
+        EnemyType::Fast => {
+            enemy.insert(Afterimages(Timer::from_seconds(
+                AFTERIMAGE_SECS,
+                TimerMode::Repeating,
+            )));
+        }
+        EnemyType::Frost => {
+            enemy.insert(FrostAura {
+                radius: 100.0,
+                slow: 0.2,
+            });
+        }
+        EnemyType::Spitter => {
+            enemy.insert(Spitter(Timer::from_seconds(
+                SPITTER_FIRE_SECS,
+                TimerMode::Repeating,
+            )));
+        }
+        EnemyType::Shielder => {
+            enemy.insert(Shielder {
+                radius: SHIELD_FIELD_RADIUS,
+            });
+        }
+        EnemyType::Healer => {
+            enemy.insert(Healer(Timer::from_seconds(
+                HEAL_INTERVAL_SECS,
+                TimerMode::Repeating,
+            )));
+        }
+        EnemyType::Phaser => {
+            enemy.insert(PhaseState::new(&mut thread_rng()));
+        }
+        EnemyType::Thief => {
+            enemy.insert(Thief::default());
+        }
+        EnemyType::Boss => {
+            enemy.insert(Boss::new(&boss::GOLIATH));
+        }
+        _ => {}
+    }
+
+    // Shielders only support and thieves only steal; touching either is harmless
+    if !matches!(enemy_type, EnemyType::Shielder | EnemyType::Thief) {
+        enemy.insert(ContactDamage(1));
+    }
+    enemy.id()
+}
+
+/// One "E" per hit a Tank can still take, up to three, and a "+" when it can
+/// take more than that.
+fn tank_glyph(health: i32) -> String {
+    let mut glyph = "E".repeat(health.clamp(0, 3) as usize);
+    if health > 3 {
+        glyph.push('+');
+    }
+    glyph
+}
+
+/// Only the glyph changes; the hitbox stays the same size however many
+/// letters are shown.
+fn update_tank_glyphs(mut tanks: Query<(&Enemy, &Health, &mut Text2d), Changed<Health>>) {
+    for (enemy, health, mut text) in &mut tanks {
+        if enemy.kind != EnemyType::Tank {
+            continue;
+        }
+        let glyph = tank_glyph(health.current);
+        if text.0 != glyph {
+            text.0 = glyph;
+        }
+    }
+}
+
+/// Heals the most damaged enemy in range, never the healer itself. Corpses
+/// have already lost `Enemy` and `Health`, so the dying are out of reach.
+fn healers_heal(
+    mut commands: Commands,
+    time: EnemyTime,
+    mut healers: Query<(Entity, &Transform, &mut Healer)>,
+    mut enemies: Query<(Entity, &Transform, &mut Health), With<Enemy>>,
+) {
+    for (healer, healer_tf, mut timer) in &mut healers {
+        if !timer.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let from = healer_tf.translation.truncate();
+        let target = enemies
+            .iter_mut()
+            .filter(|(entity, tf, health)| {
+                *entity != healer
+                    && health.current < health.max
+                    && tf.translation.truncate().distance(from) <= HEAL_RANGE
+            })
+            .max_by_key(|(_, _, health)| health.max - health.current);
+        let Some((_, tf, mut health)) = target else {
+            continue;
+        };
+
+        health.current = (health.current + 1).min(health.max);
+        commands.spawn((
+            HealBeam {
+                from,
+                to: tf.translation.truncate(),
+                timer: Timer::from_seconds(0.2, TimerMode::Once),
+            },
+            InGameEntity,
+        ));
+    }
+}
+
+fn draw_heal_beams(
+    mut commands: Commands,
+    time: GameTime,
+    mut gizmos: Gizmos,
+    mut beams: Query<(Entity, &mut HealBeam)>,
+) {
+    for (entity, mut beam) in &mut beams {
+        beam.timer.tick(time.delta());
+        if beam.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let alpha = 1.0 - beam.timer.fraction();
+        gizmos.line_2d(
+            beam.from,
+            beam.to,
+            enemy_type_color(EnemyType::Healer).with_alpha(alpha),
+        );
+    }
+}
+
+fn spitters_fire(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    time: EnemyTime,
+    players: Query<&Transform, (With<PlayerCharacter>, Without<Downed>)>,
+    mut spitters: Query<(&Transform, &mut Spitter)>,
+) {
+    let targets: Vec<Vec2> = players.iter().map(|tf| tf.translation.truncate()).collect();
+    for (tf, mut spitter) in &mut spitters {
+        if !spitter.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let position = tf.translation.truncate();
+        let Some(target) = nearest(position, targets.iter().copied()) else {
+            continue;
+        };
+        if position.distance(target) > SPITTER_RANGE {
+            continue;
+        }
+
+        patterns::spawn_enemy_projectiles(
+            &mut commands,
+            &fonts,
+            position,
+            &patterns::aimed(target - position),
+            &patterns::Volley {
+                speed: ENEMY_PROJECTILE_SPEED,
+                damage: 1,
+            },
+        );
+    }
+}
+
+fn move_enemy_projectiles(
+    mut commands: Commands,
+    time: EnemyTime,
+    mut pending: ResMut<PendingDespawns>,
+    player: Query<&Transform, (With<Player>, Without<EnemyProjectile>)>,
+    mut projectiles: Query<(Entity, &mut Transform, &EnemyProjectile), Without<Dead>>,
+) {
+    let center = player.single().map(|tf| tf.translation.truncate()).ok();
+    for (entity, mut tf, projectile) in &mut projectiles {
+        let delta = projectile.velocity * time.delta_secs();
+        tf.translation.x += delta.x;
+        tf.translation.y += delta.y;
+
+        // Well past the spawn ring is never coming back
+        if let Some(center) = center
+            && tf.translation.truncate().distance(center) > SPAWN_RING_MAX * 2.0
+        {
+            pending.push(&mut commands, entity);
+        }
+    }
+}
+
+/// Points turning projectiles along their flight. A ricochet or a reused
+/// pool bullet changes direction mid-run, so this checks every frame but
+/// only writes the transform when the angle is off.
+fn face_velocity(
+    mut bullets: Query<(&mut Transform, &Bullet), With<FacesVelocity>>,
+    mut enemy_shots: Query<
+        (&mut Transform, &EnemyProjectile),
+        (With<FacesVelocity>, Without<Bullet>),
+    >,
+) {
+    let directions = bullets
+        .iter_mut()
+        .map(|(tf, bullet)| (tf, bullet.direction))
+        .chain(
+            enemy_shots
+                .iter_mut()
+                .map(|(tf, projectile)| (tf, projectile.velocity)),
+        );
+    for (mut tf, direction) in directions {
+        if direction == Vec2::ZERO {
+            continue;
+        }
+        let rotation = Quat::from_rotation_z(direction.to_angle());
+        if tf.rotation != rotation {
+            tf.rotation = rotation;
+        }
+    }
+}
+
+/// With the parry upgrade, player bullets and enemy projectiles cancel out.
+/// Enemy projectiles are few, so a straight scan over them is cheap enough.
+fn parry_enemy_projectiles(
+    mut commands: Commands,
+    stats: Res<PlayerStats>,
+    mut score: ResMut<Score>,
+    mut pool: ResMut<BulletPool>,
+    mut pending: ResMut<PendingDespawns>,
+    projectiles: Query<(Entity, &Transform, &Hitbox), (With<EnemyProjectile>, Without<Dead>)>,
+    mut bullets: Query<(Entity, &Transform, &Hitbox, BulletParts)>,
+) {
+    if !stats.bullet_parry {
+        return;
+    }
+
+    for (projectile, projectile_tf, projectile_hitbox) in &projectiles {
+        let position = projectile_tf.translation.truncate();
+        for (entity, tf, hitbox, (mut bullet, mut visibility, pooled)) in &mut bullets {
+            if !bullet.active
+                || tf.translation.truncate().distance(position)
+                    >= hitbox.radius + projectile_hitbox.radius
+            {
+                continue;
+            }
+
+            release_bullet(
+                &mut commands,
+                &mut pool,
+                entity,
+                &mut bullet,
+                &mut visibility,
+                pooled,
+            );
+            pending.push(&mut commands, projectile);
+            score.0 += 1;
+            break;
+        }
+    }
+}
+
+fn enemy_speed(kind: EnemyType) -> f32 {
+    match kind {
+        EnemyType::Basic => 150.0,
+        EnemyType::Fast => 300.0,
+        EnemyType::Tank => 75.0,
+        EnemyType::Frost => 120.0,
+        EnemyType::Spitter => 90.0,
+        EnemyType::Shielder => 80.0,
+        EnemyType::Healer => 90.0,
+        EnemyType::Phaser => 130.0,
+        EnemyType::Thief => 220.0,
+        EnemyType::Boss => 70.0,
+    }
+}
+
+/// How close a kind is willing to get; `None` walks straight in.
+fn enemy_standoff(kind: EnemyType) -> Option<f32> {
+    match kind {
+        EnemyType::Spitter | EnemyType::Healer => Some(STANDOFF_DISTANCE),
+        _ => None,
+    }
+}
+
+/// Where an enemy walks this frame, scaled to a fraction of its full speed:
+/// at the nearest standing player (backing off inside `standoff`), or a slow
+/// meander once nobody is left.
+fn enemy_heading(
+    entity: Entity,
+    position: Vec2,
+    standoff: Option<f32>,
+    targets: &[Vec2],
+    elapsed: f32,
+) -> Vec2 {
+    match nearest(position, targets.iter().copied()) {
+        Some(target) => {
+            let toward = (target - position).normalize_or_zero();
+            let distance = target.distance(position);
+            match standoff {
+                Some(range) if distance < range => -toward,
+                Some(range) if distance < range + STANDOFF_BAND => Vec2::ZERO,
+                _ => toward,
+            }
+        }
+        None => {
+            // Each enemy drifts on its own slowly turning course
+            let angle = entity.index_u32() as f32 * 2.4 + elapsed * 0.5;
+            Vec2::from_angle(angle) * WANDER_SPEED
+        }
+    }
+}
+
+/// Set on an enemy left far behind. Movement skips it outright, so a crowd
+/// of idle enemies costs nothing until the next leash check.
+#[derive(Component)]
+struct Leashed;
+
+/// Distances are only rechecked a few times a second; bosses never idle.
+fn update_leashes(
+    mut commands: Commands,
+    players: Query<&Transform, (With<PlayerCharacter>, Without<Downed>)>,
+    enemies: Query<(Entity, &Transform, Has<Leashed>), (With<Enemy>, Without<Boss>)>,
+) {
+    let targets: Vec<Vec2> = players.iter().map(|tf| tf.translation.truncate()).collect();
+    if targets.is_empty() {
+        return;
+    }
+
+    for (entity, tf, leashed) in &enemies {
+        let position = tf.translation.truncate();
+        let closest = targets
+            .iter()
+            .map(|target| target.distance_squared(position))
+            .fold(f32::INFINITY, f32::min);
+        if !leashed && closest > LEASH_RADIUS * LEASH_RADIUS {
+            commands.entity(entity).insert(Leashed);
+        } else if leashed && closest < LEASH_REAGGRO_RADIUS * LEASH_REAGGRO_RADIUS {
+            commands.entity(entity).remove::<Leashed>();
+        }
+    }
+}
+
+fn move_enemies_toward_player(
+    time: EnemyTime,
+    rules: Res<RunRules>,
+    players: Query<&Transform, (With<PlayerCharacter>, Without<Downed>)>,
+    mut enemies: Query<
+        (
+            Entity,
+            &mut Transform,
+            &Enemy,
+            Option<&PackMember>,
+            Has<PackLeader>,
+        ),
+        (
+            Without<PlayerCharacter>,
+            Without<Boss>,
+            Without<Thief>,
+            Without<Leashed>,
+        ),
+    >,
+) {
+    let targets: Vec<Vec2> = players.iter().map(|tf| tf.translation.truncate()).collect();
+    let heading_of = |entity: Entity, position: Vec2, kind: EnemyType| {
+        enemy_heading(
+            entity,
+            position,
+            enemy_standoff(kind),
+            &targets,
+            time.elapsed_secs(),
+        )
+    };
+
+    // Each leader is looked at once, before anyone in its pack moves
+    let leaders: HashMap<Entity, LeaderPose> = enemies
+        .iter()
+        .filter(|(.., leader)| *leader)
+        .map(|(entity, tf, enemy, ..)| {
+            let position = tf.translation.truncate();
+            let facing = nearest(position, targets.iter().copied())
+                .map_or(Vec2::X, |target| (target - position).normalize_or(Vec2::X));
+            let pose = LeaderPose {
+                position,
+                heading: heading_of(entity, position, enemy.kind),
+                facing,
+            };
+            (entity, pose)
+        })
+        .collect();
+
+    for (entity, mut transform, enemy, member, _) in &mut enemies {
+        let position = transform.translation.truncate();
+        let leader = member.and_then(|member| Some((member, leaders.get(&member.leader)?)));
+        let heading = match leader {
+            Some((member, leader)) => packs::follow_heading(member, leader, position),
+            None => heading_of(entity, position, enemy.kind),
+        };
+        let delta = heading * enemy_speed(enemy.kind) * rules.enemy_speed * time.delta_secs();
+
+        transform.translation.x += delta.x;
+        transform.translation.y += delta.y;
+    }
+}
+
+/// Hits landing, deaths and what they score: everything between a collision
+/// and the points it is worth.
+struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        // Ordered after the last place a run can end in the frame, so the gate sees it
+        app.configure_sets(
+            Update,
+            GameplaySet
+                .after(player_death_system)
+                .run_if(run_not_ending),
+        )
+        .add_systems(
+            Update,
+            (
+                update_shield_fields,
+                hitbox_collision_system,
+                apply_damage_system,
+                player_death_system,
+                (
+                    enemy_death_system,
+                    apply_lifesteal,
+                    resolve_ricochets,
+                    parry_enemy_projectiles,
+                )
+                    .in_set(GameplaySet),
+            )
+                .chain()
+                .run_if(in_state(PlayState::Running).and(gameplay_unfrozen)),
+        );
+    }
+}
+
+/// What entering `Resetting` does: clear the field, run `ResetSet`, start
+/// the run.
+struct RunResetPlugin;
+
+impl Plugin for RunResetPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            OnEnter(GameState::Resetting),
+            ResetSet.after(cleanup_ingame_entities).before(start_run),
+        )
+        .add_systems(
+            OnEnter(GameState::Resetting),
+            (
+                cleanup_ingame_entities,
+                reset_run_state.in_set(ResetSet),
+                start_run,
+            ),
+        );
+    }
+}
+
+/// The root's run-scoped resources back to their fresh-run values; the
+/// plugins' own resets run beside it in `ResetSet`.
+fn reset_run_state(
+    mut score: ResMut<Score>,
+    mut gold: ResMut<Gold>,
+    mut curse: ResMut<Curse>,
+    mut stats: ResMut<PlayerStats>,
+    mut lifesteal: ResMut<LifestealAccumulator>,
+    mut clock: ResMut<GameClock>,
+) {
+    *clock = GameClock::default();
+    score.0 = 0;
+    gold.0 = 0;
+    curse.0 = 0;
+    *stats = PlayerStats::default();
+    lifesteal.0 = 0.0;
+}
+
+fn start_run(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+fn cleanup_ingame_entities(
+    mut commands: Commands,
+    mut pool: ResMut<BulletPool>,
+    query: Query<Entity, (With<InGameEntity>, Without<PooledBullet>)>,
+    mut pooled_q: Query<(Entity, &mut Bullet, &mut Visibility), With<PooledBullet>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+
+    // Pooled bullets survive the run; just park them again
+    for (entity, mut bullet, mut visibility) in &mut pooled_q {
+        release_bullet(
+            &mut commands,
+            &mut pool,
+            entity,
+            &mut bullet,
+            &mut visibility,
+            true,
+        );
+    }
+}
+
+fn restart_on_r(
+    input: Res<ButtonInput<KeyCode>>,
+    mut mode: ResMut<RunMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if input.just_pressed(KeyCode::KeyR) {
+        *mode = RunMode::Normal;
+        next_state.set(GameState::Resetting);
+    }
+}
+
+fn cycle_game_speed(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(KeyCode::Digit3) {
+        let next = GAME_SPEEDS
+            .iter()
+            .position(|&speed| speed == settings.game_speed)
+            .map_or(0, |i| (i + 1) % GAME_SPEEDS.len());
+        settings.game_speed = GAME_SPEEDS[next];
+        info!("game speed {:.0}%", settings.game_speed * 100.0);
+    }
+}
+
+/// Esc pauses and resumes. Only the running game pauses, so the in-run
+/// screens keep Esc for themselves.
+fn toggle_pause(
+    input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<PlayState>>,
+    mut next_state: ResMut<NextState<PlayState>>,
+) {
+    if !input.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    match state.get() {
+        PlayState::Running => next_state.set(PlayState::Paused),
+        PlayState::Paused => next_state.set(PlayState::Running),
+        _ => {}
+    }
+}
+
+/// Full-screen parent for one state's UI; it and all its children despawn when
+/// the state exits, so state-specific UI should always be spawned under one.
+fn spawn_state_ui_root<S: States>(commands: &mut Commands, state: S) -> Entity {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            DespawnOnExit(state),
+        ))
+        .id()
+}
+
+fn spawn_game_over_text(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    score: Res<Score>,
+    settings: Res<Settings>,
+    suspended: Res<RunSuspended>,
+    speed: Res<GameplaySpeed>,
+) {
+    let root = spawn_state_ui_root(&mut commands, GameState::GameOver);
+    let heading = if suspended.0 {
+        "RUN SAVED"
+    } else {
+        "GAME OVER"
+    };
+    // A slowed run is flagged next to its score
+    let slowed = if speed.0 < 1.0 { "*" } else { "" };
+    let coop = if settings.coop { "on" } else { "off" };
+    let endless = if settings.endless { "on" } else { "off" };
+    let hints = if settings.hints { "on" } else { "off" };
+    let ghost = if settings.ghost { "on" } else { "off" };
+    commands.spawn((
+        Text::new(format!(
+            "{heading}\nScore: {}{slowed}\nPress R to Restart\nPress 2 for co-op ({coop}), 3 for speed ({:.0}%)\nPress 4 for endless ({endless}), 5 for hints ({hints})\nPress 6 for the ghost ({ghost}), B for the bestiary",
+            score.0,
+            settings.game_speed * 100.0
+        )),
+        fonts.display(50.0),
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(40.0),
+            left: Val::Percent(25.0),
+            ..default()
+        },
+        GameOverText,
+        ChildOf(root),
+    ));
+}
+
+fn setup_new_game(mut commands: Commands, fonts: Res<FontAssets>, stats: Res<PlayerStats>) {
+    // In-run HUD
+    let hud = spawn_state_ui_root(&mut commands, GameState::Playing);
+    commands.spawn((
+        Text::new("Score: 0"),
+        fonts.body(28.0),
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        ScoreText,
+        ChildOf(hud),
+    ));
+
+    commands.spawn((
+        Text::new(""),
+        fonts.body(22.0),
+        TextColor(Color::srgb(0.8, 0.3, 1.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(44.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        CurseText,
+        ChildOf(hud),
+    ));
+
+    // Respawn player
     commands.spawn((
         Player,
+        PlayerCharacter,
+        Health::new(stats.max_health),
+        Team::Player,
+        Hitbox::new(10.0),
+        EffectiveSpeed::new(stats.move_speed),
+        Buffs::default(),
+        WeaponSlots::new(WeaponKind::Blaster),
+        Heat::default(),
         InGameEntity,
         Text2d::new("@"),
-        TextFont {
-            font_size: 20.0,
-            font: default(),
-            ..default()
-        },
+        fonts.body(20.0),
         TextColor(Color::WHITE),
-        Transform::from_translation(Vec3::ZERO),
+        Transform::from_translation(Vec3::new(0.0, 0.0, layers::PLAYER)),
     ));
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::{RunSystemOnce, SystemState};
+    use bevy::gizmos::GizmoPlugin;
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::loot::{Drop, DropPity, PityCategory};
+
+    fn headless_app() -> App {
+        let mut app = App::new();
+        // Gizmos for the melee swipe
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            AssetPlugin::default(),
+            GizmoPlugin,
+        ))
+        .insert_state(GameState::Resetting)
+        .insert_resource(Score(0))
+        .init_resource::<Gold>()
+        .init_resource::<Curse>()
+        .init_resource::<PlayerStats>()
+        .init_resource::<LifestealAccumulator>()
+        .insert_resource(BulletPool::new(0))
+        .insert_resource(FontAssets {
+            body: default(),
+            display: default(),
+        })
+        .init_resource::<GameClock>()
+        .init_resource::<GameplaySpeed>()
+        .init_resource::<savegame::RunSuspended>()
+        .init_resource::<Persistence>()
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<RunStats>()
+        .insert_resource(DifficultyConfig::default())
+        .add_message::<DamageEvent>()
+        .add_message::<DeathEvent>()
+        .add_message::<StatusPopup>()
+        .add_plugins((
+            RunResetPlugin,
+            loot::LootPlugin,
+            levelup::LevelUpPlugin,
+            melee::MeleePlugin,
+            despawn::DespawnPlugin,
+            ultimate::UltimatePlugin,
+            challenge::ChallengePlugin,
+        ))
+        .add_systems(OnEnter(GameState::Playing), setup_new_game);
+        app
+    }
+
+    /// One pity miss short of a forced shield, if the count carried over.
+    fn miss_shield(app: &mut App) -> Vec<Drop> {
+        let mut drops = Vec::new();
+        app.world_mut().resource_mut::<DropPity>().settle(
+            PityCategory::Shield,
+            2,
+            true,
+            &mut drops,
+        );
+        drops
+    }
+
+    /// Marks a cosmetic handed out by the pool.
+    #[derive(Component)]
+    struct Spark;
+
+    /// Takes a cosmetic from the pool and puts it straight back.
+    fn pool_a_cosmetic(app: &mut App) -> Entity {
+        app.world_mut()
+            .run_system_once(
+                |mut commands: Commands,
+                 mut pool: ResMut<CosmeticPool>,
+                 mut pending: ResMut<PendingDespawns>| {
+                    let entity = pool.acquire(&mut commands, Spark);
+                    pool.release::<Spark>(&mut commands, &mut pending, entity);
+                    entity
+                },
+            )
+            .unwrap()
+    }
+
+    /// Everything a run leaves behind that the next must not see.
+    fn play_a_run(world: &mut World) {
+        world.resource_mut::<Score>().0 = 120;
+        world.resource_mut::<Gold>().0 = 30;
+        world.resource_mut::<Curse>().0 = 2;
+        world.spawn(InGameEntity);
+        world.resource_mut::<GameClock>().elapsed = Duration::from_secs(90);
+        world.resource_mut::<TimeDilation>().enemies = 0.5;
+        world.resource_mut::<PlayerStats>().bullet_damage = 5;
+        *world.resource_mut::<levelup::Experience>() = levelup::Experience {
+            xp: 3.0,
+            level: 4,
+            pending: 1,
+        };
+        let mut pool = world.resource_mut::<upgrades::UpgradePool>();
+        pool.restore("clover", 2);
+        pool.banish(upgrades::find_upgrade("vitality").unwrap());
+        pool.hold(&[upgrades::find_upgrade("swift_boots").unwrap()]);
+        world.resource_mut::<melee::MeleeCooldown>().start();
+        world.resource_mut::<RunRules>().enemy_speed = 3.0;
+
+        let mut slots = world
+            .query_filtered::<&mut WeaponSlots, With<Player>>()
+            .single_mut(world)
+            .unwrap();
+        slots.add_or_level(WeaponKind::Blaster);
+        slots.add_or_level(WeaponKind::Aura);
+        slots.get_mut(WeaponKind::Blaster).unwrap().trigger();
+    }
+
+    #[test]
+    fn second_run_starts_from_fresh_state() {
+        let mut app = headless_app();
+        let mut last_cosmetic = None;
+        for run in 0..2 {
+            app.update();
+            assert_eq!(
+                *app.world().resource::<State<GameState>>().get(),
+                GameState::Playing
+            );
+            let world = app.world_mut();
+            assert_eq!(world.resource::<Score>().0, 0, "run {run}");
+            assert_eq!(world.resource::<Gold>().0, 0, "run {run}");
+            assert_eq!(world.resource::<Curse>().0, 0, "run {run}");
+            let leftovers = world
+                .query_filtered::<(), (With<InGameEntity>, Without<Player>)>()
+                .iter(world)
+                .count();
+            assert_eq!(leftovers, 0, "run {run}");
+
+            let mut enemy_time = SystemState::<EnemyTime>::new(world);
+            let time = enemy_time.get(world);
+            assert_eq!(time.elapsed_secs(), 0.0, "run {run}");
+            assert_eq!(time.scale(), 1.0, "run {run}");
+
+            assert_eq!(
+                world.resource::<PlayerStats>().bullet_damage,
+                1,
+                "run {run}"
+            );
+            let experience = world.resource::<levelup::Experience>();
+            assert_eq!(
+                (experience.xp, experience.level, experience.pending),
+                (0.0, 1, 0),
+                "run {run}"
+            );
+            let pool = world.resource::<upgrades::UpgradePool>();
+            assert_eq!(pool.banishes_left(), upgrades::BANISH_USES, "run {run}");
+            assert!(!pool.is_holding(), "run {run}");
+            let clover = upgrades::find_upgrade("clover").unwrap();
+            assert_eq!(pool.stacks(clover), 0, "run {run}");
+            let melee = world.resource::<melee::MeleeCooldown>();
+            assert_eq!(melee.readiness(), 1.0, "run {run}");
+            assert_eq!(world.resource::<RunRules>().enemy_speed, 1.0, "run {run}");
+
+            let slots = world
+                .query_filtered::<&WeaponSlots, With<Player>>()
+                .single(world)
+                .unwrap();
+            let weapons: Vec<_> = slots
+                .iter()
+                .map(|weapon| (weapon.kind, weapon.level, weapon.is_ready()))
+                .collect();
+            assert_eq!(weapons, [(WeaponKind::Blaster, 1, true)], "run {run}");
+
+            assert!(miss_shield(&mut app).is_empty(), "run {run}");
+            // A spare left over from the last run was despawned with it
+            let cosmetic = pool_a_cosmetic(&mut app);
+            assert_ne!(Some(cosmetic), last_cosmetic, "run {run}");
+            last_cosmetic = Some(cosmetic);
+
+            play_a_run(app.world_mut());
+            app.world_mut()
+                .resource_mut::<NextState<GameState>>()
+                .set(GameState::GameOver);
+            app.update();
+            app.world_mut()
+                .resource_mut::<NextState<GameState>>()
+                .set(GameState::Resetting);
+            app.update();
+        }
+    }
+
+    #[test]
+    fn aoe_falloff_is_full_at_the_center() {
+        assert_eq!(aoe_falloff(0.0, 80.0, AOE_EDGE_SHARE), Some(1.0));
+    }
+
+    #[test]
+    fn aoe_falloff_reaches_the_edge_share_at_the_radius() {
+        let share = aoe_falloff(80.0, 80.0, AOE_EDGE_SHARE).unwrap();
+        assert!((share - AOE_EDGE_SHARE).abs() < 1e-6);
+        let halfway = aoe_falloff(40.0, 80.0, AOE_EDGE_SHARE).unwrap();
+        assert!((halfway - (1.0 + AOE_EDGE_SHARE) / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn aoe_falloff_misses_beyond_the_radius() {
+        assert_eq!(aoe_falloff(80.1, 80.0, AOE_EDGE_SHARE), None);
+        assert_eq!(aoe_falloff(1.0, 0.0, AOE_EDGE_SHARE), None);
+    }
+
+    #[test]
+    fn combined_speed_multiplies_every_source() {
+        let speed = combined_speed(PLAYER_BASE_SPEED, 1.5, 0.2, true);
+        let expected = PLAYER_BASE_SPEED * 1.5 * 0.8 * FOCUS_SPEED;
+        assert!((speed - expected).abs() < 1e-3);
+        assert_eq!(
+            combined_speed(PLAYER_BASE_SPEED, 1.0, 0.0, false),
+            PLAYER_BASE_SPEED
+        );
+    }
+
+    #[test]
+    fn slows_stack_up_to_the_cap() {
+        assert!((stacked_slow([0.1, 0.2]) - 0.3).abs() < 1e-6);
+        assert_eq!(stacked_slow([0.3, 0.3, 0.3]), MAX_PLAYER_SLOW);
+        let slowest = combined_speed(PLAYER_BASE_SPEED, 1.0, stacked_slow([0.9; 4]), true);
+        assert_eq!(
+            slowest,
+            PLAYER_BASE_SPEED * (1.0 - MAX_PLAYER_SLOW) * FOCUS_SPEED
+        );
+    }
+
+    #[test]
+    fn debuffs_floor_at_a_tenth_of_the_stat() {
+        let mut buffs = Buffs::default();
+        buffs.add(BuffStat::MoveSpeed, -0.5, 5.0);
+        buffs.add(BuffStat::MoveSpeed, -0.75, 5.0);
+        buffs.add(BuffStat::FireRate, 1.0, 5.0);
+        let multiplier = buffs.multiplier(BuffStat::MoveSpeed);
+        assert_eq!(multiplier, 0.1);
+        let floored = combined_speed(PLAYER_BASE_SPEED, multiplier, 0.0, false);
+        assert!((floored - PLAYER_BASE_SPEED * 0.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn a_half_second_hitch_moves_bullets_one_capped_frame() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .insert_state(GameState::Playing)
+            .add_sub_state::<PlayState>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                500,
+            )))
+            .init_resource::<GameClock>()
+            .init_resource::<GameTimeScale>()
+            .init_resource::<RunStats>()
+            .insert_resource(BulletPool::new(0))
+            .add_systems(First, advance_game_clock.after(bevy::time::TimeSystems))
+            .add_systems(Update, bullet_movement_system);
+        let speed = PlayerStats::default().bullet_speed;
+        let bullet = app
+            .world_mut()
+            .spawn((
+                Transform::default(),
+                Visibility::default(),
+                Bullet {
+                    direction: Vec2::X,
+                    speed,
+                    active: true,
+                    hit: false,
+                    weapon: WeaponKind::Blaster,
+                    travelled: 0.0,
+                },
+            ))
+            .id();
+
+        let max_step = speed * MAX_FRAME_SECS;
+        // An enemy's hitbox plus the bullet's own span, per `MAX_FRAME_SECS`
+        assert!(max_step < 30.0);
+        let mut last = 0.0;
+        for _ in 0..4 {
+            app.update();
+            let x = app.world().get::<Transform>(bullet).unwrap().translation.x;
+            let step = x - last;
+            assert!(step <= max_step + 1e-3, "moved {step} in one frame");
+            last = x;
+        }
+        assert!(last > 0.0);
+        let clock = app.world().resource::<GameClock>();
+        assert_eq!(clock.delta, Duration::from_secs_f32(MAX_FRAME_SECS));
+    }
+
+    fn angles(dir: Vec2, count: u32) -> Vec<f32> {
+        fan_directions(dir, count)
+            .map(|d| dir.angle_to(d).to_degrees())
+            .collect()
+    }
+
+    fn assert_angles(actual: Vec<f32>, expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-3, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn fan_directions_center_on_the_aim() {
+        let aim = Vec2::new(1.0, 1.0).normalize();
+        assert_angles(angles(aim, 1), &[0.0]);
+        let half = FAN_SPACING_DEGREES / 2.0;
+        assert_angles(angles(aim, 2), &[-half, half]);
+        let full = FAN_SPACING_DEGREES;
+        assert_angles(angles(aim, 3), &[-full, 0.0, full]);
+    }
+
+    #[test]
+    fn fan_directions_always_fire_at_least_one_unit_shot() {
+        assert_angles(angles(Vec2::Y, 0), &[0.0]);
+        assert!(fan_directions(Vec2::X, 5).all(|d| (d.length() - 1.0).abs() < 1e-5));
+    }
+
+    fn spawn_context(players: Vec<Vec2>) -> SpawnContext {
+        SpawnContext {
+            players,
+            bias: None,
+        }
+    }
+
+    #[test]
+    fn spawns_too_close_to_any_player_are_invalid() {
+        let ctx = spawn_context(vec![Vec2::ZERO, Vec2::new(400.0, 0.0)]);
+        assert!(is_valid_spawn(Vec2::new(0.0, MIN_SPAWN_DISTANCE), &ctx));
+        assert!(!is_valid_spawn(
+            Vec2::new(0.0, MIN_SPAWN_DISTANCE - 1.0),
+            &ctx
+        ));
+        assert!(!is_valid_spawn(Vec2::new(350.0, 0.0), &ctx));
+    }
+
+    #[test]
+    fn rolled_spawns_keep_clear_of_a_player_on_the_ring() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let ctx = spawn_context(vec![Vec2::ZERO, Vec2::new(400.0, 0.0)]);
+        for _ in 0..500 {
+            if let Some(position) = roll_spawn_position(&mut rng, Vec2::ZERO, &ctx) {
+                assert!(is_valid_spawn(position, &ctx));
+            }
+        }
+    }
+
+    #[test]
+    fn a_fully_blocked_ring_skips_the_spawn() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let ring = (0..64)
+            .map(|i| Vec2::from_angle(i as f32 / 64.0 * std::f32::consts::TAU) * 400.0)
+            .collect();
+        let ctx = spawn_context(ring);
+        assert_eq!(roll_spawn_position(&mut rng, Vec2::ZERO, &ctx), None);
+    }
+
+    /// Queues the game over ahead of the combat chain on the next frame, as
+    /// a run timer or Save & Quit would.
+    #[derive(Resource, Default)]
+    struct EndRunNextFrame(bool);
+
+    fn queue_game_over(mut end: ResMut<EndRunNextFrame>, mut next: ResMut<NextState<GameState>>) {
+        if std::mem::take(&mut end.0) {
+            next.set(GameState::GameOver);
+        }
+    }
+
+    /// The real combat chain, collision to score, on a field of its own.
+    fn combat_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .insert_state(GameState::Playing)
+            .add_sub_state::<PlayState>()
+            .insert_resource(Score(0))
+            .insert_resource(BulletPool::new(0))
+            .insert_resource(DifficultyConfig::default())
+            .insert_resource(PerformancePreset::High.budget())
+            .insert_resource(FontAssets {
+                body: default(),
+                display: default(),
+            })
+            .init_resource::<PlayerStats>()
+            .init_resource::<LifestealAccumulator>()
+            .init_resource::<GameClock>()
+            .init_resource::<GameTimeScale>()
+            .init_resource::<RunStats>()
+            .init_resource::<PendingDespawns>()
+            .init_resource::<CosmeticPool>()
+            .init_resource::<EndRunNextFrame>()
+            .add_message::<DamageEvent>()
+            .add_message::<DeathEvent>()
+            .add_message::<EnemyKilledEvent>()
+            .add_message::<StatusPopup>()
+            .add_message::<LogMessage>()
+            .add_plugins(CombatPlugin)
+            .add_systems(Update, queue_game_over.before(hitbox_collision_system));
+        app
+    }
+
+    /// A one-hit enemy at the origin, and a player-side hit parked out of
+    /// reach until `strike` brings it in.
+    fn spawn_duel(app: &mut App) -> Entity {
+        let world = app.world_mut();
+        world.spawn((
+            Enemy {
+                kind: EnemyType::Basic,
+            },
+            Health::new(1),
+            Team::Enemy,
+            Hitbox::new(10.0),
+            Transform::default(),
+        ));
+        world
+            .spawn((
+                Team::Player,
+                Hitbox::new(5.0),
+                ContactDamage(1),
+                Transform::from_xyz(1000.0, 0.0, 0.0),
+            ))
+            .id()
+    }
+
+    fn strike(app: &mut App, attacker: Entity) {
+        app.world_mut()
+            .get_mut::<Transform>(attacker)
+            .unwrap()
+            .translation = Vec3::ZERO;
+    }
+
+    fn end_run_next_frame(app: &mut App) {
+        app.world_mut().resource_mut::<EndRunNextFrame>().0 = true;
+    }
+
+    #[test]
+    fn a_kill_the_frame_before_the_game_over_scores() {
+        let mut app = combat_app();
+        let attacker = spawn_duel(&mut app);
+        app.update();
+        strike(&mut app, attacker);
+        app.update();
+        let points = enemy_points(EnemyType::Basic);
+        assert_eq!(app.world().resource::<Score>().0, points);
+
+        end_run_next_frame(&mut app);
+        app.update();
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::GameOver
+        );
+        assert_eq!(app.world().resource::<Score>().0, points);
+    }
+
+    #[test]
+    fn a_kill_on_the_frame_the_game_over_is_queued_scores_nothing() {
+        let mut app = combat_app();
+        let attacker = spawn_duel(&mut app);
+        app.update();
+        end_run_next_frame(&mut app);
+        strike(&mut app, attacker);
+        app.update();
+        assert_eq!(app.world().resource::<Score>().0, 0);
+    }
+
+    #[test]
+    fn a_kill_the_frame_after_the_game_over_scores_nothing() {
+        let mut app = combat_app();
+        let attacker = spawn_duel(&mut app);
+        app.update();
+        end_run_next_frame(&mut app);
+        app.update();
+        strike(&mut app, attacker);
+        app.update();
+        assert_eq!(app.world().resource::<Score>().0, 0);
+    }
+
+    #[test]
+    fn tank_glyph_shows_one_letter_per_hit_left() {
+        assert_eq!(tank_glyph(1), "E");
+        assert_eq!(tank_glyph(2), "EE");
+        assert_eq!(tank_glyph(3), "EEE");
+    }
+
+    #[test]
+    fn tank_glyph_caps_at_three_and_a_plus() {
+        assert_eq!(tank_glyph(4), "EEE+");
+        assert_eq!(tank_glyph(40), "EEE+");
+    }
+
+    #[test]
+    fn a_dead_tank_shows_nothing() {
+        assert_eq!(tank_glyph(0), "");
+        assert_eq!(tank_glyph(-5), "");
+    }
+
+    fn advance(clock: &mut GameClock, scale: &mut GameTimeScale, secs: f32, simulating: bool) {
+        clock.advance(Duration::from_secs_f32(secs), simulating, scale);
+    }
+
+    #[test]
+    fn the_clock_follows_real_time_while_simulating() {
+        let (mut clock, mut scale) = (GameClock::default(), GameTimeScale::default());
+        advance(&mut clock, &mut scale, 0.02, true);
+        advance(&mut clock, &mut scale, 0.01, true);
+        assert!((clock.elapsed.as_secs_f32() - 0.03).abs() < 1e-6);
+        assert!((clock.delta.as_secs_f32() - 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn the_clock_stands_still_off_the_field() {
+        let (mut clock, mut scale) = (GameClock::default(), GameTimeScale::default());
+        advance(&mut clock, &mut scale, 0.02, false);
+        assert_eq!(clock.delta, Duration::ZERO);
+        assert_eq!(clock.elapsed, Duration::ZERO);
+    }
+
+    #[test]
+    fn a_long_frame_counts_as_one_capped_frame() {
+        let (mut clock, mut scale) = (GameClock::default(), GameTimeScale::default());
+        advance(&mut clock, &mut scale, 2.0, true);
+        assert!((clock.delta.as_secs_f32() - MAX_FRAME_SECS).abs() < 1e-6);
+    }
+
+    #[test]
+    fn the_time_scale_applies_after_the_cap() {
+        let mut clock = GameClock::default();
+        let mut scale = GameTimeScale {
+            scale: 0.5,
+            ..default()
+        };
+        advance(&mut clock, &mut scale, 2.0, true);
+        assert!((clock.delta.as_secs_f32() - MAX_FRAME_SECS / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn a_frozen_clock_moves_one_fixed_step_per_request() {
+        let mut clock = GameClock::default();
+        let mut scale = GameTimeScale {
+            frozen: true,
+            ..default()
+        };
+        advance(&mut clock, &mut scale, 0.02, true);
+        assert_eq!(clock.delta, Duration::ZERO);
+
+        scale.step = true;
+        advance(&mut clock, &mut scale, 0.02, true);
+        assert!(clock.stepped && !scale.step);
+        assert!((clock.delta.as_secs_f32() - FRAME_STEP_SECS).abs() < 1e-6);
+
+        advance(&mut clock, &mut scale, 0.02, true);
+        assert!(!clock.stepped);
+        assert_eq!(clock.delta, Duration::ZERO);
+    }
+
+    /// How many of `rolls` spawn angles land within the cone ahead of +Y.
+    fn ahead_share(bias: Option<SpawnBias>, rolls: u32) -> f32 {
+        let mut rng = StdRng::seed_from_u64(11);
+        let ahead = (0..rolls)
+            .filter(|_| {
+                let angle = roll_spawn_angle(&mut rng, bias.as_ref());
+                Vec2::from_angle(angle).angle_to(Vec2::Y).abs() < HEADING_CONE
+            })
+            .count();
+        ahead as f32 / rolls as f32
+    }
+
+    fn bias(share: f32) -> Option<SpawnBias> {
+        Some(SpawnBias {
+            heading: Vec2::Y,
+            share,
+        })
+    }
+
+    #[test]
+    fn unbiased_spawns_put_a_third_ahead() {
+        let share = ahead_share(None, 10_000);
+        assert!((share - 1.0 / 3.0).abs() < 0.02, "{share}");
+    }
+
+    #[test]
+    fn biased_spawns_put_their_share_ahead() {
+        let share = ahead_share(bias(0.6), 10_000);
+        assert!((share - 0.6).abs() < 0.02, "{share}");
+    }
+
+    #[test]
+    fn the_bias_share_is_all_or_nothing_at_its_ends() {
+        assert_eq!(ahead_share(bias(1.0), 1_000), 1.0);
+        assert_eq!(ahead_share(bias(0.0), 1_000), 0.0);
+        assert_eq!(ahead_share(bias(4.0), 1_000), 1.0);
+    }
+
+    #[test]
+    fn damage_scales_by_the_type_multiple_and_the_buff() {
+        assert_eq!(scaled_damage(3, 1.0, 1.0), 3);
+        assert_eq!(scaled_damage(3, 2.0, 1.0), 6);
+        assert_eq!(scaled_damage(3, 2.0, 1.5), 9);
+    }
+
+    #[test]
+    fn a_resisted_hit_still_lands_for_one() {
+        assert_eq!(scaled_damage(1, 0.5, 1.0), 1);
+        assert_eq!(scaled_damage(1, 0.1, 0.5), 1);
+        assert_eq!(scaled_damage(4, 0.5, 1.0), 2);
+    }
+
+    #[test]
+    fn only_a_zero_multiple_is_immune() {
+        assert_eq!(scaled_damage(100, 0.0, 2.0), 0);
+    }
+
+    /// A live player bullet sitting on an enemy shot, with parrying taken.
+    fn spawn_parry(app: &mut App) {
+        let world = app.world_mut();
+        world.resource_mut::<PlayerStats>().bullet_parry = true;
+        world.spawn((
+            Bullet {
+                direction: Vec2::X,
+                speed: 0.0,
+                active: true,
+                hit: false,
+                weapon: WeaponKind::Blaster,
+                travelled: 0.0,
+            },
+            Visibility::default(),
+            Hitbox::new(5.0),
+            Transform::default(),
+        ));
+        world.spawn((
+            EnemyProjectile {
+                velocity: Vec2::ZERO,
+            },
+            Hitbox::new(6.0),
+            Transform::default(),
+        ));
+    }
+
+    #[test]
+    fn a_parry_scores_a_point_while_the_run_goes_on() {
+        let mut app = combat_app();
+        spawn_parry(&mut app);
+        app.update();
+        assert_eq!(app.world().resource::<Score>().0, 1);
+        let world = app.world_mut();
+        assert_eq!(world.query::<&Dead>().iter(world).count(), 1);
+    }
+
+    #[test]
+    fn a_parry_as_the_run_ends_scores_nothing() {
+        let mut app = combat_app();
+        spawn_parry(&mut app);
+        end_run_next_frame(&mut app);
+        app.update();
+        assert_eq!(app.world().resource::<Score>().0, 0);
+    }
+
+    /// Pushes a fresh target 100 units, as `kind` if it is an enemy; how far
+    /// it will travel and what popups went up.
+    fn knock(
+        difficulty: DifficultyConfig,
+        kind: Option<EnemyType>,
+    ) -> (Option<f32>, Vec<&'static str>) {
+        let mut world = World::new();
+        world.insert_resource(difficulty);
+        world.init_resource::<Messages<StatusPopup>>();
+        let target = world.spawn_empty().id();
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      mut popups: MessageWriter<StatusPopup>,
+                      difficulty: Res<DifficultyConfig>| {
+                    let enemy = kind.map(|kind| Enemy { kind });
+                    apply_knockback_to(
+                        &mut commands,
+                        &mut popups,
+                        &difficulty,
+                        target,
+                        Vec2::ZERO,
+                        enemy.as_ref(),
+                        Knockback::with_distance(Vec2::X, 100.0),
+                    );
+                },
+            )
+            .unwrap();
+        let distance = world.get::<Knockback>(target).map(|k| k.distance);
+        let popups = world
+            .resource::<Messages<StatusPopup>>()
+            .iter_current_update_messages()
+            .map(|popup| popup.text)
+            .collect();
+        (distance, popups)
+    }
+
+    fn resisting(kind: EnemyType, resistance: f32) -> DifficultyConfig {
+        let mut difficulty = DifficultyConfig::default();
+        for tuning in &mut difficulty.enemies {
+            if tuning.kind == kind {
+                tuning.knockback_resistance = resistance;
+            }
+        }
+        difficulty
+    }
+
+    #[test]
+    fn partial_resistance_shortens_the_push() {
+        let difficulty = resisting(EnemyType::Tank, 0.25);
+        assert_eq!(
+            knock(difficulty, Some(EnemyType::Tank)),
+            (Some(75.0), vec![])
+        );
+    }
+
+    #[test]
+    fn full_resistance_leaves_the_target_in_place_and_says_so() {
+        let difficulty = resisting(EnemyType::Tank, 1.0);
+        assert_eq!(
+            knock(difficulty, Some(EnemyType::Tank)),
+            (None, vec!["immune"])
+        );
+    }
+
+    #[test]
+    fn bosses_shrug_off_knockback_by_default_and_others_take_it_all() {
+        let difficulty = DifficultyConfig::default;
+        assert_eq!(
+            knock(difficulty(), Some(EnemyType::Boss)),
+            (None, vec!["immune"])
+        );
+        assert_eq!(
+            knock(difficulty(), Some(EnemyType::Basic)),
+            (Some(100.0), vec![])
+        );
+    }
+
+    #[test]
+    fn targets_that_are_not_enemies_ignore_enemy_resistance() {
+        let mut difficulty = DifficultyConfig::default();
+        for tuning in &mut difficulty.enemies {
+            tuning.knockback_resistance = 1.0;
+        }
+        assert_eq!(knock(difficulty, None), (Some(100.0), vec![]));
+    }
+
+    /// A live bullet at `x` flying right at 1000 units a second.
+    fn spawn_flying_bullet(world: &mut World, x: f32, hit: bool) -> Entity {
+        world
+            .spawn((
+                Transform::from_xyz(x, 0.0, 0.0),
+                Visibility::default(),
+                Bullet {
+                    direction: Vec2::X,
+                    speed: 1000.0,
+                    active: true,
+                    hit,
+                    // Whatever fired it is charged with the miss
+                    weapon: WeaponKind::Aura,
+                    travelled: 0.0,
+                },
+            ))
+            .id()
+    }
+
+    fn fly_for_a_second(world: &mut World) {
+        world.resource_mut::<GameClock>().delta = Duration::from_secs(1);
+        world.run_system_once(bullet_movement_system).unwrap();
+    }
+
+    fn misses(world: &mut World, weapon: WeaponKind) -> u32 {
+        world.resource_mut::<RunStats>().weapon_mut(weapon).misses
+    }
+
+    fn bullet_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<GameClock>();
+        world.init_resource::<RunStats>();
+        world.insert_resource(BulletPool::new(0));
+        world
+    }
+
+    #[test]
+    fn a_bullet_expires_by_its_range_not_its_distance_from_the_origin() {
+        let mut world = bullet_world();
+        let far_out = spawn_flying_bullet(&mut world, 2.0 * BULLET_RANGE, false);
+        let seconds_in_range = (BULLET_RANGE / 1000.0) as usize;
+        for _ in 0..seconds_in_range {
+            fly_for_a_second(&mut world);
+            assert!(world.get_entity(far_out).is_ok());
+            assert_eq!(misses(&mut world, WeaponKind::Aura), 0);
+        }
+        fly_for_a_second(&mut world);
+        assert!(world.get_entity(far_out).is_err());
+        assert_eq!(misses(&mut world, WeaponKind::Aura), 1);
+        assert_eq!(misses(&mut world, WeaponKind::Blaster), 0);
+    }
+
+    #[test]
+    fn a_bullet_that_hit_something_expires_without_a_miss() {
+        let mut world = bullet_world();
+        let bullet = spawn_flying_bullet(&mut world, 0.0, true);
+        while world.get_entity(bullet).is_ok() {
+            fly_for_a_second(&mut world);
+        }
+        assert_eq!(misses(&mut world, WeaponKind::Aura), 0);
+    }
+
+    /// `headless_app` a frame into its run, resolving damage every frame.
+    /// Gameplay time only moves as `frame` says.
+    fn damage_app() -> App {
+        let mut app = headless_app();
+        app.add_systems(Update, (tick_invulnerability, apply_damage_system).chain());
+        app.update();
+        app
+    }
+
+    fn spawn_target(app: &mut App, team: Team, health: i32) -> Entity {
+        app.world_mut()
+            .spawn((Health::new(health), team, Transform::default()))
+            .id()
+    }
+
+    fn hit(app: &mut App, target: Entity, source_team: Team, kind: DamageKind) {
+        app.world_mut().write_message(DamageEvent {
+            target,
+            amount: 1,
+            source_team,
+            kind,
+            damage_type: DamageType::Physical,
+            origin: Vec2::new(-50.0, 0.0),
+            weapon: None,
+        });
+    }
+
+    /// One frame that advances gameplay by `secs`.
+    fn frame(app: &mut App, secs: f32) {
+        app.world_mut().resource_mut::<GameClock>().delta = Duration::from_secs_f32(secs);
+        app.update();
+    }
+
+    fn health(app: &App, entity: Entity) -> i32 {
+        app.world().get::<Health>(entity).unwrap().current
+    }
+
+    #[test]
+    fn i_frames_block_repeat_hits_until_the_grace_runs_out() {
+        let mut app = damage_app();
+        let player = spawn_target(&mut app, Team::Player, 5);
+
+        // Two touches on one frame count once
+        hit(&mut app, player, Team::Enemy, DamageKind::Contact);
+        hit(&mut app, player, Team::Enemy, DamageKind::Contact);
+        frame(&mut app, 0.0);
+        assert_eq!(health(&app, player), 4);
+        assert!(app.world().get::<Invulnerable>(player).is_some());
+
+        hit(&mut app, player, Team::Enemy, DamageKind::Contact);
+        frame(&mut app, HIT_GRACE_SECS * 0.9);
+        assert_eq!(health(&app, player), 4);
+
+        // The grace ends this frame, before the hit lands
+        hit(&mut app, player, Team::Enemy, DamageKind::Contact);
+        frame(&mut app, HIT_GRACE_SECS * 0.2);
+        assert_eq!(health(&app, player), 3);
+    }
+
+    #[test]
+    fn only_contact_hits_grant_i_frames() {
+        let mut app = damage_app();
+        let player = spawn_target(&mut app, Team::Player, 5);
+        for _ in 0..3 {
+            hit(&mut app, player, Team::Enemy, DamageKind::Projectile);
+            frame(&mut app, 0.0);
+        }
+        assert_eq!(health(&app, player), 2);
+        assert!(app.world().get::<Invulnerable>(player).is_none());
+    }
+
+    /// Drains the deaths sent so far, as the buffers only swap on fixed steps
+    fn deaths(app: &mut App) -> Vec<(Entity, DamageKind)> {
+        app.world_mut()
+            .resource_mut::<Messages<DeathEvent>>()
+            .drain()
+            .map(|death| (death.entity, death.kind))
+            .collect()
+    }
+
+    #[test]
+    fn teams_only_hurt_their_opponents() {
+        let mut app = damage_app();
+        let player = spawn_target(&mut app, Team::Player, 3);
+        let enemy = spawn_target(&mut app, Team::Enemy, 3);
+        let neutral = spawn_target(&mut app, Team::Neutral, 3);
+        for target in [player, enemy, neutral] {
+            for team in [Team::Player, Team::Enemy] {
+                hit(&mut app, target, team, DamageKind::Projectile);
+            }
+        }
+        frame(&mut app, 0.0);
+        assert_eq!(health(&app, player), 2);
+        assert_eq!(health(&app, enemy), 2);
+        assert_eq!(health(&app, neutral), 2);
+        assert!(deaths(&mut app).is_empty());
+    }
+
+    #[test]
+    fn a_lethal_hit_sends_one_death_with_what_landed_it() {
+        let mut app = damage_app();
+        let enemy = spawn_target(&mut app, Team::Enemy, 2);
+        hit(&mut app, enemy, Team::Player, DamageKind::Melee);
+        frame(&mut app, 0.0);
+        assert_eq!(health(&app, enemy), 1);
+        assert!(deaths(&mut app).is_empty());
+
+        // Anything after the killing blow finds it already dead
+        hit(&mut app, enemy, Team::Player, DamageKind::Projectile);
+        hit(&mut app, enemy, Team::Player, DamageKind::Melee);
+        frame(&mut app, 0.0);
+        assert_eq!(health(&app, enemy), 0);
+        assert_eq!(deaths(&mut app), [(enemy, DamageKind::Projectile)]);
+
+        hit(&mut app, enemy, Team::Player, DamageKind::Projectile);
+        frame(&mut app, 0.0);
+        assert_eq!(health(&app, enemy), 0);
+        assert!(deaths(&mut app).is_empty());
+    }
+
+    #[test]
+    fn hits_on_entities_without_health_are_dropped() {
+        let mut app = damage_app();
+        let scenery = app
+            .world_mut()
+            .spawn((Team::Enemy, Transform::default()))
+            .id();
+        hit(&mut app, scenery, Team::Player, DamageKind::Projectile);
+        frame(&mut app, 0.0);
+        assert!(deaths(&mut app).is_empty());
+    }
+}
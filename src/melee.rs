@@ -0,0 +1,195 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use bevy::prelude::*;
+
+use crate::input::PlayerInputState;
+use crate::{
+    DamageEvent, DamageKind, DamageType, Downed, Enemy, GameState, GameTime, Hitbox, InGameEntity,
+    PlayState, Player, ResetSet, Team, gameplay_unfrozen,
+};
+
+const MELEE_RANGE: f32 = 100.0;
+/// Either side of the facing, so a quarter circle in all.
+const MELEE_HALF_ANGLE: f32 = FRAC_PI_4;
+const MELEE_DAMAGE: i32 = 3;
+const MELEE_COOLDOWN_SECS: f32 = 0.8;
+const SWIPE_SECS: f32 = 0.2;
+const SWIPE_COLOR: Color = Color::srgb(1.0, 0.95, 0.8);
+
+pub struct MeleePlugin;
+
+impl Plugin for MeleePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeleeCooldown>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                reset_melee_cooldown.in_set(ResetSet),
+            )
+            .add_systems(
+                Update,
+                (swing_melee, fade_swipes)
+                    .run_if(in_state(PlayState::Running).and(gameplay_unfrozen)),
+            )
+            .add_systems(Update, draw_swipes);
+    }
+}
+
+/// Time left before the next swipe; zero means ready.
+#[derive(Resource, Default)]
+pub struct MeleeCooldown(f32);
+
+impl MeleeCooldown {
+    /// How far the swipe is back toward ready, from 0 to 1.
+    pub fn readiness(&self) -> f32 {
+        1.0 - self.0 / MELEE_COOLDOWN_SECS
+    }
+
+    /// A swipe just went out.
+    pub fn start(&mut self) {
+        self.0 = MELEE_COOLDOWN_SECS;
+    }
+}
+
+/// The arc left behind by a swipe for a moment, drawn fading out.
+#[derive(Component)]
+struct MeleeSwipe {
+    origin: Vec2,
+    facing: Vec2,
+    timer: Timer,
+}
+
+fn reset_melee_cooldown(mut cooldown: ResMut<MeleeCooldown>) {
+    cooldown.0 = 0.0;
+}
+
+/// Whether `point` lies within `range` of `origin` and no more than
+/// `half_angle` radians either side of `facing`. Both edges count as inside.
+fn in_arc(origin: Vec2, facing: Vec2, half_angle: f32, range: f32, point: Vec2) -> bool {
+    let offset = point - origin;
+    if offset.length_squared() > range * range {
+        return false;
+    }
+    // Right on top of the player is always a hit, whichever way it faces
+    if offset == Vec2::ZERO {
+        return true;
+    }
+    facing.angle_to(offset).abs() <= half_angle
+}
+
+/// Right click swipes toward the cursor, hitting everything in the arc at
+/// once. Knockback and kills are left to the damage pass, like any other hit.
+fn swing_melee(
+    mut commands: Commands,
+    time: GameTime,
+    input: Res<PlayerInputState>,
+    mut cooldown: ResMut<MeleeCooldown>,
+    mut damage_events: MessageWriter<DamageEvent>,
+    player: Query<&Transform, (With<Player>, Without<Downed>)>,
+    enemies: Query<(Entity, &Transform, &Hitbox), With<Enemy>>,
+) {
+    cooldown.0 = (cooldown.0 - time.delta_secs()).max(0.0);
+    if !input.melee || cooldown.0 > 0.0 {
+        return;
+    }
+    let (Ok(player), Some(aim)) = (player.single(), input.aim) else {
+        return;
+    };
+    let origin = player.translation.truncate();
+    let Some(facing) = (aim - origin).try_normalize() else {
+        return;
+    };
+    cooldown.start();
+
+    for (entity, tf, hitbox) in &enemies {
+        if hitbox.intangible {
+            continue;
+        }
+        // Anything the blade's edge grazes counts, not just enemy centres
+        let reach = MELEE_RANGE + hitbox.radius;
+        if in_arc(
+            origin,
+            facing,
+            MELEE_HALF_ANGLE,
+            reach,
+            tf.translation.truncate(),
+        ) {
+            damage_events.write(DamageEvent {
+                target: entity,
+                amount: MELEE_DAMAGE,
+                source_team: Team::Player,
+                kind: DamageKind::Melee,
+                damage_type: DamageType::Physical,
+                origin,
+                weapon: None,
+            });
+        }
+    }
+
+    commands.spawn((
+        MeleeSwipe {
+            origin,
+            facing,
+            timer: Timer::from_seconds(SWIPE_SECS, TimerMode::Once),
+        },
+        InGameEntity,
+    ));
+}
+
+fn fade_swipes(
+    mut commands: Commands,
+    time: GameTime,
+    mut swipes: Query<(Entity, &mut MeleeSwipe)>,
+) {
+    for (entity, mut swipe) in &mut swipes {
+        if swipe.timer.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn draw_swipes(mut gizmos: Gizmos, swipes: Query<&MeleeSwipe>) {
+    for swipe in &swipes {
+        let color = SWIPE_COLOR.with_alpha(swipe.timer.fraction_remaining());
+        // Gizmo arcs start from +Y and sweep counterclockwise; turn the
+        // start edge to one side of the facing so the arc is centred on it
+        let start = swipe.facing.to_angle() - FRAC_PI_2 - MELEE_HALF_ANGLE;
+        let isometry = Isometry2d::new(swipe.origin, Rot2::radians(start));
+        gizmos.arc_2d(isometry, MELEE_HALF_ANGLE * 2.0, MELEE_RANGE, color);
+        gizmos.arc_2d(isometry, MELEE_HALF_ANGLE * 2.0, MELEE_RANGE * 0.6, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+
+    fn hit(point: Vec2) -> bool {
+        in_arc(Vec2::ZERO, Vec2::X, FRAC_PI_4, 50.0, point)
+    }
+
+    #[test]
+    fn in_arc_covers_the_facing_wedge() {
+        assert!(hit(Vec2::new(30.0, 0.0)));
+        assert!(hit(Vec2::from_angle(FRAC_PI_4 - 0.01) * 40.0));
+        assert!(hit(Vec2::from_angle(-FRAC_PI_4 + 0.01) * 40.0));
+        assert!(!hit(Vec2::from_angle(FRAC_PI_4 + 0.01) * 40.0));
+        assert!(!hit(Vec2::new(-30.0, 0.0)));
+    }
+
+    #[test]
+    fn in_arc_counts_the_range_edge_and_the_origin() {
+        assert!(hit(Vec2::new(50.0, 0.0)));
+        assert!(!hit(Vec2::new(50.1, 0.0)));
+        assert!(hit(Vec2::ZERO));
+        assert!(in_arc(Vec2::ZERO, -Vec2::Y, 0.1, 50.0, Vec2::ZERO));
+    }
+
+    #[test]
+    fn melee_readiness_climbs_back_as_the_cooldown_runs_down() {
+        assert_eq!(MeleeCooldown(MELEE_COOLDOWN_SECS).readiness(), 0.0);
+        assert_eq!(MeleeCooldown(MELEE_COOLDOWN_SECS / 2.0).readiness(), 0.5);
+        assert_eq!(MeleeCooldown::default().readiness(), 1.0);
+    }
+}
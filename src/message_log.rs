@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::{GameState, ResetSet};
+
+/// Messages kept for scrolling back through; older ones drop off.
+const LOG_CAPACITY: usize = 50;
+/// Lines visible at once. The panel owns exactly this many text entities.
+const VISIBLE_LINES: usize = 12;
+
+pub struct MessageLogPlugin;
+
+impl Plugin for MessageLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<LogMessage>()
+            .init_resource::<MessageLog>()
+            .add_systems(Startup, spawn_log_panel)
+            .add_systems(OnEnter(GameState::Resetting), clear_log.in_set(ResetSet))
+            .add_systems(
+                Update,
+                (collect_log_messages, log_input, update_log_panel).chain(),
+            );
+    }
+}
+
+/// A gameplay breadcrumb for the in-game log; any system may write one.
+#[derive(Message)]
+pub struct LogMessage(pub String);
+
+#[derive(Resource, Default)]
+struct MessageLog {
+    lines: VecDeque<String>,
+    /// Lines scrolled back from the newest.
+    scroll: usize,
+    open: bool,
+    /// Set whenever what the panel should show has changed.
+    dirty: bool,
+}
+
+#[derive(Component)]
+struct LogPanel;
+
+/// Row `0` is the top of the panel.
+#[derive(Component)]
+struct LogLine(usize);
+
+fn spawn_log_panel(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(80.0),
+                left: Val::Px(10.0),
+                width: Val::Px(360.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            Visibility::Hidden,
+            LogPanel,
+        ))
+        .with_children(|panel| {
+            for row in 0..VISIBLE_LINES {
+                panel.spawn((
+                    Text::new(""),
+                    fonts.body(14.0),
+                    TextColor(Color::srgb(0.85, 0.85, 0.85)),
+                    LogLine(row),
+                ));
+            }
+        });
+}
+
+fn clear_log(mut log: ResMut<MessageLog>) {
+    log.lines.clear();
+    log.scroll = 0;
+    log.dirty = true;
+}
+
+fn collect_log_messages(mut messages: MessageReader<LogMessage>, mut log: ResMut<MessageLog>) {
+    for LogMessage(text) in messages.read() {
+        info!("{text}");
+        if log.lines.len() == LOG_CAPACITY {
+            log.lines.pop_front();
+        }
+        log.lines.push_back(text.clone());
+        log.dirty = true;
+    }
+}
+
+/// L opens and closes the log; Page Up and Page Down scroll it.
+fn log_input(keys: Res<ButtonInput<KeyCode>>, mut log: ResMut<MessageLog>) {
+    if keys.just_pressed(KeyCode::KeyL) {
+        log.open = !log.open;
+        log.scroll = 0;
+        log.dirty = true;
+    }
+    if !log.open {
+        return;
+    }
+
+    let max_scroll = log.lines.len().saturating_sub(VISIBLE_LINES);
+    if keys.just_pressed(KeyCode::PageUp) {
+        log.scroll = (log.scroll + VISIBLE_LINES / 2).min(max_scroll);
+        log.dirty = true;
+    }
+    if keys.just_pressed(KeyCode::PageDown) {
+        log.scroll = log.scroll.saturating_sub(VISIBLE_LINES / 2);
+        log.dirty = true;
+    }
+}
+
+fn update_log_panel(
+    mut log: ResMut<MessageLog>,
+    mut panel: Query<&mut Visibility, With<LogPanel>>,
+    mut lines: Query<(&mut Text, &LogLine)>,
+) {
+    if !log.dirty {
+        return;
+    }
+    log.dirty = false;
+
+    for mut visibility in &mut panel {
+        *visibility = if log.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    // The newest line sits at the bottom unless scrolled back
+    let end = log.lines.len().saturating_sub(log.scroll);
+    let start = end.saturating_sub(VISIBLE_LINES);
+    for (mut text, line) in &mut lines {
+        text.0 = log
+            .lines
+            .get(start + line.0)
+            .filter(|_| start + line.0 < end)
+            .cloned()
+            .unwrap_or_default();
+    }
+}
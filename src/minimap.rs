@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+
+use crate::chests::Chest;
+use crate::pickups::Pickup;
+use crate::shrines::Shrine;
+use crate::{Enemy, Player, Settings, enemy_type_color};
+
+const MINIMAP_SIZE: f32 = 150.0;
+const MINIMAP_RANGE: f32 = 1200.0;
+const MINIMAP_UPDATE_HZ: f32 = 20.0;
+const MARKER_SIZE: f32 = 3.0;
+/// Pickups, chests and shrines, drawn bigger and over the enemies.
+const POINT_OF_INTEREST_SIZE: f32 = 5.0;
+const PICKUP_MARKER_COLOR: Color = Color::srgb(0.3, 0.9, 1.0);
+const CHEST_MARKER_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+const SHRINE_MARKER_COLOR: Color = Color::srgb(1.0, 0.5, 0.9);
+const MAX_MARKERS: usize = 400;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MinimapTimer(Timer::from_seconds(
+            1.0 / MINIMAP_UPDATE_HZ,
+            TimerMode::Repeating,
+        )))
+        .init_resource::<MinimapMarkers>()
+        .add_systems(Startup, spawn_minimap)
+        .add_systems(
+            Update,
+            (toggle_minimap, apply_minimap_visibility, update_minimap).chain(),
+        );
+    }
+}
+
+#[derive(Component)]
+struct MinimapPanel;
+
+#[derive(Component)]
+struct MinimapMarker;
+
+#[derive(Resource)]
+struct MinimapTimer(Timer);
+
+/// Marker nodes are reused between updates; extras are hidden, never despawned.
+#[derive(Resource, Default)]
+struct MinimapMarkers(Vec<Entity>);
+
+fn spawn_minimap(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                right: Val::Px(10.0),
+                width: Val::Px(MINIMAP_SIZE),
+                height: Val::Px(MINIMAP_SIZE),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            MinimapPanel,
+        ))
+        .with_children(|panel| {
+            // The player always sits at the center
+            panel.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(MINIMAP_SIZE / 2.0 - MARKER_SIZE),
+                    top: Val::Px(MINIMAP_SIZE / 2.0 - MARKER_SIZE),
+                    width: Val::Px(MARKER_SIZE * 2.0),
+                    height: Val::Px(MARKER_SIZE * 2.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.3, 1.0, 0.3)),
+            ));
+        });
+}
+
+fn toggle_minimap(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(KeyCode::KeyM) {
+        settings.show_minimap = !settings.show_minimap;
+    }
+}
+
+fn apply_minimap_visibility(
+    settings: Res<Settings>,
+    mut panel: Single<&mut Visibility, With<MinimapPanel>>,
+) {
+    if settings.is_changed() {
+        **panel = if settings.show_minimap {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Enemies first, so the points of interest after them are drawn on top
+/// and are never the ones cut by `MAX_MARKERS`.
+fn update_minimap(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut timer: ResMut<MinimapTimer>,
+    mut markers: ResMut<MinimapMarkers>,
+    panel: Single<Entity, With<MinimapPanel>>,
+    player: Query<&Transform, With<Player>>,
+    enemies: Query<(&Transform, &Enemy)>,
+    interest: Query<
+        (&Transform, Has<Chest>, Has<Shrine>),
+        Or<(With<Pickup>, With<Chest>, With<Shrine>)>,
+    >,
+    mut marker_nodes: Query<(&mut Node, &mut BackgroundColor), With<MinimapMarker>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() || !settings.show_minimap {
+        return;
+    }
+
+    let center = player.single().map(|tf| tf.translation.truncate()).ok();
+    let scale = (MINIMAP_SIZE / 2.0) / MINIMAP_RANGE;
+    let in_range = |tf: &Transform| {
+        let offset = tf.translation.truncate() - center?;
+        (offset.length_squared() <= MINIMAP_RANGE * MINIMAP_RANGE).then_some(offset)
+    };
+
+    let mut points: Vec<(Vec2, Color, f32)> = interest
+        .iter()
+        .filter_map(|(tf, chest, shrine)| {
+            let color = if chest {
+                CHEST_MARKER_COLOR
+            } else if shrine {
+                SHRINE_MARKER_COLOR
+            } else {
+                PICKUP_MARKER_COLOR
+            };
+            Some((in_range(tf)?, color, POINT_OF_INTEREST_SIZE))
+        })
+        .collect();
+    points.truncate(MAX_MARKERS);
+    let room = MAX_MARKERS - points.len();
+    let enemy_points: Vec<_> = enemies
+        .iter()
+        .filter_map(|(tf, enemy)| Some((in_range(tf)?, enemy_type_color(enemy.kind), MARKER_SIZE)))
+        .take(room)
+        .collect();
+    points.splice(0..0, enemy_points);
+
+    for (used, &(offset, color, size)) in points.iter().enumerate() {
+        // World y points up, UI y points down
+        let left = Val::Px(MINIMAP_SIZE / 2.0 + offset.x * scale - size / 2.0);
+        let top = Val::Px(MINIMAP_SIZE / 2.0 - offset.y * scale - size / 2.0);
+
+        match markers.0.get(used) {
+            Some(&marker) => {
+                if let Ok((mut node, mut background)) = marker_nodes.get_mut(marker) {
+                    node.left = left;
+                    node.top = top;
+                    node.width = Val::Px(size);
+                    node.height = Val::Px(size);
+                    node.display = Display::Flex;
+                    background.0 = color;
+                }
+            }
+            None => {
+                let marker = commands
+                    .spawn((
+                        Node {
+                            position_type: PositionType::Absolute,
+                            left,
+                            top,
+                            width: Val::Px(size),
+                            height: Val::Px(size),
+                            ..default()
+                        },
+                        BackgroundColor(color),
+                        MinimapMarker,
+                        ChildOf(*panel),
+                    ))
+                    .id();
+                markers.0.push(marker);
+            }
+        }
+    }
+
+    for &marker in &markers.0[points.len().min(markers.0.len())..] {
+        if let Ok((mut node, _)) = marker_nodes.get_mut(marker) {
+            node.display = Display::None;
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::time::Duration;
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+
+use crate::boss::Boss;
+use crate::sfx::AudioDirector;
+use crate::{Enemy, GameState, Health, PlayerCharacter};
+
+/// One loop per intensity, all the same length so they stay in step.
+const LAYER_PATHS: [&str; 3] = ["music/calm.ogg", "music/combat.ogg", "music/frantic.ogg"];
+/// Played on its own when any layer is missing.
+const SINGLE_TRACK_PATH: &str = "music/theme.ogg";
+const MUSIC_VOLUME: f32 = 0.6;
+/// Enemies this close to a player count towards the intensity.
+const NEARBY_RADIUS: f32 = 600.0;
+const INTENSITY_CHECK_SECS: f32 = 1.0;
+/// Each level is entered at the higher count and only left below the lower
+/// one, so a count hovering at a threshold doesn't flip the music back and forth.
+const COMBAT_ENTER: usize = 12;
+const COMBAT_EXIT: usize = 8;
+const FRANTIC_ENTER: usize = 40;
+const FRANTIC_EXIT: usize = 30;
+const CROSSFADE_SECS: f32 = 2.0;
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicIntensity>()
+            .add_systems(Startup, load_music)
+            .add_systems(
+                Update,
+                (
+                    start_music.run_if(resource_exists::<MusicTracks>),
+                    update_intensity
+                        .run_if(on_timer(Duration::from_secs_f32(INTENSITY_CHECK_SECS))),
+                    crossfade_layers,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+enum MusicIntensity {
+    #[default]
+    Calm,
+    Combat,
+    Frantic,
+}
+
+impl MusicIntensity {
+    const ALL: [MusicIntensity; 3] = [
+        MusicIntensity::Calm,
+        MusicIntensity::Combat,
+        MusicIntensity::Frantic,
+    ];
+
+    /// The level to play with `nearby` enemies around, coming from `self`.
+    fn settle(self, nearby: usize) -> Self {
+        use MusicIntensity::*;
+        match self {
+            _ if nearby >= FRANTIC_ENTER => Frantic,
+            Frantic if nearby >= FRANTIC_EXIT => Frantic,
+            _ if nearby >= COMBAT_ENTER => Combat,
+            Combat | Frantic if nearby >= COMBAT_EXIT => Combat,
+            _ => Calm,
+        }
+    }
+}
+
+/// Handles still loading; removed once the music has started.
+#[derive(Resource)]
+struct MusicTracks {
+    layers: [Handle<AudioSource>; 3],
+    single: Handle<AudioSource>,
+}
+
+/// One playing loop. The single-track fallback has no intensity and always
+/// plays at full volume.
+#[derive(Component)]
+struct MusicLayer {
+    intensity: Option<MusicIntensity>,
+    /// Kept here rather than read back from the sink, which only exists once
+    /// playback has begun.
+    volume: f32,
+    /// The duck gain the sink was last set with.
+    gain: f32,
+}
+
+fn load_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(MusicTracks {
+        layers: LAYER_PATHS.map(|path| asset_server.load(path)),
+        single: asset_server.load(SINGLE_TRACK_PATH),
+    });
+}
+
+/// Waits for every layer so they can all start on the same frame. Without
+/// them the single track plays, and without that there is simply no music.
+fn start_music(mut commands: Commands, asset_server: Res<AssetServer>, tracks: Res<MusicTracks>) {
+    let loaded = |handle: &Handle<AudioSource>| asset_server.is_loaded_with_dependencies(handle);
+    let failed = |handle: &Handle<AudioSource>| asset_server.load_state(handle).is_failed();
+
+    if tracks.layers.iter().all(loaded) {
+        for (handle, intensity) in tracks.layers.iter().zip(MusicIntensity::ALL) {
+            let volume = if intensity == MusicIntensity::Calm {
+                1.0
+            } else {
+                0.0
+            };
+            commands.spawn((
+                AudioPlayer::new(handle.clone()),
+                PlaybackSettings::LOOP.with_volume(Volume::Linear(volume * MUSIC_VOLUME)),
+                MusicLayer {
+                    intensity: Some(intensity),
+                    volume,
+                    gain: 1.0,
+                },
+            ));
+        }
+    } else if !tracks.layers.iter().any(failed) {
+        return;
+    } else if loaded(&tracks.single) {
+        info!("music layers missing; playing the single track");
+        commands.spawn((
+            AudioPlayer::new(tracks.single.clone()),
+            PlaybackSettings::LOOP.with_volume(Volume::Linear(MUSIC_VOLUME)),
+            MusicLayer {
+                intensity: None,
+                volume: 1.0,
+                gain: 1.0,
+            },
+        ));
+    } else if failed(&tracks.single) {
+        warn!("no music found under assets/music/");
+    } else {
+        return;
+    }
+    commands.remove_resource::<MusicTracks>();
+}
+
+/// Counts enemies near any player; outside a run the music settles back to
+/// calm. A living boss holds it at frantic, however empty the field.
+fn update_intensity(
+    state: Res<State<GameState>>,
+    mut intensity: ResMut<MusicIntensity>,
+    players: Query<&Transform, With<PlayerCharacter>>,
+    enemies: Query<&Transform, With<Enemy>>,
+    bosses: Query<&Health, With<Boss>>,
+) {
+    let playing = *state.get() == GameState::Playing;
+    let nearby = if playing {
+        let players: Vec<Vec2> = players.iter().map(|tf| tf.translation.truncate()).collect();
+        enemies
+            .iter()
+            .filter(|tf| {
+                let position = tf.translation.truncate();
+                players
+                    .iter()
+                    .any(|player| player.distance(position) <= NEARBY_RADIUS)
+            })
+            .count()
+    } else {
+        0
+    };
+    let next = if playing && bosses.iter().any(|health| health.current > 0) {
+        MusicIntensity::Frantic
+    } else {
+        intensity.settle(nearby)
+    };
+    if *intensity != next {
+        *intensity = next;
+    }
+}
+
+/// Fades the current level's layer in and every other one out, on real time so
+/// pausing or slowing the game doesn't stall a fade halfway. Everything sits
+/// under whatever duck a priority sound has on.
+fn crossfade_layers(
+    time: Res<Time>,
+    intensity: Res<MusicIntensity>,
+    director: Res<AudioDirector>,
+    mut layers: Query<(&mut MusicLayer, &mut AudioSink)>,
+) {
+    let gain = director.gain();
+    let step = time.delta_secs() / CROSSFADE_SECS;
+    for (mut layer, mut sink) in &mut layers {
+        let target = match layer.intensity {
+            Some(level) if level != *intensity => 0.0,
+            _ => 1.0,
+        };
+        if layer.volume == target && layer.gain == gain {
+            continue;
+        }
+        layer.volume += (target - layer.volume).clamp(-step, step);
+        layer.gain = gain;
+        sink.set_volume(Volume::Linear(layer.volume * MUSIC_VOLUME * gain));
+    }
+}
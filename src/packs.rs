@@ -0,0 +1,174 @@
+//! Packs: now and then the director sends a group of one kind together in a
+//! wedge or a ring around a leader. The others hold their places around the
+//! leader as it hunts rather than each making its own beeline, and scatter
+//! to ordinary pursuit once it falls. Killing the leader before any of the
+//! rest is worth a bonus.
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::challenge::RunRules;
+use crate::difficulty::DifficultyConfig;
+use crate::fonts::FontAssets;
+use crate::popups::StatusPopup;
+use crate::{Curse, DeathEvent, Enemy, EnemyType, GameplaySet, PlayState, Score, spawn_enemy};
+
+/// Chance per spawn tick of a pack, once the run is this far in.
+pub const PACK_CHANCE: f64 = 0.03;
+pub const PACK_FIRST_SECS: f32 = 60.0;
+const PACK_SIZES: std::ops::RangeInclusive<usize> = 5..=8;
+const FORMATION_SPACING: f32 = 28.0;
+/// A follower this far out of place steers straight back to it.
+const SLOT_SLACK: f32 = 40.0;
+/// Top speed of a follower catching up, as a share of its kind's speed.
+const CATCH_UP: f32 = 1.3;
+const LEADER_FIRST_POINTS: u32 = 20;
+
+pub struct PackPlugin;
+
+impl Plugin for PackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (score_leader_kills, disband_packs)
+                .chain()
+                .in_set(GameplaySet)
+                .run_if(in_state(PlayState::Running)),
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct PackLeader {
+    /// How many followed it in, to tell whether any have fallen since.
+    followers: usize,
+}
+
+/// `offset` is the member's place in the formation, in the leader's frame
+/// with +X the way it faces.
+#[derive(Component)]
+pub struct PackMember {
+    pub leader: Entity,
+    pub offset: Vec2,
+}
+
+/// A leader as its pack sees it this frame, gathered once per leader
+/// before anyone moves.
+pub struct LeaderPose {
+    pub position: Vec2,
+    /// Its own heading, which the pack matches.
+    pub heading: Vec2,
+    /// Which way the formation points.
+    pub facing: Vec2,
+}
+
+/// Match the leader's heading, pulled back towards the member's place by
+/// how far out of it the member has drifted. Places are spaced apart, so
+/// holding them keeps the pack from bunching up.
+pub fn follow_heading(member: &PackMember, leader: &LeaderPose, position: Vec2) -> Vec2 {
+    let slot = leader.position + leader.facing.rotate(member.offset);
+    (leader.heading + (slot - position) / SLOT_SLACK).clamp_length_max(CATCH_UP)
+}
+
+/// The kinds that come in packs.
+fn roll_pack_kind(rng: &mut impl Rng) -> EnemyType {
+    if rng.gen_bool(0.5) {
+        EnemyType::Basic
+    } else {
+        EnemyType::Fast
+    }
+}
+
+/// Places around the leader for `followers` members: a wedge trailing
+/// behind it, or a ring around it.
+fn formation(rng: &mut impl Rng, followers: usize) -> Vec<Vec2> {
+    if rng.gen_bool(0.5) {
+        (0..followers)
+            .map(|i| {
+                let row = (i / 2 + 1) as f32;
+                let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+                Vec2::new(-row, side * row) * FORMATION_SPACING
+            })
+            .collect()
+    } else {
+        let radius = FORMATION_SPACING * (1.0 + followers as f32 / 6.0);
+        (0..followers)
+            .map(|i| Vec2::from_angle(i as f32 * std::f32::consts::TAU / followers as f32) * radius)
+            .collect()
+    }
+}
+
+/// A whole pack at `position`, its formation pointing along `facing`.
+pub fn spawn_pack(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    rng: &mut impl Rng,
+    position: Vec2,
+    facing: Vec2,
+    curse: &Curse,
+    rules: &RunRules,
+    difficulty: &DifficultyConfig,
+) {
+    let kind = roll_pack_kind(rng);
+    let followers = rng.gen_range(PACK_SIZES) - 1;
+    let leader = spawn_enemy(commands, fonts, position, kind, curse, rules, difficulty);
+    commands.entity(leader).insert(PackLeader { followers });
+
+    let facing = facing.normalize_or(Vec2::X);
+    for offset in formation(rng, followers) {
+        let member = spawn_enemy(
+            commands,
+            fonts,
+            position + facing.rotate(offset),
+            kind,
+            curse,
+            rules,
+            difficulty,
+        );
+        commands
+            .entity(member)
+            .insert(PackMember { leader, offset });
+    }
+}
+
+/// Dead members lose `PackMember`, so a leader that dies with its whole
+/// pack still behind it was killed first. The leader keeps `PackLeader` as a
+/// corpse, so its death can be read here whichever runs first.
+fn score_leader_kills(
+    mut deaths: MessageReader<DeathEvent>,
+    mut score: ResMut<Score>,
+    mut popups: MessageWriter<StatusPopup>,
+    leaders: Query<(&PackLeader, &Transform)>,
+    members: Query<&PackMember, With<Enemy>>,
+) {
+    for death in deaths.read() {
+        let Ok((leader, tf)) = leaders.get(death.entity) else {
+            continue;
+        };
+        let standing = members
+            .iter()
+            .filter(|member| member.leader == death.entity)
+            .count();
+        if leader.followers > 0 && standing == leader.followers {
+            score.0 += LEADER_FIRST_POINTS;
+            popups.write(StatusPopup {
+                position: tf.translation.truncate() + Vec2::new(0.0, 20.0),
+                text: "Pack leader!",
+            });
+        }
+    }
+}
+
+/// A leader that has died, or fallen out of the game some other way,
+/// leaves its pack to hunt alone.
+fn disband_packs(
+    mut commands: Commands,
+    leaders: Query<(), (With<PackLeader>, With<Enemy>)>,
+    members: Query<(Entity, &PackMember)>,
+) {
+    for (entity, member) in &members {
+        if !leaders.contains(member.leader) {
+            commands.entity(entity).remove::<PackMember>();
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Shapes for enemy volleys. Each pattern is a list of shots, every one an
+//! offset from the shooter and a unit direction, so any enemy with a ranged
+//! attack picks a shape here and hands it to `spawn_enemy_projectiles`
+//! instead of laying out its own bullets.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::weapons::ENEMY_PROJECTILE;
+use crate::{ContactDamage, EnemyProjectile, Hitbox, InGameEntity, Team, layers};
+
+/// Where one shot starts, relative to the shooter, and which way it flies.
+pub type Shot = (Vec2, Vec2);
+
+/// One shot straight at the target.
+pub fn aimed(aim: Vec2) -> Vec<Shot> {
+    vec![(Vec2::ZERO, aim.normalize_or(Vec2::X))]
+}
+
+/// `count` shots fanned evenly across `spread` radians, centered on the aim.
+#[allow(dead_code)] // no spread shooter yet
+pub fn spread(aim: Vec2, count: usize, spread: f32) -> Vec<Shot> {
+    let aim = aim.normalize_or(Vec2::X);
+    if count <= 1 {
+        return aimed(aim);
+    }
+    let step = spread / (count - 1) as f32;
+    (0..count)
+        .map(|i| {
+            let angle = -spread / 2.0 + i as f32 * step;
+            (Vec2::ZERO, Vec2::from_angle(angle).rotate(aim))
+        })
+        .collect()
+}
+
+/// `count` shots evenly around a full circle, the first along +X.
+pub fn ring(count: usize) -> Vec<Shot> {
+    spiral(count, 0.0)
+}
+
+/// A ring turned by `phase` radians. A shooter that advances its phase a
+/// little each volley sweeps its gaps around, so standing still stops
+/// working.
+pub fn spiral(count: usize, phase: f32) -> Vec<Shot> {
+    (0..count)
+        .map(|i| {
+            let angle = phase + i as f32 * TAU / count as f32;
+            (Vec2::ZERO, Vec2::from_angle(angle))
+        })
+        .collect()
+}
+
+/// A wall of `count` shots side by side, `gap` apart, all flying along the
+/// aim.
+#[allow(dead_code)] // no wall shooter yet
+pub fn line(aim: Vec2, count: usize, gap: f32) -> Vec<Shot> {
+    let aim = aim.normalize_or(Vec2::X);
+    let across = aim.perp();
+    let half = (count.max(1) - 1) as f32 / 2.0;
+    (0..count)
+        .map(|i| ((i as f32 - half) * gap * across, aim))
+        .collect()
+}
+
+/// How a volley's bullets fly and hit. They all look alike, in the color
+/// kept for enemy fire.
+pub struct Volley {
+    pub speed: f32,
+    pub damage: i32,
+}
+
+pub fn spawn_enemy_projectiles(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    origin: Vec2,
+    shots: &[Shot],
+    volley: &Volley,
+) {
+    for &(offset, direction) in shots {
+        let mut projectile = commands.spawn((
+            EnemyProjectile {
+                velocity: direction * volley.speed,
+            },
+            Team::Enemy,
+            Hitbox::new(6.0),
+            ContactDamage(volley.damage),
+            Transform::from_translation((origin + offset).extend(layers::BULLETS)),
+            InGameEntity,
+        ));
+        ENEMY_PROJECTILE.apply(&mut projectile, fonts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    use super::*;
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(actual.abs_diff_eq(expected, 1e-5), "{actual} != {expected}");
+    }
+
+    #[test]
+    fn aimed_normalizes_and_falls_back_to_x() {
+        let shots = aimed(Vec2::new(0.0, 5.0));
+        assert_eq!(shots.len(), 1);
+        assert_near(shots[0].1, Vec2::Y);
+        assert_near(aimed(Vec2::ZERO)[0].1, Vec2::X);
+    }
+
+    #[test]
+    fn spread_fans_across_the_arc_centered_on_the_aim() {
+        let shots = spread(Vec2::Y, 3, FRAC_PI_2);
+        assert_eq!(shots.len(), 3);
+        assert_near(shots[0].1, Vec2::from_angle(-FRAC_PI_4).rotate(Vec2::Y));
+        assert_near(shots[1].1, Vec2::Y);
+        assert_near(shots[2].1, Vec2::from_angle(FRAC_PI_4).rotate(Vec2::Y));
+        assert_eq!(spread(Vec2::Y, 1, FRAC_PI_2).len(), 1);
+    }
+
+    #[test]
+    fn ring_spaces_shots_evenly_from_x() {
+        let shots = ring(4);
+        for (shot, expected) in shots.iter().zip([Vec2::X, Vec2::Y, -Vec2::X, -Vec2::Y]) {
+            assert_eq!(shot.0, Vec2::ZERO);
+            assert_near(shot.1, expected);
+        }
+        assert!(ring(0).is_empty());
+    }
+
+    #[test]
+    fn spiral_turns_the_ring_by_its_phase() {
+        let shots = spiral(4, FRAC_PI_2);
+        assert_near(shots[0].1, Vec2::Y);
+        assert_near(shots[3].1, Vec2::X);
+    }
+
+    #[test]
+    fn line_centers_the_wall_across_the_aim() {
+        let shots = line(Vec2::X, 3, 10.0);
+        let offsets: Vec<Vec2> = shots.iter().map(|shot| shot.0).collect();
+        assert_eq!(
+            offsets,
+            [Vec2::new(0.0, -10.0), Vec2::ZERO, Vec2::new(0.0, 10.0)]
+        );
+        assert!(shots.iter().all(|shot| shot.1 == Vec2::X));
+        assert!(line(Vec2::X, 0, 10.0).is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+//! Performance presets for weak hardware. The preset is a setting, and
+//! `CosmeticBudget` is what it works out to: how many of each purely
+//! cosmetic entity may be alive at once, and which decorative effects are
+//! drawn at all. Anything that spawns or draws one of those checks the
+//! budget first, and the field janitor culls whatever a lower preset has
+//! left over budget. Nothing here touches gameplay or its warnings.
+
+use bevy::prelude::*;
+
+use crate::Settings;
+
+pub struct PerformancePlugin;
+
+impl Plugin for PerformancePlugin {
+    fn build(&self, app: &mut App) {
+        let budget = app.world().resource::<Settings>().performance.budget();
+        app.insert_resource(budget)
+            .add_systems(PreUpdate, (cycle_performance, apply_performance).chain());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PerformancePreset {
+    Low,
+    Medium,
+    High,
+}
+
+impl PerformancePreset {
+    pub fn id(self) -> &'static str {
+        match self {
+            PerformancePreset::Low => "low",
+            PerformancePreset::Medium => "medium",
+            PerformancePreset::High => "high",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "low" => Some(PerformancePreset::Low),
+            "medium" => Some(PerformancePreset::Medium),
+            "high" => Some(PerformancePreset::High),
+            _ => None,
+        }
+    }
+
+    /// The next preset down, wrapping from Low back to High.
+    fn next(self) -> Self {
+        match self {
+            PerformancePreset::High => PerformancePreset::Medium,
+            PerformancePreset::Medium => PerformancePreset::Low,
+            PerformancePreset::Low => PerformancePreset::High,
+        }
+    }
+
+    pub fn budget(self) -> CosmeticBudget {
+        match self {
+            PerformancePreset::Low => CosmeticBudget {
+                trails: 0,
+                particles: 0,
+                popups: 16,
+                vignette: false,
+                flourishes: false,
+            },
+            PerformancePreset::Medium => CosmeticBudget {
+                trails: 48,
+                particles: 48,
+                popups: 24,
+                vignette: true,
+                flourishes: true,
+            },
+            PerformancePreset::High => CosmeticBudget {
+                trails: 160,
+                particles: 160,
+                popups: 32,
+                vignette: true,
+                flourishes: true,
+            },
+        }
+    }
+}
+
+/// Caps on live cosmetics, by category. A cap of 0 turns the category off.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CosmeticBudget {
+    /// Afterimages left behind fast enemies.
+    pub trails: usize,
+    /// Debris thrown out by deaths.
+    pub particles: usize,
+    /// Score popups on screen at once.
+    pub popups: usize,
+    pub vignette: bool,
+    /// Gizmos that only dress up something already shown another way, such
+    /// as the close-call pulse.
+    pub flourishes: bool,
+}
+
+/// O steps down a preset, from any screen.
+fn cycle_performance(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(KeyCode::KeyO) {
+        settings.performance = settings.performance.next();
+        info!("performance {}", settings.performance.id());
+    }
+}
+
+/// Takes effect the frame the setting changes; the janitor sweeps as soon as
+/// the budget does.
+fn apply_performance(settings: Res<Settings>, mut budget: ResMut<CosmeticBudget>) {
+    if settings.is_changed() {
+        budget.set_if_neq(settings.performance.budget());
+    }
+}
@@ -0,0 +1,83 @@
+//! The Phaser: an enemy that slips in and out of reach, two seconds solid
+//! and one phased. While phased it fades, and its hitbox goes intangible, so
+//! bullets pass through it, the aura and melee miss it, and it can't hurt
+//! anyone, but it keeps moving all the same.
+//!
+//! Intangibility is decided where hits are, never where damage lands, so
+//! anything already working on it keeps ticking through the phased window.
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::{EnemyTime, GameplaySet, Hitbox, PlayState, SpawnProtection};
+
+const TANGIBLE_SECS: f32 = 2.0;
+const PHASED_SECS: f32 = 1.0;
+const PHASED_ALPHA: f32 = 0.3;
+
+pub struct PhaserPlugin;
+
+impl Plugin for PhaserPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            cycle_phases
+                .in_set(GameplaySet)
+                .run_if(in_state(PlayState::Running)),
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct PhaseState {
+    /// Runs out at the end of the current half of the cycle.
+    timer: Timer,
+    phased: bool,
+}
+
+impl PhaseState {
+    /// Starts somewhere random in the cycle, so a crowd of them doesn't
+    /// blink in unison.
+    pub fn new(rng: &mut impl Rng) -> Self {
+        let offset = rng.gen_range(0.0..TANGIBLE_SECS + PHASED_SECS);
+        let phased = offset >= TANGIBLE_SECS;
+        let mut timer = Self::half(phased);
+        let into = if phased {
+            offset - TANGIBLE_SECS
+        } else {
+            offset
+        };
+        timer.set_elapsed(std::time::Duration::from_secs_f32(into));
+        Self { timer, phased }
+    }
+
+    fn half(phased: bool) -> Timer {
+        let secs = if phased { PHASED_SECS } else { TANGIBLE_SECS };
+        Timer::from_seconds(secs, TimerMode::Once)
+    }
+
+    /// Sets the hitbox and fade to match the current half of the cycle.
+    pub fn show(&self, hitbox: &mut Hitbox, color: &mut TextColor) {
+        hitbox.intangible = self.phased;
+        color.0 = color
+            .0
+            .with_alpha(if self.phased { PHASED_ALPHA } else { 1.0 });
+    }
+}
+
+/// Phasers run on the enemy clock, so dilation stretches their cycle too.
+/// The cycle only starts once spawn protection is over, which hands the
+/// hitbox and fade back through `PhaseState::show`.
+fn cycle_phases(
+    time: EnemyTime,
+    mut phasers: Query<(&mut PhaseState, &mut Hitbox, &mut TextColor), Without<SpawnProtection>>,
+) {
+    for (mut phase, mut hitbox, mut color) in &mut phasers {
+        if !phase.timer.tick(time.delta()).is_finished() {
+            continue;
+        }
+        phase.phased = !phase.phased;
+        phase.timer = PhaseState::half(phase.phased);
+        phase.show(&mut hitbox, &mut color);
+    }
+}
@@ -0,0 +1,192 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+
+use crate::debug::DebugDraw;
+use crate::fonts::FontAssets;
+use crate::{PlayState, storage};
+
+const PHOTO_DIR: &str = "screenshots";
+/// Hidden UI comes back after this long even if the capture never arrives.
+const CAPTURE_TIMEOUT_SECS: f32 = 1.0;
+const TOAST_SECS: f32 = 2.5;
+
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            take_photo
+                .run_if(in_state(PlayState::Paused).and(not(resource_exists::<PhotoCapture>))),
+        )
+        // Not tied to the pause, so unpausing mid-capture still gets the UI back
+        .add_systems(
+            Update,
+            (
+                finish_photo.run_if(resource_exists::<PhotoCapture>),
+                fade_toasts,
+            ),
+        );
+    }
+}
+
+/// A screenshot on its way. Everything hidden for it is put back exactly as
+/// it was once the capture lands or times out.
+#[derive(Resource)]
+struct PhotoCapture {
+    /// Top-level UI nodes and the visibility each had before.
+    hidden: Vec<(Entity, Visibility)>,
+    debug: Option<DebugDraw>,
+    timeout: Timer,
+    /// Set by the capture observer with the line to show.
+    result: Option<String>,
+}
+
+#[derive(Component)]
+struct PhotoToast(Timer);
+
+/// The first free `photo-<time>.png` name, numbered if several land in one second.
+fn photo_path() -> PathBuf {
+    let stamp = storage::unix_time_secs();
+    let mut path = Path::new(PHOTO_DIR).join(format!("photo-{stamp}.png"));
+    let mut n = 2;
+    while path.exists() {
+        path = Path::new(PHOTO_DIR).join(format!("photo-{stamp}-{n}.png"));
+        n += 1;
+    }
+    path
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_photo(image: &Image, path: &Path) -> Result<(), String> {
+    let image = image
+        .clone()
+        .try_into_dynamic()
+        .map_err(|err| err.to_string())?;
+    let format = bevy::image::ImageFormat::Png
+        .as_image_crate_format()
+        .ok_or("this build can't encode PNG")?;
+    let mut png = std::io::Cursor::new(Vec::new());
+    image
+        .to_rgb8()
+        .write_to(&mut png, format)
+        .map_err(|err| err.to_string())?;
+    storage::write_file(path, png.get_ref()).map_err(|err| err.to_string())
+}
+
+/// On the web the download is started by Bevy's own observer instead.
+#[cfg(target_arch = "wasm32")]
+fn write_photo(_image: &Image, _path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// F12 hides the UI and debug overlays for a clean shot of the game;
+/// Shift+F12 takes the screen exactly as it is.
+fn take_photo(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    mut debug: ResMut<DebugDraw>,
+    roots: Query<(Entity, &Visibility), (With<Node>, Without<ChildOf>)>,
+) {
+    if !input.just_pressed(KeyCode::F12) {
+        return;
+    }
+    let keep_ui = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    let mut capture = PhotoCapture {
+        hidden: Vec::new(),
+        debug: None,
+        timeout: Timer::from_seconds(CAPTURE_TIMEOUT_SECS, TimerMode::Once),
+        result: None,
+    };
+    if !keep_ui {
+        for (entity, visibility) in &roots {
+            if *visibility != Visibility::Hidden {
+                capture.hidden.push((entity, *visibility));
+                commands.entity(entity).insert(Visibility::Hidden);
+            }
+        }
+        capture.debug = Some(std::mem::take(&mut *debug));
+    }
+    commands.insert_resource(capture);
+
+    let path = photo_path();
+    let mut screenshot = commands.spawn(Screenshot::primary_window());
+    // The browser can't write files, so the web build hands it over as a download
+    #[cfg(target_arch = "wasm32")]
+    screenshot.observe(bevy::render::view::screenshot::save_to_disk(path.clone()));
+    screenshot.observe(
+        move |captured: On<ScreenshotCaptured>, capture: Option<ResMut<PhotoCapture>>| {
+            let message = match write_photo(&captured.image, &path) {
+                Ok(()) => format!("Saved to {}", path.display()),
+                Err(err) => {
+                    warn!("could not save screenshot to {}: {err}", path.display());
+                    format!("Could not save screenshot: {err}")
+                }
+            };
+            // Gone if the capture already timed out; the UI is back either way
+            if let Some(mut capture) = capture {
+                capture.result = Some(message);
+            }
+        },
+    );
+}
+
+fn finish_photo(
+    mut commands: Commands,
+    time: Res<Time>,
+    fonts: Res<FontAssets>,
+    mut capture: ResMut<PhotoCapture>,
+    mut debug: ResMut<DebugDraw>,
+) {
+    let message = match capture.result.take() {
+        Some(message) => message,
+        None if capture.timeout.tick(time.delta()).is_finished() => {
+            warn!("screenshot never arrived");
+            "Screenshot failed".to_string()
+        }
+        None => return,
+    };
+
+    // Anything despawned meanwhile, such as the pause screen, is simply skipped
+    for &(entity, visibility) in &capture.hidden {
+        commands.entity(entity).try_insert(visibility);
+    }
+    if let Some(saved) = capture.debug.take() {
+        *debug = saved;
+    }
+    commands.remove_resource::<PhotoCapture>();
+
+    commands.spawn((
+        Text::new(message),
+        fonts.body(18.0),
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(40.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TextLayout::new_with_justify(Justify::Center),
+        PhotoToast(Timer::from_seconds(TOAST_SECS, TimerMode::Once)),
+    ));
+}
+
+fn fade_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut PhotoToast, &mut TextColor)>,
+) {
+    for (entity, mut toast, mut color) in &mut toasts {
+        if toast.0.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        color.0 = color
+            .0
+            .with_alpha(toast.0.fraction_remaining().min(0.5) * 2.0);
+    }
+}
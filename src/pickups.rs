@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::beacons::{Beacon, Rarity, spawn_beacon};
+use crate::challenge::RunRules;
+use crate::despawn::Dead;
+use crate::difficulty::DifficultyConfig;
+use crate::fonts::FontAssets;
+use crate::loot::{Drop, DropPity, DropTable, LootEntry, LootSpawner, PityCategory};
+use crate::message_log::LogMessage;
+use crate::{
+    Barrier, EnemyKilledEvent, Health, InGameEntity, MAX_BARRIER_CHARGES, PlayState, Player,
+    PlayerStats, layers,
+};
+
+const PICKUP_RADIUS: f32 = 20.0;
+
+/// Rolled for every enemy killed.
+const ENEMY_DROPS: DropTable = DropTable::new(&[
+    (0.03, LootEntry::Drop(Drop::Shield)),
+    (0.97, LootEntry::Nothing),
+]);
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<PickupCollected>().add_systems(
+            Update,
+            (drop_pickups, collect_pickups)
+                .chain()
+                .run_if(in_state(PlayState::Running)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PickupKind {
+    Shield,
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+}
+
+/// Sent for each pickup the player walks over.
+#[derive(Message)]
+pub struct PickupCollected;
+
+pub fn spawn_pickup(commands: &mut Commands, fonts: &FontAssets, kind: PickupKind, position: Vec2) {
+    let (glyph, color, beacon) = match kind {
+        PickupKind::Shield => (
+            "O",
+            Color::srgb(0.3, 0.9, 1.0),
+            Some(Beacon {
+                label: "SHIELD",
+                rarity: Rarity::Uncommon,
+            }),
+        ),
+    };
+
+    let pickup = commands
+        .spawn((
+            Text2d::new(glyph),
+            fonts.body(18.0),
+            TextColor(color),
+            Transform::from_translation(position.extend(layers::PICKUPS)),
+            Pickup { kind },
+            InGameEntity,
+        ))
+        .id();
+    if let Some(beacon) = beacon {
+        spawn_beacon(commands, fonts, pickup, beacon);
+    }
+}
+
+/// The shield pity only counts kills made while the player is hurt, so the
+/// guarantee lands when a shield is actually needed.
+fn drop_pickups(
+    mut loot: LootSpawner,
+    mut pity: ResMut<DropPity>,
+    rules: Res<RunRules>,
+    difficulty: Res<DifficultyConfig>,
+    stats: Res<PlayerStats>,
+    player: Query<&Health, With<Player>>,
+    mut kills: MessageReader<EnemyKilledEvent>,
+) {
+    let thresholds = difficulty.pity;
+    let hurt = player
+        .single()
+        .is_ok_and(|health| (health.current as f32) < thresholds.shield_health * health.max as f32);
+    let mut rng = thread_rng();
+    for kill in kills.read() {
+        let mut drops = ENEMY_DROPS.roll(stats.luck, &mut rng);
+        if rules.shield_drops {
+            pity.settle(PityCategory::Shield, thresholds.shield, hurt, &mut drops);
+        } else {
+            drops.retain(|&drop| drop != Drop::Shield);
+        }
+        loot.spawn(drops, kill.position);
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    mut log: MessageWriter<LogMessage>,
+    mut collected: MessageWriter<PickupCollected>,
+    mut player: Query<(Entity, &Transform, Option<&mut Barrier>), With<Player>>,
+    pickups: Query<(Entity, &Transform, &Pickup), Without<Dead>>,
+) {
+    let Ok((player_entity, player_tf, barrier)) = player.single_mut() else {
+        return;
+    };
+    let player_pos = player_tf.translation.truncate();
+
+    let mut shields = 0;
+    for (entity, tf, pickup) in &pickups {
+        if tf.translation.truncate().distance(player_pos) > PICKUP_RADIUS {
+            continue;
+        }
+
+        match pickup.kind {
+            PickupKind::Shield => shields += 1,
+        }
+
+        commands.entity(entity).despawn();
+        collected.write(PickupCollected);
+    }
+
+    if shields > 0 {
+        log.write(LogMessage("Picked up a shield".to_string()));
+        match barrier {
+            Some(mut barrier) => {
+                barrier.charges = (barrier.charges + shields).min(MAX_BARRIER_CHARGES);
+            }
+            None => {
+                commands.entity(player_entity).insert(Barrier {
+                    charges: shields.min(MAX_BARRIER_CHARGES),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::performance::CosmeticBudget;
+use crate::{
+    EnemyKilledEvent, EnemyType, GameState, InGameEntity, ResetSet, enemy_type_color, layers,
+};
+
+const POPUP_LIFETIME: f32 = 0.5;
+const STATUS_POPUP_COLOR: Color = Color::srgb(0.75, 0.75, 0.8);
+const POPUP_RISE_SPEED: f32 = 60.0;
+
+/// Kills landing in the same cell within this window share one popup.
+const MERGE_CELL_SIZE: f32 = 64.0;
+const MERGE_WINDOW: f32 = 0.1;
+
+pub struct ScorePopupPlugin;
+
+impl Plugin for ScorePopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingPopups>()
+            .add_message::<StatusPopup>()
+            .add_systems(
+                Update,
+                (
+                    collect_kill_popups,
+                    flush_pending_popups,
+                    spawn_status_popups,
+                    animate_popups,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                clear_pending_popups.in_set(ResetSet),
+            );
+    }
+}
+
+/// A word floated over something that just shrugged off an effect.
+#[derive(Message)]
+pub struct StatusPopup {
+    pub position: Vec2,
+    pub text: &'static str,
+}
+
+#[derive(Component)]
+pub struct ScorePopup {
+    timer: Timer,
+}
+
+/// Kills waiting to be turned into a popup, keyed by rough grid cell.
+#[derive(Resource, Default)]
+struct PendingPopups {
+    cells: HashMap<IVec2, PendingPopup>,
+}
+
+struct PendingPopup {
+    position_sum: Vec2,
+    kills: u32,
+    points: u32,
+    // Colored after the most valuable enemy in the group
+    best_kind: EnemyType,
+    best_points: u32,
+    age: f32,
+}
+
+fn collect_kill_popups(
+    mut kills: MessageReader<EnemyKilledEvent>,
+    mut pending: ResMut<PendingPopups>,
+) {
+    for kill in kills.read() {
+        let cell = (kill.position / MERGE_CELL_SIZE).floor().as_ivec2();
+        let entry = pending.cells.entry(cell).or_insert(PendingPopup {
+            position_sum: Vec2::ZERO,
+            kills: 0,
+            points: 0,
+            best_kind: kill.kind,
+            best_points: 0,
+            age: 0.0,
+        });
+
+        entry.position_sum += kill.position;
+        entry.kills += 1;
+        entry.points += kill.points;
+        if kill.points > entry.best_points {
+            entry.best_kind = kill.kind;
+            entry.best_points = kill.points;
+        }
+    }
+}
+
+fn flush_pending_popups(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    time: Res<Time>,
+    budget: Res<CosmeticBudget>,
+    mut pending: ResMut<PendingPopups>,
+    popups: Query<(Entity, &ScorePopup)>,
+) {
+    let mut live = popups.iter().count();
+    let mut oldest: Vec<(Entity, f32)> = Vec::new();
+
+    pending.cells.retain(|_, entry| {
+        entry.age += time.delta_secs();
+        if entry.age < MERGE_WINDOW {
+            return true;
+        }
+
+        // Make room by retiring the oldest popups on screen
+        while live >= budget.popups.max(1) {
+            if oldest.is_empty() {
+                oldest = popups
+                    .iter()
+                    .map(|(entity, popup)| (entity, popup.timer.elapsed_secs()))
+                    .collect();
+                oldest.sort_by(|a, b| a.1.total_cmp(&b.1));
+            }
+            let Some((entity, _)) = oldest.pop() else {
+                break;
+            };
+            // May also be expiring this frame in `animate_popups`
+            commands.entity(entity).try_despawn();
+            live -= 1;
+        }
+
+        let position = entry.position_sum / entry.kills as f32;
+        commands.spawn((
+            Text2d::new(format!("+{}", entry.points)),
+            fonts.body(18.0),
+            TextColor(enemy_type_color(entry.best_kind)),
+            Transform::from_translation(position.extend(layers::FLOATING_TEXT)),
+            ScorePopup {
+                timer: Timer::from_seconds(POPUP_LIFETIME, TimerMode::Once),
+            },
+            InGameEntity,
+        ));
+        live += 1;
+
+        false
+    });
+}
+
+/// Smaller and greyer than score popups, and outside their cap: they're rare,
+/// and short enough not to pile up.
+fn spawn_status_popups(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut popups: MessageReader<StatusPopup>,
+) {
+    for popup in popups.read() {
+        commands.spawn((
+            Text2d::new(popup.text),
+            fonts.body(12.0),
+            TextColor(STATUS_POPUP_COLOR),
+            Transform::from_translation(popup.position.extend(layers::FLOATING_TEXT)),
+            ScorePopup {
+                timer: Timer::from_seconds(POPUP_LIFETIME, TimerMode::Once),
+            },
+            InGameEntity,
+        ));
+    }
+}
+
+fn animate_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut Transform, &mut TextColor, &mut ScorePopup)>,
+) {
+    for (entity, mut tf, mut color, mut popup) in &mut popups {
+        popup.timer.tick(time.delta());
+        if popup.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        tf.translation.y += POPUP_RISE_SPEED * time.delta_secs();
+        color.0.set_alpha(1.0 - popup.timer.fraction());
+    }
+}
+
+fn clear_pending_popups(mut pending: ResMut<PendingPopups>) {
+    pending.cells.clear();
+}
@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+use bevy::input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy::prelude::*;
+
+use crate::{
+    EnemyKilledEvent, EnemyType, GameState, Health, PlayState, Player, ResetSet, Settings,
+};
+
+/// Strength and length of each kind of pulse.
+const HURT_RUMBLE: (f32, f32) = (0.8, 0.25);
+const KILL_RUMBLE: (f32, f32) = (0.15, 0.05);
+const BOSS_DEATH_RUMBLE: (f32, f32) = (1.0, 1.5);
+/// At most one kill tick this often, however many enemies die.
+const KILL_TICK_SECS: f32 = 0.15;
+
+pub struct RumblePlugin;
+
+impl Plugin for RumblePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rumble>()
+            .add_systems(OnEnter(GameState::Resetting), reset_rumble.in_set(ResetSet))
+            .add_systems(Update, toggle_rumble)
+            .add_systems(
+                Update,
+                (rumble_on_hurt, rumble_on_kills).run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(Update, drive_rumble.after(rumble_on_kills));
+    }
+}
+
+/// The one rumble every connected pad plays. New requests merge into it,
+/// keeping the stronger intensity and the longer time left, instead of
+/// stacking up on the motors.
+#[derive(Resource, Default)]
+struct Rumble {
+    intensity: f32,
+    remaining: f32,
+    /// Whether the pads need the merged rumble sent again.
+    changed: bool,
+    /// Player health seen last frame; a drop is a hit.
+    last_health: Option<i32>,
+    since_kill_tick: f32,
+}
+
+impl Rumble {
+    fn request(&mut self, (intensity, secs): (f32, f32)) {
+        if intensity > self.intensity || (intensity == self.intensity && secs > self.remaining) {
+            self.intensity = self.intensity.max(intensity);
+            self.remaining = self.remaining.max(secs);
+            self.changed = true;
+        }
+    }
+}
+
+fn reset_rumble(mut rumble: ResMut<Rumble>) {
+    *rumble = Rumble::default();
+}
+
+fn toggle_rumble(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(KeyCode::KeyG) {
+        settings.rumble = !settings.rumble;
+        info!("rumble {}", if settings.rumble { "on" } else { "off" });
+    }
+}
+
+fn rumble_on_hurt(mut rumble: ResMut<Rumble>, player: Query<&Health, With<Player>>) {
+    let Ok(health) = player.single() else {
+        return;
+    };
+    if let Some(before) = rumble.last_health
+        && health.current < before
+    {
+        rumble.request(HURT_RUMBLE);
+    }
+    rumble.last_health = Some(health.current);
+}
+
+fn rumble_on_kills(
+    time: Res<Time>,
+    mut rumble: ResMut<Rumble>,
+    mut kills: MessageReader<EnemyKilledEvent>,
+) {
+    rumble.since_kill_tick += time.delta_secs();
+    let mut killed = false;
+    for kill in kills.read() {
+        if kill.kind == EnemyType::Boss {
+            rumble.request(BOSS_DEATH_RUMBLE);
+        }
+        killed = true;
+    }
+    if killed && rumble.since_kill_tick >= KILL_TICK_SECS {
+        rumble.since_kill_tick = 0.0;
+        rumble.request(KILL_RUMBLE);
+    }
+}
+
+/// Sends the merged rumble to whatever pads are connected right now, so a
+/// pad unplugged mid-rumble is simply never addressed again.
+fn drive_rumble(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut rumble: ResMut<Rumble>,
+    mut requests: MessageWriter<GamepadRumbleRequest>,
+    gamepads: Query<Entity, With<Gamepad>>,
+) {
+    rumble.remaining = (rumble.remaining - time.delta_secs()).max(0.0);
+    if rumble.remaining == 0.0 {
+        rumble.intensity = 0.0;
+    }
+    if !rumble.changed {
+        return;
+    }
+    rumble.changed = false;
+    if !settings.rumble {
+        return;
+    }
+
+    let intensity = GamepadRumbleIntensity {
+        strong_motor: rumble.intensity,
+        weak_motor: rumble.intensity,
+    };
+    for gamepad in &gamepads {
+        // The motors add up whatever they are sent, so replace rather than add
+        requests.write(GamepadRumbleRequest::Stop { gamepad });
+        requests.write(GamepadRumbleRequest::Add {
+            duration: Duration::from_secs_f32(rumble.remaining),
+            intensity,
+            gamepad,
+        });
+    }
+}
@@ -0,0 +1,142 @@
+//! A JSON summary of every finished run, written through `storage` as the
+//! results screen opens, so balance changes can be compared run to run. Only
+//! the last run is kept; copy the file out to keep more.
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::run_stats::{RunStats, SAMPLE_SECS, WeaponStats};
+use crate::savegame::RunSuspended;
+use crate::storage::{self, Persistence};
+use crate::telemetry::Telemetry;
+use crate::unlocks::RunRecord;
+use crate::{Curse, GameState, GameplaySpeed, Score};
+
+const EXPORT_KEY: &str = "run_summary.json";
+
+pub struct RunExportPlugin;
+
+impl Plugin for RunExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::GameOver), export_run_summary);
+    }
+}
+
+#[derive(Serialize)]
+struct RunSummary {
+    /// Unix seconds.
+    finished_at: u64,
+    score: u32,
+    secs: f32,
+    kills: u64,
+    /// Curse points held when the run ended.
+    curse: u32,
+    /// The game-speed setting the run was played at.
+    game_speed: f32,
+    /// Played below full speed, so it sets no bests.
+    reduced_speed: bool,
+    weapons: Vec<WeaponSummary>,
+    /// The last minute of the run, one sample a second.
+    spawns: SpawnSummary,
+    timeline: TimelineSummary,
+}
+
+#[derive(Serialize)]
+struct WeaponSummary {
+    weapon: &'static str,
+    shots: u32,
+    hits: u32,
+    misses: u32,
+    damage: u32,
+    kills: u32,
+}
+
+impl WeaponSummary {
+    fn new(weapon: &'static str, stats: &WeaponStats) -> Self {
+        Self {
+            weapon,
+            shots: stats.shots,
+            hits: stats.hits,
+            misses: stats.misses,
+            damage: stats.damage,
+            kills: stats.kills,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SpawnSummary {
+    spawned: Vec<u32>,
+    killed: Vec<u32>,
+    live: Vec<u32>,
+}
+
+impl SpawnSummary {
+    fn new(telemetry: &Telemetry) -> Self {
+        let [spawned, killed, live] = telemetry
+            .strips()
+            .map(|strip| strip.iter().copied().collect());
+        Self {
+            spawned,
+            killed,
+            live,
+        }
+    }
+}
+
+/// One sample every `sample_secs` from `start_secs` on, the latest two
+/// hours of the run at most.
+#[derive(Serialize)]
+struct TimelineSummary {
+    start_secs: f32,
+    sample_secs: f32,
+    score: Vec<u32>,
+    kills: Vec<u32>,
+}
+
+impl TimelineSummary {
+    fn new(stats: &RunStats) -> Self {
+        let (start_secs, score, kills) = stats.timeline();
+        Self {
+            start_secs,
+            sample_secs: SAMPLE_SECS,
+            score: score.iter().copied().collect(),
+            kills: kills.iter().copied().collect(),
+        }
+    }
+}
+
+/// A run left through Save & Quit isn't over, so it isn't exported.
+fn export_run_summary(
+    score: Res<Score>,
+    curse: Res<Curse>,
+    speed: Res<GameplaySpeed>,
+    record: Res<RunRecord>,
+    stats: Res<RunStats>,
+    telemetry: Res<Telemetry>,
+    suspended: Res<RunSuspended>,
+    mut persistence: ResMut<Persistence>,
+) {
+    if suspended.0 {
+        return;
+    }
+    let summary = RunSummary {
+        finished_at: storage::unix_time_secs(),
+        score: score.0,
+        secs: record.secs(),
+        kills: record.kills(),
+        curse: curse.0,
+        game_speed: speed.0,
+        reduced_speed: speed.0 < 1.0,
+        weapons: stats
+            .weapons()
+            .map(|(kind, weapon)| WeaponSummary::new(kind.id(), weapon))
+            .collect(),
+        spawns: SpawnSummary::new(&telemetry),
+        timeline: TimelineSummary::new(&stats),
+    };
+    match serde_json::to_string_pretty(&summary) {
+        Ok(json) => persistence.save(EXPORT_KEY, json),
+        Err(err) => warn!("could not export the run summary: {err}"),
+    }
+}
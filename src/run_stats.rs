@@ -0,0 +1,329 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::unlocks::RunRecord;
+use crate::weapons::WeaponKind;
+use crate::{GameState, GameTime, PlayState, ResetSet, Score, spawn_state_ui_root};
+
+const CELL_WIDTH: f32 = 72.0;
+const COLUMNS: [&str; 6] = ["Weapon", "Shots", "Acc.", "Misses", "Damage", "Kills"];
+pub const SAMPLE_SECS: f32 = 5.0;
+/// Two hours of samples; an endless run past that keeps the latest two.
+const MAX_SAMPLES: usize = 1440;
+/// Longer timelines are thinned to this many bars.
+const MAX_GRAPH_POINTS: usize = 120;
+/// Fewer samples than this say too little to be worth a graph.
+const MIN_GRAPH_SAMPLES: usize = 3;
+const GRAPH_WIDTH: f32 = 240.0;
+const GRAPH_HEIGHT: f32 = 48.0;
+const SCORE_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+const KILLS_COLOR: Color = Color::srgb(1.0, 0.4, 0.35);
+const AXIS_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+
+pub struct RunStatsPlugin;
+
+impl Plugin for RunStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                reset_run_stats.in_set(ResetSet),
+            )
+            .add_systems(Update, sample_timeline.run_if(in_state(PlayState::Running)))
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (spawn_weapon_breakdown, spawn_timeline_graph),
+            );
+    }
+}
+
+/// Shots only ever count for weapons that fire bullets; a bullet is a hit
+/// once it strikes anything and a miss only if it flies out of range
+/// without ever having done so.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct WeaponStats {
+    pub shots: u32,
+    pub hits: u32,
+    pub misses: u32,
+    pub damage: u32,
+    pub kills: u32,
+}
+
+impl WeaponStats {
+    /// Share of shots that hit, or `None` for weapons that never fire any.
+    fn accuracy(&self) -> Option<f32> {
+        (self.shots > 0).then(|| self.hits as f32 / self.shots as f32)
+    }
+}
+
+/// Score and kills so far, taken every `SAMPLE_SECS` of play, oldest first.
+#[derive(Default)]
+struct Timeline {
+    since_sample: f32,
+    score: VecDeque<u32>,
+    kills: VecDeque<u32>,
+    /// Samples pushed out the front once `MAX_SAMPLES` was reached.
+    dropped: usize,
+}
+
+impl Timeline {
+    fn push(&mut self, score: u32, kills: u32) {
+        if self.score.len() == MAX_SAMPLES {
+            self.score.pop_front();
+            self.kills.pop_front();
+            self.dropped += 1;
+        }
+        self.score.push_back(score);
+        self.kills.push_back(kills);
+    }
+
+    fn len(&self) -> usize {
+        self.score.len()
+    }
+
+    /// Run seconds at the first kept sample and the last.
+    fn span_secs(&self) -> (f32, f32) {
+        let start = (self.dropped + 1) as f32 * SAMPLE_SECS;
+        (
+            start,
+            start + self.len().saturating_sub(1) as f32 * SAMPLE_SECS,
+        )
+    }
+}
+
+/// Per-weapon numbers for the current run, in the order each weapon was first used.
+#[derive(Resource, Default)]
+pub struct RunStats {
+    weapons: Vec<(WeaponKind, WeaponStats)>,
+    timeline: Timeline,
+}
+
+impl RunStats {
+    pub fn weapon_mut(&mut self, kind: WeaponKind) -> &mut WeaponStats {
+        let index = match self.weapons.iter().position(|(k, _)| *k == kind) {
+            Some(index) => index,
+            None => {
+                self.weapons.push((kind, WeaponStats::default()));
+                self.weapons.len() - 1
+            }
+        };
+        &mut self.weapons[index].1
+    }
+
+    pub fn weapons(&self) -> impl Iterator<Item = (WeaponKind, &WeaponStats)> {
+        self.weapons.iter().map(|(kind, stats)| (*kind, stats))
+    }
+
+    /// The sampled score and kills, oldest first, and the run second the
+    /// first kept sample was taken at.
+    pub fn timeline(&self) -> (f32, &VecDeque<u32>, &VecDeque<u32>) {
+        let timeline = &self.timeline;
+        (timeline.span_secs().0, &timeline.score, &timeline.kills)
+    }
+}
+
+fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+/// One addition a frame; the buffers only move every `SAMPLE_SECS`.
+fn sample_timeline(
+    time: GameTime,
+    score: Res<Score>,
+    record: Res<RunRecord>,
+    mut stats: ResMut<RunStats>,
+) {
+    let timeline = &mut stats.timeline;
+    timeline.since_sample += time.delta_secs();
+    if timeline.since_sample < SAMPLE_SECS {
+        return;
+    }
+    timeline.since_sample -= SAMPLE_SECS;
+    timeline.push(score.0, u32::try_from(record.kills()).unwrap_or(u32::MAX));
+}
+
+/// At most `max` of `samples`, evenly spaced. Counted back from the end, so
+/// the final value is always among them.
+fn downsample(samples: &VecDeque<u32>, max: usize) -> Vec<u32> {
+    let step = samples.len().div_ceil(max).max(1);
+    let mut points: Vec<u32> = samples.iter().rev().step_by(step).copied().collect();
+    points.reverse();
+    points
+}
+
+/// Whole minutes worth marking between `start` and `end` seconds, spaced
+/// so there are never more than about five.
+fn minute_marks(start: f32, end: f32) -> Vec<u32> {
+    let span = (end - start) / 60.0;
+    let step = ((span / 4.0).ceil() as u32).max(1);
+    let first = (start / 60.0).ceil() as u32;
+    (first..=(end / 60.0) as u32)
+        .filter(|minute| minute % step == 0)
+        .collect()
+}
+
+/// Zeroes read as "--", so a weapon picked up late or one that never fires
+/// doesn't look like it failed.
+fn cell(value: u32) -> String {
+    if value == 0 {
+        "--".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn spawn_weapon_breakdown(mut commands: Commands, fonts: Res<FontAssets>, stats: Res<RunStats>) {
+    if stats.weapons.is_empty() {
+        return;
+    }
+
+    let mut rows = vec![COLUMNS.map(String::from)];
+    for (kind, weapon) in &stats.weapons {
+        let accuracy = weapon
+            .accuracy()
+            .map_or("--".to_string(), |acc| format!("{:.0}%", acc * 100.0));
+        rows.push([
+            format!("{} {}", kind.icon(), kind.id()),
+            cell(weapon.shots),
+            accuracy,
+            cell(weapon.misses),
+            cell(weapon.damage),
+            cell(weapon.kills),
+        ]);
+    }
+
+    let root = spawn_state_ui_root(&mut commands, GameState::GameOver);
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(5.0),
+                right: Val::Percent(3.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            ChildOf(root),
+        ))
+        .with_children(|table| {
+            for (i, row) in rows.into_iter().enumerate() {
+                let color = if i == 0 {
+                    Color::srgb(0.7, 0.7, 0.7)
+                } else {
+                    Color::WHITE
+                };
+                table.spawn(Node::default()).with_children(|line| {
+                    for value in row {
+                        line.spawn((
+                            Text::new(value),
+                            fonts.body(16.0),
+                            TextColor(color),
+                            Node {
+                                width: Val::Px(CELL_WIDTH),
+                                ..default()
+                            },
+                        ));
+                    }
+                });
+            }
+        });
+}
+
+/// Score and kills over the run as two bar strips, each scaled to its own
+/// peak, over an axis marked in minutes. Both only ever go up, so a stretch
+/// where a strip flattens out is where the run stalled.
+fn spawn_timeline_graph(mut commands: Commands, fonts: Res<FontAssets>, stats: Res<RunStats>) {
+    let timeline = &stats.timeline;
+    let root = spawn_state_ui_root(&mut commands, GameState::GameOver);
+    let panel = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(5.0),
+                left: Val::Percent(3.0),
+                width: Val::Px(GRAPH_WIDTH),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ChildOf(root),
+        ))
+        .id();
+
+    if timeline.len() < MIN_GRAPH_SAMPLES {
+        commands.spawn((
+            Text::new("Too short a run to chart"),
+            fonts.body(16.0),
+            TextColor(AXIS_COLOR),
+            ChildOf(panel),
+        ));
+        return;
+    }
+
+    for (label, samples, color) in [
+        ("Score", &timeline.score, SCORE_COLOR),
+        ("Kills", &timeline.kills, KILLS_COLOR),
+    ] {
+        let points = downsample(samples, MAX_GRAPH_POINTS);
+        let peak = points.iter().copied().max().unwrap_or(0).max(1) as f32;
+        commands.spawn((
+            Text::new(format!("{label}: {}", points.last().copied().unwrap_or(0))),
+            fonts.body(16.0),
+            TextColor(color),
+            ChildOf(panel),
+        ));
+        let strip = commands
+            .spawn((
+                Node {
+                    width: Val::Px(GRAPH_WIDTH),
+                    height: Val::Px(GRAPH_HEIGHT),
+                    align_items: AlignItems::FlexEnd,
+                    border: UiRect::bottom(Val::Px(1.0)),
+                    ..default()
+                },
+                BorderColor::all(AXIS_COLOR),
+                ChildOf(panel),
+            ))
+            .id();
+        let bar_width = GRAPH_WIDTH / points.len() as f32;
+        for value in points {
+            commands.spawn((
+                Node {
+                    width: Val::Px(bar_width),
+                    height: Val::Percent(value as f32 / peak * 100.0),
+                    ..default()
+                },
+                BackgroundColor(color),
+                ChildOf(strip),
+            ));
+        }
+    }
+
+    let (start, end) = timeline.span_secs();
+    let axis = commands
+        .spawn((
+            Node {
+                width: Val::Px(GRAPH_WIDTH),
+                height: Val::Px(16.0),
+                ..default()
+            },
+            ChildOf(panel),
+        ))
+        .id();
+    for minute in minute_marks(start, end) {
+        let at = (minute as f32 * 60.0 - start) / (end - start);
+        commands.spawn((
+            Text::new(format!("{minute}m")),
+            fonts.body(12.0),
+            TextColor(AXIS_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(at * 100.0),
+                ..default()
+            },
+            ChildOf(axis),
+        ));
+    }
+}
@@ -0,0 +1,461 @@
+use std::str::FromStr;
+
+use bevy::prelude::*;
+
+use crate::build_hud::BuildChanged;
+use crate::challenge::{RunMode, RunRules, daily_modifiers};
+use crate::difficulty::DifficultyConfig;
+use crate::fonts::FontAssets;
+use crate::levelup::Experience;
+use crate::sharecode;
+use crate::storage::Persistence;
+use crate::unlocks::RunRecord;
+use crate::upgrades::UpgradePool;
+use crate::weapons::{FireMode, TargetingMode, WeaponKind, WeaponSlots};
+use crate::weekly::{self, IsoWeek};
+use crate::{
+    Curse, Enemy, EnemyType, GameState, Gold, Health, PlayState, Player, PlayerStats, ResetSet,
+    Score, layers, spawn_enemy, spawn_state_ui_root,
+};
+
+const RUN_KEY: &str = "run";
+/// Bumped whenever the format changes; older saves are thrown away.
+const SAVE_VERSION: u32 = 1;
+/// Only the closest enemies are kept; the director refills the rest.
+const MAX_SAVED_ENEMIES: usize = 60;
+
+pub struct RunSavePlugin;
+
+impl Plugin for RunSavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunSuspended>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                clear_suspended.in_set(ResetSet),
+            )
+            .add_systems(
+                Update,
+                save_and_quit.run_if(in_state(PlayState::Running).or(in_state(PlayState::Paused))),
+            )
+            .add_systems(OnEnter(GameState::GameOver), spawn_continue_prompt)
+            .add_systems(Update, continue_run.run_if(in_state(GameState::GameOver)))
+            // The first frame of the run, once `ResetSet` and every `OnEnter` setup have run
+            .add_systems(
+                Update,
+                resume_run.run_if(in_state(GameState::Playing).and(resource_exists::<ResumedRun>)),
+            );
+    }
+}
+
+/// Set while leaving a run through Save & Quit, so the results screen does
+/// not score a run that is coming back.
+#[derive(Resource, Default)]
+pub struct RunSuspended(pub bool);
+
+/// A loaded save waiting to be laid over the freshly set up run.
+#[derive(Resource)]
+struct ResumedRun(RunSnapshot);
+
+#[derive(Component)]
+struct ContinuePrompt;
+
+/// The essentials of a run in progress. Everything else (buffs, projectiles,
+/// pickups, the spawn plan) starts fresh on resume.
+struct RunSnapshot {
+    mode: RunMode,
+    score: u32,
+    gold: u32,
+    curse: u32,
+    secs: f32,
+    kills: u64,
+    xp: f32,
+    level: u32,
+    health: i32,
+    position: Vec2,
+    stats: PlayerStats,
+    weapons: Vec<(WeaponKind, u32, FireMode, TargetingMode)>,
+    upgrades: Vec<(String, u32)>,
+    /// Kind, position, current and max health.
+    enemies: Vec<(EnemyType, Vec2, i32, i32)>,
+}
+
+fn field<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("bad value: {value}"))
+}
+
+impl RunSnapshot {
+    /// `name=value` lines behind a version line; weapons, upgrades and
+    /// enemies repeat their line once per entry.
+    fn to_text(&self) -> String {
+        let stats = &self.stats;
+        let mode = match &self.mode {
+            RunMode::Normal => "normal".to_string(),
+            RunMode::Daily { day, .. } => format!("daily:{day}"),
+            RunMode::Weekly(challenge) => format!("weekly:{}", challenge.week.id()),
+            RunMode::Shared { modifiers } => format!("shared:{}", sharecode::encode(modifiers)),
+        };
+        let mut lines = vec![
+            format!("version={SAVE_VERSION}"),
+            format!("mode={mode}"),
+            format!("score={}", self.score),
+            format!("gold={}", self.gold),
+            format!("curse={}", self.curse),
+            format!("secs={}", self.secs),
+            format!("kills={}", self.kills),
+            format!("xp={}", self.xp),
+            format!("level={}", self.level),
+            format!("health={}", self.health),
+            format!("position={},{}", self.position.x, self.position.y),
+            format!("move_speed={}", stats.move_speed),
+            format!("bullet_speed={}", stats.bullet_speed),
+            format!("bullet_damage={}", stats.bullet_damage),
+            format!("max_health={}", stats.max_health),
+            format!("luck={}", stats.luck),
+            format!("lifesteal={}", stats.lifesteal),
+            format!("rerolls={}", stats.rerolls),
+            format!("ricochet={}", stats.ricochet),
+            format!("bullet_parry={}", stats.bullet_parry),
+            format!("heat_per_shot={}", stats.heat_per_shot),
+            format!("heat_decay={}", stats.heat_decay),
+            format!("max_heat={}", stats.max_heat),
+            format!("projectile_count={}", stats.projectile_count),
+        ];
+        for (kind, level, mode, targeting) in &self.weapons {
+            lines.push(format!(
+                "weapon={},{level},{},{}",
+                kind.id(),
+                mode.id(),
+                targeting.id()
+            ));
+        }
+        for (id, stacks) in &self.upgrades {
+            lines.push(format!("upgrade={id},{stacks}"));
+        }
+        for (kind, position, current, max) in &self.enemies {
+            lines.push(format!(
+                "enemy={},{},{},{current},{max}",
+                kind.id(),
+                position.x,
+                position.y
+            ));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    /// Any unknown version, unknown name or bad value rejects the whole save;
+    /// a half-restored run would be worse than none.
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+        let version = lines.next().unwrap_or_default();
+        if version != format!("version={SAVE_VERSION}") {
+            return Err(format!("unsupported save ({version})"));
+        }
+
+        let mut snapshot = Self {
+            mode: RunMode::Normal,
+            score: 0,
+            gold: 0,
+            curse: 0,
+            secs: 0.0,
+            kills: 0,
+            xp: 0.0,
+            level: 1,
+            health: 1,
+            position: Vec2::ZERO,
+            stats: PlayerStats::default(),
+            weapons: Vec::new(),
+            upgrades: Vec::new(),
+            enemies: Vec::new(),
+        };
+        for line in lines {
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("bad line: {line}"))?;
+            let parts: Vec<&str> = value.split(',').collect();
+            let stats = &mut snapshot.stats;
+            match (name.trim(), parts.as_slice()) {
+                ("mode", ["normal"]) => snapshot.mode = RunMode::Normal,
+                ("mode", [daily]) if daily.starts_with("daily:") => {
+                    let day = field(&daily["daily:".len()..])?;
+                    snapshot.mode = RunMode::Daily {
+                        day,
+                        modifiers: daily_modifiers(day),
+                    };
+                }
+                // Only the week is kept; the rest comes from the schedule
+                ("mode", [weekly]) if weekly.starts_with("weekly:") => {
+                    let week = IsoWeek::parse(&weekly["weekly:".len()..])
+                        .ok_or_else(|| format!("bad week: {weekly}"))?;
+                    let challenge = weekly::scheduled(week)
+                        .ok_or_else(|| format!("{} is no longer scheduled", week.id()))?;
+                    snapshot.mode = RunMode::Weekly(challenge);
+                }
+                ("mode", [shared]) if shared.starts_with("shared:") => {
+                    snapshot.mode = RunMode::Shared {
+                        modifiers: sharecode::decode(&shared["shared:".len()..])?,
+                    };
+                }
+                ("score", _) => snapshot.score = field(value)?,
+                ("gold", _) => snapshot.gold = field(value)?,
+                ("curse", _) => snapshot.curse = field(value)?,
+                ("secs", _) => snapshot.secs = field(value)?,
+                ("kills", _) => snapshot.kills = field(value)?,
+                ("xp", _) => snapshot.xp = field(value)?,
+                ("level", _) => snapshot.level = field(value)?,
+                ("health", _) => snapshot.health = field(value)?,
+                ("position", [x, y]) => snapshot.position = Vec2::new(field(x)?, field(y)?),
+                ("move_speed", _) => stats.move_speed = field(value)?,
+                ("bullet_speed", _) => stats.bullet_speed = field(value)?,
+                ("bullet_damage", _) => stats.bullet_damage = field(value)?,
+                ("max_health", _) => stats.max_health = field(value)?,
+                ("luck", _) => stats.luck = field(value)?,
+                ("lifesteal", _) => stats.lifesteal = field(value)?,
+                ("rerolls", _) => stats.rerolls = field(value)?,
+                ("ricochet", _) => stats.ricochet = field(value)?,
+                ("bullet_parry", _) => stats.bullet_parry = field(value)?,
+                ("heat_per_shot", _) => stats.heat_per_shot = field(value)?,
+                ("heat_decay", _) => stats.heat_decay = field(value)?,
+                ("max_heat", _) => stats.max_heat = field(value)?,
+                ("projectile_count", _) => stats.projectile_count = field(value)?,
+                // Saves from before fire modes have no third part, and from
+                // before targeting no fourth
+                ("weapon", [kind, level, rest @ ..]) if rest.len() <= 2 => {
+                    let kind = WeaponKind::from_id(kind)
+                        .ok_or_else(|| format!("unknown weapon: {kind}"))?;
+                    let mode = match rest.first() {
+                        Some(mode) => FireMode::from_id(mode)
+                            .ok_or_else(|| format!("unknown fire mode: {mode}"))?,
+                        None => FireMode::default(),
+                    };
+                    let targeting = match rest.get(1) {
+                        Some(targeting) => TargetingMode::from_id(targeting)
+                            .ok_or_else(|| format!("unknown targeting: {targeting}"))?,
+                        None => TargetingMode::default(),
+                    };
+                    snapshot
+                        .weapons
+                        .push((kind, field(level)?, mode, targeting));
+                }
+                ("upgrade", [id, stacks]) => {
+                    snapshot.upgrades.push((id.to_string(), field(stacks)?));
+                }
+                ("enemy", [kind, x, y, current, max]) => {
+                    let kind =
+                        EnemyType::from_id(kind).ok_or_else(|| format!("unknown enemy: {kind}"))?;
+                    snapshot.enemies.push((
+                        kind,
+                        Vec2::new(field(x)?, field(y)?),
+                        field(current)?,
+                        field(max)?,
+                    ));
+                }
+                _ => return Err(format!("bad line: {line}")),
+            }
+        }
+        if snapshot.weapons.is_empty() {
+            return Err("no weapons".to_string());
+        }
+        Ok(snapshot)
+    }
+}
+
+fn clear_suspended(mut suspended: ResMut<RunSuspended>) {
+    suspended.0 = false;
+}
+
+/// F9, in the run or from the pause screen, writes the run out and leaves to the results screen without scoring it.
+fn save_and_quit(
+    keys: Res<ButtonInput<KeyCode>>,
+    mode: Res<RunMode>,
+    score: Res<Score>,
+    gold: Res<Gold>,
+    curse: Res<Curse>,
+    record: Res<RunRecord>,
+    experience: Res<Experience>,
+    stats: Res<PlayerStats>,
+    pool: Res<UpgradePool>,
+    player: Query<(&Transform, &Health, &WeaponSlots), With<Player>>,
+    enemies: Query<(&Transform, &Health, &Enemy)>,
+    mut suspended: ResMut<RunSuspended>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut persistence: ResMut<Persistence>,
+) {
+    if !keys.just_pressed(KeyCode::F9) {
+        return;
+    }
+    let Ok((player_tf, health, weapons)) = player.single() else {
+        return;
+    };
+    let position = player_tf.translation.truncate();
+
+    let mut nearby: Vec<_> = enemies
+        .iter()
+        .map(|(tf, health, enemy)| {
+            let at = tf.translation.truncate();
+            (enemy.kind, at, health.current, health.max)
+        })
+        .collect();
+    nearby.sort_by(|a, b| {
+        a.1.distance_squared(position)
+            .total_cmp(&b.1.distance_squared(position))
+    });
+    nearby.truncate(MAX_SAVED_ENEMIES);
+
+    let snapshot = RunSnapshot {
+        mode: mode.clone(),
+        score: score.0,
+        gold: gold.0,
+        curse: curse.0,
+        secs: record.secs(),
+        kills: record.kills(),
+        xp: experience.xp,
+        level: experience.level,
+        health: health.current,
+        position,
+        stats: stats.clone(),
+        weapons: weapons
+            .iter()
+            .map(|w| (w.kind, w.level, w.mode(), w.targeting))
+            .collect(),
+        upgrades: pool.taken().map(|(id, n)| (id.to_string(), n)).collect(),
+        enemies: nearby,
+    };
+    persistence.save(RUN_KEY, snapshot.to_text());
+    suspended.0 = true;
+    next_state.set(GameState::GameOver);
+}
+
+fn spawn_continue_prompt(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    persistence: Res<Persistence>,
+) {
+    if persistence.load(RUN_KEY).is_none() {
+        return;
+    }
+    let root = spawn_state_ui_root(&mut commands, GameState::GameOver);
+    commands.spawn((
+        Text::new("Press Enter to continue your saved run"),
+        fonts.body(24.0),
+        TextColor(Color::srgb(0.6, 1.0, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(5.0),
+            left: Val::Percent(25.0),
+            ..default()
+        },
+        ContinuePrompt,
+        ChildOf(root),
+    ));
+}
+
+/// The save is deleted as soon as it is read, whether or not it loads, so a
+/// run can only be continued once.
+fn continue_run(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut mode: ResMut<RunMode>,
+    prompt: Query<Entity, With<ContinuePrompt>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut persistence: ResMut<Persistence>,
+) {
+    if !keys.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    let Some(text) = persistence.load(RUN_KEY) else {
+        return;
+    };
+    persistence.remove(RUN_KEY);
+
+    match RunSnapshot::parse(&text) {
+        Ok(snapshot) => {
+            *mode = snapshot.mode.clone();
+            commands.insert_resource(ResumedRun(snapshot));
+            next_state.set(GameState::Resetting);
+        }
+        Err(err) => {
+            warn!("discarding the run save: {err}");
+            for entity in &prompt {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+/// Lays the save over the new run. Weapons are re-added and enemies
+/// respawned through the normal helpers, so they pick up current balance.
+fn resume_run(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    resumed: Res<ResumedRun>,
+    mut score: ResMut<Score>,
+    mut gold: ResMut<Gold>,
+    mut curse: ResMut<Curse>,
+    rules: Res<RunRules>,
+    difficulty: Res<DifficultyConfig>,
+    mut record: ResMut<RunRecord>,
+    mut experience: ResMut<Experience>,
+    mut stats: ResMut<PlayerStats>,
+    mut pool: ResMut<UpgradePool>,
+    mut build_changed: MessageWriter<BuildChanged>,
+    mut player: Query<(&mut Transform, &mut Health, &mut WeaponSlots), With<Player>>,
+) {
+    let Ok((mut player_tf, mut health, mut weapons)) = player.single_mut() else {
+        return;
+    };
+    commands.remove_resource::<ResumedRun>();
+    let snapshot = &resumed.0;
+
+    score.0 = snapshot.score;
+    gold.0 = snapshot.gold;
+    curse.0 = snapshot.curse;
+    record.restore(snapshot.secs, snapshot.kills);
+    experience.xp = snapshot.xp;
+    experience.level = snapshot.level;
+    *stats = snapshot.stats.clone();
+
+    pool.reset();
+    for (id, stacks) in &snapshot.upgrades {
+        pool.restore(id, *stacks);
+    }
+
+    let (first, first_level, ..) = snapshot.weapons[0];
+    *weapons = WeaponSlots::new(first);
+    for _ in 1..first_level {
+        weapons.add_or_level(first);
+    }
+    for &(kind, level, ..) in &snapshot.weapons[1..] {
+        for _ in 0..level {
+            weapons.add_or_level(kind);
+        }
+    }
+    for &(kind, _, mode, targeting) in &snapshot.weapons {
+        if let Some(weapon) = weapons.get_mut(kind) {
+            weapon.set_mode(mode);
+            weapon.targeting = targeting;
+        }
+    }
+
+    build_changed.write(BuildChanged);
+
+    health.max = stats.max_health;
+    health.current = snapshot.health.min(health.max).max(1);
+    player_tf.translation = snapshot.position.extend(layers::PLAYER);
+
+    for &(kind, position, current, max) in &snapshot.enemies {
+        let enemy = spawn_enemy(
+            &mut commands,
+            &fonts,
+            position,
+            kind,
+            &curse,
+            &rules,
+            &difficulty,
+        );
+        commands.entity(enemy).insert(Health { current, max });
+    }
+    info!("resumed a saved run at {:.0}s", snapshot.secs);
+}
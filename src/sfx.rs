@@ -0,0 +1,305 @@
+//! Sound effects and the mixing between them. Each kind of sound may only
+//! have so many copies playing at once, the newest pushing out the oldest,
+//! so forty kills in one frame never stack into forty kill sounds. Every
+//! copy plays at a slightly different pitch so repeats don't phase.
+//!
+//! A priority sound ducks the music and the ordinary sounds under it for
+//! as long as it lasts, and they come back up gradually afterwards.
+//!
+//! Sounds are read from `assets/sfx/<id>.ogg`; any that are missing stay
+//! silent.
+
+use bevy::audio::Volume;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::boss::Boss;
+use crate::{DamageEvent, EnemyKilledEvent, GameState, PlayState, Settings, Team};
+
+const SFX_VOLUME: f32 = 0.8;
+/// Each copy's playback speed, and so its pitch, is picked in this range.
+const PITCH_VARIATION: f32 = 0.06;
+/// How far a priority sound pulls everything else down.
+const DUCK_DEPTH: f32 = 0.4;
+const DUCK_RECOVER_SECS: f32 = 0.5;
+
+pub struct SfxPlugin;
+
+impl Plugin for SfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioDirector>()
+            .add_systems(Startup, load_sounds)
+            .add_systems(OnEnter(PlayState::LevelUp), level_up_sound)
+            .add_systems(OnEnter(GameState::GameOver), game_over_sound)
+            .add_systems(
+                Update,
+                (hit_and_kill_sounds, boss_roar).run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(Update, (forget_finished_sounds, duck_sounds).chain());
+    }
+}
+
+/// Also on each playing copy, naming what it is.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SoundKind {
+    Hit,
+    Kill,
+    BossRoar,
+    LevelUp,
+    GameOver,
+}
+
+const ALL_SOUNDS: [SoundKind; 5] = [
+    SoundKind::Hit,
+    SoundKind::Kill,
+    SoundKind::BossRoar,
+    SoundKind::LevelUp,
+    SoundKind::GameOver,
+];
+
+impl SoundKind {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn id(self) -> &'static str {
+        match self {
+            SoundKind::Hit => "hit",
+            SoundKind::Kill => "kill",
+            SoundKind::BossRoar => "boss_roar",
+            SoundKind::LevelUp => "level_up",
+            SoundKind::GameOver => "game_over",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        ALL_SOUNDS.into_iter().find(|kind| kind.id() == id)
+    }
+
+    fn default_cap(self) -> usize {
+        match self {
+            SoundKind::Hit => 4,
+            SoundKind::Kill => 2,
+            SoundKind::BossRoar | SoundKind::LevelUp | SoundKind::GameOver => 1,
+        }
+    }
+
+    /// How long a priority sound holds everything else down; `None` for
+    /// sounds that get ducked themselves. Held for a set time rather than
+    /// the clip's length, which isn't known until it has decoded.
+    fn duck_secs(self) -> Option<f32> {
+        match self {
+            SoundKind::Hit | SoundKind::Kill => None,
+            SoundKind::BossRoar => Some(1.5),
+            SoundKind::LevelUp => Some(1.0),
+            SoundKind::GameOver => Some(2.0),
+        }
+    }
+}
+
+/// Copies of each kind allowed at once. Kept in the settings as
+/// `sound_cap_<id>` lines.
+#[derive(Clone, Copy)]
+pub struct SoundCaps([usize; ALL_SOUNDS.len()]);
+
+impl Default for SoundCaps {
+    fn default() -> Self {
+        Self(ALL_SOUNDS.map(SoundKind::default_cap))
+    }
+}
+
+impl SoundCaps {
+    const PREFIX: &str = "sound_cap_";
+
+    pub fn get(&self, kind: SoundKind) -> usize {
+        self.0[kind.index()]
+    }
+
+    /// Takes one settings line if it is a cap, keeping the old value for a
+    /// count that doesn't parse. Returns whether the line was one.
+    pub fn read_setting(&mut self, name: &str, value: &str) -> bool {
+        let Some(kind) = name.strip_prefix(Self::PREFIX).and_then(SoundKind::from_id) else {
+            return false;
+        };
+        if let Ok(cap) = value.parse::<usize>() {
+            self.0[kind.index()] = cap;
+        }
+        true
+    }
+
+    pub fn settings_lines(&self) -> String {
+        ALL_SOUNDS
+            .iter()
+            .map(|&kind| format!("{}{}={}\n", Self::PREFIX, kind.id(), self.get(kind)))
+            .collect()
+    }
+}
+
+#[derive(Resource)]
+struct SoundHandles([Handle<AudioSource>; ALL_SOUNDS.len()]);
+
+/// The copies of each kind still playing, oldest first, and the duck
+/// envelope.
+#[derive(Resource, Default)]
+pub struct AudioDirector {
+    live: [Vec<Entity>; ALL_SOUNDS.len()],
+    /// Seconds left at full duck.
+    duck_hold: f32,
+    /// 0 when nothing is ducked, 1 at full duck.
+    duck_level: f32,
+    /// The gain last set on ducked sounds, so it's only sent on a change.
+    applied_gain: f32,
+}
+
+impl AudioDirector {
+    /// What the music and ordinary sounds are scaled by right now.
+    pub fn gain(&self) -> f32 {
+        1.0 - DUCK_DEPTH * self.duck_level
+    }
+
+    fn play(
+        &mut self,
+        commands: &mut Commands,
+        handles: &SoundHandles,
+        asset_server: &AssetServer,
+        caps: &SoundCaps,
+        kind: SoundKind,
+    ) {
+        let handle = &handles.0[kind.index()];
+        if !asset_server.is_loaded_with_dependencies(handle) {
+            return;
+        }
+        let cap = caps.get(kind);
+        if cap == 0 {
+            return;
+        }
+        let volume = match kind.duck_secs() {
+            Some(secs) => {
+                self.duck_hold = self.duck_hold.max(secs);
+                self.duck_level = 1.0;
+                SFX_VOLUME
+            }
+            None => SFX_VOLUME * self.gain(),
+        };
+
+        let live = &mut self.live[kind.index()];
+        while live.len() >= cap {
+            let oldest = live.remove(0);
+            commands.entity(oldest).try_despawn();
+        }
+        let speed = thread_rng().gen_range(1.0 - PITCH_VARIATION..=1.0 + PITCH_VARIATION);
+        let entity = commands
+            .spawn((
+                AudioPlayer::new(handle.clone()),
+                PlaybackSettings::DESPAWN
+                    .with_volume(Volume::Linear(volume))
+                    .with_speed(speed),
+                kind,
+            ))
+            .id();
+        live.push(entity);
+    }
+}
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundHandles(
+        ALL_SOUNDS.map(|kind| asset_server.load(format!("sfx/{}.ogg", kind.id()))),
+    ));
+}
+
+/// Everything the sound systems need to play one.
+#[derive(SystemParam)]
+struct SoundPlayer<'w, 's> {
+    commands: Commands<'w, 's>,
+    director: ResMut<'w, AudioDirector>,
+    handles: Res<'w, SoundHandles>,
+    asset_server: Res<'w, AssetServer>,
+    settings: Res<'w, Settings>,
+}
+
+impl SoundPlayer<'_, '_> {
+    fn play(&mut self, kind: SoundKind) {
+        self.director.play(
+            &mut self.commands,
+            &self.handles,
+            &self.asset_server,
+            &self.settings.sound_caps,
+            kind,
+        );
+    }
+}
+
+fn hit_and_kill_sounds(
+    mut sounds: SoundPlayer,
+    mut damage: MessageReader<DamageEvent>,
+    mut kills: MessageReader<EnemyKilledEvent>,
+) {
+    // The caps keep only the newest few anyway, so past that many per frame
+    // there is nothing to gain from spawning more
+    let hits = damage
+        .read()
+        .filter(|hit| hit.source_team == Team::Player)
+        .count()
+        .min(sounds.settings.sound_caps.get(SoundKind::Hit));
+    let killed = kills
+        .read()
+        .count()
+        .min(sounds.settings.sound_caps.get(SoundKind::Kill));
+    for _ in 0..hits {
+        sounds.play(SoundKind::Hit);
+    }
+    for _ in 0..killed {
+        sounds.play(SoundKind::Kill);
+    }
+}
+
+fn boss_roar(mut sounds: SoundPlayer, new_bosses: Query<(), Added<Boss>>) {
+    if !new_bosses.is_empty() {
+        sounds.play(SoundKind::BossRoar);
+    }
+}
+
+fn level_up_sound(mut sounds: SoundPlayer) {
+    sounds.play(SoundKind::LevelUp);
+}
+
+fn game_over_sound(mut sounds: SoundPlayer) {
+    sounds.play(SoundKind::GameOver);
+}
+
+/// Copies despawn themselves when they finish.
+fn forget_finished_sounds(
+    mut director: ResMut<AudioDirector>,
+    playing: Query<(), With<SoundKind>>,
+) {
+    for live in &mut director.live {
+        live.retain(|&entity| playing.contains(entity));
+    }
+}
+
+/// Runs the duck envelope on real time, and sets the ordinary sounds still
+/// playing to match. The music reads `AudioDirector::gain` itself.
+fn duck_sounds(
+    time: Res<Time>,
+    mut director: ResMut<AudioDirector>,
+    mut sinks: Query<(&SoundKind, &mut AudioSink)>,
+) {
+    if director.duck_hold > 0.0 {
+        director.duck_hold = (director.duck_hold - time.delta_secs()).max(0.0);
+    } else if director.duck_level > 0.0 {
+        director.duck_level =
+            (director.duck_level - time.delta_secs() / DUCK_RECOVER_SECS).max(0.0);
+    }
+
+    let gain = director.gain();
+    if gain == director.applied_gain {
+        return;
+    }
+    director.applied_gain = gain;
+    for (kind, mut sink) in &mut sinks {
+        if kind.duck_secs().is_none() {
+            sink.set_volume(Volume::Linear(SFX_VOLUME * gain));
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Short codes for sharing a run's setup. The results screen shows the code
+//! for the run just played, and K there takes one typed in and starts a run
+//! set up the same way.
+//!
+//! Runs are not seeded, so a code carries the setup only: which modifiers
+//! were on. It is a version byte and a modifier bitmask, written as hex.
+//! Bytes past the ones this version knows are ignored, so a later version
+//! can append fields without breaking old codes.
+
+use bevy::input::InputSystems;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+
+use crate::challenge::{ALL_MODIFIERS, RunMode, RunModifier};
+use crate::fonts::FontAssets;
+use crate::input::PlayerInputSystems;
+use crate::{GameState, spawn_state_ui_root};
+
+const CODE_VERSION: u8 = 1;
+const MAX_CODE_LEN: usize = 32;
+
+// The modifier mask is one byte; more modifiers need a new code version
+const _: () = assert!(ALL_MODIFIERS.len() <= u8::BITS as usize);
+
+pub struct ShareCodePlugin;
+
+impl Plugin for ShareCodePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CodeEntry>()
+            .add_systems(OnEnter(GameState::GameOver), spawn_share_code)
+            .add_systems(OnExit(GameState::GameOver), close_code_entry)
+            .add_systems(
+                PreUpdate,
+                capture_code_entry
+                    .after(InputSystems)
+                    .before(PlayerInputSystems)
+                    .run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(
+                Update,
+                update_code_entry_text.run_if(in_state(GameState::GameOver)),
+            );
+    }
+}
+
+/// The mask bit for the modifier at `index` in `ALL_MODIFIERS`.
+fn modifier_bit(index: usize) -> u8 {
+    u32::try_from(index)
+        .ok()
+        .and_then(|index| 1u8.checked_shl(index))
+        .unwrap_or(0)
+}
+
+/// The modifiers a run started with, as a code.
+pub fn encode(modifiers: &[RunModifier]) -> String {
+    let mask = ALL_MODIFIERS
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| modifiers.contains(m))
+        .fold(0u8, |mask, (i, _)| mask | modifier_bit(i));
+    [CODE_VERSION, mask]
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect()
+}
+
+/// Case, spaces and dashes don't matter. Errors are worded for the player.
+pub fn decode(code: &str) -> Result<Vec<RunModifier>, String> {
+    let digits: Vec<char> = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+    if digits.is_empty() {
+        return Err("Type a code first".to_string());
+    }
+    if !digits.len().is_multiple_of(2) || !digits.iter().all(char::is_ascii_hexdigit) {
+        return Err("That doesn't look like a run code".to_string());
+    }
+    let bytes: Vec<u8> = digits
+        .chunks(2)
+        .map(|pair| {
+            let high = pair[0].to_digit(16).unwrap_or(0);
+            let low = pair[1].to_digit(16).unwrap_or(0);
+            (high * 16 + low) as u8
+        })
+        .collect();
+
+    match bytes[0] {
+        CODE_VERSION => {}
+        v if v > CODE_VERSION => {
+            return Err("That code is from a newer version of the game".to_string());
+        }
+        _ => return Err("That doesn't look like a run code".to_string()),
+    }
+    let Some(&mask) = bytes.get(1) else {
+        return Err("That code is cut short".to_string());
+    };
+    let known = (0..ALL_MODIFIERS.len()).fold(0u8, |known, i| known | modifier_bit(i));
+    if mask & !known != 0 {
+        return Err("That code uses modifiers this version doesn't have".to_string());
+    }
+    Ok(ALL_MODIFIERS
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| mask & modifier_bit(i) != 0)
+        .map(|(_, &m)| m)
+        .collect())
+}
+
+/// The code being typed on the results screen, if the field is open.
+#[derive(Resource, Default)]
+struct CodeEntry {
+    open: bool,
+    line: String,
+    /// Why the last code was turned down.
+    error: Option<String>,
+}
+
+#[derive(Component)]
+struct CodeEntryText;
+
+fn spawn_share_code(mut commands: Commands, fonts: Res<FontAssets>, mode: Res<RunMode>) {
+    let code = encode(mode.modifiers());
+    info!("run code: {code}");
+
+    let root = spawn_state_ui_root(&mut commands, GameState::GameOver);
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Percent(5.0),
+                left: Val::Percent(3.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ChildOf(root),
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(format!("Run code: {code}")),
+                fonts.body(20.0),
+                TextColor(Color::WHITE),
+            ));
+            panel.spawn((
+                Text::new(""),
+                fonts.body(18.0),
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                CodeEntryText,
+            ));
+        });
+}
+
+fn close_code_entry(mut entry: ResMut<CodeEntry>) {
+    *entry = CodeEntry::default();
+}
+
+/// K opens the field. While it's open it takes every key for itself, the
+/// way the dev console does, so typing a code never restarts the run.
+fn capture_code_entry(
+    mut events: MessageReader<KeyboardInput>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut entry: ResMut<CodeEntry>,
+    mut mode: ResMut<RunMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !entry.open {
+        if keys.just_pressed(KeyCode::KeyK) {
+            entry.open = true;
+            // The K that opened it is already in the queue
+            events.clear();
+            keys.reset_all();
+        }
+        return;
+    }
+
+    for event in events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Escape => *entry = CodeEntry::default(),
+            Key::Enter => match decode(&entry.line) {
+                Ok(modifiers) => {
+                    *mode = RunMode::Shared { modifiers };
+                    next_state.set(GameState::Resetting);
+                }
+                Err(err) => entry.error = Some(err),
+            },
+            Key::Backspace => {
+                entry.line.pop();
+                entry.error = None;
+            }
+            _ => {
+                if let Some(text) = &event.text
+                    && entry.line.len() < MAX_CODE_LEN
+                {
+                    entry.line.extend(text.chars().filter(|c| !c.is_control()));
+                    entry.error = None;
+                }
+            }
+        }
+    }
+    keys.reset_all();
+}
+
+fn update_code_entry_text(entry: Res<CodeEntry>, mut text: Query<(&mut Text, Ref<CodeEntryText>)>) {
+    if !entry.is_changed() && !text.iter().any(|(_, marker)| marker.is_added()) {
+        return;
+    }
+    let line = if !entry.open {
+        "Press K to play from a code".to_string()
+    } else if let Some(err) = &entry.error {
+        format!("> {}_\n{err}", entry.line)
+    } else {
+        format!("> {}_\n[Enter] Play   [Esc] Cancel", entry.line)
+    };
+    for (mut text, _) in &mut text {
+        text.0 = line.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_modifier_set_round_trips() {
+        for bits in 0..1u32 << ALL_MODIFIERS.len() {
+            let modifiers: Vec<RunModifier> = ALL_MODIFIERS
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| bits & 1 << i != 0)
+                .map(|(_, &m)| m)
+                .collect();
+            assert_eq!(decode(&encode(&modifiers)), Ok(modifiers));
+        }
+    }
+
+    #[test]
+    fn decode_ignores_case_spaces_dashes_and_extra_bytes() {
+        let code = encode(&[RunModifier::NoShields, RunModifier::Cursed]);
+        let typed = format!("{}-{} ff", &code[..2], code[2..].to_lowercase());
+        assert_eq!(
+            decode(&typed),
+            Ok(vec![RunModifier::NoShields, RunModifier::Cursed])
+        );
+    }
+
+    #[test]
+    fn decode_rejects_bad_codes() {
+        for code in ["", "  ", "0", "01G0", "xyz1", "00 00", "02 00", "01"] {
+            assert!(decode(code).is_err(), "{code:?} decoded");
+        }
+    }
+
+    #[test]
+    fn decode_rejects_unknown_modifiers() {
+        let unknown = 1u8 << ALL_MODIFIERS.len();
+        assert!(decode(&format!("{CODE_VERSION:02X}{unknown:02X}")).is_err());
+        assert!(decode(&format!("{CODE_VERSION:02X}FF")).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::loot::{Drop, LootSpawner};
+use crate::message_log::LogMessage;
+use crate::{
+    BuffStat, Buffs, Curse, GameState, GameTime, Health, InGameEntity, PlayState, Player,
+    PlayerStats, ResetSet, layers,
+};
+
+/// A new shrine appears this often, as long as none is alive; there is only
+/// ever the one.
+const SHRINE_INTERVAL_SECS: f32 = 90.0;
+const SHRINE_RADIUS: f32 = 24.0;
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.2, 0.1);
+
+pub struct ShrinePlugin;
+
+impl Plugin for ShrinePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ShrineTimer(Timer::from_seconds(
+            SHRINE_INTERVAL_SECS,
+            TimerMode::Repeating,
+        )))
+        .add_systems(
+            OnEnter(GameState::Resetting),
+            reset_shrine_timer.in_set(ResetSet),
+        )
+        .add_systems(
+            Update,
+            (spawn_shrines, touch_shrines)
+                .chain()
+                .run_if(in_state(PlayState::Running)),
+        )
+        .add_systems(OnEnter(PlayState::Shrine), spawn_shrine_ui)
+        .add_systems(Update, shrine_input.run_if(in_state(PlayState::Shrine)));
+    }
+}
+
+/// Everything a shrine offer is allowed to touch.
+struct ShrineTargets<'a> {
+    stats: &'a mut PlayerStats,
+    curse: &'a mut Curse,
+    /// Gold and experience, paid out through `LootSpawner` once the offer
+    /// is applied.
+    drops: &'a mut Vec<Drop>,
+    health: &'a mut Health,
+    buffs: &'a mut Buffs,
+}
+
+/// A blessing paired with its price.
+struct ShrineOffer {
+    description: &'static str,
+    weight: u32,
+    apply: fn(&mut ShrineTargets),
+}
+
+const SHRINE_OFFERS: &[ShrineOffer] = &[
+    ShrineOffer {
+        description: "Gain 50 XP, but +1 curse",
+        weight: 3,
+        apply: |t| {
+            t.drops.push(Drop::Xp(50.0));
+            t.curse.0 += 1;
+        },
+    },
+    ShrineOffer {
+        description: "Heal to full, but -10% move speed for 60s",
+        weight: 3,
+        apply: |t| {
+            t.health.current = t.health.max;
+            t.buffs.add(BuffStat::MoveSpeed, -0.1, 60.0);
+        },
+    },
+    ShrineOffer {
+        description: "+50 gold, but -15% move speed for 60s",
+        weight: 2,
+        apply: |t| {
+            t.drops.push(Drop::Gold(50));
+            t.buffs.add(BuffStat::MoveSpeed, -0.15, 60.0);
+        },
+    },
+    ShrineOffer {
+        description: "+1 max health, but +1 curse",
+        weight: 2,
+        apply: |t| {
+            t.stats.max_health += 1;
+            t.curse.0 += 1;
+        },
+    },
+    ShrineOffer {
+        description: "+1 bullet damage, but +2 curse",
+        weight: 1,
+        apply: |t| {
+            t.stats.bullet_damage += 1;
+            t.curse.0 += 2;
+        },
+    },
+];
+
+/// Weighted draw of two distinct offers.
+fn draw_offers(rng: &mut impl Rng) -> [&'static ShrineOffer; 2] {
+    let first = SHRINE_OFFERS.choose_weighted(rng, |o| o.weight).unwrap();
+    let rest: Vec<&'static ShrineOffer> = SHRINE_OFFERS
+        .iter()
+        .filter(|o| o.description != first.description)
+        .collect();
+    let second = rest.choose_weighted(rng, |o| o.weight).unwrap();
+    [first, second]
+}
+
+#[derive(Resource)]
+struct ShrineTimer(Timer);
+
+#[derive(Component)]
+pub struct Shrine {
+    offers: [&'static ShrineOffer; 2],
+    /// Cleared when the player walks away from the choice, so standing on the
+    /// shrine doesn't reopen it every frame.
+    armed: bool,
+}
+
+#[derive(Component, Clone, Copy)]
+enum ShrineButton {
+    Pick(usize),
+    Leave,
+}
+
+fn reset_shrine_timer(mut timer: ResMut<ShrineTimer>) {
+    timer.0.reset();
+}
+
+fn spawn_shrines(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    time: GameTime,
+    mut timer: ResMut<ShrineTimer>,
+    player: Query<&Transform, With<Player>>,
+    shrines: Query<(), With<Shrine>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() || !shrines.is_empty() {
+        return;
+    }
+    let Ok(player) = player.single() else {
+        return;
+    };
+
+    let mut rng = thread_rng();
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    let distance = rng.gen_range(200.0..400.0);
+    let position = player.translation.truncate() + Vec2::from_angle(angle) * distance;
+
+    commands.spawn((
+        Text2d::new("#"),
+        fonts.body(28.0),
+        TextColor(Color::srgb(1.0, 0.8, 0.3)),
+        Transform::from_translation(position.extend(layers::PICKUPS)),
+        Shrine {
+            offers: draw_offers(&mut rng),
+            armed: true,
+        },
+        InGameEntity,
+    ));
+}
+
+fn touch_shrines(
+    player: Query<&Transform, With<Player>>,
+    mut shrines: Query<(&Transform, &mut Shrine)>,
+    mut next_state: ResMut<NextState<PlayState>>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let player_pos = player.translation.truncate();
+
+    for (tf, mut shrine) in &mut shrines {
+        let touching = tf.translation.truncate().distance(player_pos) <= SHRINE_RADIUS;
+        if !touching {
+            shrine.armed = true;
+        } else if shrine.armed {
+            next_state.set(PlayState::Shrine);
+        }
+    }
+}
+
+fn spawn_shrine_ui(mut commands: Commands, fonts: Res<FontAssets>, shrine: Single<&Shrine>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            DespawnOnExit(PlayState::Shrine),
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Text::new("A SHRINE"),
+                fonts.display(40.0),
+                TextColor(Color::srgb(1.0, 0.8, 0.3)),
+            ));
+
+            for (i, offer) in shrine.offers.iter().enumerate() {
+                spawn_button(
+                    root,
+                    &fonts,
+                    format!("{}. {}", i + 1, offer.description),
+                    ShrineButton::Pick(i),
+                );
+            }
+            spawn_button(
+                root,
+                &fonts,
+                "Leave it for now [Esc]".to_string(),
+                ShrineButton::Leave,
+            );
+        });
+}
+
+fn shrine_input(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Query<(&Interaction, &ShrineButton), Changed<Interaction>>,
+    mut shrines: Query<(Entity, &mut Shrine)>,
+    mut player: Query<(&Transform, &mut Health, &mut Buffs), With<Player>>,
+    mut stats: ResMut<PlayerStats>,
+    mut payout: ParamSet<(ResMut<Curse>, LootSpawner)>,
+    mut log: MessageWriter<LogMessage>,
+    mut next_state: ResMut<NextState<PlayState>>,
+) {
+    let clicked = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| *button);
+
+    let pressed = if keys.just_pressed(KeyCode::Digit1) {
+        Some(ShrineButton::Pick(0))
+    } else if keys.just_pressed(KeyCode::Digit2) {
+        Some(ShrineButton::Pick(1))
+    } else if keys.just_pressed(KeyCode::Escape) {
+        Some(ShrineButton::Leave)
+    } else {
+        None
+    };
+
+    let Some(action) = clicked.or(pressed) else {
+        return;
+    };
+    let (Ok((entity, mut shrine)), Ok((player_tf, mut health, mut buffs))) =
+        (shrines.single_mut(), player.single_mut())
+    else {
+        next_state.set(PlayState::Running);
+        return;
+    };
+
+    match action {
+        ShrineButton::Pick(index) => {
+            log.write(LogMessage(format!(
+                "Shrine: {}",
+                shrine.offers[index].description
+            )));
+            let mut drops = Vec::new();
+            (shrine.offers[index].apply)(&mut ShrineTargets {
+                stats: &mut stats,
+                curse: &mut payout.p0(),
+                drops: &mut drops,
+                health: &mut health,
+                buffs: &mut buffs,
+            });
+            payout.p1().spawn(drops, player_tf.translation.truncate());
+            commands.entity(entity).despawn();
+        }
+        ShrineButton::Leave => shrine.armed = false,
+    }
+    next_state.set(PlayState::Running);
+}
+
+fn spawn_button(
+    parent: &mut ChildSpawnerCommands,
+    fonts: &FontAssets,
+    label: String,
+    button: ShrineButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(420.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(BUTTON_COLOR),
+            button,
+        ))
+        .with_children(|b| {
+            b.spawn((Text::new(label), fonts.body(22.0), TextColor(Color::WHITE)));
+        });
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::input::PlayerInputState;
+use crate::{GameState, PlayState, ResetSet, spawn_state_ui_root};
+
+/// How long the camera stays free after the last player falls.
+const SPECTATE_SECS: f32 = 10.0;
+const CAMERA_PAN_SPEED: f32 = 400.0;
+
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpectateTimer(Timer::from_seconds(
+            SPECTATE_SECS,
+            TimerMode::Once,
+        )))
+        .add_systems(OnEnter(GameState::Resetting), reset_camera.in_set(ResetSet))
+        .add_systems(OnEnter(PlayState::Spectating), start_spectating)
+        .add_systems(
+            Update,
+            (pan_camera, finish_spectating, update_spectate_text)
+                .chain()
+                .run_if(in_state(PlayState::Spectating)),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct SpectateTimer(Timer);
+
+#[derive(Component)]
+struct SpectateText;
+
+fn reset_camera(mut camera: Query<&mut Transform, With<Camera2d>>) {
+    for mut tf in &mut camera {
+        tf.translation.x = 0.0;
+        tf.translation.y = 0.0;
+    }
+}
+
+fn start_spectating(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut timer: ResMut<SpectateTimer>,
+) {
+    timer.0.reset();
+
+    let root = spawn_state_ui_root(&mut commands, PlayState::Spectating);
+    commands.spawn((
+        Text::new(""),
+        fonts.body(26.0),
+        TextColor(Color::srgb(0.8, 0.8, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(90.0),
+            left: Val::Percent(30.0),
+            ..default()
+        },
+        SpectateText,
+        ChildOf(root),
+    ));
+}
+
+fn pan_camera(
+    time: Res<Time>,
+    input: Res<PlayerInputState>,
+    mut camera: Query<&mut Transform, With<Camera2d>>,
+) {
+    let delta = input.movement.normalize_or_zero() * CAMERA_PAN_SPEED * time.delta_secs();
+    for mut tf in &mut camera {
+        tf.translation.x += delta.x;
+        tf.translation.y += delta.y;
+    }
+}
+
+/// R skips ahead; otherwise the run wraps up when the timer runs out.
+fn finish_spectating(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut timer: ResMut<SpectateTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if timer.0.tick(time.delta()).just_finished() || keys.just_pressed(KeyCode::KeyR) {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn update_spectate_text(timer: Res<SpectateTimer>, mut text: Query<&mut Text, With<SpectateText>>) {
+    let line = format!(
+        "You fell. WASD to look around, R to continue ({:.0}s)",
+        timer.0.remaining_secs().ceil()
+    );
+    // Only touch the text when the countdown ticks over
+    for mut text in &mut text {
+        if text.0 != line {
+            text.0.clone_from(&line);
+        }
+    }
+}
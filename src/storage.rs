@@ -0,0 +1,445 @@
+//! Small key/value persistence: files under `save/` natively, `localStorage`
+//! on the web. Values are plain bytes; callers own their format. Files
+//! outside `save/` go through here too, so every write is atomic.
+//!
+//! Loading at startup reads straight from the backend. Everything written
+//! while the game runs goes through `Persistence`, which does the write on
+//! the IO task pool so a save never stalls a frame.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::tasks::{IoTaskPool, Task, block_on, poll_once};
+
+use crate::message_log::LogMessage;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod backend {
+    use std::path::{Path, PathBuf};
+
+    const SAVE_DIR: &str = "save";
+
+    fn path(key: &str) -> PathBuf {
+        PathBuf::from(SAVE_DIR).join(key)
+    }
+
+    pub fn load_bytes(key: &str) -> Option<Vec<u8>> {
+        read_file(&path(key)).ok()
+    }
+
+    pub fn save_bytes(key: &str, bytes: &[u8]) -> std::io::Result<()> {
+        write_file(&path(key), bytes)
+    }
+
+    pub fn remove(key: &str) -> std::io::Result<()> {
+        remove_file(&path(key))
+    }
+
+    pub fn read_file(path: &Path) -> std::io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    /// Written beside the real file and renamed over it, so a crash mid-write
+    /// leaves the previous save intact rather than half a new one.
+    pub fn write_file(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(partial, path)
+    }
+
+    /// Already gone counts as removed.
+    pub fn remove_file(path: &Path) -> std::io::Result<()> {
+        match std::fs::remove_file(path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod backend {
+    use std::path::Path;
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn load_bytes(key: &str) -> Option<Vec<u8>> {
+        local_storage()?.get_item(key).ok()?.map(String::into_bytes)
+    }
+
+    /// `localStorage` only holds strings, and a single `set_item` is already atomic.
+    pub fn save_bytes(key: &str, bytes: &[u8]) -> std::io::Result<()> {
+        let value = std::str::from_utf8(bytes)
+            .map_err(|_| std::io::Error::other("only text can be saved on the web"))?;
+        local_storage()
+            .and_then(|storage| storage.set_item(key, value).ok())
+            .ok_or_else(|| std::io::Error::other("localStorage is unavailable"))
+    }
+
+    pub fn remove(key: &str) -> std::io::Result<()> {
+        local_storage()
+            .and_then(|storage| storage.remove_item(key).ok())
+            .ok_or_else(|| std::io::Error::other("localStorage is unavailable"))
+    }
+
+    pub fn read_file(_path: &Path) -> std::io::Result<Vec<u8>> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn write_file(_path: &Path, _bytes: &[u8]) -> std::io::Result<()> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn remove_file(_path: &Path) -> std::io::Result<()> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// Keys live under `save/` natively and in `localStorage` on the web. The
+/// `_file` variants take a path of their own, for files that sit elsewhere
+/// (assets, replays, screenshots); the web build has no files to reach.
+pub use backend::{load_bytes, read_file, remove, remove_file, save_bytes, write_file};
+
+/// A stored value as text; anything that isn't valid UTF-8 reads as missing.
+pub fn load(key: &str) -> Option<String> {
+    String::from_utf8(load_bytes(key)?).ok()
+}
+
+/// A file as text, the same way.
+pub fn load_file(path: &Path) -> Option<String> {
+    String::from_utf8(read_file(path).ok()?).ok()
+}
+
+/// Seconds since the Unix epoch. `SystemTime` panics on wasm32, so the web
+/// build asks the browser instead.
+pub fn unix_time_secs() -> u64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        (js_sys::Date::now() / 1000.0) as u64
+    }
+}
+
+pub struct PersistencePlugin;
+
+impl Plugin for PersistencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Persistence>()
+            .add_message::<PersistenceFinished>()
+            .add_systems(
+                Last,
+                (poll_persistence, report_save_failures, flush_on_exit).chain(),
+            );
+    }
+}
+
+/// Sent once for every background write or removal as it completes.
+#[derive(Message)]
+pub struct PersistenceFinished {
+    /// The key, or for `save_file` the path.
+    pub key: String,
+    pub result: Result<(), String>,
+}
+
+/// `Some(bytes)` writes the target, `None` removes it.
+type Operation = Option<Vec<u8>>;
+
+/// Where an operation lands: a storage key, or a file at its own path.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+enum Target {
+    Key(String),
+    File(PathBuf),
+}
+
+impl Target {
+    fn apply(&self, op: Option<&[u8]>) -> std::io::Result<()> {
+        match (self, op) {
+            (Target::Key(key), Some(bytes)) => save_bytes(key, bytes),
+            (Target::Key(key), None) => remove(key),
+            (Target::File(path), Some(bytes)) => write_file(path, bytes),
+            (Target::File(path), None) => remove_file(path),
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::Key(key) => f.write_str(key),
+            Target::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// One target's work: what is on the pool now, and the newest operation
+/// waiting for it to finish.
+struct KeyQueue {
+    running: Task<std::io::Result<()>>,
+    running_op: Operation,
+    next: Option<Operation>,
+}
+
+impl KeyQueue {
+    /// The value the key will hold once everything queued has landed.
+    fn latest(&self) -> &Operation {
+        self.next.as_ref().unwrap_or(&self.running_op)
+    }
+}
+
+/// Background saves. Each key or file runs at most one operation at a
+/// time, so writes land in the order they were made; several saves while
+/// one is still running collapse into just the newest.
+#[derive(Resource, Default)]
+pub struct Persistence {
+    keys: HashMap<Target, KeyQueue>,
+}
+
+impl Persistence {
+    pub fn save(&mut self, key: &str, bytes: impl Into<Vec<u8>>) {
+        self.submit(Target::Key(key.to_string()), Some(bytes.into()));
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.submit(Target::Key(key.to_string()), None);
+    }
+
+    /// Like `save`, to a file outside the save directory.
+    pub fn save_file(&mut self, path: impl Into<PathBuf>, bytes: impl Into<Vec<u8>>) {
+        self.submit(Target::File(path.into()), Some(bytes.into()));
+    }
+
+    /// Reads what was last saved, counting saves still under way.
+    pub fn load(&self, key: &str) -> Option<String> {
+        match self.keys.get(&Target::Key(key.to_string())) {
+            Some(queue) => queue
+                .latest()
+                .as_ref()
+                .and_then(|bytes| String::from_utf8(bytes.clone()).ok()),
+            None => load(key),
+        }
+    }
+
+    fn submit(&mut self, target: Target, op: Operation) {
+        match self.keys.get_mut(&target) {
+            Some(queue) => queue.next = Some(op),
+            None => {
+                let queue = KeyQueue {
+                    running: spawn_operation(&target, &op),
+                    running_op: op,
+                    next: None,
+                };
+                self.keys.insert(target, queue);
+            }
+        }
+    }
+}
+
+fn spawn_operation(target: &Target, op: &Operation) -> Task<std::io::Result<()>> {
+    let target = target.clone();
+    let op = op.clone();
+    IoTaskPool::get().spawn(async move { target.apply(op.as_deref()) })
+}
+
+/// Collects finished work and starts whatever was waiting behind it.
+fn poll_persistence(
+    mut persistence: ResMut<Persistence>,
+    mut finished: MessageWriter<PersistenceFinished>,
+) {
+    persistence.keys.retain(|target, queue| {
+        let Some(result) = block_on(poll_once(&mut queue.running)) else {
+            return true;
+        };
+        finished.write(PersistenceFinished {
+            key: target.to_string(),
+            result: result.map_err(|err| err.to_string()),
+        });
+        match queue.next.take() {
+            Some(op) => {
+                queue.running = spawn_operation(target, &op);
+                queue.running_op = op;
+                true
+            }
+            None => false,
+        }
+    });
+}
+
+fn report_save_failures(
+    mut finished: MessageReader<PersistenceFinished>,
+    mut log: MessageWriter<LogMessage>,
+) {
+    for done in finished.read() {
+        if let Err(err) = &done.result {
+            warn!("could not save {}: {err}", done.key);
+            log.write(LogMessage(format!("Save failed: {err}")));
+        }
+    }
+}
+
+/// Quitting mustn't lose a save that hasn't landed yet: wait for what is
+/// running and write anything still queued on the spot.
+fn flush_on_exit(mut exits: MessageReader<AppExit>, mut persistence: ResMut<Persistence>) {
+    if exits.read().count() == 0 {
+        return;
+    }
+    for (target, queue) in persistence.keys.drain() {
+        // The web build has no exit to wait for, and a task there can't
+        // finish while the main thread blocks on it
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut result = block_on(queue.running);
+        #[cfg(target_arch = "wasm32")]
+        let mut result = Ok(());
+        if let Some(op) = queue.next {
+            result = target.apply(op.as_deref());
+        }
+        if let Err(err) = result {
+            warn!("could not save {target}: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::tasks::TaskPool;
+
+    use super::*;
+
+    fn queue(running_op: Operation, next: Option<Operation>) -> KeyQueue {
+        let pool = IoTaskPool::get_or_init(TaskPool::new);
+        KeyQueue {
+            running: pool.spawn(async { Ok(()) }),
+            running_op,
+            next,
+        }
+    }
+
+    fn persistence(queue: KeyQueue) -> Persistence {
+        Persistence {
+            keys: HashMap::from([(Target::Key("key".to_string()), queue)]),
+        }
+    }
+
+    #[test]
+    fn latest_is_the_running_write_with_nothing_queued() {
+        let queue = queue(Some(b"old".to_vec()), None);
+        assert_eq!(queue.latest(), &Some(b"old".to_vec()));
+    }
+
+    #[test]
+    fn latest_is_the_queued_operation() {
+        let write = queue(Some(b"old".to_vec()), Some(Some(b"new".to_vec())));
+        assert_eq!(write.latest(), &Some(b"new".to_vec()));
+        let removal = queue(Some(b"old".to_vec()), Some(None));
+        assert_eq!(removal.latest(), &None);
+    }
+
+    #[test]
+    fn load_sees_saves_still_under_way() {
+        let persistence = persistence(queue(None, Some(Some(b"new".to_vec()))));
+        assert_eq!(persistence.load("key").as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn load_sees_a_pending_removal_as_gone() {
+        let persistence = persistence(queue(Some(b"old".to_vec()), Some(None)));
+        assert_eq!(persistence.load("key"), None);
+    }
+
+    /// A fresh directory under the system temp dir for one test.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vamp-storage-{}-{test}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn a_write_creates_its_directory_and_leaves_no_partial_behind() {
+        let dir = temp_dir("create");
+        let path = dir.join("nested").join("best.txt");
+        write_file(&path, b"42").unwrap();
+        assert_eq!(read_file(&path).unwrap(), b"42");
+        assert!(!path.with_extension("partial").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn a_write_replaces_the_old_contents_whole() {
+        let dir = temp_dir("overwrite");
+        let path = dir.join("best.txt");
+        write_file(&path, b"a much longer first value").unwrap();
+        write_file(&path, b"short").unwrap();
+        assert_eq!(read_file(&path).unwrap(), b"short");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn a_partial_left_by_a_crash_neither_loads_nor_survives_the_next_write() {
+        let dir = temp_dir("partial");
+        let path = dir.join("best.txt");
+        write_file(&path, b"saved").unwrap();
+        std::fs::write(path.with_extension("partial"), b"half a wr").unwrap();
+        assert_eq!(read_file(&path).unwrap(), b"saved");
+
+        write_file(&path, b"next").unwrap();
+        assert_eq!(read_file(&path).unwrap(), b"next");
+        assert!(!path.with_extension("partial").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn removing_a_missing_file_is_fine_and_removing_twice_too() {
+        let dir = temp_dir("remove");
+        let path = dir.join("best.txt");
+        assert!(remove_file(&path).is_ok());
+        assert!(remove("never-saved").is_ok());
+        write_file(&path, b"42").unwrap();
+        remove_file(&path).unwrap();
+        assert!(read_file(&path).is_err());
+        assert!(remove_file(&path).is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn text_that_is_not_utf8_loads_as_missing() {
+        let dir = temp_dir("utf8");
+        let path = dir.join("best.txt");
+        write_file(&path, &[0xff, 0xfe]).unwrap();
+        assert_eq!(load_file(&path), None);
+        write_file(&path, "ok".as_bytes()).unwrap();
+        assert_eq!(load_file(&path).as_deref(), Some("ok"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keys_and_files_queue_apart() {
+        let mut persistence = persistence(queue(None, None));
+        persistence
+            .keys
+            .insert(Target::File(PathBuf::from("key")), queue(None, None));
+        persistence.save("key", "new");
+        assert_eq!(persistence.load("key").as_deref(), Some("new"));
+        assert_eq!(
+            persistence.keys[&Target::File(PathBuf::from("key"))].latest(),
+            &None
+        );
+    }
+}
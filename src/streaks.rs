@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::announcements::{AnnouncementStyle, Announcements};
+use crate::{BuffStat, Buffs, EnemyKilledEvent, GameState, Health, PlayState, Player, ResetSet};
+
+const STREAK_BUFF_SECS: f32 = 8.0;
+
+/// Kills needed, the stat it boosts and by how much, and its banner name.
+const MILESTONES: &[(u32, BuffStat, f32, &str)] = &[
+    (25, BuffStat::MoveSpeed, 0.25, "SPEED"),
+    (50, BuffStat::FireRate, 0.3, "FIRE RATE"),
+    (100, BuffStat::Damage, 0.5, "DAMAGE"),
+];
+
+pub struct StreakPlugin;
+
+impl Plugin for StreakPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KillStreak>()
+            .add_systems(OnEnter(GameState::Resetting), reset_streak.in_set(ResetSet))
+            .add_systems(Update, track_streak.run_if(in_state(PlayState::Running)));
+    }
+}
+
+/// Kills since the player last lost health.
+#[derive(Resource, Default)]
+pub struct KillStreak {
+    count: u32,
+    /// Player health seen last frame; a drop ends the streak.
+    last_health: Option<i32>,
+}
+
+impl KillStreak {
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+fn reset_streak(mut streak: ResMut<KillStreak>) {
+    *streak = KillStreak::default();
+}
+
+/// A whole frame's kills land at once, so one big clear can pass several
+/// milestones in a single step: each buff is granted once, in order, under one
+/// shared banner.
+fn track_streak(
+    mut streak: ResMut<KillStreak>,
+    mut kills: MessageReader<EnemyKilledEvent>,
+    mut announcements: ResMut<Announcements>,
+    player: Single<(&Health, &mut Buffs), With<Player>>,
+) {
+    let (health, mut buffs) = player.into_inner();
+    if let Some(before) = streak.last_health
+        && health.current < before
+    {
+        streak.count = 0;
+    }
+    streak.last_health = Some(health.current);
+
+    let killed = kills.read().count() as u32;
+    if killed == 0 {
+        return;
+    }
+    let before = streak.count;
+    streak.count += killed;
+
+    let mut names = Vec::new();
+    for &(kills, stat, amount, name) in MILESTONES {
+        if before < kills && kills <= streak.count {
+            buffs.add(stat, amount, STREAK_BUFF_SECS);
+            names.push(name);
+        }
+    }
+    if !names.is_empty() {
+        announcements.push(
+            format!("{} STREAK! +{}", streak.count, names.join(" +")),
+            1.5,
+            AnnouncementStyle::Milestone,
+        );
+    }
+}
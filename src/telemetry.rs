@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::debug::DebugDraw;
+use crate::{Enemy, EnemyKilledEvent, GameState, GameTime, PlayState, ResetSet};
+
+/// Seconds of history kept; each buffer holds one sample per second.
+const HISTORY_SECS: usize = 60;
+const BAR_WIDTH: f32 = 3.0;
+const STRIP_HEIGHT: f32 = 40.0;
+const STRIP_GAP: f32 = 12.0;
+const MARGIN: f32 = 16.0;
+/// Spawned, killed and live, top to bottom.
+const STRIP_COLORS: [Color; 3] = [
+    Color::srgb(1.0, 0.5, 0.3),
+    Color::srgb(0.4, 1.0, 0.4),
+    Color::srgb(0.5, 0.7, 1.0),
+];
+
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Telemetry>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                reset_telemetry.in_set(ResetSet),
+            )
+            // Always sampling, so the history is there whenever the graph is opened
+            .add_systems(
+                Update,
+                sample_telemetry.run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(
+                Update,
+                draw_telemetry.run_if(|d: Res<DebugDraw>| d.telemetry),
+            );
+    }
+}
+
+/// The last `HISTORY_SECS` seconds of spawns, kills and live enemies, oldest first.
+#[derive(Resource)]
+pub struct Telemetry {
+    window: Timer,
+    spawned_this_second: u32,
+    killed_this_second: u32,
+    spawned: VecDeque<u32>,
+    killed: VecDeque<u32>,
+    live: VecDeque<u32>,
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self {
+            window: Timer::from_seconds(1.0, TimerMode::Repeating),
+            spawned_this_second: 0,
+            killed_this_second: 0,
+            spawned: VecDeque::with_capacity(HISTORY_SECS),
+            killed: VecDeque::with_capacity(HISTORY_SECS),
+            live: VecDeque::with_capacity(HISTORY_SECS),
+        }
+    }
+}
+
+impl Telemetry {
+    /// Spawned, killed and live, in that order.
+    pub fn strips(&self) -> [&VecDeque<u32>; 3] {
+        [&self.spawned, &self.killed, &self.live]
+    }
+}
+
+fn push_capped(buffer: &mut VecDeque<u32>, value: u32) {
+    if buffer.len() == HISTORY_SECS {
+        buffer.pop_front();
+    }
+    buffer.push_back(value);
+}
+
+fn reset_telemetry(mut telemetry: ResMut<Telemetry>) {
+    *telemetry = Telemetry::default();
+}
+
+/// Counting is a couple of additions a frame; the buffers only move once a second.
+fn sample_telemetry(
+    time: GameTime,
+    mut telemetry: ResMut<Telemetry>,
+    mut kills: MessageReader<EnemyKilledEvent>,
+    spawned: Query<(), Added<Enemy>>,
+    live: Query<(), With<Enemy>>,
+) {
+    telemetry.spawned_this_second += spawned.iter().count() as u32;
+    telemetry.killed_this_second += kills.read().count() as u32;
+    if !telemetry.window.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let spawned = std::mem::take(&mut telemetry.spawned_this_second);
+    let killed = std::mem::take(&mut telemetry.killed_this_second);
+    push_capped(&mut telemetry.spawned, spawned);
+    push_capped(&mut telemetry.killed, killed);
+    push_capped(&mut telemetry.live, live.iter().count() as u32);
+}
+
+/// Three bar strips in the top-right corner, each scaled to its own peak so
+/// the shape of the curve shows even when the counts differ wildly.
+fn draw_telemetry(
+    mut gizmos: Gizmos,
+    telemetry: Res<Telemetry>,
+    windows: Query<&Window>,
+    camera: Query<&Transform, With<Camera2d>>,
+) {
+    let (Ok(window), Ok(camera)) = (windows.single(), camera.single()) else {
+        return;
+    };
+    let top_right = camera.translation.truncate() + window.size() / 2.0;
+    let width = HISTORY_SECS as f32 * BAR_WIDTH;
+    let left = top_right.x - MARGIN - width;
+
+    for (i, (buffer, color)) in telemetry.strips().into_iter().zip(STRIP_COLORS).enumerate() {
+        let bottom = top_right.y - MARGIN - (i as f32 + 1.0) * STRIP_HEIGHT - i as f32 * STRIP_GAP;
+        gizmos.line_2d(
+            Vec2::new(left, bottom),
+            Vec2::new(left + width, bottom),
+            color.with_alpha(0.4),
+        );
+
+        let peak = buffer.iter().copied().max().unwrap_or(0).max(1) as f32;
+        // Newest sample on the right edge
+        let start = left + (HISTORY_SECS - buffer.len()) as f32 * BAR_WIDTH;
+        for (n, &value) in buffer.iter().enumerate() {
+            let x = start + n as f32 * BAR_WIDTH;
+            let height = value as f32 / peak * STRIP_HEIGHT;
+            gizmos.line_2d(Vec2::new(x, bottom), Vec2::new(x, bottom + height), color);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! The Thief: a quick, fragile enemy that has no interest in the player,
+//! only in what is lying on the ground. It makes for the nearest pickup,
+//! swallows it, and runs. Killing it gives back everything it took and some
+//! gold on top; one that escapes past the leash radius drops its haul where
+//! it vanished, so nothing it swallowed is ever lost outright.
+//!
+//! With nothing to steal it shadows the player from standoff range, waiting
+//! for something to drop. It has no contact damage at all.
+
+use bevy::prelude::*;
+
+use crate::challenge::RunRules;
+use crate::despawn::{Dead, PendingDespawns};
+use crate::fonts::FontAssets;
+use crate::loot::{Drop, LootSpawner};
+use crate::pickups::{Pickup, PickupKind, spawn_pickup};
+use crate::popups::StatusPopup;
+use crate::{
+    DeathEvent, Downed, Enemy, EnemyTime, EnemyType, GameplaySet, Hitbox, LEASH_RADIUS, PlayState,
+    PlayerCharacter, STANDOFF_DISTANCE, enemy_heading, enemy_speed, nearest,
+};
+
+/// Running away is faster than sneaking in.
+const FLEE_SPEED_SCALE: f32 = 1.6;
+/// Reach for swallowing, beyond the thief's own hitbox.
+const SWALLOW_REACH: f32 = 10.0;
+const THIEF_BONUS_GOLD: u32 = 5;
+/// How far apart a dropped haul lands, so it doesn't stack in one spot.
+const HAUL_SPREAD: f32 = 16.0;
+
+pub struct ThiefPlugin;
+
+impl Plugin for ThiefPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (move_thieves, swallow_pickups, escape_thieves, drop_hauls)
+                .chain()
+                .in_set(GameplaySet)
+                .run_if(in_state(PlayState::Running)),
+        );
+    }
+}
+
+/// What a thief has swallowed so far. Fleeing once it holds anything.
+#[derive(Component, Default)]
+pub struct Thief {
+    haul: Vec<PickupKind>,
+}
+
+/// Everything in `haul` back on the ground around `center`.
+fn spill_haul(commands: &mut Commands, fonts: &FontAssets, haul: &[PickupKind], center: Vec2) {
+    for (i, &kind) in haul.iter().enumerate() {
+        let angle = i as f32 * std::f32::consts::TAU / haul.len() as f32;
+        let offset = if haul.len() > 1 {
+            Vec2::from_angle(angle) * HAUL_SPREAD
+        } else {
+            Vec2::ZERO
+        };
+        spawn_pickup(commands, fonts, kind, center + offset);
+    }
+}
+
+/// Empty-handed, straight for the nearest pickup, or trailing the player
+/// when there isn't one; carrying, straight away from the nearest player.
+/// Thieves are left out of ordinary enemy movement, leash idling included,
+/// so every step they take is decided here.
+fn move_thieves(
+    time: EnemyTime,
+    rules: Res<RunRules>,
+    players: Query<&Transform, (With<PlayerCharacter>, Without<Downed>)>,
+    pickups: Query<&Transform, (With<Pickup>, Without<Dead>, Without<Thief>)>,
+    mut thieves: Query<(Entity, &mut Transform, &Thief), (With<Enemy>, Without<PlayerCharacter>)>,
+) {
+    let targets: Vec<Vec2> = players.iter().map(|tf| tf.translation.truncate()).collect();
+    let speed = enemy_speed(EnemyType::Thief) * rules.enemy_speed;
+    for (entity, mut tf, thief) in &mut thieves {
+        let position = tf.translation.truncate();
+        let velocity = if !thief.haul.is_empty() {
+            let away = nearest(position, targets.iter().copied())
+                .map_or(Vec2::ZERO, |player| (position - player).normalize_or_zero());
+            away * speed * FLEE_SPEED_SCALE
+        } else if let Some(pickup) =
+            nearest(position, pickups.iter().map(|tf| tf.translation.truncate()))
+        {
+            (pickup - position).normalize_or_zero() * speed
+        } else {
+            let heading = enemy_heading(
+                entity,
+                position,
+                Some(STANDOFF_DISTANCE),
+                &targets,
+                time.elapsed_secs(),
+            );
+            heading * speed
+        };
+        let delta = velocity * time.delta_secs();
+        tf.translation.x += delta.x;
+        tf.translation.y += delta.y;
+    }
+}
+
+/// A pickup touched by two thieves in the same frame goes to the first.
+fn swallow_pickups(
+    mut commands: Commands,
+    mut popups: MessageWriter<StatusPopup>,
+    pickups: Query<(Entity, &Transform, &Pickup), Without<Dead>>,
+    mut thieves: Query<(&Transform, &Hitbox, &mut Thief), With<Enemy>>,
+) {
+    let mut taken = Vec::new();
+    for (tf, hitbox, mut thief) in &mut thieves {
+        // One at a time: a full thief is already running
+        if !thief.haul.is_empty() || hitbox.intangible {
+            continue;
+        }
+        let position = tf.translation.truncate();
+        let reach = hitbox.radius + SWALLOW_REACH;
+        let Some((entity, pickup)) = pickups.iter().find_map(|(entity, pickup_tf, pickup)| {
+            let close = pickup_tf.translation.truncate().distance(position) <= reach;
+            (close && !taken.contains(&entity)).then_some((entity, pickup))
+        }) else {
+            continue;
+        };
+        taken.push(entity);
+        thief.haul.push(pickup.kind);
+        // The player may be walking over it this same frame
+        commands.entity(entity).try_despawn();
+        popups.write(StatusPopup {
+            position: position + Vec2::new(0.0, 20.0),
+            text: "Stolen!",
+        });
+    }
+}
+
+/// Past the leash radius of every standing player, a carrying thief is
+/// gone, leaving its haul behind.
+fn escape_thieves(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut pending: ResMut<PendingDespawns>,
+    players: Query<&Transform, (With<PlayerCharacter>, Without<Downed>)>,
+    mut thieves: Query<(Entity, &Transform, &mut Thief), (With<Enemy>, Without<Dead>)>,
+) {
+    let targets: Vec<Vec2> = players.iter().map(|tf| tf.translation.truncate()).collect();
+    if targets.is_empty() {
+        return;
+    }
+    for (entity, tf, mut thief) in &mut thieves {
+        let position = tf.translation.truncate();
+        let far = targets
+            .iter()
+            .all(|player| player.distance(position) > LEASH_RADIUS);
+        if thief.haul.is_empty() || !far {
+            continue;
+        }
+        spill_haul(&mut commands, &fonts, &thief.haul, position);
+        thief.haul.clear();
+        pending.push(&mut commands, entity);
+    }
+}
+
+/// The corpse keeps `Thief`, so its haul can be read here whichever of the
+/// death systems runs first.
+fn drop_hauls(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    mut loot: LootSpawner,
+    mut deaths: MessageReader<DeathEvent>,
+    mut thieves: Query<(&Transform, &mut Thief)>,
+) {
+    for death in deaths.read() {
+        let Ok((tf, mut thief)) = thieves.get_mut(death.entity) else {
+            continue;
+        };
+        let position = tf.translation.truncate();
+        spill_haul(&mut commands, &fonts, &thief.haul, position);
+        thief.haul.clear();
+        loot.spawn([Drop::Gold(THIEF_BONUS_GOLD)], position);
+        commands.entity(death.entity).remove::<Thief>();
+    }
+}
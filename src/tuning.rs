@@ -0,0 +1,248 @@
+use bevy::prelude::*;
+
+use crate::difficulty::{CONFIG_PATH, DifficultyConfig};
+use crate::director::enemy_type_label;
+use crate::fonts::FontAssets;
+
+const BUTTON_COLOR: Color = Color::srgb(0.2, 0.2, 0.25);
+const INTERVAL_STEP: f32 = 0.05;
+const MIN_INTERVAL: f32 = 0.05;
+const WEIGHT_STEP: f32 = 0.5;
+const HEALTH_STEP: f32 = 0.1;
+const MIN_HEALTH: f32 = 0.1;
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_tuning_panel).add_systems(
+            Update,
+            (toggle_tuning_panel, tuning_buttons, update_tuning_values).chain(),
+        );
+    }
+}
+
+/// One adjustable number in `DifficultyConfig`, by its index in the list it
+/// lives in.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TuningField {
+    Interval(usize),
+    Weight(usize),
+    Health(usize),
+}
+
+impl TuningField {
+    fn label(self, config: &DifficultyConfig) -> String {
+        match self {
+            TuningField::Interval(i) => {
+                format!("Interval at {:.0}s", config.spawn_interval[i].secs)
+            }
+            TuningField::Weight(i) => {
+                format!("{} weight", enemy_type_label(config.enemies[i].kind))
+            }
+            TuningField::Health(i) => {
+                format!("{} health", enemy_type_label(config.enemies[i].kind))
+            }
+        }
+    }
+
+    fn value(self, config: &DifficultyConfig) -> f32 {
+        match self {
+            TuningField::Interval(i) => config.spawn_interval[i].interval,
+            TuningField::Weight(i) => config.enemies[i].weight,
+            TuningField::Health(i) => config.enemies[i].health,
+        }
+    }
+
+    /// One step up or down, never past the value's floor. Rounded to two
+    /// places so repeated steps don't drift.
+    fn nudge(self, config: &mut DifficultyConfig, up: bool) {
+        let sign = if up { 1.0 } else { -1.0 };
+        let (value, step, min) = match self {
+            TuningField::Interval(i) => (
+                &mut config.spawn_interval[i].interval,
+                INTERVAL_STEP,
+                MIN_INTERVAL,
+            ),
+            TuningField::Weight(i) => (&mut config.enemies[i].weight, WEIGHT_STEP, 0.0),
+            TuningField::Health(i) => (&mut config.enemies[i].health, HEALTH_STEP, MIN_HEALTH),
+        };
+        *value = ((*value + sign * step).max(min) * 100.0).round() / 100.0;
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum TuningButton {
+    Nudge(TuningField, bool),
+    Save,
+}
+
+#[derive(Component)]
+struct TuningPanel;
+
+#[derive(Component)]
+struct TuningValue(TuningField);
+
+/// Outcome of the last save.
+#[derive(Component)]
+struct TuningStatus;
+
+/// Built once from the config loaded at startup: the panel only changes
+/// values, so the rows never need rebuilding.
+fn spawn_tuning_panel(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    config: Res<DifficultyConfig>,
+) {
+    let fields: Vec<TuningField> = (0..config.spawn_interval.len())
+        .map(TuningField::Interval)
+        .chain((0..config.enemies.len()).map(TuningField::Weight))
+        .chain((0..config.enemies.len()).map(TuningField::Health))
+        .collect();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(40.0),
+                right: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(2.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            // Under the console, over everything else
+            GlobalZIndex(9),
+            Visibility::Hidden,
+            TuningPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new("Difficulty [F10]"),
+                fonts.body(18.0),
+                TextColor(Color::WHITE),
+            ));
+            for field in fields {
+                panel
+                    .spawn(Node {
+                        column_gap: Val::Px(6.0),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            Text::new(field.label(&config)),
+                            fonts.body(14.0),
+                            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                            Node {
+                                width: Val::Px(150.0),
+                                ..default()
+                            },
+                        ));
+                        spawn_button(row, &fonts, "-", TuningButton::Nudge(field, false));
+                        row.spawn((
+                            Text::new(""),
+                            fonts.body(14.0),
+                            TextColor(Color::WHITE),
+                            Node {
+                                width: Val::Px(44.0),
+                                ..default()
+                            },
+                            TuningValue(field),
+                        ));
+                        spawn_button(row, &fonts, "+", TuningButton::Nudge(field, true));
+                    });
+            }
+            spawn_button(
+                panel,
+                &fonts,
+                &format!("Save to {CONFIG_PATH}"),
+                TuningButton::Save,
+            );
+            panel.spawn((
+                Text::new(""),
+                fonts.body(14.0),
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                TuningStatus,
+            ));
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildSpawnerCommands,
+    fonts: &FontAssets,
+    label: &str,
+    button: TuningButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(BUTTON_COLOR),
+            button,
+        ))
+        .with_children(|b| {
+            b.spawn((Text::new(label), fonts.body(14.0), TextColor(Color::WHITE)));
+        });
+}
+
+fn toggle_tuning_panel(
+    input: Res<ButtonInput<KeyCode>>,
+    mut panel: Query<&mut Visibility, With<TuningPanel>>,
+) {
+    if !input.just_pressed(KeyCode::F10) {
+        return;
+    }
+    for mut visibility in &mut panel {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn tuning_buttons(
+    buttons: Query<(&Interaction, &TuningButton), Changed<Interaction>>,
+    mut config: ResMut<DifficultyConfig>,
+    mut status: Query<&mut Text, With<TuningStatus>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match *button {
+            TuningButton::Nudge(field, up) => field.nudge(&mut config, up),
+            TuningButton::Save => {
+                let message = match config.save() {
+                    Ok(()) => {
+                        info!("difficulty saved to {CONFIG_PATH}");
+                        "Saved".to_string()
+                    }
+                    Err(err) => {
+                        warn!("could not save {CONFIG_PATH}: {err}");
+                        format!("Not saved: {err}")
+                    }
+                };
+                for mut text in &mut status {
+                    text.0 = message.clone();
+                }
+            }
+        }
+    }
+}
+
+fn update_tuning_values(
+    config: Res<DifficultyConfig>,
+    mut values: Query<(&mut Text, Ref<TuningValue>)>,
+) {
+    for (mut text, value) in &mut values {
+        if config.is_changed() || value.is_added() {
+            text.0 = format!("{:.2}", value.0.value(&config));
+        }
+    }
+}
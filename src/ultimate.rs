@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+
+use crate::announcements::{AnnouncementStyle, Announcements};
+use crate::input::PlayerInputState;
+use crate::{EnemyKilledEvent, GameState, GameTime, PlayState, ResetSet, spawn_state_ui_root};
+
+const KILLS_TO_CHARGE: f32 = 40.0;
+const SLOW_SECS: f32 = 5.0;
+const ENEMY_SLOW: f32 = 0.3;
+const BAR_WIDTH: f32 = 120.0;
+const CHARGING_COLOR: Color = Color::srgb(0.4, 0.6, 1.0);
+const READY_COLOR: Color = Color::srgb(0.7, 0.9, 1.0);
+
+pub struct UltimatePlugin;
+
+impl Plugin for UltimatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ultimate>()
+            .init_resource::<TimeDilation>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                reset_ultimate.in_set(ResetSet),
+            )
+            .add_systems(OnEnter(GameState::Playing), spawn_ultimate_bar)
+            .add_systems(
+                Update,
+                (charge_ultimate, activate_ultimate, tick_ultimate)
+                    .chain()
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(Update, update_ultimate_bar);
+    }
+}
+
+/// Speed multipliers for enemy-side simulation, one per source so neither
+/// undoes the other. Enemy systems see their product through `EnemyTime`;
+/// `Time` itself is never touched so the player keeps full speed.
+#[derive(Resource)]
+pub struct TimeDilation {
+    pub enemies: f32,
+    /// The brief slow after a close call.
+    pub close_call: f32,
+}
+
+impl Default for TimeDilation {
+    fn default() -> Self {
+        Self {
+            enemies: 1.0,
+            close_call: 1.0,
+        }
+    }
+}
+
+impl TimeDilation {
+    pub fn scale(&self) -> f32 {
+        self.enemies * self.close_call
+    }
+}
+
+/// Kill-charged time slow, fired with F once the meter is full.
+#[derive(Resource, Default)]
+pub struct Ultimate {
+    /// 0..=1; full means ready.
+    charge: f32,
+    active: Option<Timer>,
+}
+
+impl Ultimate {
+    /// The charge, which stays empty while the slow runs.
+    pub fn readiness(&self) -> f32 {
+        self.charge
+    }
+}
+
+fn reset_ultimate(mut ultimate: ResMut<Ultimate>, mut dilation: ResMut<TimeDilation>) {
+    *ultimate = Ultimate::default();
+    *dilation = TimeDilation::default();
+}
+
+fn charge_ultimate(mut kills: MessageReader<EnemyKilledEvent>, mut ultimate: ResMut<Ultimate>) {
+    let count = kills.read().count();
+    // No charging while the slow is running
+    if count == 0 || ultimate.active.is_some() {
+        return;
+    }
+    ultimate.charge = (ultimate.charge + count as f32 / KILLS_TO_CHARGE).min(1.0);
+}
+
+fn activate_ultimate(
+    input: Res<PlayerInputState>,
+    mut ultimate: ResMut<Ultimate>,
+    mut dilation: ResMut<TimeDilation>,
+    mut announcements: ResMut<Announcements>,
+) {
+    if !input.ultimate || ultimate.charge < 1.0 || ultimate.active.is_some() {
+        return;
+    }
+
+    ultimate.charge = 0.0;
+    ultimate.active = Some(Timer::from_seconds(SLOW_SECS, TimerMode::Once));
+    dilation.enemies = ENEMY_SLOW;
+    announcements.push("Time slows", 1.0, AnnouncementStyle::Info);
+}
+
+fn tick_ultimate(
+    time: GameTime,
+    mut ultimate: ResMut<Ultimate>,
+    mut dilation: ResMut<TimeDilation>,
+) {
+    let Some(active) = ultimate.active.as_mut() else {
+        return;
+    };
+    if active.tick(time.delta()).is_finished() {
+        ultimate.active = None;
+        dilation.enemies = 1.0;
+    }
+}
+
+#[derive(Component)]
+struct UltimateBarFill;
+
+fn spawn_ultimate_bar(mut commands: Commands) {
+    let root = spawn_state_ui_root(&mut commands, GameState::Playing);
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(70.0),
+                left: Val::Px(10.0),
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            ChildOf(root),
+        ))
+        .with_children(|bar| {
+            bar.spawn((
+                Node {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(CHARGING_COLOR),
+                UltimateBarFill,
+            ));
+        });
+}
+
+/// Shows the charge, or the time left while the slow is running.
+fn update_ultimate_bar(
+    ultimate: Res<Ultimate>,
+    mut fill: Query<(&mut Node, &mut BackgroundColor), With<UltimateBarFill>>,
+) {
+    let Ok((mut node, mut color)) = fill.single_mut() else {
+        return;
+    };
+    if !ultimate.is_changed() {
+        return;
+    }
+
+    let (fraction, bar_color) = match &ultimate.active {
+        Some(active) => (1.0 - active.fraction(), READY_COLOR),
+        None if ultimate.charge >= 1.0 => (1.0, READY_COLOR),
+        None => (ultimate.charge, CHARGING_COLOR),
+    };
+    node.width = Val::Percent(100.0 * fraction);
+    color.0 = bar_color;
+}
@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::savegame::RunSuspended;
+use crate::storage::{self, Persistence};
+use crate::upgrades::UpgradePool;
+use crate::{EnemyKilledEvent, GameState, GameTime, PlayState, ResetSet, spawn_state_ui_root};
+
+const UNLOCKS_KEY: &str = "unlocks";
+
+pub struct UnlockPlugin;
+
+impl Plugin for UnlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(UnlockState::load())
+            .init_resource::<RunRecord>()
+            .add_systems(
+                OnEnter(GameState::Resetting),
+                (reset_run_record, lock_upgrades).in_set(ResetSet),
+            )
+            .add_systems(
+                Update,
+                track_run_record.run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), check_unlocks);
+    }
+}
+
+/// Something earned across runs rather than within one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Unlock {
+    Runner,
+    ChainLightning,
+}
+
+const ALL_UNLOCKS: &[Unlock] = &[Unlock::Runner, Unlock::ChainLightning];
+
+impl Unlock {
+    fn id(self) -> &'static str {
+        match self {
+            Unlock::Runner => "runner",
+            Unlock::ChainLightning => "chain_lightning",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Unlock::Runner => "Runner",
+            Unlock::ChainLightning => "Chain Lightning",
+        }
+    }
+
+    fn condition(self) -> &'static str {
+        match self {
+            Unlock::Runner => "Survive 10 minutes in one run",
+            Unlock::ChainLightning => "Defeat 500 enemies in total",
+        }
+    }
+
+    /// The upgrade this unlock gates, if it is one.
+    fn upgrade_id(self) -> Option<&'static str> {
+        match self {
+            Unlock::Runner => None,
+            Unlock::ChainLightning => Some("chain_lightning"),
+        }
+    }
+
+    fn is_met(self, state: &UnlockState) -> bool {
+        match self {
+            Unlock::Runner => state.best_survival_secs >= 600.0,
+            Unlock::ChainLightning => state.lifetime_kills >= 500,
+        }
+    }
+}
+
+/// Lifetime stats and what they have earned, persisted as `name=value` lines.
+#[derive(Resource, Default)]
+pub struct UnlockState {
+    lifetime_kills: u64,
+    best_survival_secs: f32,
+    unlocked: Vec<Unlock>,
+}
+
+impl UnlockState {
+    fn load() -> Self {
+        let mut state = Self::default();
+        for line in storage::load(UNLOCKS_KEY).unwrap_or_default().lines() {
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim() {
+                "lifetime_kills" => state.lifetime_kills = value.parse().unwrap_or(0),
+                "best_survival_secs" => state.best_survival_secs = value.parse().unwrap_or(0.0),
+                "unlocked" => {
+                    state.unlocked = value
+                        .split(',')
+                        .filter_map(|id| ALL_UNLOCKS.iter().copied().find(|u| u.id() == id))
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+        state
+    }
+
+    fn save(&self, persistence: &mut Persistence) {
+        let ids: Vec<&str> = self.unlocked.iter().map(|u| u.id()).collect();
+        let text = format!(
+            "lifetime_kills={}\nbest_survival_secs={}\nunlocked={}\n",
+            self.lifetime_kills,
+            self.best_survival_secs,
+            ids.join(",")
+        );
+        persistence.save(UNLOCKS_KEY, text);
+    }
+
+    pub fn is_unlocked(&self, unlock: Unlock) -> bool {
+        self.unlocked.contains(&unlock)
+    }
+}
+
+/// What this run adds to the lifetime stats.
+#[derive(Resource, Default)]
+pub struct RunRecord {
+    secs: f32,
+    kills: u64,
+}
+
+impl RunRecord {
+    /// Gameplay time survived so far this run.
+    pub fn secs(&self) -> f32 {
+        self.secs
+    }
+
+    pub fn kills(&self) -> u64 {
+        self.kills
+    }
+
+    /// Picks a saved run's tally back up where it left off.
+    pub fn restore(&mut self, secs: f32, kills: u64) {
+        self.secs = secs;
+        self.kills = kills;
+    }
+}
+
+fn reset_run_record(mut record: ResMut<RunRecord>) {
+    *record = RunRecord::default();
+}
+
+fn lock_upgrades(state: Res<UnlockState>, mut pool: ResMut<UpgradePool>) {
+    let locked = ALL_UNLOCKS
+        .iter()
+        .filter(|u| !state.is_unlocked(**u))
+        .filter_map(|u| u.upgrade_id())
+        .collect();
+    pool.set_locked(locked);
+}
+
+fn track_run_record(
+    time: GameTime,
+    mut kills: MessageReader<EnemyKilledEvent>,
+    mut record: ResMut<RunRecord>,
+) {
+    record.secs += time.delta_secs();
+    record.kills += kills.read().count() as u64;
+}
+
+/// Folds the run into the lifetime stats, then lists what it unlocked and
+/// what is still to earn on the results screen.
+/// A run left through Save & Quit is folded in when it finally ends instead.
+fn check_unlocks(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    record: Res<RunRecord>,
+    suspended: Res<RunSuspended>,
+    mut state: ResMut<UnlockState>,
+    mut persistence: ResMut<Persistence>,
+) {
+    if suspended.0 {
+        return;
+    }
+    state.lifetime_kills += record.kills;
+    state.best_survival_secs = state.best_survival_secs.max(record.secs);
+
+    let newly: Vec<Unlock> = ALL_UNLOCKS
+        .iter()
+        .copied()
+        .filter(|u| !state.is_unlocked(*u) && u.is_met(&state))
+        .collect();
+    state.unlocked.extend(&newly);
+    state.save(&mut persistence);
+
+    let root = spawn_state_ui_root(&mut commands, GameState::GameOver);
+    let mut lines: Vec<(String, Color)> = newly
+        .iter()
+        .map(|u| {
+            (
+                format!("Unlocked: {}!", u.name()),
+                Color::srgb(1.0, 0.85, 0.3),
+            )
+        })
+        .collect();
+    lines.extend(
+        ALL_UNLOCKS
+            .iter()
+            .filter(|u| !state.is_unlocked(**u))
+            .map(|u| {
+                (
+                    format!("Locked: {} ({})", u.name(), u.condition()),
+                    Color::srgb(0.45, 0.45, 0.45),
+                )
+            }),
+    );
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(15.0),
+                left: Val::Percent(25.0),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ChildOf(root),
+        ))
+        .with_children(|list| {
+            for (text, color) in lines {
+                list.spawn((Text::new(text), fonts.body(20.0), TextColor(color)));
+            }
+        });
+}
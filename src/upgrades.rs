@@ -0,0 +1,727 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::loot::weighted_index;
+use crate::weapons::{FireMode, MAX_WEAPON_LEVEL, WeaponKind, WeaponSlots, aura_radius};
+use crate::{Curse, PlayerStats};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rarity {
+    Common,
+    Rare,
+    Epic,
+}
+
+impl Rarity {
+    /// Draw weight before luck; luck only ever makes rarer tiers likelier.
+    fn weight(self, luck: f32) -> f32 {
+        let luck = luck.max(0.0);
+        match self {
+            Rarity::Common => 60.0,
+            Rarity::Rare => 30.0 * (1.0 + luck),
+            Rarity::Epic => 10.0 * (1.0 + 2.0 * luck),
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Rarity::Common => Color::srgb(0.15, 0.15, 0.2),
+            Rarity::Rare => Color::srgb(0.1, 0.25, 0.5),
+            Rarity::Epic => Color::srgb(0.4, 0.15, 0.5),
+        }
+    }
+}
+
+/// Everything an upgrade is allowed to touch when taken.
+pub struct UpgradeTargets<'a> {
+    pub stats: &'a mut PlayerStats,
+    pub curse: &'a mut Curse,
+    pub weapons: &'a mut WeaponSlots,
+}
+
+pub struct UpgradeDef {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// A single glyph for the build strip.
+    pub icon: &'static str,
+    pub description: &'static str,
+    pub rarity: Rarity,
+    pub max_stacks: u32,
+    pub apply: fn(&mut UpgradeTargets),
+}
+
+/// Weapon upgrades rely on `max_stacks` to stop at the level cap; there are
+/// fewer weapon kinds than slots, so a new one always has somewhere to go.
+pub const UPGRADES: &[UpgradeDef] = &[
+    UpgradeDef {
+        id: "blaster",
+        name: "Blaster",
+        icon: "*",
+        description: "Blaster: fire faster",
+        rarity: Rarity::Common,
+        max_stacks: MAX_WEAPON_LEVEL - 1,
+        apply: |t| {
+            t.weapons.add_or_level(WeaponKind::Blaster);
+        },
+    },
+    UpgradeDef {
+        id: "aura",
+        name: "Aura",
+        icon: "O",
+        description: "Aura: burn nearby enemies",
+        rarity: Rarity::Rare,
+        max_stacks: MAX_WEAPON_LEVEL,
+        apply: |t| {
+            t.weapons.add_or_level(WeaponKind::Aura);
+        },
+    },
+    UpgradeDef {
+        id: "split_shot",
+        name: "Split Shot",
+        icon: "W",
+        description: "+1 projectile per shot",
+        rarity: Rarity::Rare,
+        max_stacks: 4,
+        apply: |t| t.stats.projectile_count += 1,
+    },
+    UpgradeDef {
+        id: "burst_fire",
+        name: "Burst Fire",
+        icon: ":",
+        description: "Blaster fires three-shot bursts",
+        rarity: Rarity::Rare,
+        max_stacks: 1,
+        apply: |t| set_blaster_mode(t, FireMode::Burst),
+    },
+    UpgradeDef {
+        id: "charge_shot",
+        name: "Charge Shot",
+        icon: ")",
+        description: "Blaster charges while held, firing one big shot",
+        rarity: Rarity::Rare,
+        max_stacks: 1,
+        apply: |t| set_blaster_mode(t, FireMode::Charge),
+    },
+    UpgradeDef {
+        id: "swift_boots",
+        name: "Swift Boots",
+        icon: ">",
+        description: "+10% move speed",
+        rarity: Rarity::Common,
+        max_stacks: 5,
+        apply: |t| t.stats.move_speed *= 1.1,
+    },
+    UpgradeDef {
+        id: "light_rounds",
+        name: "Light Rounds",
+        icon: "-",
+        description: "+15% bullet speed",
+        rarity: Rarity::Common,
+        max_stacks: 5,
+        apply: |t| t.stats.bullet_speed *= 1.15,
+    },
+    UpgradeDef {
+        id: "heavy_rounds",
+        name: "Heavy Rounds",
+        icon: "#",
+        description: "+1 bullet damage",
+        rarity: Rarity::Rare,
+        max_stacks: 5,
+        apply: |t| t.stats.bullet_damage += 1,
+    },
+    UpgradeDef {
+        id: "vitality",
+        name: "Vitality",
+        icon: "+",
+        description: "+1 max health",
+        rarity: Rarity::Rare,
+        max_stacks: 5,
+        apply: |t| t.stats.max_health += 1,
+    },
+    UpgradeDef {
+        id: "clover",
+        name: "Clover",
+        icon: "%",
+        description: "+25% luck",
+        rarity: Rarity::Rare,
+        max_stacks: 3,
+        apply: |t| t.stats.luck += 0.25,
+    },
+    UpgradeDef {
+        id: "cursed_idol",
+        name: "Cursed Idol",
+        icon: "&",
+        description: "+1 curse, +1 bullet damage",
+        rarity: Rarity::Rare,
+        max_stacks: 5,
+        apply: |t| {
+            t.curse.0 += 1;
+            t.stats.bullet_damage += 1;
+        },
+    },
+    UpgradeDef {
+        id: "parry_rounds",
+        name: "Parry Rounds",
+        icon: "/",
+        description: "Bullets shoot down enemy projectiles",
+        rarity: Rarity::Rare,
+        max_stacks: 1,
+        apply: |t| t.stats.bullet_parry = true,
+    },
+    UpgradeDef {
+        id: "vampire_fang",
+        name: "Vampire Fang",
+        icon: "v",
+        description: "+5% lifesteal",
+        rarity: Rarity::Epic,
+        max_stacks: 3,
+        apply: |t| t.stats.lifesteal += 0.05,
+    },
+    UpgradeDef {
+        id: "ricochet",
+        name: "Ricochet",
+        icon: "~",
+        description: "Bullets bounce to a nearby enemy after a kill",
+        rarity: Rarity::Epic,
+        max_stacks: 3,
+        apply: |t| t.stats.ricochet += 1,
+    },
+    UpgradeDef {
+        id: "overcharged_core",
+        name: "Overcharged Core",
+        icon: "!",
+        description: "+2 bullet damage, but shots build heat",
+        rarity: Rarity::Epic,
+        max_stacks: 1,
+        apply: |t| {
+            t.stats.bullet_damage += 2;
+            t.stats.heat_per_shot = 0.15;
+        },
+    },
+    UpgradeDef {
+        id: "second_thoughts",
+        name: "Second Thoughts",
+        icon: "?",
+        description: "+1 level-up reroll",
+        rarity: Rarity::Epic,
+        max_stacks: 2,
+        apply: |t| t.stats.rerolls += 1,
+    },
+];
+
+/// Fire modes replace each other; the newest one taken wins.
+fn set_blaster_mode(targets: &mut UpgradeTargets, mode: FireMode) {
+    if let Some(blaster) = targets.weapons.get_mut(WeaponKind::Blaster) {
+        blaster.set_mode(mode);
+    }
+}
+
+impl UpgradeDef {
+    /// Weapon upgrades share their id with the weapon they add or level.
+    pub fn weapon(&self) -> Option<WeaponKind> {
+        WeaponKind::from_id(self.id)
+    }
+
+    /// What taking this would change, worked out by applying it to copies,
+    /// so the card can never disagree with the effect. `stats` should be the
+    /// base stats: buffs come and go, while the upgrade stays.
+    ///
+    /// A weapon card levelling one already equipped shows the step to the
+    /// next level; one granting a new weapon shows its base stats, with
+    /// before and after equal.
+    pub fn preview(
+        &self,
+        stats: &PlayerStats,
+        curse: &Curse,
+        weapons: &WeaponSlots,
+    ) -> Vec<StatDelta> {
+        if let Some(kind) = self.weapon() {
+            let level = weapons.iter().find(|w| w.kind == kind).map(|w| w.level);
+            let before = weapon_stats(kind, level.unwrap_or(1));
+            let after = weapon_stats(kind, level.map_or(1, |level| level + 1));
+            return before
+                .into_iter()
+                .zip(after)
+                .map(|((stat, before), (_, after))| (stat, before, after))
+                .collect();
+        }
+
+        let mut after_stats = stats.clone();
+        let mut after_curse = Curse(curse.0);
+        // No other upgrade reads the weapons beyond the blaster's fire mode
+        let mut scratch = WeaponSlots::new(WeaponKind::Blaster);
+        (self.apply)(&mut UpgradeTargets {
+            stats: &mut after_stats,
+            curse: &mut after_curse,
+            weapons: &mut scratch,
+        });
+        stat_values(stats, curse)
+            .into_iter()
+            .zip(stat_values(&after_stats, &after_curse))
+            .filter(|((_, before), (_, after))| before != after)
+            .map(|((stat, before), (_, after))| (stat, before, after))
+            .collect()
+    }
+}
+
+/// A stat an upgrade card can show.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StatName {
+    MoveSpeed,
+    BulletSpeed,
+    Damage,
+    MaxHealth,
+    Luck,
+    Lifesteal,
+    Rerolls,
+    Ricochet,
+    Parry,
+    Heat,
+    Projectiles,
+    Curse,
+    Cooldown,
+    Radius,
+}
+
+/// A stat with its value before and after an upgrade.
+pub type StatDelta = (StatName, f32, f32);
+
+impl StatName {
+    pub fn label(self) -> &'static str {
+        match self {
+            StatName::MoveSpeed => "Move speed",
+            StatName::BulletSpeed => "Bullet speed",
+            StatName::Damage => "Damage",
+            StatName::MaxHealth => "Max health",
+            StatName::Luck => "Luck",
+            StatName::Lifesteal => "Lifesteal",
+            StatName::Rerolls => "Rerolls",
+            StatName::Ricochet => "Bounces",
+            StatName::Parry => "Parry",
+            StatName::Heat => "Heat per shot",
+            StatName::Projectiles => "Projectiles",
+            StatName::Curse => "Curse",
+            StatName::Cooldown => "Fire cooldown",
+            StatName::Radius => "Radius",
+        }
+    }
+
+    /// `value` the way a card shows it. Fractions of one show as whole
+    /// percents and cooldowns to the hundredth of a second.
+    pub fn format(self, value: f32) -> String {
+        match self {
+            StatName::Luck | StatName::Lifesteal => format!("{:.0}%", value * 100.0),
+            StatName::Cooldown => format!("{value:.2}s"),
+            StatName::Heat => format!("{value:.2}"),
+            StatName::Parry if value > 0.0 => "on".to_string(),
+            StatName::Parry => "off".to_string(),
+            _ => format!("{value:.0}"),
+        }
+    }
+}
+
+/// One line of a card: "Damage 3 -> 4", or just the value when it stays.
+/// The arrow is ASCII since the fallback font has no arrow glyph.
+pub fn format_delta((stat, before, after): StatDelta) -> String {
+    if before == after {
+        format!("{} {}", stat.label(), stat.format(before))
+    } else {
+        format!(
+            "{} {} -> {}",
+            stat.label(),
+            stat.format(before),
+            stat.format(after)
+        )
+    }
+}
+
+/// Every stat a non-weapon upgrade can touch, in the order cards list them.
+fn stat_values(stats: &PlayerStats, curse: &Curse) -> [(StatName, f32); 12] {
+    [
+        (StatName::Damage, stats.bullet_damage as f32),
+        (StatName::Projectiles, stats.projectile_count as f32),
+        (StatName::MoveSpeed, stats.move_speed),
+        (StatName::BulletSpeed, stats.bullet_speed),
+        (StatName::MaxHealth, stats.max_health as f32),
+        (StatName::Luck, stats.luck),
+        (StatName::Lifesteal, stats.lifesteal),
+        (StatName::Rerolls, stats.rerolls as f32),
+        (StatName::Ricochet, stats.ricochet as f32),
+        (StatName::Parry, stats.bullet_parry as u8 as f32),
+        (StatName::Heat, stats.heat_per_shot),
+        (StatName::Curse, curse.0 as f32),
+    ]
+}
+
+fn weapon_stats(kind: WeaponKind, level: u32) -> Vec<(StatName, f32)> {
+    let mut values = vec![(StatName::Cooldown, kind.cooldown(level))];
+    if kind == WeaponKind::Aura {
+        values.push((StatName::Radius, aura_radius(level)));
+    }
+    values
+}
+
+pub fn find_upgrade(id: &str) -> Option<&'static UpgradeDef> {
+    UPGRADES.iter().find(|def| def.id == id)
+}
+
+/// Banishes each run starts with.
+pub const BANISH_USES: u32 = 3;
+
+/// How many times each upgrade has been taken this run, and the level-up
+/// screen's banishes and held offer.
+#[derive(Resource)]
+pub struct UpgradePool {
+    stacks: HashMap<&'static str, u32>,
+    /// Upgrade ids not unlocked yet; never offered.
+    locked: Vec<&'static str>,
+    /// Banished this run; never offered again either.
+    banished: Vec<&'static str>,
+    banishes_left: u32,
+    /// An offer kept by a lock, to be shown again at the next level-up.
+    held: Option<Vec<&'static UpgradeDef>>,
+}
+
+impl Default for UpgradePool {
+    fn default() -> Self {
+        Self {
+            stacks: HashMap::new(),
+            locked: Vec::new(),
+            banished: Vec::new(),
+            banishes_left: BANISH_USES,
+            held: None,
+        }
+    }
+}
+
+impl UpgradePool {
+    pub fn stacks(&self, def: &UpgradeDef) -> u32 {
+        self.stacks.get(def.id).copied().unwrap_or(0)
+    }
+
+    pub fn take(&mut self, def: &'static UpgradeDef, targets: &mut UpgradeTargets) {
+        (def.apply)(targets);
+        *self.stacks.entry(def.id).or_insert(0) += 1;
+    }
+
+    /// Everything but the unlocks, which outlast the run.
+    pub fn reset(&mut self) {
+        self.stacks.clear();
+        self.banished.clear();
+        self.banishes_left = BANISH_USES;
+        self.held = None;
+    }
+
+    pub fn banishes_left(&self) -> u32 {
+        self.banishes_left
+    }
+
+    /// Takes `def` out of the pool for the rest of the run. False with no
+    /// banishes left.
+    pub fn banish(&mut self, def: &UpgradeDef) -> bool {
+        if self.banishes_left == 0 {
+            return false;
+        }
+        self.banishes_left -= 1;
+        self.banished.push(def.id);
+        true
+    }
+
+    /// Keeps `offer` for the next level-up, replacing any offer already kept.
+    pub fn hold(&mut self, offer: &[&'static UpgradeDef]) {
+        self.held = Some(offer.to_vec());
+    }
+
+    pub fn release_hold(&mut self) {
+        self.held = None;
+    }
+
+    pub fn is_holding(&self) -> bool {
+        self.held.is_some()
+    }
+
+    /// The next level-up's options: the held offer if there is one, which
+    /// also ends the hold, otherwise a fresh draw.
+    pub fn next_offer(
+        &mut self,
+        count: usize,
+        luck: f32,
+        rng: &mut impl Rng,
+    ) -> Vec<&'static UpgradeDef> {
+        match self.held.take() {
+            Some(mut offer) => {
+                self.refill(&mut offer, count, luck, rng);
+                offer
+            }
+            None => self.draw(count, luck, rng),
+        }
+    }
+
+    /// Drops whatever in `offer` can no longer be offered, maxed out or
+    /// banished since, and tops it back up to `count` with fresh draws. Any
+    /// eligible upgrade may fill a gap, so the last weapon going still leaves
+    /// the passives to choose from.
+    pub fn refill(
+        &self,
+        offer: &mut Vec<&'static UpgradeDef>,
+        count: usize,
+        luck: f32,
+        rng: &mut impl Rng,
+    ) {
+        offer.retain(|def| self.eligible().any(|other| other.id == def.id));
+        let fresh = self.draw_excluding(count.saturating_sub(offer.len()), luck, rng, offer);
+        offer.extend(fresh);
+    }
+
+    /// Every upgrade taken so far with its stack count.
+    pub fn taken(&self) -> impl Iterator<Item = (&'static str, u32)> + '_ {
+        self.stacks.iter().map(|(&id, &stacks)| (id, stacks))
+    }
+
+    /// Puts back stacks recorded by `taken`; ids no longer in `UPGRADES` are
+    /// dropped. Only the counts are restored, not the effects.
+    pub fn restore(&mut self, id: &str, stacks: u32) {
+        if let Some(def) = find_upgrade(id) {
+            self.stacks.insert(def.id, stacks.min(def.max_stacks));
+        }
+    }
+
+    pub fn set_locked(&mut self, locked: Vec<&'static str>) {
+        self.locked = locked;
+    }
+
+    fn eligible(&self) -> impl Iterator<Item = &'static UpgradeDef> + '_ {
+        UPGRADES.iter().filter(|def| {
+            self.stacks(def) < def.max_stacks
+                && !self.locked.contains(&def.id)
+                && !self.banished.contains(&def.id)
+        })
+    }
+
+    /// Weighted draw of up to `count` distinct upgrades that aren't maxed out.
+    /// Returns fewer when the eligible pool runs dry.
+    pub fn draw(&self, count: usize, luck: f32, rng: &mut impl Rng) -> Vec<&'static UpgradeDef> {
+        self.draw_excluding(count, luck, rng, &[])
+    }
+
+    /// `draw`, leaving out everything in `exclude`.
+    fn draw_excluding(
+        &self,
+        count: usize,
+        luck: f32,
+        rng: &mut impl Rng,
+        exclude: &[&'static UpgradeDef],
+    ) -> Vec<&'static UpgradeDef> {
+        let mut candidates: Vec<_> = self
+            .eligible()
+            .filter(|def| exclude.iter().all(|other| other.id != def.id))
+            .collect();
+        let mut offer = Vec::with_capacity(count);
+
+        while offer.len() < count {
+            let weights: Vec<f32> = candidates
+                .iter()
+                .map(|def| def.rarity.weight(luck))
+                .collect();
+            let Some(index) = weighted_index(&weights, rng) else {
+                break;
+            };
+            offer.push(candidates.swap_remove(index));
+        }
+
+        offer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview(id: &str, weapons: &WeaponSlots) -> Vec<StatDelta> {
+        find_upgrade(id)
+            .unwrap()
+            .preview(&PlayerStats::default(), &Curse::default(), weapons)
+    }
+
+    #[test]
+    fn preview_lists_only_what_changes_in_card_order() {
+        let blaster = WeaponSlots::new(WeaponKind::Blaster);
+        assert_eq!(
+            preview("cursed_idol", &blaster),
+            [(StatName::Damage, 1.0, 2.0), (StatName::Curse, 0.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn a_fire_mode_changes_no_listed_stat() {
+        let blaster = WeaponSlots::new(WeaponKind::Blaster);
+        assert!(preview("charge_shot", &blaster).is_empty());
+    }
+
+    #[test]
+    fn an_equipped_weapon_previews_its_next_level() {
+        let blaster = WeaponSlots::new(WeaponKind::Blaster);
+        let deltas = preview("blaster", &blaster);
+        assert_eq!(
+            deltas,
+            [(
+                StatName::Cooldown,
+                WeaponKind::Blaster.cooldown(1),
+                WeaponKind::Blaster.cooldown(2)
+            )]
+        );
+    }
+
+    #[test]
+    fn a_new_weapon_previews_its_base_stats() {
+        let blaster = WeaponSlots::new(WeaponKind::Blaster);
+        let cooldown = WeaponKind::Aura.cooldown(1);
+        let radius = aura_radius(1);
+        assert_eq!(
+            preview("aura", &blaster),
+            [
+                (StatName::Cooldown, cooldown, cooldown),
+                (StatName::Radius, radius, radius)
+            ]
+        );
+    }
+
+    #[test]
+    fn format_delta_shows_the_step_or_the_value() {
+        assert_eq!(format_delta((StatName::Damage, 1.0, 2.0)), "Damage 1 -> 2");
+        assert_eq!(
+            format_delta((StatName::Cooldown, 0.8, 0.8)),
+            "Fire cooldown 0.80s"
+        );
+    }
+
+    #[test]
+    fn format_delta_writes_fractions_as_percents_and_flags_as_words() {
+        assert_eq!(format_delta((StatName::Luck, 0.0, 0.25)), "Luck 0% -> 25%");
+        assert_eq!(format_delta((StatName::Parry, 0.0, 1.0)), "Parry off -> on");
+        assert_eq!(
+            format_delta((StatName::Heat, 0.0, 0.15)),
+            "Heat per shot 0.00 -> 0.15"
+        );
+    }
+
+    /// A pool offering nothing but `ids`.
+    fn only(ids: &[&str]) -> UpgradePool {
+        let mut pool = UpgradePool::default();
+        pool.set_locked(
+            UPGRADES
+                .iter()
+                .map(|def| def.id)
+                .filter(|id| !ids.contains(id))
+                .collect(),
+        );
+        pool
+    }
+
+    fn ids(offer: &[&'static UpgradeDef]) -> Vec<&'static str> {
+        offer.iter().map(|def| def.id).collect()
+    }
+
+    fn draw_seeded(pool: &UpgradePool, count: usize, seed: u64) -> Vec<&'static str> {
+        ids(&pool.draw(count, 0.0, &mut StdRng::seed_from_u64(seed)))
+    }
+
+    #[test]
+    fn a_fixed_seed_draws_the_same_offer() {
+        let pool = UpgradePool::default();
+        for seed in 0..20 {
+            let offer = draw_seeded(&pool, 3, seed);
+            assert_eq!(offer, draw_seeded(&pool, 3, seed));
+            assert_eq!(offer.len(), 3);
+            assert!(
+                offer
+                    .iter()
+                    .all(|id| offer.iter().filter(|o| *o == id).count() == 1)
+            );
+        }
+    }
+
+    #[test]
+    fn fewer_eligible_upgrades_than_slots_offers_what_is_left() {
+        for eligible in [&[][..], &["clover"], &["clover", "vitality"]] {
+            let mut offer = draw_seeded(&only(eligible), 3, 1);
+            offer.sort();
+            let mut expected = eligible.to_vec();
+            expected.sort();
+            assert_eq!(offer, expected);
+        }
+    }
+
+    #[test]
+    fn maxed_out_upgrades_are_never_drawn() {
+        let mut pool = only(&["clover", "vitality", "parry_rounds"]);
+        pool.restore("parry_rounds", 1);
+        for seed in 0..20 {
+            assert!(!draw_seeded(&pool, 3, seed).contains(&"parry_rounds"));
+        }
+    }
+
+    #[test]
+    fn banished_upgrades_leave_the_pool_until_the_banishes_run_out() {
+        let mut pool = only(&["clover", "vitality", "swift_boots", "light_rounds"]);
+        for id in ["clover", "vitality", "swift_boots"] {
+            assert!(pool.banish(find_upgrade(id).unwrap()));
+        }
+        assert_eq!(pool.banishes_left(), 0);
+        assert!(!pool.banish(find_upgrade("light_rounds").unwrap()));
+        assert_eq!(draw_seeded(&pool, 3, 4), ["light_rounds"]);
+
+        pool.reset();
+        assert_eq!(pool.banishes_left(), BANISH_USES);
+        assert_eq!(draw_seeded(&pool, 3, 4).len(), 3);
+    }
+
+    #[test]
+    fn a_held_offer_comes_back_once() {
+        let mut pool = UpgradePool::default();
+        let mut rng = StdRng::seed_from_u64(9);
+        let offer = pool.draw(3, 0.0, &mut rng);
+        pool.hold(&offer);
+        assert!(pool.is_holding());
+        assert_eq!(ids(&pool.next_offer(3, 0.0, &mut rng)), ids(&offer));
+        assert!(!pool.is_holding());
+    }
+
+    #[test]
+    fn a_held_offer_is_topped_up_past_what_was_banished() {
+        let mut pool = only(&["clover", "vitality", "swift_boots", "light_rounds"]);
+        let held = ["clover", "vitality", "swift_boots"].map(|id| find_upgrade(id).unwrap());
+        pool.hold(&held);
+        pool.banish(held[0]);
+        let mut offer = ids(&pool.next_offer(3, 0.0, &mut StdRng::seed_from_u64(2)));
+        offer.sort();
+        assert_eq!(offer, ["light_rounds", "swift_boots", "vitality"]);
+    }
+
+    /// How often the Epic `vampire_fang` beats the Common `swift_boots` to a
+    /// single slot.
+    fn epic_share(luck: f32) -> f32 {
+        let pool = only(&["vampire_fang", "swift_boots"]);
+        let mut rng = StdRng::seed_from_u64(13);
+        let epics = (0..10_000)
+            .filter(|_| pool.draw(1, luck, &mut rng)[0].id == "vampire_fang")
+            .count();
+        epics as f32 / 10_000.0
+    }
+
+    #[test]
+    fn rarity_weights_set_the_odds() {
+        // 10 against 60 without luck
+        let share = epic_share(0.0);
+        assert!((share - 1.0 / 7.0).abs() < 0.015, "{share}");
+    }
+
+    #[test]
+    fn luck_makes_rarer_tiers_likelier() {
+        // Luck 1 triples the epic weight: 30 against 60
+        let share = epic_share(1.0);
+        assert!((share - 1.0 / 3.0).abs() < 0.015, "{share}");
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+
+use crate::performance::CosmeticBudget;
+use crate::{Health, Player, Settings};
+
+/// How thick the red edge gets at its strongest.
+const VIGNETTE_WIDTH: f32 = 60.0;
+const VIGNETTE_MAX_ALPHA: f32 = 0.5;
+/// Below this fraction of health the vignette pulses.
+const LOW_HEALTH_FRACTION: f32 = 0.25;
+const HIT_FLASH_SECS: f32 = 0.15;
+const HIT_FLASH_ALPHA: f32 = 0.35;
+
+pub struct VignettePlugin;
+
+impl Plugin for VignettePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_vignette)
+            .add_systems(Update, (toggle_reduced_motion, update_vignette).chain());
+    }
+}
+
+/// One full-screen node for both effects: its border is the low-health
+/// vignette and its background the hit flash.
+#[derive(Component)]
+struct Vignette {
+    flash: Timer,
+    /// Player health seen last frame; a drop means a hit landed.
+    last_health: Option<i32>,
+}
+
+fn spawn_vignette(mut commands: Commands) {
+    let mut flash = Timer::from_seconds(HIT_FLASH_SECS, TimerMode::Once);
+    flash.finish();
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            border: UiRect::all(Val::Px(VIGNETTE_WIDTH)),
+            ..default()
+        },
+        BorderColor::DEFAULT,
+        BackgroundColor(Color::NONE),
+        // Over the world, under every other piece of UI
+        GlobalZIndex(-1),
+        Pickable::IGNORE,
+        Vignette {
+            flash,
+            last_health: None,
+        },
+    ));
+}
+
+fn toggle_reduced_motion(input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if input.just_pressed(KeyCode::KeyV) {
+        settings.reduced_motion = !settings.reduced_motion;
+    }
+}
+
+fn update_vignette(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    budget: Res<CosmeticBudget>,
+    player: Query<&Health, With<Player>>,
+    mut vignette: Query<(&mut Vignette, &mut BorderColor, &mut BackgroundColor)>,
+) {
+    let Ok((mut vignette, mut border, mut background)) = vignette.single_mut() else {
+        return;
+    };
+    let health = player.single().ok();
+
+    let current = health.map(|h| h.current);
+    if let (Some(now), Some(before)) = (current, vignette.last_health)
+        && now < before
+    {
+        vignette.flash.reset();
+    }
+    vignette.last_health = current;
+    vignette.flash.tick(time.delta());
+
+    let (edge, flash) = match health {
+        Some(health) if !settings.reduced_motion && budget.vignette => {
+            let fraction = (health.current.max(0) as f32 / health.max.max(1) as f32).min(1.0);
+            let mut edge = (1.0 - fraction) * VIGNETTE_MAX_ALPHA;
+            if fraction < LOW_HEALTH_FRACTION {
+                edge *= 0.75 + 0.25 * (time.elapsed_secs() * 6.0).sin();
+            }
+            let flash = if vignette.flash.is_finished() {
+                0.0
+            } else {
+                (1.0 - vignette.flash.fraction()) * HIT_FLASH_ALPHA
+            };
+            (edge, flash)
+        }
+        _ => (0.0, 0.0),
+    };
+
+    let edge_color = Color::srgba(0.8, 0.0, 0.0, edge);
+    if border.top != edge_color {
+        *border = BorderColor::all(edge_color);
+    }
+    let flash_color = Color::srgba(0.1, 0.0, 0.0, flash);
+    if background.0 != flash_color {
+        background.0 = flash_color;
+    }
+}
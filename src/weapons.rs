@@ -0,0 +1,740 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::fonts::FontAssets;
+use crate::input::PlayerInputState;
+use crate::{
+    BuffStat, Buffs, CHARGE_DAMAGE_SCALE, DamageEvent, DamageKind, DamageType, Enemy, GameTime,
+    Hitbox, PlayState, Player, PlayerStats, Team,
+};
+
+pub const MAX_WEAPON_SLOTS: usize = 4;
+pub const MAX_WEAPON_LEVEL: u32 = 5;
+const BURST_SHOTS: u32 = 3;
+const BURST_GAP_SECS: f32 = 0.06;
+/// A charge shot fires by itself once held this long.
+pub const MAX_CHARGE_SECS: f32 = 1.5;
+const CHARGE_BAR_WIDTH: f32 = 30.0;
+/// How far below the aim point the charge bar sits.
+const CHARGE_BAR_OFFSET: f32 = 18.0;
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (tick_weapons, fire_auras)
+                .chain()
+                .run_if(in_state(PlayState::Running)),
+        )
+        .add_systems(Update, (draw_auras, draw_charge_bar));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WeaponKind {
+    /// Fires a bullet toward the cursor on click.
+    Blaster,
+    /// Pulses damage into every enemy close to the player.
+    Aura,
+}
+
+/// Every weapon, in the order listings show them.
+pub const ALL_WEAPONS: &[WeaponKind] = &[WeaponKind::Blaster, WeaponKind::Aura];
+
+impl WeaponKind {
+    /// Seconds between shots at a given level.
+    pub fn cooldown(self, level: u32) -> f32 {
+        let steps = level.saturating_sub(1) as i32;
+        match self {
+            WeaponKind::Blaster => 0.2 * 0.85_f32.powi(steps),
+            WeaponKind::Aura => 0.8 * 0.9_f32.powi(steps),
+        }
+    }
+
+    /// Stable name for saves.
+    pub fn id(self) -> &'static str {
+        match self {
+            WeaponKind::Blaster => "blaster",
+            WeaponKind::Aura => "aura",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "blaster" => Some(WeaponKind::Blaster),
+            "aura" => Some(WeaponKind::Aura),
+            _ => None,
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            WeaponKind::Blaster => "*",
+            WeaponKind::Aura => "O",
+        }
+    }
+
+    /// What its hits are made of.
+    pub fn damage_type(self) -> DamageType {
+        match self {
+            WeaponKind::Blaster => DamageType::Physical,
+            // It burns whatever stands too close
+            WeaponKind::Aura => DamageType::Fire,
+        }
+    }
+
+    /// Picks enemies on its own somewhere, so its `TargetingMode` matters:
+    /// the blaster through its ricochets. The aura hits everything in reach.
+    pub fn picks_targets(self) -> bool {
+        self == WeaponKind::Blaster
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            WeaponKind::Blaster => Color::WHITE,
+            WeaponKind::Aura => Color::srgb(1.0, 0.9, 0.5),
+        }
+    }
+}
+
+/// How a projectile looks, set on it when it spawns.
+#[derive(Clone, Copy, Debug)]
+pub struct ProjectileVisual {
+    pub glyph: &'static str,
+    pub color: Color,
+    pub size: f32,
+    /// Turned to point along its flight, for glyphs with a front to them.
+    pub faces_velocity: bool,
+}
+
+/// Enemy fire's own color. No player weapon may use it, so anything this
+/// color flying at the player is always a threat.
+pub const ENEMY_PROJECTILE_COLOR: Color = Color::srgb(1.0, 0.2, 0.9);
+
+pub const BLASTER_PROJECTILE: ProjectileVisual = ProjectileVisual {
+    glyph: "*",
+    color: Color::WHITE,
+    size: 20.0,
+    faces_velocity: false,
+};
+
+pub const ENEMY_PROJECTILE: ProjectileVisual = ProjectileVisual {
+    glyph: "o",
+    color: ENEMY_PROJECTILE_COLOR,
+    size: 16.0,
+    faces_velocity: false,
+};
+
+/// On a projectile whose glyph turns with its direction.
+#[derive(Component)]
+pub struct FacesVelocity;
+
+impl ProjectileVisual {
+    /// Gives `entity` this look. One that turns is pointed along its flight
+    /// by `face_velocity` from its first frame on.
+    pub fn apply(&self, entity: &mut EntityCommands, fonts: &FontAssets) {
+        entity.insert((
+            Text2d::new(self.glyph),
+            fonts.body(self.size),
+            TextColor(self.color),
+        ));
+        if self.faces_velocity {
+            entity.insert(FacesVelocity);
+        }
+    }
+}
+
+/// How the blaster turns the trigger into shots. Lives on the weapon, so
+/// only the one it is set on changes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FireMode {
+    /// One volley per click.
+    #[default]
+    Single,
+    /// Three volleys per click, `BURST_GAP_SECS` apart; the cooldown runs
+    /// between bursts.
+    Burst,
+    /// Hold to charge, release to fire one big bullet; no cooldown.
+    Charge,
+}
+
+impl FireMode {
+    /// Stable name for saves.
+    pub fn id(self) -> &'static str {
+        match self {
+            FireMode::Single => "single",
+            FireMode::Burst => "burst",
+            FireMode::Charge => "charge",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "single" => Some(FireMode::Single),
+            "burst" => Some(FireMode::Burst),
+            "charge" => Some(FireMode::Charge),
+            _ => None,
+        }
+    }
+}
+
+/// Which enemy a weapon goes for when it picks one itself. Read at the moment
+/// of picking, so a change applies from the next shot on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TargetingMode {
+    #[default]
+    Nearest,
+    LowestHealth,
+    HighestHealth,
+    /// Elites and bosses, nearest first, then everything else.
+    ElitesFirst,
+    Random,
+}
+
+const ALL_TARGETING_MODES: [TargetingMode; 5] = [
+    TargetingMode::Nearest,
+    TargetingMode::LowestHealth,
+    TargetingMode::HighestHealth,
+    TargetingMode::ElitesFirst,
+    TargetingMode::Random,
+];
+
+impl TargetingMode {
+    /// Stable name for saves.
+    pub fn id(self) -> &'static str {
+        match self {
+            TargetingMode::Nearest => "nearest",
+            TargetingMode::LowestHealth => "lowest_health",
+            TargetingMode::HighestHealth => "highest_health",
+            TargetingMode::ElitesFirst => "elites_first",
+            TargetingMode::Random => "random",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        ALL_TARGETING_MODES.into_iter().find(|mode| mode.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TargetingMode::Nearest => "Nearest",
+            TargetingMode::LowestHealth => "Lowest HP",
+            TargetingMode::HighestHealth => "Highest HP",
+            TargetingMode::ElitesFirst => "Elites first",
+            TargetingMode::Random => "Random",
+        }
+    }
+
+    /// Shown on the HUD weapon icon.
+    pub fn letter(self) -> &'static str {
+        match self {
+            TargetingMode::Nearest => "N",
+            TargetingMode::LowestHealth => "L",
+            TargetingMode::HighestHealth => "H",
+            TargetingMode::ElitesFirst => "E",
+            TargetingMode::Random => "R",
+        }
+    }
+
+    /// The next mode in the pause screen's cycle.
+    pub fn next(self) -> Self {
+        let i = ALL_TARGETING_MODES
+            .iter()
+            .position(|&mode| mode == self)
+            .unwrap_or(0);
+        ALL_TARGETING_MODES[(i + 1) % ALL_TARGETING_MODES.len()]
+    }
+}
+
+/// One enemy as target selection sees it.
+#[derive(Clone, Copy, Debug)]
+pub struct TargetCandidate {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub health: i32,
+    pub elite: bool,
+}
+
+/// The candidate within `range` of `origin` that `mode` prefers. Every mode
+/// but `Random` breaks ties on distance and then on entity, so the same
+/// field always gives the same pick.
+pub fn select_target(
+    mode: TargetingMode,
+    origin: Vec2,
+    range: f32,
+    candidates: impl IntoIterator<Item = TargetCandidate>,
+    rng: &mut impl Rng,
+) -> Option<Entity> {
+    let in_range = candidates
+        .into_iter()
+        .filter(|c| c.position.distance_squared(origin) <= range * range);
+    if mode == TargetingMode::Random {
+        return in_range.choose(rng).map(|c| c.entity);
+    }
+
+    in_range
+        .min_by(|a, b| {
+            let first = match mode {
+                TargetingMode::LowestHealth => a.health.cmp(&b.health),
+                TargetingMode::HighestHealth => b.health.cmp(&a.health),
+                TargetingMode::ElitesFirst => b.elite.cmp(&a.elite),
+                TargetingMode::Nearest | TargetingMode::Random => std::cmp::Ordering::Equal,
+            };
+            first
+                .then_with(|| {
+                    a.position
+                        .distance_squared(origin)
+                        .total_cmp(&b.position.distance_squared(origin))
+                })
+                .then_with(|| a.entity.cmp(&b.entity))
+        })
+        .map(|c| c.entity)
+}
+
+/// Where a weapon is in its fire mode's cycle.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+enum FireState {
+    #[default]
+    Idle,
+    /// Shots still to come and seconds until the next one.
+    Bursting { remaining: u32, next_in: f32 },
+    /// Seconds the trigger has been held.
+    Charging(f32),
+}
+
+/// What the trigger produced this frame.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Shot {
+    Volley,
+    /// Released after charging; `0..=1` of a full charge.
+    Charged(f32),
+}
+
+pub fn aura_radius(level: u32) -> f32 {
+    70.0 + 10.0 * level as f32
+}
+
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub level: u32,
+    mode: FireMode,
+    pub targeting: TargetingMode,
+    /// Seconds until the weapon may fire again.
+    ready_in: f32,
+    state: FireState,
+}
+
+impl Weapon {
+    fn new(kind: WeaponKind) -> Self {
+        Self {
+            kind,
+            level: 1,
+            mode: FireMode::default(),
+            targeting: TargetingMode::default(),
+            ready_in: 0.0,
+            state: FireState::Idle,
+        }
+    }
+
+    pub fn mode(&self) -> FireMode {
+        self.mode
+    }
+
+    /// Drops any burst or charge under way; it belonged to the old mode.
+    pub fn set_mode(&mut self, mode: FireMode) {
+        self.mode = mode;
+        self.state = FireState::Idle;
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready_in <= 0.0
+    }
+
+    /// Starts the cooldown; call once per shot.
+    pub fn trigger(&mut self) {
+        self.ready_in = self.kind.cooldown(self.level);
+    }
+
+    /// Runs the fire mode for one frame of `dt` seconds. `pressed` is this
+    /// frame's click and `held` whether the trigger is down at all; with
+    /// `can_start` false nothing new begins, but a burst or charge already
+    /// under way still plays out.
+    pub fn pull(&mut self, pressed: bool, held: bool, can_start: bool, dt: f32) -> Option<Shot> {
+        match (self.mode, self.state) {
+            (FireMode::Single, _) => {
+                let fire = pressed && can_start && self.is_ready();
+                if fire {
+                    self.trigger();
+                }
+                fire.then_some(Shot::Volley)
+            }
+            (FireMode::Burst, FireState::Idle) => {
+                if !(pressed && can_start && self.is_ready()) {
+                    return None;
+                }
+                self.state = FireState::Bursting {
+                    remaining: BURST_SHOTS - 1,
+                    next_in: BURST_GAP_SECS,
+                };
+                Some(Shot::Volley)
+            }
+            // Letting go mid-burst doesn't cut it short
+            (FireMode::Burst, FireState::Bursting { remaining, next_in }) => {
+                let next_in = next_in - dt;
+                if next_in > 0.0 {
+                    self.state = FireState::Bursting { remaining, next_in };
+                    return None;
+                }
+                if remaining > 1 {
+                    self.state = FireState::Bursting {
+                        remaining: remaining - 1,
+                        next_in: next_in + BURST_GAP_SECS,
+                    };
+                } else {
+                    self.state = FireState::Idle;
+                    self.trigger();
+                }
+                Some(Shot::Volley)
+            }
+            (FireMode::Charge, FireState::Idle) => {
+                if held && can_start {
+                    self.state = FireState::Charging(0.0);
+                }
+                None
+            }
+            (FireMode::Charge, FireState::Charging(secs)) => {
+                let secs = (secs + dt).min(MAX_CHARGE_SECS);
+                if held && secs < MAX_CHARGE_SECS {
+                    self.state = FireState::Charging(secs);
+                    return None;
+                }
+                self.state = FireState::Idle;
+                Some(Shot::Charged(secs / MAX_CHARGE_SECS))
+            }
+            // `set_mode` always resets the cycle, so these never meet
+            (FireMode::Burst, FireState::Charging(_))
+            | (FireMode::Charge, FireState::Bursting { .. }) => {
+                self.state = FireState::Idle;
+                None
+            }
+        }
+    }
+
+    /// Damage a second into one enemy, with every shot landing and the
+    /// trigger pulled the moment it is ready. Buffs aren't counted.
+    pub fn estimated_dps(&self, stats: &PlayerStats) -> f32 {
+        let cooldown = self.kind.cooldown(self.level);
+        match self.kind {
+            // One point a pulse, as `fire_auras` deals it
+            WeaponKind::Aura => 1.0 / cooldown,
+            WeaponKind::Blaster => {
+                let volley = (stats.bullet_damage * stats.projectile_count as i32) as f32;
+                match self.mode {
+                    FireMode::Single => volley / cooldown,
+                    FireMode::Burst => {
+                        let gaps = (BURST_SHOTS - 1) as f32 * BURST_GAP_SECS;
+                        volley * BURST_SHOTS as f32 / (cooldown + gaps)
+                    }
+                    // A lone bullet, fully charged
+                    FireMode::Charge => {
+                        (stats.bullet_damage as f32 * CHARGE_DAMAGE_SCALE).round() / MAX_CHARGE_SECS
+                    }
+                }
+            }
+        }
+    }
+
+    /// `0..=1` of a full charge while one is building.
+    pub fn charge(&self) -> Option<f32> {
+        match self.state {
+            FireState::Charging(secs) => Some(secs / MAX_CHARGE_SECS),
+            _ => None,
+        }
+    }
+}
+
+/// The player's equipped weapons, each on its own cooldown. The starting
+/// weapon sits in slot 0 like any other.
+#[derive(Component)]
+pub struct WeaponSlots {
+    slots: Vec<Weapon>,
+}
+
+impl WeaponSlots {
+    pub fn new(first: WeaponKind) -> Self {
+        Self {
+            slots: vec![Weapon::new(first)],
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Weapon> {
+        self.slots.iter()
+    }
+
+    pub fn get_mut(&mut self, kind: WeaponKind) -> Option<&mut Weapon> {
+        self.slots.iter_mut().find(|w| w.kind == kind)
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&Weapon> {
+        self.slots.get(slot)
+    }
+
+    /// Trades two slots' weapons. Each moves whole, cooldown and any burst
+    /// or charge under way included. False if either slot is empty.
+    pub fn swap(&mut self, a: usize, b: usize) -> bool {
+        if a == b || a >= self.slots.len() || b >= self.slots.len() {
+            return false;
+        }
+        self.slots.swap(a, b);
+        true
+    }
+
+    /// Levels the weapon if equipped, otherwise puts it in the first free slot.
+    /// Returns false when there is neither room nor a level left to gain.
+    pub fn add_or_level(&mut self, kind: WeaponKind) -> bool {
+        if let Some(weapon) = self.get_mut(kind) {
+            if weapon.level >= MAX_WEAPON_LEVEL {
+                return false;
+            }
+            weapon.level += 1;
+            return true;
+        }
+
+        if self.slots.len() >= MAX_WEAPON_SLOTS {
+            return false;
+        }
+        self.slots.push(Weapon::new(kind));
+        true
+    }
+}
+
+fn tick_weapons(time: GameTime, mut slots: Query<(&mut WeaponSlots, Option<&Buffs>)>) {
+    for (mut slots, buffs) in &mut slots {
+        let rate = buffs.map_or(1.0, |buffs| buffs.multiplier(BuffStat::FireRate));
+        for weapon in &mut slots.slots {
+            weapon.ready_in = (weapon.ready_in - time.delta_secs() * rate).max(0.0);
+        }
+    }
+}
+
+fn fire_auras(
+    mut damage_events: MessageWriter<DamageEvent>,
+    mut player: Query<(&Transform, &mut WeaponSlots), With<Player>>,
+    enemies: Query<(Entity, &Transform, &Hitbox), With<Enemy>>,
+) {
+    let Ok((player_tf, mut slots)) = player.single_mut() else {
+        return;
+    };
+    let Some(aura) = slots.get_mut(WeaponKind::Aura) else {
+        return;
+    };
+    if !aura.is_ready() {
+        return;
+    }
+    aura.trigger();
+
+    let center = player_tf.translation.truncate();
+    let radius = aura_radius(aura.level);
+    for (entity, tf, hitbox) in &enemies {
+        if !hitbox.intangible && tf.translation.truncate().distance(center) <= radius {
+            damage_events.write(DamageEvent {
+                target: entity,
+                amount: 1,
+                source_team: Team::Player,
+                kind: DamageKind::Aura,
+                damage_type: WeaponKind::Aura.damage_type(),
+                origin: center,
+                weapon: Some(WeaponKind::Aura),
+            });
+        }
+    }
+}
+
+/// A small bar under the aim point, filling as the charge builds.
+fn draw_charge_bar(
+    mut gizmos: Gizmos,
+    input: Res<PlayerInputState>,
+    player: Query<&WeaponSlots, With<Player>>,
+) {
+    let Some(aim) = input.aim else {
+        return;
+    };
+    let Some(charge) = player
+        .iter()
+        .flat_map(WeaponSlots::iter)
+        .find_map(Weapon::charge)
+    else {
+        return;
+    };
+
+    let left = aim + Vec2::new(-CHARGE_BAR_WIDTH / 2.0, -CHARGE_BAR_OFFSET);
+    gizmos.line_2d(
+        left,
+        left + Vec2::X * CHARGE_BAR_WIDTH,
+        Color::srgba(1.0, 1.0, 1.0, 0.25),
+    );
+    let full = if charge >= 1.0 {
+        Color::srgb(1.0, 0.5, 0.2)
+    } else {
+        Color::WHITE
+    };
+    gizmos.line_2d(left, left + Vec2::X * CHARGE_BAR_WIDTH * charge, full);
+}
+
+fn draw_auras(mut gizmos: Gizmos, player: Query<(&Transform, &WeaponSlots), With<Player>>) {
+    for (tf, slots) in &player {
+        if let Some(aura) = slots.slots.iter().find(|w| w.kind == WeaponKind::Aura) {
+            gizmos.circle_2d(
+                tf.translation.truncate(),
+                aura_radius(aura.level),
+                WeaponKind::Aura.color().with_alpha(0.25),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    fn candidate(index: u32, x: f32, health: i32, elite: bool) -> TargetCandidate {
+        TargetCandidate {
+            entity: Entity::from_raw_u32(index).unwrap(),
+            position: Vec2::new(x, 0.0),
+            health,
+            elite,
+        }
+    }
+
+    /// Near to far: a weak elite, a strong one, the weakest, and one out of range.
+    fn field() -> [TargetCandidate; 4] {
+        [
+            candidate(1, 50.0, 4, true),
+            candidate(2, 100.0, 9, false),
+            candidate(3, 150.0, 1, false),
+            candidate(4, 500.0, 20, false),
+        ]
+    }
+
+    fn pick(mode: TargetingMode, candidates: &[TargetCandidate]) -> Option<u32> {
+        let mut rng = StdRng::seed_from_u64(1);
+        select_target(
+            mode,
+            Vec2::ZERO,
+            200.0,
+            candidates.iter().copied(),
+            &mut rng,
+        )
+        .map(Entity::index_u32)
+    }
+
+    #[test]
+    fn each_mode_picks_its_preferred_target() {
+        assert_eq!(pick(TargetingMode::Nearest, &field()), Some(1));
+        assert_eq!(pick(TargetingMode::HighestHealth, &field()), Some(2));
+        assert_eq!(pick(TargetingMode::LowestHealth, &field()), Some(3));
+        assert_eq!(pick(TargetingMode::ElitesFirst, &field()), Some(1));
+    }
+
+    #[test]
+    fn random_only_picks_in_range() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut seen = [false; 5];
+        for _ in 0..200 {
+            let picked =
+                select_target(TargetingMode::Random, Vec2::ZERO, 200.0, field(), &mut rng).unwrap();
+            seen[picked.index_u32() as usize] = true;
+        }
+        assert_eq!(seen, [false, true, true, true, false]);
+    }
+
+    #[test]
+    fn ties_break_on_distance_then_entity() {
+        let mut tied = [
+            candidate(7, 120.0, 5, false),
+            candidate(6, 60.0, 5, false),
+            candidate(5, 60.0, 5, false),
+        ];
+        let first = pick(TargetingMode::LowestHealth, &tied);
+        assert!(matches!(first, Some(5 | 6)));
+        tied.reverse();
+        assert_eq!(pick(TargetingMode::LowestHealth, &tied), first);
+        assert_eq!(pick(TargetingMode::Nearest, &[]), None);
+    }
+
+    fn stats(bullet_damage: i32, projectile_count: u32) -> PlayerStats {
+        PlayerStats {
+            bullet_damage,
+            projectile_count,
+            ..default()
+        }
+    }
+
+    fn weapon(kind: WeaponKind, level: u32, mode: FireMode) -> Weapon {
+        let mut weapon = Weapon::new(kind);
+        weapon.level = level;
+        weapon.set_mode(mode);
+        weapon
+    }
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn aura_dps_is_one_pulse_per_cooldown_whatever_the_stats() {
+        for level in 1..=5 {
+            let aura = weapon(WeaponKind::Aura, level, FireMode::Single);
+            let expected = 1.0 / WeaponKind::Aura.cooldown(level);
+            assert_near(aura.estimated_dps(&stats(1, 1)), expected);
+            assert_near(aura.estimated_dps(&stats(9, 4)), expected);
+        }
+        assert_near(
+            weapon(WeaponKind::Aura, 1, FireMode::Single).estimated_dps(&stats(1, 1)),
+            1.25,
+        );
+    }
+
+    #[test]
+    fn single_fire_dps_is_the_volley_over_the_cooldown() {
+        let blaster = weapon(WeaponKind::Blaster, 1, FireMode::Single);
+        assert_near(blaster.estimated_dps(&stats(1, 1)), 5.0);
+        assert_near(blaster.estimated_dps(&stats(2, 1)), 10.0);
+        assert_near(blaster.estimated_dps(&stats(2, 3)), 30.0);
+
+        let levelled = weapon(WeaponKind::Blaster, 3, FireMode::Single);
+        assert_near(
+            levelled.estimated_dps(&stats(1, 1)),
+            1.0 / (0.2 * 0.85 * 0.85),
+        );
+    }
+
+    #[test]
+    fn burst_dps_spreads_three_volleys_over_the_cooldown_and_gaps() {
+        let burst = weapon(WeaponKind::Blaster, 1, FireMode::Burst);
+        // Three shots every 0.2 + 2 * 0.06 seconds
+        assert_near(burst.estimated_dps(&stats(1, 1)), 3.0 / 0.32);
+        assert_near(burst.estimated_dps(&stats(2, 2)), 12.0 / 0.32);
+        assert!(
+            burst.estimated_dps(&stats(1, 1))
+                > weapon(WeaponKind::Blaster, 1, FireMode::Single).estimated_dps(&stats(1, 1))
+        );
+    }
+
+    #[test]
+    fn charge_dps_is_one_full_charge_and_ignores_projectiles_and_level() {
+        let charge = weapon(WeaponKind::Blaster, 1, FireMode::Charge);
+        let full = CHARGE_DAMAGE_SCALE / MAX_CHARGE_SECS;
+        assert_near(charge.estimated_dps(&stats(1, 1)), full);
+        assert_near(charge.estimated_dps(&stats(1, 5)), full);
+        assert_near(charge.estimated_dps(&stats(3, 1)), 3.0 * full);
+        assert_near(
+            weapon(WeaponKind::Blaster, 4, FireMode::Charge).estimated_dps(&stats(1, 1)),
+            full,
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! The weekly challenge schedule. `assets/challenges.ron` lists challenges
+//! by ISO week, each with its modifiers, a score to reach and a name; the
+//! entry for the current week, if there is one, stands in for the daily
+//! challenge all week. A missing or broken file only means no weekly
+//! challenges, never a game that won't start.
+
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::challenge::{RunModifier, civil_from_days, days_from_civil};
+use crate::storage;
+
+const SCHEDULE_PATH: &str = "assets/challenges.ron";
+
+/// An ISO 8601 week: weeks start on Monday, and week 1 is the one holding
+/// the year's first Thursday, so the last days of December can fall in
+/// week 1 of the next year and the first days of January in the last week
+/// of the one before.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct IsoWeek {
+    pub year: i64,
+    pub week: i64,
+}
+
+impl IsoWeek {
+    /// The week holding `day`, in days since the Unix epoch.
+    pub fn from_day(day: u64) -> Self {
+        let day = day as i64;
+        // The epoch was a Thursday; 0 is Monday
+        let weekday = (day + 3).rem_euclid(7);
+        let thursday = day - weekday + 3;
+        let (year, ..) = civil_from_days(thursday);
+        let week = (thursday - days_from_civil(year, 1, 1)) / 7 + 1;
+        Self { year, week }
+    }
+
+    /// Takes `2026-W07`. Week 53 only exists in some years, so a week is
+    /// only accepted if it is really on the calendar.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (year, week) = text.trim().split_once("-W")?;
+        let parsed = Self {
+            year: year.parse().ok()?,
+            week: week.parse().ok()?,
+        };
+        if !(1..=53).contains(&parsed.week) {
+            return None;
+        }
+        // Week 1 is the one with January 4th in it
+        let jan4 = days_from_civil(parsed.year, 1, 4);
+        let monday = jan4 - (jan4 + 3).rem_euclid(7) + (parsed.week - 1) * 7;
+        (monday >= 0 && Self::from_day(monday as u64) == parsed).then_some(parsed)
+    }
+
+    pub fn id(self) -> String {
+        format!("{:04}-W{:02}", self.year, self.week)
+    }
+}
+
+/// One scheduled week, as written in the file.
+#[derive(Deserialize)]
+struct ScheduleEntry {
+    week: String,
+    name: String,
+    modifiers: Vec<RunModifier>,
+    target: u32,
+}
+
+/// A week's challenge, checked and ready to play.
+#[derive(Clone, PartialEq, Debug)]
+pub struct WeeklyChallenge {
+    pub week: IsoWeek,
+    pub name: String,
+    pub modifiers: Vec<RunModifier>,
+    /// Reaching this score completes it.
+    pub target: u32,
+}
+
+/// Every entry in `text`, or what is wrong with the first bad one. One bad
+/// entry throws out the whole file, so a typo is noticed rather than
+/// quietly skipping a week.
+fn parse_schedule(text: &str) -> Result<Vec<WeeklyChallenge>, String> {
+    let entries: Vec<ScheduleEntry> = ron::from_str(text).map_err(|err| err.to_string())?;
+    let mut schedule: Vec<WeeklyChallenge> = Vec::new();
+    for (i, entry) in entries.into_iter().enumerate() {
+        let week = IsoWeek::parse(&entry.week)
+            .ok_or_else(|| format!("entry {i}: \"{}\" is not a week like 2026-W07", entry.week))?;
+        if schedule.iter().any(|other| other.week == week) {
+            return Err(format!("entry {i}: {} is scheduled twice", week.id()));
+        }
+        if entry.modifiers.is_empty() {
+            return Err(format!("entry {i}: needs at least one modifier"));
+        }
+        if entry.target == 0 {
+            return Err(format!("entry {i}: target must be above 0"));
+        }
+        schedule.push(WeeklyChallenge {
+            week,
+            name: entry.name,
+            modifiers: entry.modifiers,
+            target: entry.target,
+        });
+    }
+    Ok(schedule)
+}
+
+fn load_schedule() -> Vec<WeeklyChallenge> {
+    let Some(text) = storage::load_file(Path::new(SCHEDULE_PATH)) else {
+        return Vec::new();
+    };
+    parse_schedule(&text).unwrap_or_else(|err| {
+        error!("{SCHEDULE_PATH}: {err}; no weekly challenges");
+        Vec::new()
+    })
+}
+
+/// The challenge scheduled for `week`, if any.
+pub fn scheduled(week: IsoWeek) -> Option<WeeklyChallenge> {
+    load_schedule()
+        .into_iter()
+        .find(|challenge| challenge.week == week)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn week_of(year: i64, month: i64, day: i64) -> IsoWeek {
+        IsoWeek::from_day(days_from_civil(year, month, day) as u64)
+    }
+
+    fn week(year: i64, week: i64) -> IsoWeek {
+        IsoWeek { year, week }
+    }
+
+    #[test]
+    fn the_epoch_is_in_week_one() {
+        assert_eq!(IsoWeek::from_day(0), week(1970, 1));
+    }
+
+    #[test]
+    fn late_december_can_belong_to_next_year() {
+        // 2026 starts on a Thursday, so its week 1 reaches back to Monday
+        assert_eq!(week_of(2025, 12, 28), week(2025, 52));
+        assert_eq!(week_of(2025, 12, 29), week(2026, 1));
+        assert_eq!(week_of(2026, 1, 4), week(2026, 1));
+        assert_eq!(week_of(2026, 1, 5), week(2026, 2));
+    }
+
+    #[test]
+    fn early_january_can_belong_to_last_year() {
+        assert_eq!(week_of(2021, 1, 3), week(2020, 53));
+        assert_eq!(week_of(2021, 1, 4), week(2021, 1));
+        assert_eq!(week_of(2027, 1, 3), week(2026, 53));
+    }
+
+    #[test]
+    fn parse_reads_what_id_writes() {
+        assert_eq!(IsoWeek::parse("2026-W07"), Some(week(2026, 7)));
+        assert_eq!(IsoWeek::parse(" 2026-W42 "), Some(week(2026, 42)));
+        assert_eq!(week(2026, 7).id(), "2026-W07");
+    }
+
+    #[test]
+    fn parse_only_takes_weeks_on_the_calendar() {
+        assert_eq!(IsoWeek::parse("2026-W53"), Some(week(2026, 53)));
+        assert_eq!(IsoWeek::parse("2025-W53"), None);
+        assert_eq!(IsoWeek::parse("2026-W00"), None);
+        assert_eq!(IsoWeek::parse("2026-W54"), None);
+    }
+
+    #[test]
+    fn parse_rejects_other_formats() {
+        for text in ["2026-07", "2026W07", "W07", "2026-Wxx", ""] {
+            assert_eq!(IsoWeek::parse(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn the_shipped_schedule_parses() {
+        let text = storage::load_file(Path::new(SCHEDULE_PATH)).unwrap();
+        assert!(!parse_schedule(&text).unwrap().is_empty());
+    }
+
+    #[test]
+    fn a_week_scheduled_twice_is_rejected() {
+        let text = r#"[
+            (week: "2026-W42", name: "A", modifiers: [Cursed], target: 1),
+            (week: "2026-W42", name: "B", modifiers: [Cursed], target: 1),
+        ]"#;
+        assert!(parse_schedule(text).unwrap_err().contains("twice"));
+    }
+
+    #[test]
+    fn entries_need_a_modifier_and_a_target() {
+        let no_modifiers = r#"[(week: "2026-W42", name: "A", modifiers: [], target: 1)]"#;
+        let no_target = r#"[(week: "2026-W42", name: "A", modifiers: [Cursed], target: 0)]"#;
+        assert!(parse_schedule(no_modifiers).is_err());
+        assert!(parse_schedule(no_target).is_err());
+    }
+}